@@ -0,0 +1,42 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "proto")]
+    {
+        println!("cargo:rerun-if-changed=proto/records.proto");
+        // Parse the schema with protox so building does not need protoc.
+        let fds = protox::compile(["proto/records.proto"], ["proto"])
+            .expect("failed to parse proto/records.proto");
+        prost_build::Config::new()
+            .compile_fds(fds)
+            .expect("failed to generate the record messages");
+    }
+    #[cfg(feature = "grpc-server")]
+    {
+        println!("cargo:rerun-if-changed=proto/ip2location.proto");
+        let fds = protox::compile(["proto/ip2location.proto"], ["proto"])
+            .expect("failed to parse proto/ip2location.proto");
+        // The records are the messages of the `proto` module of the crate;
+        // only the service and its requests are generated here, apart from
+        // them.
+        let out_dir = std::path::PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("grpc");
+        std::fs::create_dir_all(&out_dir).expect("failed to create the gRPC output directory");
+        let mut config = tonic_prost_build::configure()
+            .build_client(false)
+            .out_dir(out_dir);
+        for message in [
+            "Record",
+            "Country",
+            "LocationRecord",
+            "ProxyStatus",
+            "ProxyRecord",
+        ] {
+            config = config.extern_path(
+                format!(".ip2location.v1.{}", message),
+                format!("::ip2location::proto::{}", message),
+            );
+        }
+        config
+            .compile_fds(fds)
+            .expect("failed to generate the gRPC server");
+    }
+}
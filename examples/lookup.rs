@@ -19,8 +19,7 @@ fn main() -> Result<(), String> {
         .unwrap();
 
     // print the db information
-    db.print_db_info();
-    println!();
+    println!("{}", db.info());
 
     // print the IP information
     match db.ip_lookup(ip) {
@@ -0,0 +1,36 @@
+/* C interface to the ip2location crate, built with the `ffi` feature:
+ *
+ *   cargo rustc --release --features ffi --crate-type cdylib
+ *
+ * Functions that fail return NULL; ip2location_last_error() then describes
+ * the failure on the calling thread.
+ */
+#ifndef IP2LOCATION_H
+#define IP2LOCATION_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct ip2location_db ip2location_db;
+
+/* Opens an IP2Location or IP2Proxy BIN database. The handle may be shared
+ * between threads. */
+ip2location_db *ip2location_open(const char *path);
+
+/* Looks up an IPv4 or IPv6 address and returns its record as a JSON object,
+ * to be released with ip2location_free_string. */
+char *ip2location_lookup(const ip2location_db *db, const char *ip);
+
+void ip2location_free_string(char *s);
+
+void ip2location_close(ip2location_db *db);
+
+/* Owned by the library, valid until the next call on the same thread. */
+const char *ip2location_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* IP2LOCATION_H */
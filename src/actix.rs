@@ -0,0 +1,157 @@
+//! [actix-web](https://crates.io/crates/actix-web) integration, available
+//! with the `actix` feature.
+//!
+//! `GeoIpMiddleware` looks up the client address of every request and
+//! stores the owned `Record` in the request extensions, where handlers can
+//! read it with `web::ReqData<Record<'static>>`.
+//!
+//!```rust,no_run
+//! use actix_web::{web, App, HttpServer};
+//! use ip2location::{actix::GeoIpMiddleware, Record, DB};
+//! use std::sync::Arc;
+//!
+//! async fn index(record: Option<web::ReqData<Record<'static>>>) -> String {
+//!     format!("{:?}", record.map(|r| r.into_inner()))
+//! }
+//!
+//! let db = Arc::new(DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap());
+//! let server = HttpServer::new(move || {
+//!     App::new()
+//!         .wrap(GeoIpMiddleware::new(db.clone()).trusted_proxies(1))
+//!         .route("/", web::get().to(index))
+//! });
+//! actix_web::rt::System::new()
+//!     .block_on(server.bind(("127.0.0.1", 8080)).unwrap().run())
+//!     .unwrap();
+//!```
+use crate::{error::Error, Record, DB};
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::StatusCode,
+    HttpMessage, HttpResponse,
+};
+use std::{
+    future::{ready, Future, Ready},
+    pin::Pin,
+    sync::Arc,
+};
+
+/// What `GeoIpMiddleware` does with a request whose client address cannot
+/// be determined or looked up.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum OnFailure {
+    /// Passes the request on without a record in its extensions.
+    #[default]
+    Continue,
+    /// Answers the request with an error status: 400 when the client
+    /// address is unknown, 404 when it is not in the database and 500 for
+    /// any other lookup error.
+    Reject,
+}
+
+/// Middleware looking up the client address of each request.
+#[derive(Debug, Clone)]
+pub struct GeoIpMiddleware {
+    db: Arc<DB>,
+    trusted_proxies: usize,
+    on_failure: OnFailure,
+}
+
+impl GeoIpMiddleware {
+    /// Uses the given database and the peer address of the connection as
+    /// the client address.
+    pub fn new(db: Arc<DB>) -> Self {
+        Self {
+            db,
+            trusted_proxies: 0,
+            on_failure: OnFailure::default(),
+        }
+    }
+
+    /// Number of reverse proxies in front of the server. When non zero the
+    /// client address is taken from `X-Forwarded-For`, skipping the entries
+    /// appended by the trusted proxies, instead of the peer address.
+    pub fn trusted_proxies(mut self, count: usize) -> Self {
+        self.trusted_proxies = count;
+        self
+    }
+
+    /// Sets what happens to requests whose lookup fails.
+    pub fn on_failure(mut self, on_failure: OnFailure) -> Self {
+        self.on_failure = on_failure;
+        self
+    }
+
+    fn lookup(&self, req: &ServiceRequest) -> Result<Record<'static>, StatusCode> {
+        let ip = crate::forwarded::client_ip(
+            req.headers()
+                .get_all("x-forwarded-for")
+                .filter_map(|value| value.to_str().ok()),
+            self.trusted_proxies,
+            req.peer_addr().map(crate::addr::socket_ip),
+        )
+        .ok_or(StatusCode::BAD_REQUEST)?;
+        match self.db.ip_lookup(ip) {
+            Ok(record) => Ok(record.into_owned()),
+            Err(Error::RecordNotFound) => Err(StatusCode::NOT_FOUND),
+            Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for GeoIpMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Transform = GeoIpService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(GeoIpService {
+            service,
+            config: self.clone(),
+        }))
+    }
+}
+
+/// The service created by `GeoIpMiddleware`.
+pub struct GeoIpService<S> {
+    service: S,
+    config: GeoIpMiddleware,
+}
+
+type LocalBoxFuture<T> = Pin<Box<dyn Future<Output = T>>>;
+
+impl<S, B> Service<ServiceRequest> for GeoIpService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        match self.config.lookup(&req) {
+            Ok(record) => {
+                req.extensions_mut().insert(record);
+            }
+            Err(status) if self.config.on_failure == OnFailure::Reject => {
+                let response = HttpResponse::new(status).map_into_right_body();
+                return Box::pin(ready(Ok(req.into_response(response))));
+            }
+            Err(_) => {}
+        }
+        let fut = self.service.call(req);
+        Box::pin(async move { fut.await.map(ServiceResponse::map_into_left_body) })
+    }
+}
@@ -0,0 +1,98 @@
+use crate::error::Error;
+use alloc::{borrow::Cow, format, string::String};
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+
+/// Values `DB::lookup_addr` accepts in place of an `IpAddr`: the address
+/// types of `std::net`, socket addresses and strings holding either.
+///
+/// Socket addresses are reduced as by `DB::socket_lookup`: the port and
+/// scope ID are dropped and IPv4-mapped IPv6 addresses become IPv4.
+pub trait IntoIpAddr {
+    /// The address to look up; fails only for strings that are not an
+    /// address.
+    fn into_ip_addr(self) -> Result<IpAddr, Error>;
+}
+
+impl IntoIpAddr for IpAddr {
+    fn into_ip_addr(self) -> Result<IpAddr, Error> {
+        Ok(self)
+    }
+}
+
+impl IntoIpAddr for Ipv4Addr {
+    fn into_ip_addr(self) -> Result<IpAddr, Error> {
+        Ok(IpAddr::V4(self))
+    }
+}
+
+impl IntoIpAddr for Ipv6Addr {
+    fn into_ip_addr(self) -> Result<IpAddr, Error> {
+        Ok(IpAddr::V6(self))
+    }
+}
+
+impl IntoIpAddr for SocketAddr {
+    fn into_ip_addr(self) -> Result<IpAddr, Error> {
+        Ok(socket_ip(self))
+    }
+}
+
+impl IntoIpAddr for SocketAddrV4 {
+    fn into_ip_addr(self) -> Result<IpAddr, Error> {
+        Ok(IpAddr::V4(*self.ip()))
+    }
+}
+
+impl IntoIpAddr for SocketAddrV6 {
+    fn into_ip_addr(self) -> Result<IpAddr, Error> {
+        Ok(socket_ip(SocketAddr::V6(self)))
+    }
+}
+
+/// An address such as `1.2.3.4` or `2a01::1`, or a socket address such as
+/// `1.2.3.4:80` or `[2a01::1]:443`. A zone (`fe80::1%eth0`) is ignored.
+impl IntoIpAddr for &str {
+    fn into_ip_addr(self) -> Result<IpAddr, Error> {
+        let value = without_zone(self.trim());
+        match value.parse::<IpAddr>() {
+            Ok(ip) => Ok(ip),
+            Err(err) => value
+                .parse::<SocketAddr>()
+                .map(socket_ip)
+                .map_err(|_| err.into()),
+        }
+    }
+}
+
+impl IntoIpAddr for &String {
+    fn into_ip_addr(self) -> Result<IpAddr, Error> {
+        self.as_str().into_ip_addr()
+    }
+}
+
+impl IntoIpAddr for String {
+    fn into_ip_addr(self) -> Result<IpAddr, Error> {
+        self.as_str().into_ip_addr()
+    }
+}
+
+/// The address a socket address should be looked up as: without its port
+/// and scope ID, and as IPv4 when IPv4-mapped, as dual-stack sockets
+/// report IPv4 peers.
+pub(crate) fn socket_ip(addr: SocketAddr) -> IpAddr {
+    addr.ip().to_canonical()
+}
+
+/// Removes the zone of a scoped IPv6 address, `%eth0` in `fe80::1%eth0`
+/// or `[fe80::1%eth0]:80`.
+fn without_zone(value: &str) -> Cow<'_, str> {
+    match value.find('%') {
+        Some(start) => {
+            let end = value[start..]
+                .find(']')
+                .map_or(value.len(), |end| start + end);
+            Cow::Owned(format!("{}{}", &value[..start], &value[end..]))
+        }
+        None => Cow::Borrowed(value),
+    }
+}
@@ -0,0 +1,129 @@
+//! Lookup results as Arrow record batches, for DataFusion, Parquet
+//! writers and the like.
+use crate::{
+    error::Error,
+    schema::{ColumnType, ColumnValue},
+    LocationRecord, ProxyRecord, Record,
+};
+use arrow_array::{
+    builder::{Float32Builder, StringBuilder},
+    ArrayRef, RecordBatch,
+};
+use arrow_schema::{DataType, Field, Schema};
+use std::sync::Arc;
+
+/// A builder for the values of one column.
+enum Builder {
+    Float(Float32Builder),
+    Text(StringBuilder),
+}
+
+impl Builder {
+    fn new(column_type: ColumnType) -> Self {
+        match column_type {
+            ColumnType::Float => Builder::Float(Float32Builder::new()),
+            ColumnType::Ip | ColumnType::Text => Builder::Text(StringBuilder::new()),
+        }
+    }
+
+    fn append(&mut self, value: Option<ColumnValue<'_>>) {
+        match (self, value) {
+            (Builder::Float(builder), Some(ColumnValue::Float(value))) => {
+                builder.append_value(value)
+            }
+            (Builder::Text(builder), Some(ColumnValue::Ip(ip))) => {
+                builder.append_value(ip.to_string())
+            }
+            (Builder::Text(builder), Some(ColumnValue::Text(value))) => builder.append_value(value),
+            (Builder::Float(builder), _) => builder.append_null(),
+            (Builder::Text(builder), _) => builder.append_null(),
+        }
+    }
+
+    fn finish(&mut self) -> ArrayRef {
+        match self {
+            Builder::Float(builder) => Arc::new(builder.finish()),
+            Builder::Text(builder) => Arc::new(builder.finish()),
+        }
+    }
+}
+
+/// The columns of a batch of `records`, which all come from the same kind
+/// of database.
+fn columns(records: &[Record<'_>]) -> Result<Vec<(&'static str, ColumnType)>, Error> {
+    let Some(first) = records.first() else {
+        return Ok(Vec::new());
+    };
+    // The types of columns do not depend on the database type.
+    let schema = match first {
+        Record::LocationDb(_) => LocationRecord::schema(0),
+        Record::ProxyDb(_) => ProxyRecord::schema(0),
+    };
+    let mixed = records.iter().any(|record| {
+        matches!(record, Record::LocationDb(_)) != matches!(first, Record::LocationDb(_))
+    });
+    if mixed {
+        return Err(Error::GenericError(
+            "records of location and proxy databases cannot share a batch".into(),
+        ));
+    }
+    Ok(schema
+        .into_iter()
+        .map(|column| (column.name, column.column_type))
+        .collect())
+}
+
+pub fn records_to_arrow<'a>(
+    records: impl IntoIterator<Item = Record<'a>>,
+) -> Result<RecordBatch, Error> {
+    //! Collects lookup results into a `RecordBatch` with the columns of
+    //! `LocationRecord::COLUMNS` or `ProxyRecord::COLUMNS`, all nullable:
+    //! addresses and text as `Utf8`, coordinates as `Float32`. Fields a
+    //! record does not have are null. Location and proxy records cannot be
+    //! mixed; no records make a batch without columns.
+    //!
+    //! ## Example usage
+    //!
+    //!```rust
+    //! use ip2location::{records_to_arrow, DB};
+    //!
+    //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+    //! let records = ["43.224.159.155", "1.1.1.1"]
+    //!     .iter()
+    //!     .map(|ip| db.ip_lookup(ip.parse().unwrap()).unwrap());
+    //! let batch = records_to_arrow(records).unwrap();
+    //! assert_eq!(batch.num_rows(), 2);
+    //! assert!(batch.column_by_name("country_short_name").is_some());
+    //!```
+    let records: Vec<Record<'a>> = records.into_iter().collect();
+    let columns = columns(&records)?;
+    let mut builders: Vec<Builder> = columns
+        .iter()
+        .map(|&(_, column_type)| Builder::new(column_type))
+        .collect();
+    for record in &records {
+        for ((name, _), builder) in columns.iter().zip(&mut builders) {
+            builder.append(match record {
+                Record::LocationDb(record) => record.column(name),
+                Record::ProxyDb(record) => record.column(name),
+            });
+        }
+    }
+    let schema = Schema::new(
+        columns
+            .iter()
+            .map(|&(name, column_type)| {
+                let data_type = match column_type {
+                    ColumnType::Float => DataType::Float32,
+                    ColumnType::Ip | ColumnType::Text => DataType::Utf8,
+                };
+                Field::new(name, data_type, true)
+            })
+            .collect::<Vec<_>>(),
+    );
+    if columns.is_empty() {
+        return Ok(RecordBatch::new_empty(Arc::new(schema)));
+    }
+    let arrays = builders.iter_mut().map(Builder::finish).collect();
+    RecordBatch::try_new(Arc::new(schema), arrays).map_err(|e| Error::GenericError(e.to_string()))
+}
@@ -0,0 +1,185 @@
+//! The IP2Location ASN database, read from its CSV distribution
+//! (`IP2LOCATION-LITE-ASN.CSV` or `IP2LOCATION-LITE-ASN.IPV6.CSV`).
+use crate::{error::Error, writer::parse_ip, IpRange};
+use serde::Serialize;
+use std::{
+    borrow::Cow,
+    fs::File,
+    io::Read,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    path::Path,
+};
+
+/// The autonomous system an address belongs to.
+#[derive(PartialEq, Eq, Debug, Clone, Serialize)]
+pub struct AsnRecord<'a> {
+    pub ip: IpAddr,
+    /// The AS number, "-" for unallocated ranges.
+    pub asn: Cow<'a, str>,
+    pub as_name: Cow<'a, str>,
+    /// The network announced by the AS, e.g. `1.0.4.0/22`.
+    pub cidr: Cow<'a, str>,
+}
+
+impl AsnRecord<'_> {
+    /// Converts the record into one that owns its strings.
+    pub fn into_owned(self) -> AsnRecord<'static> {
+        AsnRecord {
+            ip: self.ip,
+            asn: Cow::Owned(self.asn.into_owned()),
+            as_name: Cow::Owned(self.as_name.into_owned()),
+            cidr: Cow::Owned(self.cidr.into_owned()),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Row<T> {
+    start: T,
+    end: T,
+    cidr: String,
+    asn: String,
+    as_name: String,
+}
+
+/// An IP2Location ASN database held in memory, answering
+/// `asn_lookup` with a binary search over its ranges.
+///
+/// The ASN product is read from its CSV files: `ip_from`, `ip_to` (as
+/// integers or addresses), `cidr`, `asn` and `as`. IPv4-mapped ranges of
+/// the IPv6 file answer IPv4 lookups.
+#[derive(Debug, Default)]
+pub struct AsnDB {
+    ipv4: Vec<Row<u32>>,
+    ipv6: Vec<Row<u128>>,
+}
+
+impl AsnDB {
+    pub fn from_csv_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        //! Loads an ASN database from its CSV file.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::AsnDB;
+        //!
+        //! let path = std::env::temp_dir().join("ip2location-asn-doc.csv");
+        //! std::fs::write(&path, "\"16778240\",\"16779263\",\"1.0.4.0/22\",\"38803\",\"Wirefreebroadband Pty Ltd\"\n").unwrap();
+        //! let db = AsnDB::from_csv_file(&path).unwrap();
+        //! let record = db.asn_lookup("1.0.5.1".parse().unwrap()).unwrap();
+        //! assert_eq!(record.asn, "38803");
+        //! assert_eq!(record.cidr, "1.0.4.0/22");
+        //!```
+        Self::from_csv(File::open(path)?)
+    }
+
+    /// Loads an ASN database from CSV rows without a header.
+    pub fn from_csv<R: Read>(reader: R) -> Result<Self, Error> {
+        let mut db = Self::default();
+        let mut rdr = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(reader);
+        for (line, row) in rdr.records().enumerate() {
+            let row = row?;
+            let err = |msg: &str| Error::GenericError(format!("line {}: {}", line + 1, msg));
+            if row.len() != 5 {
+                return Err(err(&format!("expected 5 columns, found {}", row.len())));
+            }
+            let start = parse_ip(&row[0]).ok_or_else(|| err("invalid ip_from"))?;
+            let end = parse_ip(&row[1]).ok_or_else(|| err("invalid ip_to"))?;
+            // The IPv6 file starts at "0", which reads as an IPv4 number.
+            let (start, end) = match (start, end) {
+                (IpAddr::V4(start), IpAddr::V6(end)) => (
+                    IpAddr::V6(Ipv6Addr::from(u32::from(start) as u128)),
+                    IpAddr::V6(end),
+                ),
+                range => range,
+            };
+            let (cidr, asn, as_name) = (row[2].to_string(), row[3].to_string(), row[4].to_string());
+            match (start.to_canonical(), end.to_canonical()) {
+                (IpAddr::V4(start), IpAddr::V4(end)) if start <= end => db.ipv4.push(Row {
+                    start: start.into(),
+                    end: end.into(),
+                    cidr,
+                    asn,
+                    as_name,
+                }),
+                (IpAddr::V6(start), IpAddr::V6(end)) if start <= end => db.ipv6.push(Row {
+                    start: start.into(),
+                    end: end.into(),
+                    cidr,
+                    asn,
+                    as_name,
+                }),
+                _ => return Err(err("invalid range")),
+            }
+        }
+        db.ipv4.sort_by_key(|row| row.start);
+        db.ipv6.sort_by_key(|row| row.start);
+        Ok(db)
+    }
+
+    /// Number of IPv4 and IPv6 ranges.
+    pub fn len(&self) -> usize {
+        self.ipv4.len() + self.ipv6.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Looks up the AS of the address; `Error::RecordNotFound` when no
+    /// range contains it.
+    pub fn asn_lookup(&self, ip: IpAddr) -> Result<AsnRecord<'_>, Error> {
+        self.lookup(ip).map(|(_, record)| record)
+    }
+
+    /// Looks up the address and returns the matched range along with the
+    /// record.
+    pub(crate) fn lookup(&self, ip: IpAddr) -> Result<(IpRange, AsnRecord<'_>), Error> {
+        let (range, cidr, asn, as_name) = match ip.to_canonical() {
+            IpAddr::V4(ipv4) => {
+                let row = find(&self.ipv4, u32::from(ipv4)).ok_or(Error::RecordNotFound)?;
+                let range = IpRange {
+                    start: IpAddr::V4(Ipv4Addr::from(row.start)),
+                    end: IpAddr::V4(Ipv4Addr::from(row.end)),
+                };
+                (
+                    range,
+                    row.cidr.as_str(),
+                    row.asn.as_str(),
+                    row.as_name.as_str(),
+                )
+            }
+            IpAddr::V6(ipv6) => {
+                let row = find(&self.ipv6, u128::from(ipv6)).ok_or(Error::RecordNotFound)?;
+                let range = IpRange {
+                    start: IpAddr::V6(Ipv6Addr::from(row.start)),
+                    end: IpAddr::V6(Ipv6Addr::from(row.end)),
+                };
+                (
+                    range,
+                    row.cidr.as_str(),
+                    row.asn.as_str(),
+                    row.as_name.as_str(),
+                )
+            }
+        };
+        Ok((
+            range,
+            AsnRecord {
+                ip,
+                asn: Cow::Borrowed(asn),
+                as_name: Cow::Borrowed(as_name),
+                cidr: Cow::Borrowed(cidr),
+            },
+        ))
+    }
+}
+
+/// The row containing `ip`, in rows sorted by their start.
+fn find<T: Ord + Copy>(rows: &[Row<T>], ip: T) -> Option<&Row<T>> {
+    let after = rows.partition_point(|row| row.start <= ip);
+    let row = rows.get(after.checked_sub(1)?)?;
+    (ip <= row.end).then_some(row)
+}
@@ -0,0 +1,146 @@
+//! [axum](https://crates.io/crates/axum) integration, available with the
+//! `axum` feature.
+//!
+//! Add a `GeoIpConfig` to the router as an `Extension` and take `GeoIp` as a
+//! handler argument to receive the location of the client.
+//!
+//!```rust
+//! use axum::{routing::get, Extension, Router};
+//! use ip2location::{axum::{GeoIp, GeoIpConfig}, DB};
+//! use std::sync::Arc;
+//!
+//! async fn country(GeoIp(record): GeoIp) -> String {
+//!     record.country.map(|c| c.long_name.into_owned()).unwrap_or_default()
+//! }
+//!
+//! let db = Arc::new(DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap());
+//! let app: Router = Router::new()
+//!     .route("/country", get(country))
+//!     .layer(Extension(GeoIpConfig::new(db).trusted_proxies(1)));
+//!```
+use crate::{error::Error, LocationRecord, Record, DB};
+use ::axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::{request::Parts, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use std::{
+    fmt,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+
+/// How `GeoIp` finds the database and the client address. Must be added to
+/// the router as an `Extension`.
+#[derive(Debug, Clone)]
+pub struct GeoIpConfig {
+    db: Arc<DB>,
+    trusted_proxies: usize,
+}
+
+impl GeoIpConfig {
+    /// Uses the given location database and the peer address of the
+    /// connection as the client address.
+    pub fn new(db: Arc<DB>) -> Self {
+        Self {
+            db,
+            trusted_proxies: 0,
+        }
+    }
+
+    /// Number of reverse proxies in front of the server. When non zero the
+    /// client address is taken from `X-Forwarded-For`, skipping the entries
+    /// appended by the trusted proxies, instead of the peer address.
+    pub fn trusted_proxies(mut self, count: usize) -> Self {
+        self.trusted_proxies = count;
+        self
+    }
+
+    /// Returns the database used for lookups.
+    pub fn db(&self) -> &Arc<DB> {
+        &self.db
+    }
+
+    /// Resolves the client address from the `X-Forwarded-For` header, if
+    /// proxies are trusted, or from the peer address.
+    pub fn client_ip(&self, headers: &HeaderMap, peer: Option<IpAddr>) -> Option<IpAddr> {
+        crate::forwarded::client_ip(
+            headers
+                .get_all("x-forwarded-for")
+                .iter()
+                .filter_map(|value| value.to_str().ok()),
+            self.trusted_proxies,
+            peer,
+        )
+    }
+}
+
+/// Extractor resolving the client address and looking it up in the
+/// location database configured with `GeoIpConfig`.
+#[derive(Debug, Clone)]
+pub struct GeoIp(pub LocationRecord<'static>);
+
+/// Reasons the `GeoIp` extractor can fail.
+#[derive(Debug, PartialEq, Eq)]
+pub enum GeoIpRejection {
+    /// No `GeoIpConfig` extension was added to the router.
+    MissingConfig,
+    /// The client address could not be determined.
+    MissingClientIp,
+    /// The configured database is not an IP2Location database.
+    NotLocationDb,
+    /// The address is not covered by the database.
+    NotFound(IpAddr),
+    /// The lookup failed.
+    Lookup(Error),
+}
+
+impl fmt::Display for GeoIpRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingConfig => write!(f, "GeoIpConfig extension is missing"),
+            Self::MissingClientIp => write!(f, "could not determine the client address"),
+            Self::NotLocationDb => write!(f, "GeoIp requires an IP2Location database"),
+            Self::NotFound(ip) => write!(f, "no location found for {}", ip),
+            Self::Lookup(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for GeoIpRejection {}
+
+impl IntoResponse for GeoIpRejection {
+    fn into_response(self) -> Response {
+        let status = match self {
+            Self::MissingClientIp => StatusCode::BAD_REQUEST,
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for GeoIp {
+    type Rejection = GeoIpRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let config = parts
+            .extensions
+            .get::<GeoIpConfig>()
+            .cloned()
+            .ok_or(GeoIpRejection::MissingConfig)?;
+        let peer = ConnectInfo::<SocketAddr>::from_request_parts(parts, state)
+            .await
+            .ok()
+            .map(|ConnectInfo(addr)| crate::addr::socket_ip(addr));
+        let ip = config
+            .client_ip(&parts.headers, peer)
+            .ok_or(GeoIpRejection::MissingClientIp)?;
+        match config.db.ip_lookup(ip) {
+            Ok(Record::LocationDb(record)) => Ok(GeoIp(record.into_owned())),
+            Ok(Record::ProxyDb(_)) => Err(GeoIpRejection::NotLocationDb),
+            Err(Error::RecordNotFound) => Err(GeoIpRejection::NotFound(ip)),
+            Err(err) => Err(GeoIpRejection::Lookup(err)),
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use crate::error::Error;
+use serde::Serialize;
+use std::{
+    net::IpAddr,
+    time::{Duration, Instant},
+};
+
+/// Throughput and latency distribution measured by `DB::bench`.
+#[derive(PartialEq, Debug, Clone, Copy, Default, Serialize)]
+pub struct BenchReport {
+    pub lookups: u64,
+    /// Lookups that returned an error, including `RecordNotFound`.
+    pub errors: u64,
+    pub elapsed: Duration,
+    pub lookups_per_sec: f64,
+    pub min: Duration,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub p999: Duration,
+    pub max: Duration,
+}
+
+impl std::fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} lookups ({} errors) in {:?}: {:.0} lookups/sec",
+            self.lookups, self.errors, self.elapsed, self.lookups_per_sec
+        )?;
+        write!(
+            f,
+            "latency min {:?} p50 {:?} p90 {:?} p99 {:?} p99.9 {:?} max {:?}",
+            self.min, self.p50, self.p90, self.p99, self.p999, self.max
+        )
+    }
+}
+
+/// Times `lookup` for every address and summarizes the latencies.
+pub(crate) fn run<I, F, T>(ips: I, mut lookup: F) -> BenchReport
+where
+    I: IntoIterator<Item = IpAddr>,
+    F: FnMut(IpAddr) -> Result<T, Error>,
+{
+    let mut latencies = Vec::new();
+    let mut errors = 0;
+    let started = Instant::now();
+    for ip in ips {
+        let start = Instant::now();
+        let result = lookup(ip);
+        latencies.push(start.elapsed());
+        if result.is_err() {
+            errors += 1;
+        }
+        std::hint::black_box(result.ok());
+    }
+    let elapsed = started.elapsed();
+    report(latencies, errors, elapsed)
+}
+
+/// `run` on `threads` (at least one) threads, each timing the lookups of
+/// a slice of `ips`; the throughput is over the wall time of them all.
+pub(crate) fn run_threads<F, T>(ips: &[IpAddr], threads: usize, lookup: F) -> BenchReport
+where
+    F: Fn(IpAddr) -> Result<T, Error> + Sync,
+{
+    let per_thread = ips.len().div_ceil(threads.max(1)).max(1);
+    let started = Instant::now();
+    let runs: Vec<(Vec<Duration>, u64)> = std::thread::scope(|scope| {
+        let handles: Vec<_> = ips
+            .chunks(per_thread)
+            .map(|chunk| {
+                let lookup = &lookup;
+                scope.spawn(move || {
+                    let mut latencies = Vec::with_capacity(chunk.len());
+                    let mut errors = 0;
+                    for &ip in chunk {
+                        let start = Instant::now();
+                        let result = lookup(ip);
+                        latencies.push(start.elapsed());
+                        if result.is_err() {
+                            errors += 1;
+                        }
+                        std::hint::black_box(result.ok());
+                    }
+                    (latencies, errors)
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("bench thread panicked"))
+            .collect()
+    });
+    let elapsed = started.elapsed();
+    let errors = runs.iter().map(|(_, errors)| errors).sum();
+    let latencies = runs
+        .into_iter()
+        .flat_map(|(latencies, _)| latencies)
+        .collect();
+    report(latencies, errors, elapsed)
+}
+
+pub(crate) fn report(mut latencies: Vec<Duration>, errors: u64, elapsed: Duration) -> BenchReport {
+    if latencies.is_empty() {
+        return BenchReport {
+            elapsed,
+            ..Default::default()
+        };
+    }
+    latencies.sort_unstable();
+    let percentile = |p: f64| {
+        let rank = ((latencies.len() as f64 * p).ceil() as usize).clamp(1, latencies.len());
+        latencies[rank - 1]
+    };
+    let lookups = latencies.len() as u64;
+    BenchReport {
+        lookups,
+        errors,
+        elapsed,
+        lookups_per_sec: lookups as f64 / elapsed.as_secs_f64().max(f64::MIN_POSITIVE),
+        min: latencies[0],
+        p50: percentile(0.50),
+        p90: percentile(0.90),
+        p99: percentile(0.99),
+        p999: percentile(0.999),
+        max: latencies[latencies.len() - 1],
+    }
+}
@@ -0,0 +1,99 @@
+//! gRPC lookup server.
+//!
+//! Serves `Lookup` and `BatchLookup` from `proto/ip2location.proto` backed by
+//! a BIN database that is reloaded whenever its file changes.
+//!
+//! Usage: ip2location-grpc <DB> [LISTEN_ADDR] [RELOAD_SECS]
+use ip2location::{error::Error, proto, ReloadableDb};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+use tonic::{transport::Server, Request, Response, Status};
+
+/// The service and its requests; the records are those of `proto`.
+#[allow(clippy::large_enum_variant)]
+mod pb {
+    include!(concat!(env!("OUT_DIR"), "/grpc/ip2location.v1.rs"));
+}
+
+use pb::ip2_location_server::{Ip2Location, Ip2LocationServer};
+
+/// Upper bound on the number of addresses in a single `BatchLookup`.
+const MAX_BATCH: usize = 10_000;
+
+struct LookupServer {
+    db: Arc<ReloadableDb>,
+}
+
+impl LookupServer {
+    fn lookup(&self, ip: &str) -> Result<proto::Record, Status> {
+        let ip = ip
+            .parse()
+            .map_err(|_| Status::invalid_argument(format!("invalid IP address: {}", ip)))?;
+        match self.db.current().ip_lookup(ip) {
+            Ok(record) => Ok(record.into()),
+            Err(Error::RecordNotFound) => Err(Status::not_found(format!("no record for {}", ip))),
+            Err(err) => Err(Status::internal(err.to_string())),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl Ip2Location for LookupServer {
+    async fn lookup(
+        &self,
+        request: Request<pb::LookupRequest>,
+    ) -> Result<Response<pb::LookupResponse>, Status> {
+        let record = LookupServer::lookup(self, &request.into_inner().ip)?;
+        Ok(Response::new(pb::LookupResponse {
+            record: Some(record),
+        }))
+    }
+
+    async fn batch_lookup(
+        &self,
+        request: Request<pb::BatchLookupRequest>,
+    ) -> Result<Response<pb::BatchLookupResponse>, Status> {
+        let ips = request.into_inner().ips;
+        if ips.len() > MAX_BATCH {
+            return Err(Status::invalid_argument(format!(
+                "at most {} addresses per batch",
+                MAX_BATCH
+            )));
+        }
+        let results = ips
+            .into_iter()
+            .map(|ip| {
+                let result = match LookupServer::lookup(self, &ip) {
+                    Ok(record) => pb::batch_result::Result::Record(record),
+                    Err(status) => pb::batch_result::Result::Error(status.message().to_string()),
+                };
+                pb::BatchResult {
+                    ip,
+                    result: Some(result),
+                }
+            })
+            .collect();
+        Ok(Response::new(pb::BatchLookupResponse { results }))
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let path = args
+        .next()
+        .ok_or("usage: ip2location-grpc <DB> [LISTEN_ADDR] [RELOAD_SECS]")?;
+    let addr: SocketAddr = args
+        .next()
+        .unwrap_or_else(|| "127.0.0.1:50051".to_string())
+        .parse()?;
+    let reload_every = Duration::from_secs(args.next().map_or(Ok(30), |s| s.parse())?);
+
+    let db = Arc::new(ReloadableDb::open(&path)?);
+    let _watcher = db.watch(reload_every);
+    println!("Serving {} on {}", path, addr);
+    Server::builder()
+        .add_service(Ip2LocationServer::new(LookupServer { db }))
+        .serve(addr)
+        .await?;
+    Ok(())
+}
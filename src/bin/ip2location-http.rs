@@ -0,0 +1,27 @@
+//! HTTP/JSON lookup server.
+//!
+//! Serves `GET /v1/lookup/{ip}` and `GET /v1/info` from a BIN database that
+//! is reloaded whenever its file changes.
+//!
+//! Usage: ip2location-http <DB> [LISTEN_ADDR] [RELOAD_SECS]
+use ip2location::{server, ReloadableDb};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let path = args
+        .next()
+        .ok_or("usage: ip2location-http <DB> [LISTEN_ADDR] [RELOAD_SECS]")?;
+    let addr: SocketAddr = args
+        .next()
+        .unwrap_or_else(|| "127.0.0.1:8080".to_string())
+        .parse()?;
+    let reload_every = Duration::from_secs(args.next().map_or(Ok(30), |s| s.parse())?);
+
+    let db = Arc::new(ReloadableDb::open(&path)?);
+    let _watcher = db.watch(reload_every);
+    println!("Serving {} on http://{}", path, addr);
+    server::serve(db, addr).await?;
+    Ok(())
+}
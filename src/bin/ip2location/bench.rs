@@ -0,0 +1,124 @@
+use crate::{Failure, EXIT_OK};
+use ip2location::{Backend, DbOptions};
+use std::{
+    fs,
+    io::{self, Write},
+    net::IpAddr,
+    path::PathBuf,
+};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Path to the BIN database
+    #[arg(long, short)]
+    db: PathBuf,
+    /// File with one address per line to look up
+    #[arg(long, short)]
+    ips: PathBuf,
+    /// Number of lookup threads
+    #[arg(long, short = 'j', default_value_t = 1)]
+    threads: usize,
+    /// Comma-separated backends to measure; every available backend when
+    /// missing
+    #[arg(long, value_enum, value_delimiter = ',')]
+    backend: Vec<BackendArg>,
+    /// Print the reports as JSON, keyed by backend
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum BackendArg {
+    Mmap,
+    InMemory,
+    Compact,
+    #[cfg(any(unix, windows))]
+    Pread,
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    IoUring,
+}
+
+impl BackendArg {
+    const ALL: &'static [BackendArg] = &[
+        BackendArg::Mmap,
+        BackendArg::InMemory,
+        BackendArg::Compact,
+        #[cfg(any(unix, windows))]
+        BackendArg::Pread,
+        #[cfg(all(target_os = "linux", feature = "io-uring"))]
+        BackendArg::IoUring,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            BackendArg::Mmap => "mmap",
+            BackendArg::InMemory => "in-memory",
+            BackendArg::Compact => "compact",
+            #[cfg(any(unix, windows))]
+            BackendArg::Pread => "pread",
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            BackendArg::IoUring => "io-uring",
+        }
+    }
+
+    fn backend(self) -> Backend {
+        match self {
+            BackendArg::Mmap => Backend::Mmap,
+            BackendArg::InMemory => Backend::InMemory,
+            BackendArg::Compact => Backend::Compact,
+            #[cfg(any(unix, windows))]
+            BackendArg::Pread => Backend::Pread,
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            BackendArg::IoUring => Backend::IoUring,
+        }
+    }
+}
+
+/// Reads the addresses of the file, skipping blank lines.
+fn read_ips(path: &PathBuf) -> Result<Vec<IpAddr>, Failure> {
+    let text = fs::read_to_string(path)?;
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            line.parse()
+                .map_err(|_| Failure::Usage(format!("invalid IP address: {}", line)))
+        })
+        .collect()
+}
+
+pub fn run(args: &Args) -> Result<u8, Failure> {
+    let ips = read_ips(&args.ips)?;
+    let backends = if args.backend.is_empty() {
+        BackendArg::ALL
+    } else {
+        &args.backend
+    };
+    let mut reports = serde_json::Map::new();
+    let mut out = io::stdout().lock();
+    for &backend in backends {
+        let db = DbOptions::new()
+            .path(&args.db)
+            .backend(backend.backend())
+            .open()
+            .map_err(Failure::Db)?;
+        // One untimed pass, so every backend starts with the pages of the
+        // file in the page cache.
+        db.bench(ips.iter().copied());
+        let report = db.bench_threads(&ips, args.threads);
+        if args.json {
+            reports.insert(
+                backend.name().to_string(),
+                serde_json::to_value(report).expect("reports serialize to JSON"),
+            );
+        } else {
+            writeln!(out, "{} ({} threads)", backend.name(), args.threads.max(1))?;
+            writeln!(out, "{}", report)?;
+        }
+    }
+    if args.json {
+        serde_json::to_writer_pretty(&mut out, &reports).map_err(io::Error::from)?;
+        writeln!(out)?;
+    }
+    Ok(EXIT_OK)
+}
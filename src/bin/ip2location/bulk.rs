@@ -0,0 +1,126 @@
+use crate::{
+    lookup_row, open,
+    output::{Format, Row, RowWriter},
+    Failure, EXIT_NOT_FOUND, EXIT_OK,
+};
+use ip2location::{error::Error, DB};
+use serde_json::Value;
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    net::IpAddr,
+    path::PathBuf,
+    thread,
+};
+
+/// Number of lines handed to each thread at a time.
+const CHUNK: usize = 4096;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Path to the BIN database
+    #[arg(long, short)]
+    db: PathBuf,
+    /// File with one address per line; stdin when missing or "-"
+    #[arg(long, short)]
+    input: Option<PathBuf>,
+    /// File to write the results to; stdout when missing
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+    #[arg(long, short, value_enum, default_value = "jsonl")]
+    format: Format,
+    /// Comma-separated fields to write, in order (e.g.
+    /// `ip,country_short_name`); every field when missing
+    #[arg(long, value_delimiter = ',')]
+    fields: Vec<String>,
+    /// Number of lookup threads
+    #[arg(long, short = 'j', default_value_t = 1)]
+    threads: usize,
+}
+
+fn error_row(ip: &str, error: String) -> Row {
+    let mut row = Row::new();
+    row.insert("ip".to_string(), Value::String(ip.to_string()));
+    row.insert("error".to_string(), Value::String(error));
+    row
+}
+
+fn resolve(db: &DB, line: &str) -> Row {
+    match line.parse::<IpAddr>() {
+        Ok(ip) => lookup_row(db, ip).unwrap_or_else(|err| match err {
+            Error::RecordNotFound => error_row(line, "no record found".to_string()),
+            err => error_row(line, err.to_string()),
+        }),
+        Err(_) => error_row(line, format!("invalid IP address: {}", line)),
+    }
+}
+
+/// Resolves the lines, splitting them across the threads while keeping
+/// their order.
+fn resolve_all(db: &DB, lines: &[String], threads: usize) -> Vec<Row> {
+    if threads <= 1 {
+        return lines.iter().map(|line| resolve(db, line)).collect();
+    }
+    let per_thread = lines.len().div_ceil(threads).max(1);
+    thread::scope(|scope| {
+        let handles: Vec<_> = lines
+            .chunks(per_thread)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|line| resolve(db, line))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("lookup thread panicked"))
+            .collect()
+    })
+}
+
+pub fn run(args: &Args) -> Result<u8, Failure> {
+    let db = open(&args.db)?;
+    let input: Box<dyn BufRead> = match &args.input {
+        Some(path) if path.as_os_str() != "-" => Box::new(BufReader::new(File::open(path)?)),
+        _ => Box::new(io::stdin().lock()),
+    };
+    let output: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    let mut writer = RowWriter::new(output, args.format).fields(&args.fields);
+    let batch = CHUNK * args.threads.max(1);
+    let mut lines = input.lines();
+    let mut failed = 0_usize;
+    loop {
+        let mut chunk = Vec::with_capacity(batch);
+        for line in lines.by_ref() {
+            let line = line?;
+            let line = line.trim();
+            if !line.is_empty() {
+                chunk.push(line.to_string());
+            }
+            if chunk.len() == batch {
+                break;
+            }
+        }
+        if chunk.is_empty() {
+            break;
+        }
+        for row in resolve_all(&db, &chunk, args.threads) {
+            if row.contains_key("error") {
+                failed += 1;
+            }
+            writer.write(row)?;
+        }
+    }
+    writer.finish()?;
+    if failed > 0 {
+        eprintln!("{} addresses could not be resolved", failed);
+        return Ok(EXIT_NOT_FOUND);
+    }
+    Ok(EXIT_OK)
+}
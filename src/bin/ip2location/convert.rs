@@ -0,0 +1,41 @@
+use crate::{Failure, EXIT_OK};
+use ip2location::writer::BinWriter;
+use std::{fs::File, io::BufReader, path::PathBuf};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Path to the IP2Location CSV file
+    input: PathBuf,
+    /// Path of the BIN database to write
+    output: PathBuf,
+    /// Database type of the CSV file, e.g. 1 for DB1 (country only)
+    #[arg(long, short = 't', default_value_t = 1)]
+    db_type: u8,
+    /// Date written to the header, as YYYY-MM-DD
+    #[arg(long)]
+    date: Option<String>,
+}
+
+/// Parses `YYYY-MM-DD` into the two-digit year, month and day of the header.
+fn parse_date(date: &str) -> Option<(u8, u8, u8)> {
+    let mut parts = date.splitn(3, '-').map(|p| p.parse::<u16>().ok());
+    let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+    if !(2000..2100).contains(&year) || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    Some(((year - 2000) as u8, month as u8, day as u8))
+}
+
+pub fn run(args: &Args) -> Result<u8, Failure> {
+    let mut writer = BinWriter::new(args.db_type).map_err(|e| Failure::Usage(e.to_string()))?;
+    if let Some(date) = &args.date {
+        let (year, month, day) =
+            parse_date(date).ok_or_else(|| Failure::Usage(format!("invalid date: {}", date)))?;
+        writer = writer.date(year, month, day);
+    }
+    let input = BufReader::new(File::open(&args.input)?);
+    writer.read_csv(input).map_err(Failure::Db)?;
+    writer.write_to(&args.output).map_err(Failure::Db)?;
+    eprintln!("wrote {} ranges to {}", writer.len(), args.output.display());
+    Ok(EXIT_OK)
+}
@@ -0,0 +1,65 @@
+use crate::{
+    output::{self, Format, Row, RowWriter},
+    Failure, EXIT_OK,
+};
+use ip2location::{AccessPattern, DbOptions, Record, DB};
+use serde_json::Value;
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Path to the BIN database
+    db: PathBuf,
+    /// File to write the ranges to; stdout when missing
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+    #[arg(long, short, value_enum, default_value = "csv")]
+    format: Format,
+    /// Comma-separated fields to write, in order (e.g.
+    /// `ip,country_short_name`); every field when missing
+    #[arg(long, value_delimiter = ',')]
+    fields: Vec<String>,
+    /// Only export the ranges of these countries (ISO 3166-1 alpha-2 codes)
+    #[arg(long, short, value_delimiter = ',')]
+    country: Vec<String>,
+}
+
+fn country_code<'a>(record: &'a Record) -> Option<&'a str> {
+    match record {
+        Record::LocationDb(rec) => rec.country.as_ref().map(|c| c.short_name.as_ref()),
+        Record::ProxyDb(rec) => rec.country.as_ref().map(|c| c.short_name.as_ref()),
+    }
+}
+
+pub fn run(args: &Args) -> Result<u8, Failure> {
+    // The ranges are read in order, which Windows caches better when told.
+    let options = DbOptions::new().access_pattern(AccessPattern::Sequential);
+    let db = DB::from_file_with_options(&args.db, &options).map_err(Failure::Db)?;
+    let output: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    let mut writer = RowWriter::new(output, args.format).fields(&args.fields);
+    for item in db.ranges() {
+        let (range, record) = item.map_err(Failure::Db)?;
+        if !args.country.is_empty()
+            && !country_code(&record)
+                .is_some_and(|code| args.country.iter().any(|c| c.eq_ignore_ascii_case(code)))
+        {
+            continue;
+        }
+        let mut row = Row::new();
+        row.insert("start".to_string(), Value::String(range.start.to_string()));
+        row.insert("end".to_string(), Value::String(range.end.to_string()));
+        let fields =
+            output::flatten(serde_json::to_value(record).expect("records serialize to JSON"));
+        row.extend(fields.into_iter().filter(|(key, _)| key != "ip"));
+        writer.write(row)?;
+    }
+    writer.finish()?;
+    Ok(EXIT_OK)
+}
@@ -0,0 +1,264 @@
+//! Command line interface to IP2Location and IP2Proxy BIN databases.
+mod bench;
+mod bulk;
+mod convert;
+mod export;
+mod output;
+#[cfg(feature = "http-server")]
+mod serve;
+
+use clap::{Parser, Subcommand};
+use ip2location::{error::Error, DB};
+use output::{Format, Row, RowWriter};
+use std::{
+    io::{self, Write},
+    net::IpAddr,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+/// Exit code when every address was found.
+const EXIT_OK: u8 = 0;
+/// Exit code when at least one address is not in the database or, for
+/// `bulk`, is not a valid address, or when `verify` finds issues.
+const EXIT_NOT_FOUND: u8 = 1;
+/// Exit code for invalid arguments, as used by clap.
+const EXIT_USAGE: u8 = 2;
+/// Exit code when the database cannot be opened or read.
+const EXIT_DB_ERROR: u8 = 3;
+
+#[derive(Parser)]
+#[command(
+    name = "ip2location",
+    version,
+    about = "Query IP2Location and IP2Proxy BIN databases",
+    after_help = "Exit codes: 0 success, 1 address not found or verification failed, 2 invalid arguments, 3 database error"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Look up one or more addresses
+    Lookup {
+        /// Path to the BIN database
+        #[arg(long, short)]
+        db: PathBuf,
+        /// Addresses or host names to look up; every address of a host is
+        /// looked up
+        #[arg(required = true)]
+        ips: Vec<String>,
+        #[arg(long, short, value_enum, default_value = "json")]
+        format: Format,
+        /// Comma-separated fields to write, in order (e.g.
+        /// `ip,country_short_name`); every field when missing
+        #[arg(long, value_delimiter = ',')]
+        fields: Vec<String>,
+    },
+    /// Look up every address of a file or of stdin, one per line
+    ///
+    /// Writes one result per input line, in input order; addresses that
+    /// are invalid or not found get an `error` field instead of a record.
+    Bulk(bulk::Args),
+    /// Write every range of a database with its record
+    Export(export::Args),
+    /// Build a BIN database from an IP2Location CSV file
+    ///
+    /// The CSV columns are `ip_from`, `ip_to` (integers or addresses) and
+    /// the fields of the database type, in the order of the IP2Location
+    /// CSV distribution.
+    Convert(convert::Args),
+    /// Serve lookups over HTTP, reloading the database when its file
+    /// changes
+    ///
+    /// Endpoints: `GET /v1/lookup/{ip}` and `GET /v1/info`. Requires the
+    /// `http-server` feature.
+    #[cfg(feature = "http-server")]
+    Serve(serve::Args),
+    /// Fails with how to build `serve`, in a binary built without it
+    #[cfg(not(feature = "http-server"))]
+    #[command(hide = true)]
+    Serve {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Measure lookups per second and latency percentiles of a database
+    /// with each backend
+    ///
+    /// The addresses of the file, one per line, are split across the
+    /// threads; every backend is measured after an untimed pass over them.
+    Bench(bench::Args),
+    /// Print the header of a database
+    Info {
+        /// Path to the BIN database
+        db: PathBuf,
+        /// Print the header as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Scan a database for unsorted ranges, out-of-bounds pointers and
+    /// header inconsistencies
+    Verify {
+        /// Path to the BIN database
+        db: PathBuf,
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+        /// Only check the header, the order of the ranges and the index
+        /// tables, stopping at the first problem
+        #[arg(long)]
+        quick: bool,
+    },
+}
+
+/// Failure of a subcommand, mapped to the exit code of the process.
+enum Failure {
+    Usage(String),
+    Db(Error),
+    Io(io::Error),
+}
+
+impl From<io::Error> for Failure {
+    fn from(err: io::Error) -> Self {
+        Failure::Io(err)
+    }
+}
+
+fn open(path: &Path) -> Result<DB, Failure> {
+    DB::from_file(path).map_err(Failure::Db)
+}
+
+/// Looks up the address and flattens the result into an output row.
+fn lookup_row(db: &DB, ip: IpAddr) -> Result<Row, Error> {
+    db.ip_lookup(ip).map(|record| {
+        output::flatten(serde_json::to_value(record).expect("records serialize to JSON"))
+    })
+}
+
+fn lookup(db: &Path, ips: &[String], format: Format, fields: &[String]) -> Result<u8, Failure> {
+    let mut addrs = Vec::new();
+    for ip in ips {
+        match ip.parse::<IpAddr>() {
+            Ok(ip) => addrs.push(ip),
+            // Anything else is taken for a host name.
+            Err(_) => addrs.extend(
+                ip2location::dns::resolve_host(ip)
+                    .map_err(|_| Failure::Usage(format!("invalid IP address or host: {}", ip)))?,
+            ),
+        }
+    }
+    let db = open(db)?;
+    let mut code = EXIT_OK;
+    let mut writer = RowWriter::new(io::stdout().lock(), format).fields(fields);
+    let mut found = false;
+    for ip in addrs {
+        match lookup_row(&db, ip) {
+            Ok(row) => {
+                writer.write(row)?;
+                found = true;
+            }
+            Err(Error::RecordNotFound) => {
+                eprintln!("{}: no record found", ip);
+                code = EXIT_NOT_FOUND;
+            }
+            Err(err) => return Err(Failure::Db(err)),
+        }
+    }
+    if found {
+        writer.finish()?;
+    }
+    Ok(code)
+}
+
+fn info(db: &Path, json: bool) -> Result<u8, Failure> {
+    let info = open(db)?.info();
+    let mut out = io::stdout().lock();
+    if json {
+        serde_json::to_writer_pretty(&mut out, &info).map_err(io::Error::from)?;
+        writeln!(out)?;
+    } else {
+        write!(out, "{}", info)?;
+    }
+    Ok(EXIT_OK)
+}
+
+fn verify(db: &Path, json: bool, quick: bool) -> Result<u8, Failure> {
+    let db = open(db)?;
+    let mut out = io::stdout().lock();
+    if quick {
+        let result = db.check_invariants();
+        if json {
+            let issue = result.as_ref().err();
+            serde_json::to_writer_pretty(&mut out, &serde_json::json!({ "issue": issue }))
+                .map_err(io::Error::from)?;
+            writeln!(out)?;
+        } else {
+            match &result {
+                Ok(()) => writeln!(out, "no issue found")?,
+                Err(issue) => writeln!(out, "{}", issue)?,
+            }
+        }
+        return Ok(if result.is_ok() {
+            EXIT_OK
+        } else {
+            EXIT_NOT_FOUND
+        });
+    }
+    let report = db.verify();
+    if json {
+        serde_json::to_writer_pretty(&mut out, &report).map_err(io::Error::from)?;
+        writeln!(out)?;
+    } else {
+        write!(out, "{}", report)?;
+    }
+    Ok(if report.is_ok() {
+        EXIT_OK
+    } else {
+        EXIT_NOT_FOUND
+    })
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match &cli.command {
+        Command::Lookup {
+            db,
+            ips,
+            format,
+            fields,
+        } => lookup(db, ips, *format, fields),
+        Command::Bulk(args) => bulk::run(args),
+        Command::Export(args) => export::run(args),
+        Command::Convert(args) => convert::run(args),
+        #[cfg(feature = "http-server")]
+        Command::Serve(args) => serve::run(args),
+        #[cfg(not(feature = "http-server"))]
+        Command::Serve { .. } => Err(Failure::Usage(
+            "this binary was built without the `http-server` feature needed by `serve`; \
+             install it with `cargo install ip2location --features cli,http-server`"
+                .to_string(),
+        )),
+        Command::Bench(args) => bench::run(args),
+        Command::Info { db, json } => info(db, *json),
+        Command::Verify { db, json, quick } => verify(db, *json, *quick),
+    };
+    match result {
+        Ok(code) => ExitCode::from(code),
+        Err(Failure::Usage(msg)) => {
+            eprintln!("error: {}", msg);
+            ExitCode::from(EXIT_USAGE)
+        }
+        Err(Failure::Db(err)) => {
+            eprintln!("error: {}", err);
+            ExitCode::from(EXIT_DB_ERROR)
+        }
+        // A closed stdout (e.g. piping into `head`) is not an error.
+        Err(Failure::Io(err)) if err.kind() == io::ErrorKind::BrokenPipe => ExitCode::from(EXIT_OK),
+        Err(Failure::Io(err)) => {
+            eprintln!("error: {}", err);
+            ExitCode::from(EXIT_DB_ERROR)
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use serde_json::{Map, Value};
+use std::io::{self, Write};
+
+/// Output formats shared by the subcommands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    /// A single object, or an array of objects for several results
+    Json,
+    /// One object per line
+    Jsonl,
+    /// Comma-separated values with a header line, quoted where needed
+    Csv,
+    /// Tab-separated values with a header line; tabs, line breaks and
+    /// backslashes in values are escaped as `\t`, `\n`, `\r` and `\\`
+    Tsv,
+    /// Aligned columns for reading in a terminal
+    Table,
+}
+
+pub type Row = Map<String, Value>;
+
+/// Flattens a serialized record into `field -> value` pairs, turning
+/// nested objects such as `country` into `country_short_name` and
+/// `country_long_name`.
+pub fn flatten(value: Value) -> Row {
+    let mut row = Map::new();
+    if let Value::Object(fields) = value {
+        for (key, value) in fields {
+            match value {
+                Value::Object(nested) => {
+                    for (sub, value) in nested {
+                        row.insert(format!("{}_{}", key, sub), value);
+                    }
+                }
+                value => {
+                    row.insert(key, value);
+                }
+            }
+        }
+    }
+    row
+}
+
+fn cell(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(value) => value.to_string(),
+    }
+}
+
+/// Column names in order of first appearance across all rows.
+fn columns(rows: &[Row]) -> Vec<String> {
+    let mut columns: Vec<String> = Vec::new();
+    for row in rows {
+        for key in row.keys() {
+            if !columns.contains(key) {
+                columns.push(key.clone());
+            }
+        }
+    }
+    columns
+}
+
+/// Writes rows in one of the output formats.
+///
+/// `jsonl` rows are written as they come. `csv` and `tsv` rows are written
+/// once the header is known, which is taken from the first row that is
+/// not an error, so the columns of a record are not lost when the input
+/// starts with failed lookups. `json` and `table` need every row before
+/// writing.
+///
+/// With a field selection, rows only keep the selected fields, in the
+/// selected order, and the header is the selection itself; fields a row
+/// does not have are `null` in JSON and empty elsewhere. Error rows keep
+/// their `error` field.
+pub struct RowWriter<W: Write> {
+    out: W,
+    format: Format,
+    fields: Option<Vec<String>>,
+    columns: Option<Vec<String>>,
+    pending: Vec<Row>,
+}
+
+impl<W: Write> RowWriter<W> {
+    pub fn new(out: W, format: Format) -> Self {
+        Self {
+            out,
+            format,
+            fields: None,
+            columns: None,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Only writes the given fields; an empty list keeps every field.
+    pub fn fields(mut self, fields: &[String]) -> Self {
+        if !fields.is_empty() {
+            self.fields = Some(fields.to_vec());
+        }
+        self
+    }
+
+    fn select(&self, mut row: Row) -> Row {
+        let Some(fields) = &self.fields else {
+            return row;
+        };
+        let error = row.remove("error");
+        let mut selected: Row = fields
+            .iter()
+            .map(|field| (field.clone(), row.remove(field).unwrap_or(Value::Null)))
+            .collect();
+        if let Some(error) = error {
+            selected.insert("error".to_string(), error);
+        }
+        selected
+    }
+
+    pub fn write(&mut self, row: Row) -> io::Result<()> {
+        let row = self.select(row);
+        match self.format {
+            Format::Jsonl => {
+                serde_json::to_writer(&mut self.out, &row)?;
+                writeln!(self.out)
+            }
+            Format::Csv | Format::Tsv => match &self.columns {
+                Some(columns) => write_delimited_row(&mut self.out, self.format, columns, &row),
+                None if self.fields.is_none() && row.contains_key("error") => {
+                    self.pending.push(row);
+                    Ok(())
+                }
+                None => {
+                    self.pending.push(row);
+                    self.write_delimited_header()
+                }
+            },
+            Format::Json | Format::Table => {
+                self.pending.push(row);
+                Ok(())
+            }
+        }
+    }
+
+    fn write_delimited_header(&mut self) -> io::Result<()> {
+        let columns = self
+            .fields
+            .clone()
+            .unwrap_or_else(|| columns(&self.pending));
+        write_delimited_line(&mut self.out, self.format, &columns)?;
+        for row in std::mem::take(&mut self.pending) {
+            write_delimited_row(&mut self.out, self.format, &columns, &row)?;
+        }
+        self.columns = Some(columns);
+        Ok(())
+    }
+
+    /// Writes the buffered rows and flushes the output.
+    pub fn finish(mut self) -> io::Result<()> {
+        match self.format {
+            Format::Jsonl => {}
+            Format::Csv | Format::Tsv => {
+                if self.columns.is_none() && !self.pending.is_empty() {
+                    self.write_delimited_header()?;
+                }
+            }
+            Format::Json => {
+                let value = match &mut self.pending[..] {
+                    [row] => Value::Object(std::mem::take(row)),
+                    rows => Value::Array(
+                        rows.iter_mut()
+                            .map(|r| Value::Object(std::mem::take(r)))
+                            .collect(),
+                    ),
+                };
+                serde_json::to_writer_pretty(&mut self.out, &value)?;
+                writeln!(self.out)?;
+            }
+            Format::Table => write_table(&mut self.out, self.fields.take(), &self.pending)?,
+        }
+        self.out.flush()
+    }
+}
+
+fn write_delimited_row<W: Write>(
+    out: &mut W,
+    format: Format,
+    columns: &[String],
+    row: &Row,
+) -> io::Result<()> {
+    let values: Vec<String> = columns.iter().map(|c| cell(row.get(c))).collect();
+    write_delimited_line(out, format, &values)
+}
+
+/// Writes one CSV or TSV line. CSV values that contain a separator, a
+/// quote or a line break are quoted; TSV values have their tabs, line
+/// breaks and backslashes escaped.
+fn write_delimited_line<W: Write>(
+    out: &mut W,
+    format: Format,
+    values: &[String],
+) -> io::Result<()> {
+    if format == Format::Tsv {
+        let escaped: Vec<String> = values
+            .iter()
+            .map(|v| {
+                v.replace('\\', "\\\\")
+                    .replace('\t', "\\t")
+                    .replace('\n', "\\n")
+                    .replace('\r', "\\r")
+            })
+            .collect();
+        return writeln!(out, "{}", escaped.join("\t"));
+    }
+    let escaped: Vec<String> = values
+        .iter()
+        .map(|v| {
+            if v.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", v.replace('"', "\"\""))
+            } else {
+                v.clone()
+            }
+        })
+        .collect();
+    writeln!(out, "{}", escaped.join(","))
+}
+
+fn write_table<W: Write>(out: &mut W, fields: Option<Vec<String>>, rows: &[Row]) -> io::Result<()> {
+    let columns = fields.unwrap_or_else(|| columns(rows));
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| columns.iter().map(|c| cell(row.get(c))).collect())
+        .collect();
+    let widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, c)| cells.iter().map(|r| r[i].len()).fold(c.len(), usize::max))
+        .collect();
+    let line = |out: &mut W, values: &[String]| -> io::Result<()> {
+        let padded: Vec<String> = values
+            .iter()
+            .zip(&widths)
+            .map(|(v, w)| format!("{:<w$}", v, w = *w))
+            .collect();
+        writeln!(out, "{}", padded.join("  ").trim_end())
+    };
+    line(out, &columns)?;
+    for row in &cells {
+        line(out, row)?;
+    }
+    Ok(())
+}
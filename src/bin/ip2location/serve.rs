@@ -0,0 +1,27 @@
+use crate::{Failure, EXIT_OK};
+use ip2location::{server, ReloadableDb};
+use std::{net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Path to the BIN database
+    #[arg(long, short)]
+    db: PathBuf,
+    /// Address to listen on
+    #[arg(long, short, default_value = "127.0.0.1:8080")]
+    listen: SocketAddr,
+    /// Seconds between checks of the database file for changes
+    #[arg(long, default_value_t = 30)]
+    reload_secs: u64,
+}
+
+pub fn run(args: &Args) -> Result<u8, Failure> {
+    let db = Arc::new(ReloadableDb::open(&args.db).map_err(Failure::Db)?);
+    let _watcher = db.watch(Duration::from_secs(args.reload_secs));
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?;
+    eprintln!("Serving {} on http://{}", args.db.display(), args.listen);
+    runtime.block_on(server::serve(db, args.listen))?;
+    Ok(EXIT_OK)
+}
@@ -0,0 +1,6 @@
+//! Generates the Kotlin and Swift bindings of the `uniffi` feature.
+//!
+//! Usage: uniffi-bindgen generate --library <LIB> --language <kotlin|swift> --out-dir <DIR>
+fn main() {
+    uniffi::uniffi_bindgen_main()
+}
@@ -0,0 +1,376 @@
+use crate::{
+    bench::{self, BenchReport},
+    common::{Record, DB},
+    error::Error,
+    intern::Interner,
+    range::IpRange,
+    stats::MemoryStats,
+};
+use lru::LruCache;
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    net::IpAddr,
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    time::{Duration, Instant},
+};
+
+/// Storage for the records memoized by `CachedDb`.
+///
+/// Entries are keyed by the range that matched in the database. `get`
+/// receives the address actually searched for, i.e. the embedded IPv4
+/// address for IPv4-mapped, NAT64, 6to4 and Teredo inputs, and must
+/// return the record of a cached range containing it.
+pub trait LookupCache: Send + Sync {
+    fn get(&self, ip: IpAddr) -> Option<Record<'static>>;
+
+    fn put(&self, range: IpRange, record: Record<'static>);
+
+    /// Number of ranges currently held.
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Maximum number of ranges held, `usize::MAX` when unbounded.
+    fn capacity(&self) -> usize;
+
+    fn clear(&self);
+
+    /// Returns true if `ip` is in an unassigned range cached by
+    /// `put_miss` that has not yet expired.
+    fn get_miss(&self, _ip: IpAddr) -> bool {
+        false
+    }
+
+    /// Remembers until `expires` that the addresses of `range` are in no
+    /// row, so lookups of them fail with `Error::RecordNotFound`. The
+    /// default implementation does not cache misses.
+    fn put_miss(&self, _range: IpRange, _expires: Instant) {}
+}
+
+/// Hit/miss counters and occupancy of a `CachedDb`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Lookups answered with `Error::RecordNotFound` from a cached miss.
+    pub negative_hits: u64,
+    pub entries: usize,
+    pub capacity: usize,
+}
+
+impl CacheStats {
+    /// Fraction of lookups answered from the cache, 0.0 when unused.
+    pub fn hit_ratio(&self) -> f64 {
+        let total = self.hits + self.negative_hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            (self.hits + self.negative_hits) as f64 / total as f64
+        }
+    }
+}
+
+/// Position of an address on a single axis covering both tables.
+fn key(ip: IpAddr) -> (bool, u128) {
+    match ip {
+        IpAddr::V4(ipv4) => (false, u32::from(ipv4) as u128),
+        IpAddr::V6(ipv6) => (true, u128::from(ipv6)),
+    }
+}
+
+/// A cached range: the record of a row, or an unassigned range until it
+/// expires.
+enum Cached {
+    Found(Record<'static>),
+    Missing(Instant),
+}
+
+struct LruRanges {
+    entries: LruCache<(bool, u128), (u128, Cached)>,
+    starts: BTreeMap<(bool, u128), ()>,
+}
+
+impl LruRanges {
+    /// The start of the cached range containing `ip` and its entry.
+    fn find(&mut self, ip: IpAddr) -> Option<((bool, u128), &Cached)> {
+        let (v6, addr) = key(ip);
+        let (&start, _) = self.starts.range(..=(v6, addr)).next_back()?;
+        if start.0 != v6 {
+            return None;
+        }
+        match self.entries.get(&start) {
+            Some((end, cached)) if addr <= *end => Some((start, cached)),
+            _ => None,
+        }
+    }
+
+    fn push(&mut self, range: IpRange, cached: Cached) {
+        let start = key(range.start);
+        let (_, end) = key(range.end);
+        if let Some((evicted, _)) = self.entries.push(start, (end, cached)) {
+            if evicted != start {
+                self.starts.remove(&evicted);
+            }
+        }
+        self.starts.insert(start, ());
+    }
+}
+
+/// The default in-memory `LookupCache`: an LRU of ranges with an ordered
+/// index of range starts, so any address inside a cached range is a hit.
+/// Unassigned ranges are held in the same LRU as the records.
+pub struct LruLookupCache {
+    inner: Mutex<LruRanges>,
+}
+
+impl LruLookupCache {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            inner: Mutex::new(LruRanges {
+                entries: LruCache::new(capacity),
+                starts: BTreeMap::new(),
+            }),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, LruRanges> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl LookupCache for LruLookupCache {
+    fn get(&self, ip: IpAddr) -> Option<Record<'static>> {
+        match self.lock().find(ip)? {
+            (_, Cached::Found(record)) => Some(record.clone()),
+            (_, Cached::Missing(_)) => None,
+        }
+    }
+
+    fn put(&self, range: IpRange, record: Record<'static>) {
+        self.lock().push(range, Cached::Found(record));
+    }
+
+    fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    fn capacity(&self) -> usize {
+        self.lock().entries.cap().get()
+    }
+
+    fn clear(&self) {
+        let mut inner = self.lock();
+        inner.entries.clear();
+        inner.starts.clear();
+    }
+
+    fn get_miss(&self, ip: IpAddr) -> bool {
+        let mut inner = self.lock();
+        let start = match inner.find(ip) {
+            Some((_, Cached::Missing(expires))) if *expires > Instant::now() => return true,
+            Some((start, Cached::Missing(_))) => start,
+            _ => return false,
+        };
+        inner.entries.pop(&start);
+        inner.starts.remove(&start);
+        false
+    }
+
+    fn put_miss(&self, range: IpRange, expires: Instant) {
+        self.lock().push(range, Cached::Missing(expires));
+    }
+}
+
+/// A `DB` wrapper that memoizes lookups per matched range in a
+/// `LookupCache`.
+///
+/// Records are returned owned, so they are independent of the cache and
+/// of the underlying database.
+pub struct CachedDb<C: LookupCache = LruLookupCache> {
+    db: DB,
+    cache: C,
+    negative_ttl: Option<Duration>,
+    interner: Option<Arc<Interner>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    negative_hits: AtomicU64,
+}
+
+impl<C: LookupCache> std::fmt::Debug for CachedDb<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("CachedDb")
+            .field("db", &self.db)
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+impl CachedDb {
+    pub fn new<D: Into<DB>>(db: D, capacity: usize) -> Self {
+        //! Wraps a database with the default LRU cache holding up to
+        //! `capacity` ranges.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::{cache::CachedDb, DB};
+        //!
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! let cached = CachedDb::new(db, 10_000);
+        //! cached.ip_lookup("43.224.159.155".parse().unwrap()).unwrap();
+        //! cached.ip_lookup("43.224.159.156".parse().unwrap()).unwrap();
+        //! assert_eq!(cached.stats().hits, 1);
+        //!```
+        Self::with_cache(db, LruLookupCache::new(capacity))
+    }
+}
+
+impl<C: LookupCache> CachedDb<C> {
+    /// Wraps a database with a caller-provided cache implementation.
+    pub fn with_cache<D: Into<DB>>(db: D, cache: C) -> Self {
+        Self {
+            db: db.into(),
+            cache,
+            negative_ttl: None,
+            interner: None,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            negative_hits: AtomicU64::new(0),
+        }
+    }
+
+    pub fn with_negative_ttl(mut self, ttl: Duration) -> Self {
+        //! Also remembers the unassigned ranges of addresses that are not
+        //! in the database for `ttl`, so lookups of any address in them
+        //! return `Error::RecordNotFound` without searching again.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::{cache::CachedDb, DB};
+        //! use std::time::Duration;
+        //!
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! let cached = CachedDb::new(db, 10_000).with_negative_ttl(Duration::from_secs(300));
+        //!```
+        self.negative_ttl = Some(ttl);
+        self
+    }
+
+    pub fn with_interner(mut self, interner: Arc<Interner>) -> Self {
+        //! Interns the strings of the cached records in `interner`, so that
+        //! ranges sharing a country or an ISP share its string, and records
+        //! returned from the cache are cloned without copying strings.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::{cache::CachedDb, Interner, DB};
+        //! use std::sync::Arc;
+        //!
+        //! let interner = Arc::new(Interner::new());
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! let cached = CachedDb::new(db, 10_000).with_interner(interner.clone());
+        //! cached.ip_lookup("43.224.159.155".parse().unwrap()).unwrap();
+        //! assert!(!interner.is_empty());
+        //!```
+        self.interner = Some(interner);
+        self
+    }
+
+    pub fn ip_lookup(&self, ip: IpAddr) -> Result<Record<'static>, Error> {
+        //! Lookup for the given IPv4 or IPv6, answering from the cache when
+        //! a previous lookup matched the same range.
+        let target = self.db.target(ip);
+        let key = target.to_ip();
+        let mut record = match self.cache.get(key) {
+            Some(record) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                record
+            }
+            None if self.negative_ttl.is_some() && self.cache.get_miss(key) => {
+                self.negative_hits.fetch_add(1, Ordering::Relaxed);
+                return Err(Error::RecordNotFound);
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                let (range, record) = match self.db.lookup(ip) {
+                    Ok(found) => found,
+                    Err(Error::RecordNotFound) => {
+                        if let Some(ttl) = self.negative_ttl {
+                            if let Ok(range) = self.db.unassigned_range(target) {
+                                self.cache.put_miss(range, Instant::now() + ttl);
+                            }
+                        }
+                        return Err(Error::RecordNotFound);
+                    }
+                    Err(e) => return Err(e),
+                };
+                let record = match &self.interner {
+                    Some(interner) => record.into_interned(interner),
+                    None => record.into_owned(),
+                };
+                self.cache.put(range, record.clone());
+                record
+            }
+        };
+        record.set_ip(ip);
+        Ok(record)
+    }
+
+    /// Returns the hit/miss counters and the current cache occupancy.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            negative_hits: self.negative_hits.load(Ordering::Relaxed),
+            entries: self.cache.len(),
+            capacity: self.cache.capacity(),
+        }
+    }
+
+    /// Reports the memory used by the wrapped database and the number of
+    /// cached ranges.
+    pub fn memory_stats(&self) -> MemoryStats {
+        MemoryStats {
+            cache_entries: self.cache.len(),
+            ..self.db.memory_stats()
+        }
+    }
+
+    /// Looks up every address of `ips` through the cache and reports the
+    /// throughput and latency percentiles.
+    pub fn bench<I: IntoIterator<Item = IpAddr>>(&self, ips: I) -> BenchReport {
+        bench::run(ips, |ip| self.ip_lookup(ip))
+    }
+
+    /// Drops every cached range and resets the counters.
+    pub fn clear(&self) {
+        self.cache.clear();
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+        self.negative_hits.store(0, Ordering::Relaxed);
+    }
+
+    /// Returns the wrapped database.
+    pub fn db(&self) -> &DB {
+        &self.db
+    }
+
+    /// Returns the cache implementation.
+    pub fn cache(&self) -> &C {
+        &self.cache
+    }
+
+    pub fn into_inner(self) -> DB {
+        self.db
+    }
+}
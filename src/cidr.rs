@@ -0,0 +1,161 @@
+//! Queries over every address of a prefix.
+use crate::{common::Target, error::Error, ip2location::record::Country, IpRange, Record, DB};
+use alloc::{borrow::Cow, vec, vec::Vec};
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use ipnet::IpNet;
+
+/// The records of the addresses of a prefix, returned by
+/// `DB::lookup_cidr`. The `ip` of the records is the network address of
+/// the prefix.
+#[derive(PartialEq, Debug, Clone)]
+pub enum CidrLookup<'a> {
+    /// Every address of the prefix has this record, so an answer for one
+    /// of them holds for the whole prefix.
+    Uniform(Record<'a>),
+    /// The prefix spans ranges with different records: each distinct
+    /// record with the parts of the prefix having it, in address order.
+    Mixed(Vec<(Record<'a>, Vec<IpRange>)>),
+}
+
+impl CidrLookup<'_> {
+    /// Returns true if every address of the prefix has the same record.
+    pub fn is_uniform(&self) -> bool {
+        matches!(self, CidrLookup::Uniform(_))
+    }
+}
+
+/// The values of the addresses of a prefix having the most addresses,
+/// returned by `DB::aggregate_cidr`.
+#[derive(PartialEq, Debug, Clone)]
+pub struct CidrAggregate<'a> {
+    /// The number of addresses in the prefix, saturating at `u128::MAX`.
+    pub addresses: u128,
+    /// The country of the most addresses, if the database has countries.
+    pub country: Option<Dominant<Country<'a>>>,
+    /// The ISP of the most addresses, if the database has ISPs.
+    pub isp: Option<Dominant<Cow<'a, str>>>,
+}
+
+/// A value with the number of addresses of the prefix having it.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Dominant<T> {
+    pub value: T,
+    pub addresses: u128,
+    /// The fraction of the addresses of the prefix having the value.
+    pub share: f64,
+}
+
+/// Address counts of the values seen, in the order first seen.
+struct Tally<T>(Vec<(T, u128)>);
+
+impl<T: PartialEq> Tally<T> {
+    fn add(&mut self, value: Option<T>, addresses: u128) {
+        let Some(value) = value else { return };
+        match self.0.iter_mut().find(|(seen, _)| *seen == value) {
+            Some((_, count)) => *count = count.saturating_add(addresses),
+            None => self.0.push((value, addresses)),
+        }
+    }
+
+    /// The value with the most addresses, the first seen on a tie.
+    fn dominant(self, total: u128) -> Option<Dominant<T>> {
+        let mut best: Option<(T, u128)> = None;
+        for (value, addresses) in self.0 {
+            if best.as_ref().is_none_or(|(_, most)| addresses > *most) {
+                best = Some((value, addresses));
+            }
+        }
+        best.map(|(value, addresses)| Dominant {
+            value,
+            addresses,
+            share: addresses as f64 / total as f64,
+        })
+    }
+}
+
+/// The number of addresses in `range`, saturating at `u128::MAX`.
+fn size(range: &IpRange) -> u128 {
+    match (range.start, range.end) {
+        (IpAddr::V4(start), IpAddr::V4(end)) => u128::from(u32::from(end) - u32::from(start)) + 1,
+        (IpAddr::V6(start), IpAddr::V6(end)) => {
+            (u128::from(end) - u128::from(start)).saturating_add(1)
+        }
+        _ => 0,
+    }
+}
+
+/// The address after `ip`, unless it is the last one of its family.
+fn next(ip: IpAddr) -> Option<IpAddr> {
+    match ip {
+        IpAddr::V4(ip) => u32::from(ip)
+            .checked_add(1)
+            .map(|n| IpAddr::V4(Ipv4Addr::from(n))),
+        IpAddr::V6(ip) => u128::from(ip)
+            .checked_add(1)
+            .map(|n| IpAddr::V6(Ipv6Addr::from(n))),
+    }
+}
+
+/// Visits the ranges overlapping `net`, calling `visit` with the part of
+/// each inside the prefix and its record, whose `ip` is the network
+/// address. Fails with `Error::RecordNotFound` if an address of the
+/// prefix is in no range.
+pub(crate) fn for_each_part<'a>(
+    db: &'a DB,
+    net: IpNet,
+    mut visit: impl FnMut(IpRange, Record<'a>),
+) -> Result<(), Error> {
+    let (first, last) = (net.network(), net.broadcast());
+    let mut ip = first;
+    loop {
+        let target = match ip {
+            IpAddr::V4(ip) => Target::V4(u32::from(ip)),
+            IpAddr::V6(_) if db.range_count(true) == 0 => return Err(Error::RecordNotFound),
+            IpAddr::V6(ip) => Target::V6(ip),
+        };
+        let (range, mut record) = db.search(target)?;
+        record.set_ip(first);
+        let end = range.end.min(last);
+        visit(IpRange { start: ip, end }, record);
+        match next(end) {
+            Some(after) if end < last => ip = after,
+            _ => return Ok(()),
+        }
+    }
+}
+
+pub(crate) fn lookup_cidr(db: &DB, net: IpNet) -> Result<CidrLookup<'_>, Error> {
+    let mut records: Vec<(Record<'_>, Vec<IpRange>)> = Vec::new();
+    for_each_part(db, net, |part, record| {
+        match records.iter_mut().find(|(seen, _)| *seen == record) {
+            Some((_, parts)) => parts.push(part),
+            None => records.push((record, vec![part])),
+        }
+    })?;
+    if records.len() == 1 {
+        let (record, _) = records.remove(0);
+        Ok(CidrLookup::Uniform(record))
+    } else {
+        Ok(CidrLookup::Mixed(records))
+    }
+}
+
+pub(crate) fn aggregate_cidr(db: &DB, net: IpNet) -> Result<CidrAggregate<'_>, Error> {
+    let mut addresses = 0u128;
+    let (mut countries, mut isps) = (Tally(Vec::new()), Tally(Vec::new()));
+    for_each_part(db, net, |part, record| {
+        let size = size(&part);
+        addresses = addresses.saturating_add(size);
+        let (country, isp) = match record {
+            Record::LocationDb(rec) => (rec.country, rec.isp),
+            Record::ProxyDb(rec) => (rec.country, rec.isp),
+        };
+        countries.add(country, size);
+        isps.add(isp, size);
+    })?;
+    Ok(CidrAggregate {
+        addresses,
+        country: countries.dominant(addresses),
+        isp: isps.dominant(addresses),
+    })
+}
@@ -1,14 +1,46 @@
+#[cfg(feature = "std")]
+use crate::bench::{self, BenchReport};
+#[cfg(all(feature = "std", any(unix, windows)))]
+use crate::file::FileSource;
 use crate::{
+    addr::{socket_ip, IntoIpAddr},
+    cidr::{self, CidrAggregate, CidrLookup},
+    country_only::CountryOnlyRecord,
     error::Error,
+    hooks::LookupEvent,
+    info::DbInfo,
     ip2location::{db::LocationDB, record::LocationRecord},
     ip2proxy::{db::ProxyDB, record::ProxyRecord},
+    offset::{header, FileOffset},
+    options::{DbOptions, Ipv6Translation},
+    range::{IpRange, Ranges},
+    raw::{IpFamily, RawRow},
+    schema::{ColumnValue, Field},
+    shared::SharedRecord,
+    source::{self, Source},
+    statistics::{self, DbStatistics},
+    stats::{LookupStats, MemoryStats},
+    verify::{Issue, VerifyReport},
 };
-use memmap2::Mmap;
-use std::{
+use alloc::{
     borrow::Cow,
-    net::{IpAddr, Ipv6Addr},
-    path::{Path, PathBuf},
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    sync::Arc,
+    vec,
+    vec::Vec,
+};
+use core::{
+    fmt,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
 };
+use ipnet::IpNet;
+#[cfg(feature = "mmap")]
+use memmap2::Mmap;
+use serde::Serialize;
+#[cfg(feature = "std")]
+use std::path::Path;
 
 // Constants for IPV6 Address
 pub const FROM_6TO4: u128 = 0x2002_0000_0000_0000_0000_0000_0000_0000;
@@ -16,78 +48,860 @@ pub const TO_6TO4: u128 = 0x2002_ffff_ffff_ffff_ffff_ffff_ffff_ffff;
 pub const FROM_TEREDO: u128 = 0x2001_0000_0000_0000_0000_0000_0000_0000;
 pub const TO_TEREDO: u128 = 0x2001_0000_ffff_ffff_ffff_ffff_ffff_ffff;
 
+/// Size of the header at the start of a database file.
+#[cfg(feature = "std")]
+const HEADER_LEN: usize = 64;
+
+/// Number of `(low, high)` pairs in an index table, one per /16 block.
+pub const INDEX_ENTRIES: usize = 65536;
+
 #[derive(Debug)]
 pub enum DB {
     LocationDb(LocationDB),
     ProxyDb(ProxyDB),
 }
 
-#[derive(Debug)]
+// Lookups take `&self`, so the databases are shared between threads
+// behind a plain `Arc`; keep them `Send + Sync` whatever the backend.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<DB>();
+    assert_send_sync::<LocationDB>();
+    assert_send_sync::<ProxyDB>();
+};
+
+/// The record of an address: a `LocationRecord` from an IP2Location
+/// database or a `ProxyRecord` from an IP2Proxy one.
+///
+/// Records borrow their strings from the database they were read from.
+/// `into_owned` turns any of them into a `Record<'static>` (or
+/// `LocationRecord<'static>`, `ProxyRecord<'static>`) to keep it around,
+/// e.g. in a cache or across a reload; there is no separate owned type.
+/// `into_interned` does the same with strings shared through an
+/// `Interner`.
+/// `into_shared` converts them into a `SharedRecord`, whose strings are
+/// `Arc<str>`.
+/// `ip_lookup_owned` looks up an `OwnedRecord` directly.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Serialize)]
+#[serde(untagged)]
 pub enum Record<'a> {
     LocationDb(Box<LocationRecord<'a>>),
     ProxyDb(Box<ProxyRecord<'a>>),
 }
 
+/// A `Record` owning its strings, as returned by `DB::ip_lookup_owned`.
+pub type OwnedRecord = Record<'static>;
+
+/// The address searched for in the database once the IPv6 translations
+/// (IPv4-mapped, NAT64, 6to4 and Teredo) have been applied.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub(crate) enum Target {
+    V4(u32),
+    V6(Ipv6Addr),
+}
+
+impl Target {
+    pub fn from_ip(ip: IpAddr, translation: &Ipv6Translation) -> Self {
+        match ip {
+            IpAddr::V4(ipv4) => Target::V4(u32::from(ipv4)),
+            IpAddr::V6(ipv6) => {
+                let in_range =
+                    |from, to| Ipv6Addr::from(from) <= ipv6 && ipv6 <= Ipv6Addr::from(to);
+                if let Some(ipv4) = translation.ipv4_mapped(ipv6) {
+                    Target::V4(ipv4)
+                } else if let Some(ipv4) = translation.nat64(ipv6) {
+                    Target::V4(ipv4)
+                } else if translation.six_to_four && in_range(FROM_6TO4, TO_6TO4) {
+                    Target::V4((u128::from(ipv6) >> 80) as u32)
+                } else if translation.teredo && in_range(FROM_TEREDO, TO_TEREDO) {
+                    Target::V4(!u128::from(ipv6) as u32)
+                } else {
+                    Target::V6(ipv6)
+                }
+            }
+        }
+    }
+
+    #[cfg_attr(not(feature = "cache"), allow(dead_code))]
+    pub fn to_ip(self) -> IpAddr {
+        match self {
+            Target::V4(ipnum) => IpAddr::V4(Ipv4Addr::from(ipnum)),
+            Target::V6(ipv6) => IpAddr::V6(ipv6),
+        }
+    }
+}
+
+impl Record<'_> {
+    /// Converts the record into one that owns all of its strings, so it
+    /// can outlive the database it was read from.
+    pub fn into_owned(self) -> Record<'static> {
+        match self {
+            Record::LocationDb(rec) => Record::LocationDb(Box::new(rec.into_owned())),
+            Record::ProxyDb(rec) => Record::ProxyDb(Box::new(rec.into_owned())),
+        }
+    }
+
+    pub fn into_shared(self) -> SharedRecord {
+        //! Converts the record into one whose strings are `Arc<str>`, which
+        //! outlives the database and is cloned without copying strings.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::{SharedRecord, DB};
+        //!
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! let record = db.ip_lookup("43.224.159.155".parse().unwrap()).unwrap().into_shared();
+        //! let clone = record.clone();
+        //! std::thread::spawn(move || {
+        //!     if let SharedRecord::LocationDb(rec) = clone {
+        //!         println!("{:?}", rec.country);
+        //!     }
+        //! });
+        //!```
+        self.into()
+    }
+
+    pub fn to_value(&self, fields: &[Field]) -> serde_json::Value {
+        //! The record as a JSON object with only the given fields, named as
+        //! in `COLUMNS` (`country` flattened into `country_short_name` and
+        //! `country_long_name`). Fields the record does not have, e.g. those
+        //! of the other kind of database, are left out.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::{Field, DB};
+        //!
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! let record = db.ip_lookup("43.224.159.155".parse().unwrap()).unwrap();
+        //! let value = record.to_value(&[Field::Ip, Field::CountryShortName, Field::City]);
+        //! assert_eq!(value["country_short_name"], "IN");
+        //! assert_eq!(value.as_object().unwrap().len(), 2);
+        //!```
+        let mut object = serde_json::Map::new();
+        for field in fields {
+            let value = match self {
+                Record::LocationDb(rec) => rec.column(field.name()),
+                Record::ProxyDb(rec) => rec.column(field.name()),
+            };
+            let value = match value {
+                Some(ColumnValue::Ip(ip)) => serde_json::Value::String(ip.to_string()),
+                // Through the shortest decimal form of the f32, as in
+                // `to_json`, rather than its exact value as an f64.
+                Some(ColumnValue::Float(value)) => format!("{}", value)
+                    .parse::<f64>()
+                    .map_or(serde_json::Value::Null, serde_json::Value::from),
+                Some(ColumnValue::Text(value)) => serde_json::Value::String(value.into()),
+                None => continue,
+            };
+            object.insert(field.name().into(), value);
+        }
+        serde_json::Value::Object(object)
+    }
+
+    /// The record as MessagePack, like `LocationRecord::to_msgpack`; it
+    /// is read back with `LocationRecord::from_msgpack` or
+    /// `ProxyRecord::from_msgpack` depending on the database.
+    #[cfg(feature = "msgpack")]
+    pub fn to_msgpack(&self) -> Vec<u8> {
+        match self {
+            Record::LocationDb(rec) => rec.to_msgpack(),
+            Record::ProxyDb(rec) => rec.to_msgpack(),
+        }
+    }
+
+    pub(crate) fn set_ip(&mut self, ip: IpAddr) {
+        match self {
+            Record::LocationDb(rec) => rec.ip = ip,
+            Record::ProxyDb(rec) => rec.ip = ip,
+        }
+    }
+}
+
+impl From<LocationDB> for DB {
+    fn from(db: LocationDB) -> Self {
+        DB::LocationDb(db)
+    }
+}
+
+impl From<ProxyDB> for DB {
+    fn from(db: ProxyDB) -> Self {
+        DB::ProxyDb(db)
+    }
+}
+
+/// Bytes of a database: a mapping of its file, a buffer for databases
+/// read into memory or handed over by the caller, the file itself, read
+/// on demand, or a source of the caller.
 #[derive(Debug)]
-pub(crate) struct Source {
-    path: PathBuf,
-    map: Mmap,
+enum Bytes {
+    #[cfg(feature = "mmap")]
+    Mapped(Mmap),
+    Shared(Arc<[u8]>),
+    #[cfg(all(feature = "std", any(unix, windows)))]
+    File(Box<FileSource>),
+    Custom(CustomSource),
+}
+
+/// A `Source` of the caller, read on demand like a file.
+struct CustomSource(Box<dyn Source>);
+
+impl fmt::Debug for CustomSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CustomSource(<{} bytes>)", self.0.len())
+    }
 }
 
-impl std::fmt::Display for Source {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{}", self.path.display())
+/// The source of the databases opened from a path or bytes, and of `DB`:
+/// the file mapped, read into memory or read on demand as the `Backend`
+/// of the options says, a buffer, or a boxed `Source` of the caller.
+#[derive(Debug)]
+pub struct DefaultSource(Bytes);
+
+impl DefaultSource {
+    /// Reads the database from a buffer. Clones share the buffer.
+    fn shared(bytes: Arc<[u8]>) -> Self {
+        DefaultSource(Bytes::Shared(bytes))
     }
 }
 
-impl Source {
-    pub fn new(path: PathBuf, map: Mmap) -> Self {
-        Self { path, map }
+impl Source for DefaultSource {
+    fn len(&self) -> u64 {
+        match &self.0 {
+            #[cfg(all(feature = "std", any(unix, windows)))]
+            Bytes::File(file) => file.len() as u64,
+            Bytes::Custom(CustomSource(source)) => source.len(),
+            _ => self.as_slice().map_or(0, |bytes| bytes.len() as u64),
+        }
     }
 
-    pub fn read_u8(&self, offset: u64) -> Result<u8, Error> {
-        Ok(self.map[(offset - 1) as usize])
+    fn read_exact_at(&self, offset: FileOffset, buf: &mut [u8]) -> Result<(), Error> {
+        match &self.0 {
+            #[cfg(all(feature = "std", any(unix, windows)))]
+            Bytes::File(file) => file.read_exact_at(offset.get(), buf),
+            Bytes::Custom(CustomSource(source)) => source.read_exact_at(offset, buf),
+            _ => {
+                let bytes = self.as_slice().unwrap_or_default();
+                buf.copy_from_slice(source::slice(bytes, offset, buf.len())?);
+                Ok(())
+            }
+        }
     }
 
-    pub fn read_u32(&self, offset: u64) -> Result<u32, Error> {
-        let result = u32::from_ne_bytes(
-            self.map[(offset - 1) as usize..(offset + 3) as usize]
-                .try_into()?,
-        );
-        Ok(result)
+    fn as_slice(&self) -> Option<&[u8]> {
+        match &self.0 {
+            #[cfg(feature = "mmap")]
+            Bytes::Mapped(map) => Some(map),
+            Bytes::Shared(bytes) => Some(bytes),
+            #[cfg(all(feature = "std", any(unix, windows)))]
+            Bytes::File(_) => None,
+            Bytes::Custom(_) => None,
+        }
+    }
+
+    fn read_batch(&self, reads: &mut [(FileOffset, &mut [u8])]) -> Result<Vec<usize>, Error> {
+        match &self.0 {
+            #[cfg(all(feature = "std", any(unix, windows)))]
+            Bytes::File(file) => {
+                let mut reads: Vec<(u64, &mut [u8])> = reads
+                    .iter_mut()
+                    .map(|(offset, buf)| (offset.get(), &mut **buf))
+                    .collect();
+                file.read_batch(&mut reads)
+            }
+            Bytes::Custom(CustomSource(source)) => source.read_batch(reads),
+            _ => {
+                let bytes = self.as_slice().unwrap_or_default();
+                Ok(reads
+                    .iter_mut()
+                    .map(|(offset, buf)| {
+                        let start = usize::try_from(offset.get())
+                            .unwrap_or(usize::MAX)
+                            .min(bytes.len());
+                        let len = buf.len().min(bytes.len() - start);
+                        buf[..len].copy_from_slice(&bytes[start..start + len]);
+                        len
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    /// All of a buffer, and for a mapping what `mincore` reports, where
+    /// available.
+    fn resident_len(&self) -> Option<u64> {
+        match &self.0 {
+            Bytes::Shared(bytes) => Some(bytes.len() as u64),
+            #[cfg(feature = "mmap")]
+            Bytes::Mapped(map) => mapped_resident_len(map).map(|len| len as u64),
+            #[cfg(all(feature = "std", any(unix, windows)))]
+            Bytes::File(_) => None,
+            Bytes::Custom(CustomSource(source)) => source.resident_len(),
+        }
+    }
+}
+
+/// A source with the limits and checks of the reads of a database.
+#[derive(Debug)]
+pub(crate) struct Storage<S> {
+    path: Option<String>,
+    map: S,
+    /// Longest string `read_str` decodes.
+    max_str_len: u8,
+    /// Byte ranges of the IPv4 and IPv6 rows, where no string pointer may
+    /// lead.
+    rows: [(FileOffset, FileOffset); 2],
+}
+
+impl<S: Source> fmt::Display for Storage<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "{}", path),
+            None if self.reads_file() => write!(f, "<source>"),
+            None => write!(f, "<memory>"),
+        }
+    }
+}
+
+impl Storage<DefaultSource> {
+    /// Opens the file at path with the backend of the options: mapped, then
+    /// applying the memory options, or read into memory. Without the
+    /// `mmap` feature the file is always read into memory.
+    #[cfg(feature = "std")]
+    pub fn open(path: &Path, options: &DbOptions) -> Result<Self, Error> {
+        let file = match options.backend {
+            #[cfg(any(unix, windows))]
+            crate::options::Backend::Pread => Some(FileSource::open(path, options.access)?),
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            crate::options::Backend::IoUring => {
+                Some(FileSource::open_io_uring(path, options.access)?)
+            }
+            _ => None,
+        };
+        let path_name = Some(path.display().to_string());
+        if let Some(file) = file {
+            return Ok(Self::with_path(
+                path_name,
+                DefaultSource(Bytes::File(Box::new(file))),
+            ));
+        }
+        // A compacted copy is built from the mapping, which is then dropped.
+        #[cfg(feature = "mmap")]
+        if matches!(
+            options.backend,
+            crate::options::Backend::Mmap | crate::options::Backend::Compact
+        ) {
+            return Self::map(path, options);
+        }
+        #[cfg(not(feature = "mmap"))]
+        let _ = options;
+        Ok(Self::with_path(
+            path_name,
+            DefaultSource::shared(std::fs::read(path)?.into()),
+        ))
     }
 
-    pub fn read_f32(&self, offset: u64) -> Result<f32, Error> {
-        let result = f32::from_ne_bytes(
-            self.map[(offset - 1) as usize..(offset + 3) as usize]
-                .try_into()?,
+    #[cfg(feature = "mmap")]
+    fn map(path: &Path, options: &DbOptions) -> Result<Self, Error> {
+        #[cfg(any(unix, windows))]
+        let db = crate::file::open(path, options.access)?;
+        #[cfg(not(any(unix, windows)))]
+        let db = std::fs::File::open(path)?;
+        let populate = options.populate && options.backend == crate::options::Backend::Mmap;
+        let mut map_options = memmap2::MmapOptions::new();
+        if populate {
+            // MAP_POPULATE on Linux and Android, ignored elsewhere.
+            map_options.populate();
+        }
+        let map = unsafe { map_options.map(&db) }?;
+        #[cfg(unix)]
+        if options.backend == crate::options::Backend::Mmap {
+            if options.access == crate::options::AccessPattern::Sequential {
+                map.advise(memmap2::Advice::Sequential)?;
+            }
+            for advice in &options.advice {
+                map.advise(advice.to_memmap())?;
+            }
+            if options.mlock {
+                map.lock()?;
+            }
+        }
+        let source = Self::with_path(
+            Some(path.display().to_string()),
+            DefaultSource(Bytes::Mapped(map)),
         );
-        Ok(result)
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        if populate {
+            source.warm();
+        }
+        Ok(source)
+    }
+
+    /// Reads the database from a buffer. Clones share the buffer.
+    pub fn from_bytes(bytes: Arc<[u8]>) -> Self {
+        Self::new(DefaultSource::shared(bytes))
+    }
+
+    /// Reads the database through a source of the caller.
+    pub fn from_custom(source: Box<dyn Source>) -> Self {
+        Self::new(DefaultSource(Bytes::Custom(CustomSource(source))))
+    }
+
+    /// A source reading `bytes` in place of this one, reported with the
+    /// same path.
+    pub fn with_bytes(&self, bytes: Arc<[u8]>) -> Self {
+        Self {
+            path: self.path.clone(),
+            map: DefaultSource::shared(bytes),
+            max_str_len: self.max_str_len,
+            rows: self.rows,
+        }
+    }
+}
+
+impl<S: Source> Storage<S> {
+    /// Reads the database from `source`.
+    pub fn new(source: S) -> Self {
+        Self::with_path(None, source)
+    }
+
+    fn with_path(path: Option<String>, source: S) -> Self {
+        Self {
+            path,
+            map: source,
+            max_str_len: u8::MAX,
+            rows: Default::default(),
+        }
+    }
+
+    /// Records where the rows of a table of `count` rows of `row_size`
+    /// bytes start, for `check_str_pointer`.
+    pub fn set_rows(&mut self, ipv6: bool, start: FileOffset, count: u32, row_size: u64) {
+        self.rows[usize::from(ipv6)] = (start, start + u64::from(count) * row_size);
+    }
+
+    /// Fails with `Error::Corrupt` unless the `offset` of a string is
+    /// inside the file and outside of the rows.
+    fn check_str_pointer(&self, offset: FileOffset) -> Result<(), Error> {
+        if offset.get() >= self.len() as u64 {
+            return Err(Error::Corrupt(format!(
+                "string pointer {} past the end of the file ({} bytes)",
+                offset,
+                self.len()
+            )));
+        }
+        if let Some((start, end)) = self
+            .rows
+            .iter()
+            .find(|&&(start, end)| (start..end).contains(&offset))
+        {
+            return Err(Error::Corrupt(format!(
+                "string pointer {} into the rows at {}..{}",
+                offset, start, end
+            )));
+        }
+        Ok(())
+    }
+
+    /// Applies the limits of the options to the reads of the source.
+    pub fn with_options(mut self, options: &DbOptions) -> Self {
+        self.max_str_len = options.max_str_len.unwrap_or(u8::MAX);
+        self
+    }
+
+    /// Returns true if the database is read from its file or source on
+    /// demand rather than from memory.
+    pub fn reads_file(&self) -> bool {
+        self.map.as_slice().is_none()
+    }
+
+    /// The `len` bytes in memory at `offset`, or `Error::Corrupt` if they
+    /// are not all in the database.
+    fn slice(&self, offset: FileOffset, len: usize) -> Result<&[u8], Error> {
+        source::slice(self.map.as_slice().unwrap_or_default(), offset, len)
+    }
+
+    /// Reads `N` bytes at `offset`.
+    fn read_array<const N: usize>(&self, offset: FileOffset) -> Result<[u8; N], Error> {
+        if self.map.as_slice().is_some() {
+            return Ok(self.slice(offset, N)?.try_into()?);
+        }
+        let mut buf = [0; N];
+        self.read_exact_at(offset, &mut buf)?;
+        Ok(buf)
+    }
+
+    /// Reads `len` bytes at `offset`, in a single read for databases read
+    /// from their file or a source.
+    pub fn read_bytes(&self, offset: FileOffset, len: usize) -> Result<Cow<'_, [u8]>, Error> {
+        if self.map.as_slice().is_some() {
+            return Ok(Cow::Borrowed(self.slice(offset, len)?));
+        }
+        let mut buf = vec![0; len];
+        self.read_exact_at(offset, &mut buf)?;
+        Ok(Cow::Owned(buf))
+    }
+
+    /// Fills `buf` from `offset`, failing with `Error::Corrupt` past the end
+    /// of the database. The source is only asked for reads that fit.
+    fn read_exact_at(&self, offset: FileOffset, buf: &mut [u8]) -> Result<(), Error> {
+        let fits = offset
+            .get()
+            .checked_add(buf.len() as u64)
+            .is_some_and(|end| end <= self.map.len());
+        if !fits {
+            return Err(source::past_end(offset, buf.len()));
+        }
+        self.map.read_exact_at(offset, buf)
+    }
+
+    /// Fills each buffer from its offset with a single batch of reads,
+    /// returning the number of bytes read into each: less than its length
+    /// only at the end of the database.
+    pub fn read_batch(&self, reads: &mut [(FileOffset, &mut [u8])]) -> Result<Vec<usize>, Error> {
+        self.map.read_batch(reads)
+    }
+
+    pub fn read_u8(&self, offset: FileOffset) -> Result<u8, Error> {
+        Ok(self.read_array::<1>(offset)?[0])
+    }
+
+    pub fn read_u32(&self, offset: FileOffset) -> Result<u32, Error> {
+        Ok(u32::from_le_bytes(self.read_array(offset)?))
+    }
+
+    pub fn read_f32(&self, offset: FileOffset) -> Result<f32, Error> {
+        Ok(f32::from_le_bytes(self.read_array(offset)?))
+    }
+
+    /// Reads the string whose length byte is at `offset`.
+    pub fn read_str(&self, offset: FileOffset) -> Result<Cow<'_, str>, Error> {
+        self.check_str_pointer(offset)?;
+        match self.map.as_slice() {
+            Some(bytes) => {
+                let buf = usize::try_from(offset.get())
+                    .ok()
+                    .and_then(|offset| bytes.get(offset..))
+                    .unwrap_or_default();
+                decode_str(buf, offset, self.max_str_len)
+            }
+            None => {
+                // The length byte and the longest string in one read.
+                let mut buf = [0; MAX_STR_READ];
+                let read = self.read_batch(&mut [(offset, &mut buf)])?[0];
+                decode_str(&buf[..read], offset, self.max_str_len)
+                    .map(|s| Cow::Owned(s.into_owned()))
+            }
+        }
+    }
+
+    /// Reads one byte of every page of the mapping so the whole file is
+    /// faulted into memory. A database read from its file or a source is
+    /// read through once, filling the page cache.
+    pub fn warm(&self) {
+        const PAGE_SIZE: usize = 4096;
+        let Some(bytes) = self.map.as_slice() else {
+            let mut buf = vec![0; 1 << 20];
+            for offset in (0..self.len()).step_by(buf.len()) {
+                let offset = FileOffset::new(offset as u64);
+                if self.read_batch(&mut [(offset, &mut buf)]).is_err() {
+                    break;
+                }
+            }
+            return;
+        };
+        let mut checksum = 0_u8;
+        for offset in (0..bytes.len()).step_by(PAGE_SIZE) {
+            checksum ^= bytes[offset];
+        }
+        core::hint::black_box(checksum);
+    }
+
+    /// Size of the database in bytes.
+    pub fn len(&self) -> usize {
+        usize::try_from(self.map.len()).unwrap_or(usize::MAX)
+    }
+
+    /// Number of bytes of the database resident in RAM, if known.
+    pub fn resident_len(&self) -> Option<usize> {
+        self.map
+            .resident_len()
+            .map(|len| usize::try_from(len).unwrap_or(usize::MAX))
+    }
+
+    /// Decodes a /16 index table of `(low, high)` row pairs at the 1-based
+    /// position `base` of the header. Returns an empty table when the
+    /// database has no index.
+    pub fn read_index(&self, base: u32) -> Result<Vec<u32>, Error> {
+        if base == 0 {
+            return Ok(Vec::new());
+        }
+        let table = self.read_bytes(FileOffset::from_position(base), INDEX_ENTRIES * 8)?;
+        Ok(table
+            .chunks_exact(4)
+            .map(|entry| u32::from_le_bytes(entry.try_into().unwrap()))
+            .collect())
+    }
+
+    pub fn read_ipv6(&self, offset: FileOffset) -> Result<Ipv6Addr, Error> {
+        Ok(Ipv6Addr::from(u128::from_le_bytes(
+            self.read_array(offset)?,
+        )))
+    }
+}
+
+/// Bytes read for a string of a database read from its file: the length
+/// byte and up to 255 bytes of text.
+const MAX_STR_READ: usize = 256;
+
+/// Decodes a string read at `offset` as its length byte followed by its
+/// text, failing if it is longer than `max`.
+fn decode_str(buf: &[u8], offset: FileOffset, max: u8) -> Result<Cow<'_, str>, Error> {
+    let past_end = || Error::Corrupt(format!("string at {} past the end of the file", offset));
+    let len = *buf.first().ok_or_else(past_end)?;
+    if len > max {
+        return Err(Error::StringTooLong {
+            offset: offset.get(),
+            len,
+            max,
+        });
+    }
+    let text = buf.get(1..1 + len as usize).ok_or_else(past_end)?;
+    Ok(String::from_utf8_lossy(text))
+}
+
+/// The fields of a record are read through this trait, so the decoding
+/// code serves both a `Storage` and a `RowReader`.
+pub(crate) trait RowSource<'a> {
+    fn read_u32(&self, offset: FileOffset) -> Result<u32, Error>;
+    fn read_f32(&self, offset: FileOffset) -> Result<f32, Error>;
+    fn read_str(&self, offset: FileOffset) -> Result<Cow<'a, str>, Error>;
+}
+
+impl<'a, S: Source> RowSource<'a> for &'a Storage<S> {
+    fn read_u32(&self, offset: FileOffset) -> Result<u32, Error> {
+        Storage::read_u32(self, offset)
+    }
+
+    fn read_f32(&self, offset: FileOffset) -> Result<f32, Error> {
+        Storage::read_f32(self, offset)
+    }
+
+    fn read_str(&self, offset: FileOffset) -> Result<Cow<'a, str>, Error> {
+        Storage::read_str(self, offset)
+    }
+}
+
+/// A row of a database read from its file, fetched with the strings its
+/// columns point to in one batch of reads, so decoding a record does not
+/// wait for one read per field.
+pub(crate) struct RowReader<'a, S> {
+    source: &'a Storage<S>,
+    /// Offset of the first byte of `row`.
+    offset: FileOffset,
+    row: Vec<u8>,
+    /// Offsets of the strings and their bytes, as `read_str` reads them.
+    strings: Vec<(FileOffset, Vec<u8>)>,
+}
+
+impl<'a, S: Source> RowReader<'a, S> {
+    /// Reads the `words` 4-byte columns at `offset`, then the strings
+    /// pointed to by the columns at `pointers` (0-based indices) and the
+    /// long country name after the short one at `country`.
+    pub fn new(
+        source: &'a Storage<S>,
+        offset: FileOffset,
+        words: usize,
+        pointers: &[usize],
+        country: Option<usize>,
+    ) -> Result<Self, Error> {
+        let row = source.read_bytes(offset, words * 4)?.into_owned();
+        let word = |i: usize| {
+            row.get(i * 4..i * 4 + 4)
+                .map(|bytes| FileOffset::from(u32::from_le_bytes(bytes.try_into().unwrap())))
+        };
+        let mut targets: Vec<FileOffset> = pointers.iter().filter_map(|&i| word(i)).collect();
+        if let Some(pointer) = country.and_then(word) {
+            targets.push(pointer + 3);
+        }
+        targets.sort_unstable();
+        targets.dedup();
+        targets.retain(|&target| target.get() < source.len() as u64);
+        let mut bufs = vec![[0_u8; MAX_STR_READ]; targets.len()];
+        let mut reads: Vec<(FileOffset, &mut [u8])> = targets
+            .iter()
+            .zip(bufs.iter_mut())
+            .map(|(&target, buf)| (target, &mut buf[..]))
+            .collect();
+        let read = source.read_batch(&mut reads)?;
+        let strings = targets
+            .into_iter()
+            .zip(bufs)
+            .zip(read)
+            .map(|((target, buf), read)| (target, buf[..read].to_vec()))
+            .collect();
+        Ok(Self {
+            source,
+            offset,
+            row,
+            strings,
+        })
+    }
+
+    fn row_bytes(&self, offset: FileOffset) -> Option<[u8; 4]> {
+        let start = usize::try_from(offset.since(self.offset)?).ok()?;
+        self.row.get(start..start + 4)?.try_into().ok()
+    }
+}
+
+impl<'a, S: Source> RowSource<'a> for RowReader<'a, S> {
+    fn read_u32(&self, offset: FileOffset) -> Result<u32, Error> {
+        match self.row_bytes(offset) {
+            Some(bytes) => Ok(u32::from_le_bytes(bytes)),
+            None => self.source.read_u32(offset),
+        }
+    }
+
+    fn read_f32(&self, offset: FileOffset) -> Result<f32, Error> {
+        match self.row_bytes(offset) {
+            Some(bytes) => Ok(f32::from_le_bytes(bytes)),
+            None => self.source.read_f32(offset),
+        }
+    }
+
+    fn read_str(&self, offset: FileOffset) -> Result<Cow<'a, str>, Error> {
+        match self.strings.iter().find(|(target, _)| *target == offset) {
+            Some((_, buf)) => {
+                self.source.check_str_pointer(offset)?;
+                decode_str(buf, offset, self.source.max_str_len).map(|s| Cow::Owned(s.into_owned()))
+            }
+            None => self.source.read_str(offset),
+        }
+    }
+}
+
+#[cfg(all(feature = "mmap", any(target_os = "linux", target_os = "android")))]
+fn mapped_resident_len(map: &Mmap) -> Option<usize> {
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if page_size <= 0 || map.is_empty() {
+        return None;
+    }
+    let page_size = page_size as usize;
+    let mut pages = vec![0_u8; map.len().div_ceil(page_size)];
+    let rc = unsafe {
+        libc::mincore(
+            map.as_ptr() as *mut libc::c_void,
+            map.len(),
+            pages.as_mut_ptr(),
+        )
+    };
+    if rc != 0 {
+        return None;
+    }
+    let resident = pages.iter().filter(|p| *p & 1 == 1).count() * page_size;
+    Some(resident.min(map.len()))
+}
+
+#[cfg(all(feature = "mmap", not(any(target_os = "linux", target_os = "android"))))]
+fn mapped_resident_len(_map: &Mmap) -> Option<usize> {
+    None
+}
+
+/// The product a database file holds.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DbKind {
+    /// An IP2Location database, read as a `LocationDB`.
+    Location,
+    /// An IP2Proxy database, read as a `ProxyDB`.
+    Proxy,
+}
+
+impl DbKind {
+    /// The product named by the product code of a header, `year` being
+    /// the year of its date. Databases older than 2021 have no product
+    /// code and are taken for IP2Location ones.
+    pub(crate) fn from_header(year: u8, product_code: u8) -> Result<Self, Error> {
+        match product_code {
+            1 => Ok(DbKind::Location),
+            2 => Ok(DbKind::Proxy),
+            0 if year <= 20 => Ok(DbKind::Location),
+            _ => Err(Error::InvalidBinDatabase(year, product_code)),
+        }
     }
 
-    pub fn read_str(&self, offset: u64) -> Result<Cow<'_, str>, Error> {
-        let len = self.read_u8(offset + 1)? as usize;
-        let s =
-            String::from_utf8_lossy(&self.map[(offset + 1) as usize..(offset + 1) as usize + len]);
-        Ok(s)
+    #[cfg(feature = "std")]
+    fn name(self) -> &'static str {
+        match self {
+            DbKind::Location => "location",
+            DbKind::Proxy => "proxy",
+        }
     }
+}
 
-    pub fn read_ipv6(&self, offset: u64) -> Result<Ipv6Addr, Error> {
-        let mut buf = [0_u8; 16];
-        let mut i = 0;
-        let mut j = 15;
-        while i < 16 {
-            buf[i] = self.read_u8(offset + j)?;
-            i += 1;
-            j = j.saturating_sub(1);
+impl fmt::Display for DbKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DbKind::Location => write!(f, "IP2Location"),
+            DbKind::Proxy => write!(f, "IP2Proxy"),
         }
-        let result = Ipv6Addr::from(buf);
-        Ok(result)
+    }
+}
+
+/// Opens `source` as the kind of the options or, without one, as the
+/// product the product code of its header names. Errors reading the
+/// header or validating the database are returned as is.
+fn open_source(source: Storage<DefaultSource>, options: &DbOptions) -> Result<DB, Error> {
+    let kind = match options.kind {
+        Some(kind) => kind,
+        None => DbKind::from_header(
+            source.read_u8(header::DB_YEAR)?,
+            source.read_u8(header::PRODUCT_CODE)?,
+        )?,
+    };
+    let mismatch = |err| match err {
+        Error::InvalidBinDatabase(_, product_code) => Error::GenericError(format!(
+            "not an {} database: the header has product code {}",
+            kind, product_code
+        )),
+        err => err,
+    };
+    match kind {
+        DbKind::Location => LocationDB::from_storage(source, options)
+            .map(DB::LocationDb)
+            .map_err(mismatch),
+        DbKind::Proxy => ProxyDB::from_storage(source, options)
+            .map(DB::ProxyDb)
+            .map_err(mismatch),
     }
 }
 
 impl DB {
+    pub fn options() -> DbOptions {
+        //! Starts the options to open a database with, the single entry
+        //! point for every setting: where the database is, how it is held
+        //! in memory and how addresses are looked up.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::{Backend, DB};
+        //!
+        //! let db = DB::options()
+        //!     .path("data/IP2LOCATION-LITE-DB1.IPV6.BIN")
+        //!     .backend(Backend::InMemory)
+        //!     .preload_index(true)
+        //!     .translate_teredo(false)
+        //!     .open()
+        //!     .unwrap();
+        //! assert!(db.ip_lookup("43.224.159.155".parse().unwrap()).is_ok());
+        //!```
+        DbOptions::new()
+    }
+
     /// Consume the unopened db and mmap the file.
+    #[cfg(feature = "std")]
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<DB, Error> {
         //! Loads a Ip2Location/Ip2Proxy Database .bin file from path using
         //! mmap (memap) feature.
@@ -99,60 +913,897 @@ impl DB {
         //!
         //! let mut db = DB::from_file("data/IP2PROXY-IP-COUNTRY.BIN").unwrap();
         //!```
+        Self::from_file_with_options(path, &DbOptions::default())
+    }
+
+    #[cfg(feature = "std")]
+    pub fn from_file_with_options<P: AsRef<Path>>(
+        path: P,
+        options: &DbOptions,
+    ) -> Result<DB, Error> {
+        //! Loads a Ip2Location/Ip2Proxy Database .bin file from path,
+        //! applying the given options.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::{DbOptions, DB};
+        //!
+        //! let options = DbOptions::new().preload_index(true);
+        //! let db = DB::from_file_with_options("data/IP2PROXY-IP-COUNTRY.BIN", &options).unwrap();
+        //!```
         if !path.as_ref().exists() {
             return Err(Error::IoError(
                 "Error opening DB file: No such file or directory".to_string(),
             ));
         }
 
-        if let Ok(location_db) = LocationDB::from_file(&path) {
-            Ok(DB::LocationDb(location_db))
-        } else if let Ok(proxy_db) = ProxyDB::from_file(&path) {
-            Ok(DB::ProxyDb(proxy_db))
-        } else {
-            Err(Error::UnknownDb)
-        }
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("open", path = %path.as_ref().display()).entered();
+        open_source(Storage::open(path.as_ref(), options)?, options)
     }
 
-    pub fn print_db_info(&self) {
-        //! Prints the DB Information of Ip2Location/Ip2Proxy to console
+    #[cfg(feature = "std")]
+    pub fn from_file_as<P: AsRef<Path>>(kind: DbKind, path: P) -> Result<DB, Error> {
+        //! Loads the database file at path as the given kind, without
+        //! trying the other one. Fails if the header names another
+        //! product.
         //!
         //! ## Example usage
         //!
-        //! ```rust
+        //!```rust
+        //! use ip2location::{DbKind, DB};
+        //!
+        //! let db = DB::from_file_as(DbKind::Proxy, "data/IP2PROXY-IP-COUNTRY.BIN").unwrap();
+        //! assert_eq!(db.kind(), DbKind::Proxy);
+        //! assert!(DB::from_file_as(DbKind::Location, "data/IP2PROXY-IP-COUNTRY.BIN").is_err());
+        //!```
+        Self::from_file_with_options(path, &DbOptions::new().kind(kind))
+    }
+
+    #[cfg(feature = "std")]
+    pub fn probe<P: AsRef<Path>>(path: P) -> Result<DbInfo, Error> {
+        //! Reads only the 64-byte header of the database file at path,
+        //! without mapping or validating the rest, and returns it, e.g. to
+        //! label files by product, type and date before opening them.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
         //! use ip2location::DB;
         //!
-        //! let mut db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
-        //! db.print_db_info();
-        //! ```
-        match self {
-            Self::LocationDb(db) => db.print_db_info(),
-            Self::ProxyDb(db) => db.print_db_info(),
-        }
+        //! let info = DB::probe("data/IP2PROXY-IP-COUNTRY.BIN").unwrap();
+        //! assert_eq!(info.kind, "proxy");
+        //! println!("PX{} of 20{:02}-{:02}-{:02}", info.db_type, info.db_year, info.db_month, info.db_day);
+        //!```
+        use std::io::Read;
+
+        let mut bytes = Vec::with_capacity(HEADER_LEN);
+        std::fs::File::open(path.as_ref())?
+            .take(HEADER_LEN as u64)
+            .read_to_end(&mut bytes)?;
+        let source = Storage::from_bytes(bytes.into());
+        let kind = DbKind::from_header(
+            source.read_u8(header::DB_YEAR)?,
+            source.read_u8(header::PRODUCT_CODE)?,
+        )?;
+        let path = path.as_ref().display().to_string();
+        Ok(DbInfo {
+            kind: kind.name(),
+            attribution: crate::info::attribution(kind.name(), &path),
+            path,
+            db_type: source.read_u8(header::DB_TYPE)?,
+            db_column: source.read_u8(header::DB_COLUMN)?,
+            db_year: source.read_u8(header::DB_YEAR)?,
+            db_month: source.read_u8(header::DB_MONTH)?,
+            db_day: source.read_u8(header::DB_DAY)?,
+            ipv4_count: source.read_u32(header::IPV4_COUNT)?,
+            ipv6_count: source.read_u32(header::IPV6_COUNT)?,
+            product_code: source.read_u8(header::PRODUCT_CODE)?,
+            license_code: source.read_u8(header::LICENSE_CODE)?,
+            database_size: source.read_u32(header::DATABASE_SIZE)?,
+            schema_version: crate::SCHEMA_VERSION,
+        })
     }
 
-    pub fn ip_lookup(&self, ip: IpAddr) -> Result<Record, Error> {
-        //! Lookup for the given IPv4 or IPv6 and returns the
-        //! Geo information or Proxy Information
+    #[cfg(feature = "std")]
+    pub fn detect_kind<P: AsRef<Path>>(path: P) -> Result<DbKind, Error> {
+        //! Whether the database file at path is an IP2Location or an
+        //! IP2Proxy database, reading only its header as `probe` does.
         //!
         //! ## Example usage
         //!
         //!```rust
-        //! use ip2location::{DB, Record};
+        //! use ip2location::{DbKind, DB};
         //!
-        //! let mut db = DB::from_file("data/IP2LOCATION-LITE-DB1.IPV6.BIN").unwrap();
-        //! let geo_info = db.ip_lookup("2a01:cb08:8d14::".parse().unwrap()).unwrap();
-        //! println!("{:#?}", geo_info);
-        //! let record = if let Record::LocationDb(rec) = geo_info {
-        //!   Some(rec)
-        //! } else { None };
-        //! let geo_info = record.unwrap();
-        //! assert!(!geo_info.country.is_none());
-        //! assert_eq!(geo_info.country.unwrap().short_name, "FR")
+        //! assert_eq!(DB::detect_kind("data/IP2LOCATION-LITE-DB1.BIN").unwrap(), DbKind::Location);
+        //!```
+        let info = Self::probe(path)?;
+        DbKind::from_header(info.db_year, info.product_code)
+    }
+
+    pub fn from_bytes<B: Into<Arc<[u8]>>>(bytes: B) -> Result<DB, Error> {
+        //! Loads a Ip2Location/Ip2Proxy database from its bytes instead of
+        //! a file, e.g. a database bundled with `include_bytes!` where
+        //! there is no file system, as in WebAssembly.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::DB;
+        //!
+        //! let bytes = std::fs::read("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! let db = DB::from_bytes(bytes).unwrap();
+        //! assert!(db.ip_lookup("43.224.159.155".parse().unwrap()).is_ok());
         //!```
+        Self::from_bytes_with_options(bytes, &DbOptions::default())
+    }
+
+    pub fn from_bytes_with_options<B: Into<Arc<[u8]>>>(
+        bytes: B,
+        options: &DbOptions,
+    ) -> Result<DB, Error> {
+        //! Loads a Ip2Location/Ip2Proxy database from its bytes, applying
+        //! the given options. The memory hints of the options only apply
+        //! to mapped files and are ignored.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::{DbOptions, DB};
+        //!
+        //! let bytes = std::fs::read("data/IP2PROXY-IP-COUNTRY.BIN").unwrap();
+        //! let options = DbOptions::new().preload_index(true);
+        //! let db = DB::from_bytes_with_options(bytes, &options).unwrap();
+        //!```
+        open_source(Storage::from_bytes(bytes.into()), options)
+    }
+
+    pub fn from_bytes_as<B: Into<Arc<[u8]>>>(kind: DbKind, bytes: B) -> Result<DB, Error> {
+        //! Loads the database from its bytes as the given kind, without
+        //! trying the other one. Fails if the header names another
+        //! product.
+        Self::from_bytes_with_options(bytes, &DbOptions::new().kind(kind))
+    }
+
+    pub fn from_source<S: Source + 'static>(source: S) -> Result<DB, Error> {
+        //! Loads a Ip2Location/Ip2Proxy database read through a `Source`
+        //! of the caller, e.g. storage that decrypts or caches the file.
+        //! Lookups read from the source on demand, in the way they read a
+        //! file with `Backend::Pread`.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::DB;
+        //!
+        //! let file = std::fs::File::open("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! let db = DB::from_source(file).unwrap();
+        //! assert!(db.ip_lookup("43.224.159.155".parse().unwrap()).is_ok());
+        //!```
+        Self::from_source_with_options(source, &DbOptions::default())
+    }
+
+    pub fn from_source_with_options<S: Source + 'static>(
+        source: S,
+        options: &DbOptions,
+    ) -> Result<DB, Error> {
+        //! Loads a Ip2Location/Ip2Proxy database read through a `Source`,
+        //! applying the given options. The memory hints of the options
+        //! only apply to mapped files and are ignored.
+        open_source(Storage::from_custom(Box::new(source)), options)
+    }
+
+    pub fn kind(&self) -> DbKind {
+        //! Whether this is an IP2Location or an IP2Proxy database.
+        match self {
+            Self::LocationDb(_) => DbKind::Location,
+            Self::ProxyDb(_) => DbKind::Proxy,
+        }
+    }
+
+    pub fn warm(&self) {
+        //! Touches every page of the database so that the first lookups
+        //! after startup do not pay for page faults.
+        //!
+        //! ## Example usage
+        //!
+        //! ```rust
+        //! use ip2location::DB;
+        //!
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.IPV6.BIN").unwrap();
+        //! db.warm();
+        //! ```
+        match self {
+            Self::LocationDb(db) => db.warm(),
+            Self::ProxyDb(db) => db.warm(),
+        }
+    }
+
+    pub fn memory_stats(&self) -> MemoryStats {
+        //! Reports the memory used by the database: the mapping, the part
+        //! of it resident in RAM and the preloaded index tables.
+        //!
+        //! ## Example usage
+        //!
+        //! ```rust
+        //! use ip2location::DB;
+        //!
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! let stats = db.memory_stats();
+        //! assert_eq!(stats.mapped_bytes, 2037500);
+        //! ```
+        match self {
+            Self::LocationDb(db) => db.memory_stats(),
+            Self::ProxyDb(db) => db.memory_stats(),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn bench<I: IntoIterator<Item = IpAddr>>(&self, ips: I) -> BenchReport {
+        //! Looks up every address of `ips` and reports the throughput and
+        //! latency percentiles with the current options.
+        //!
+        //! ## Example usage
+        //!
+        //! ```rust
+        //! use ip2location::DB;
+        //! use std::net::{IpAddr, Ipv4Addr};
+        //!
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! let ips = (0..10_000_u32).map(|i| IpAddr::V4(Ipv4Addr::from(i.wrapping_mul(2654435761))));
+        //! let report = db.bench(ips);
+        //! assert_eq!(report.lookups, 10_000);
+        //! println!("{}", report);
+        //! ```
+        bench::run(ips, |ip| self.ip_lookup(ip))
+    }
+
+    #[cfg(feature = "std")]
+    pub fn bench_threads(&self, ips: &[IpAddr], threads: usize) -> BenchReport {
+        //! `bench` with the addresses split across `threads` (at least one)
+        //! threads looking them up concurrently. The latency percentiles
+        //! are over the lookups of every thread, and the throughput over
+        //! the time until the last thread is done.
+        //!
+        //! ## Example usage
+        //!
+        //! ```rust
+        //! use ip2location::DB;
+        //! use std::net::{IpAddr, Ipv4Addr};
+        //!
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! let ips: Vec<IpAddr> = (0..10_000_u32)
+        //!     .map(|i| IpAddr::V4(Ipv4Addr::from(i.wrapping_mul(2654435761))))
+        //!     .collect();
+        //! let report = db.bench_threads(&ips, 4);
+        //! assert_eq!(report.lookups, 10_000);
+        //! ```
+        bench::run_threads(ips, threads, |ip| self.ip_lookup(ip))
+    }
+
+    pub fn stats(&self) -> Option<LookupStats> {
+        //! Returns the lookup counters, or `None` unless the database was
+        //! opened with `DbOptions::collect_stats`.
+        //!
+        //! ## Example usage
+        //!
+        //! ```rust
+        //! use ip2location::{DbOptions, DB};
+        //!
+        //! let options = DbOptions::new().collect_stats(true);
+        //! let db = DB::from_file_with_options("data/IP2LOCATION-LITE-DB1.BIN", &options).unwrap();
+        //! db.ip_lookup("43.224.159.155".parse().unwrap()).unwrap();
+        //! assert_eq!(db.stats().unwrap().hits, 1);
+        //! ```
+        match self {
+            Self::LocationDb(db) => db.stats(),
+            Self::ProxyDb(db) => db.stats(),
+        }
+    }
+
+    /// Resets the lookup counters to zero.
+    pub fn reset_stats(&self) {
+        match self {
+            Self::LocationDb(db) => db.reset_stats(),
+            Self::ProxyDb(db) => db.reset_stats(),
+        }
+    }
+
+    pub fn info(&self) -> DbInfo {
+        //! Returns the parsed header of the database.
+        //!
+        //! ## Example usage
+        //!
+        //! ```rust
+        //! use ip2location::DB;
+        //!
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! let info = db.info();
+        //! assert_eq!((info.kind, info.db_type), ("location", 1));
+        //! ```
+        match self {
+            Self::LocationDb(db) => db.info(),
+            Self::ProxyDb(db) => db.info(),
+        }
+    }
+
+    /// Product code of the header: 1 for IP2Location, 2 for IP2Proxy
+    /// databases, 0 in databases older than 2021.
+    pub fn product_code(&self) -> u8 {
+        match self {
+            Self::LocationDb(db) => db.product_code(),
+            Self::ProxyDb(db) => db.product_code(),
+        }
+    }
+
+    /// License code of the header.
+    pub fn license_code(&self) -> u8 {
+        match self {
+            Self::LocationDb(db) => db.license_code(),
+            Self::ProxyDb(db) => db.license_code(),
+        }
+    }
+
+    pub fn ipv4_record_count(&self) -> u32 {
+        //! Number of records (ranges) in the IPv4 table. Together with
+        //! `ipv6_record_count` and `file_size`, this is meant to be tracked
+        //! across updates to catch a truncated or incomplete release.
+        //!
+        //! ## Example usage
+        //!
+        //! ```rust
+        //! use ip2location::DB;
+        //!
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.IPV6.BIN").unwrap();
+        //! assert!(db.ipv4_record_count() > 0);
+        //! assert!(db.ipv6_record_count() > 0);
+        //! assert_eq!(db.file_size(), std::fs::metadata("data/IP2LOCATION-LITE-DB1.IPV6.BIN").unwrap().len());
+        //! ```
+        match self {
+            Self::LocationDb(db) => db.ipv4_record_count(),
+            Self::ProxyDb(db) => db.ipv4_record_count(),
+        }
+    }
+
+    /// Number of records (ranges) in the IPv6 table, 0 for IPv4-only
+    /// databases.
+    pub fn ipv6_record_count(&self) -> u32 {
+        match self {
+            Self::LocationDb(db) => db.ipv6_record_count(),
+            Self::ProxyDb(db) => db.ipv6_record_count(),
+        }
+    }
+
+    /// Size in bytes of the database as opened.
+    pub fn file_size(&self) -> u64 {
+        match self {
+            Self::LocationDb(db) => db.file_size(),
+            Self::ProxyDb(db) => db.file_size(),
+        }
+    }
+
+    pub fn attribution(&self) -> Option<&'static str> {
+        //! The acknowledgment ("This site or product includes IP2Location
+        //! LITE data available from ...") the LITE license requires
+        //! products to display, or `None` when the database is not a LITE
+        //! one. LITE databases are recognized by their file name, as
+        //! downloaded from the vendor; for a database read with
+        //! `from_bytes`, use `LOCATION_LITE_ATTRIBUTION` or
+        //! `PROXY_LITE_ATTRIBUTION` directly.
+        //!
+        //! ## Example usage
+        //!
+        //! ```rust
+        //! use ip2location::{DB, LOCATION_LITE_ATTRIBUTION};
+        //!
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! assert_eq!(db.attribution(), Some(LOCATION_LITE_ATTRIBUTION));
+        //! ```
+        self.info().attribution
+    }
+
+    pub fn ranges(&self) -> Ranges<'_> {
+        //! Iterates over every range of the database with its record, the
+        //! IPv4 table first, in address order. The `ip` of each record is
+        //! the start of its range.
+        //!
+        //! ## Example usage
+        //!
+        //! ```rust
+        //! use ip2location::{Record, DB};
+        //!
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! let french = db
+        //!     .ranges()
+        //!     .filter_map(Result::ok)
+        //!     .filter(|(_, record)| match record {
+        //!         Record::LocationDb(rec) => rec.country.as_ref().is_some_and(|c| c.short_name == "FR"),
+        //!         Record::ProxyDb(_) => false,
+        //!     })
+        //!     .count();
+        //! assert!(french > 0);
+        //! ```
+        Ranges::new(self)
+    }
+
+    pub fn get_raw_row(&self, index: u32, family: IpFamily) -> Result<RawRow<'_>, Error> {
+        //! Returns the undecoded bytes of the row at `index` in the IPv4 or
+        //! IPv6 table, with the offsets of its columns, for building custom
+        //! structures from the tables. Fails with `Error::RecordNotFound`
+        //! past the last row.
+        //!
+        //! ## Example usage
+        //!
+        //! ```rust
+        //! use ip2location::{IpFamily, DB};
+        //!
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! let row = db.get_raw_row(0, IpFamily::V4).unwrap();
+        //! assert_eq!(row.ip_from(), "0.0.0.0".parse::<std::net::IpAddr>().unwrap());
+        //! // The country pointer of a DB1 row is its second column.
+        //! assert!(row.word(2).is_some());
+        //! assert!(db.get_raw_row(u32::MAX, IpFamily::V4).is_err());
+        //! ```
+        match self {
+            Self::LocationDb(db) => db.get_raw_row(index, family),
+            Self::ProxyDb(db) => db.get_raw_row(index, family),
+        }
+    }
+
+    pub fn statistics(&self) -> Result<DbStatistics, Error> {
+        //! Scans every range and reports the records per country, the
+        //! share of records with each column populated and the share of
+        //! the address space with a country, e.g. to compare a new release
+        //! with the previous one before deploying it.
+        //!
+        //! ## Example usage
+        //!
+        //! ```rust
+        //! use ip2location::DB;
+        //!
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! let stats = db.statistics().unwrap();
+        //! assert!(stats.countries["US"] > 0);
+        //! assert!(stats.fill_rates["country"] > 90.0);
+        //! assert!(stats.ipv4_coverage > 50.0);
+        //! ```
+        statistics::scan(self)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn country_cidrs(&self, countries: &[&str]) -> Result<Vec<ipnet::IpNet>, Error> {
+        //! Returns the smallest list of CIDRs covering the ranges of the
+        //! given countries (ISO 3166 codes, in any case), IPv4 first. The
+        //! IPv4-mapped ranges of an IPv6 table are left out, being the IPv4
+        //! ranges again.
+        //!
+        //! ## Example usage
+        //!
+        //! ```rust
+        //! use ip2location::DB;
+        //!
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! let cidrs = db.country_cidrs(&["fr", "BE"]).unwrap();
+        //! assert!(cidrs.iter().any(|net| net.contains(&"2.0.0.1".parse::<std::net::IpAddr>().unwrap())));
+        //! ```
+        crate::firewall::country_cidrs(self, countries)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn export_firewall<W: std::io::Write>(
+        &self,
+        countries: &[&str],
+        format: crate::FirewallFormat,
+        name: &str,
+        out: W,
+    ) -> Result<(), Error> {
+        //! Writes the `country_cidrs` of the given countries in a format for
+        //! a firewall: plain text, nftables sets or an ipset restore file.
+        //! `name` names the sets, suffixed with `-v4` and `-v6`.
+        //!
+        //! ## Example usage
+        //!
+        //! ```rust
+        //! use ip2location::{FirewallFormat, DB};
+        //!
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! let mut out = Vec::new();
+        //! db.export_firewall(&["FR"], FirewallFormat::Nftables, "geo-fr", &mut out).unwrap();
+        //! // Load with `nft -f geo-fr.nft`, then match with `ip saddr @geo-fr-v4 drop`.
+        //! assert!(String::from_utf8(out).unwrap().starts_with("add set inet filter geo-fr-v4"));
+        //! ```
+        let nets = self.country_cidrs(countries)?;
+        crate::firewall::write(&nets, format, name, out)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn enrich_csv<R: std::io::Read, W: std::io::Write>(
+        &self,
+        reader: R,
+        writer: W,
+        ip_column: &str,
+        fields: &[crate::Field],
+    ) -> Result<u64, Error> {
+        //! Streams a CSV with a header line from `reader` to `writer`,
+        //! appending a column for each of `fields` with the value from the
+        //! record of the address in the column `ip_column`. One row is held
+        //! at a time, so files of any size are enriched in constant memory.
+        //! Rows whose address does not parse or has no record get empty
+        //! cells, as do fields the record does not have. Returns the number
+        //! of rows, without the header.
+        //!
+        //! ## Example usage
+        //!
+        //! ```rust
+        //! use ip2location::{Field, DB};
+        //!
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! let input = "user,addr\nalice,43.224.159.155\nbob,not an ip\n";
+        //! let mut output = Vec::new();
+        //! let rows = db
+        //!     .enrich_csv(input.as_bytes(), &mut output, "addr", &[Field::CountryShortName])
+        //!     .unwrap();
+        //! assert_eq!(rows, 2);
+        //! assert_eq!(
+        //!     String::from_utf8(output).unwrap(),
+        //!     "user,addr,country_short_name\nalice,43.224.159.155,IN\nbob,not an ip,\n"
+        //! );
+        //! ```
+        crate::enrich::enrich_csv(self, reader, writer, ip_column, fields)
+    }
+
+    pub fn lookup_cidr(&self, net: IpNet) -> Result<CidrLookup<'_>, Error> {
+        //! Reports whether every address of a prefix has the same record,
+        //! returning that record, or else each distinct record with the
+        //! parts of the prefix having it, e.g. to decide whether an answer
+        //! cached for the prefix is safe. The prefix is searched in the
+        //! table of its family, without the IPv6 translations of
+        //! `ip_lookup`. Fails with `Error::RecordNotFound` if an address of
+        //! the prefix is in no range.
+        //!
+        //! ## Example usage
+        //!
+        //! ```rust
+        //! use ip2location::{CidrLookup, DB};
+        //!
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! assert!(db.lookup_cidr("43.224.159.0/24".parse().unwrap()).unwrap().is_uniform());
+        //! if let CidrLookup::Mixed(records) = db.lookup_cidr("2.0.0.0/8".parse().unwrap()).unwrap() {
+        //!     assert!(records.len() > 1);
+        //! }
+        //! ```
+        cidr::lookup_cidr(self, net)
+    }
+
+    pub fn aggregate_cidr(&self, net: IpNet) -> Result<CidrAggregate<'_>, Error> {
+        //! Finds the country and the ISP of the most addresses of a prefix,
+        //! with their share of its addresses, e.g. to report that a /16 is
+        //! mostly one ISP in one country without enumerating it. Ranges
+        //! are searched as by `lookup_cidr`; unassigned ranges count with
+        //! the values the database gives them, usually `-`.
+        //!
+        //! ## Example usage
+        //!
+        //! ```rust
+        //! use ip2location::DB;
+        //!
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! let aggregate = db.aggregate_cidr("2.0.0.0/8".parse().unwrap()).unwrap();
+        //! let country = aggregate.country.unwrap();
+        //! println!("{} ({:.0}%)", country.value.short_name, country.share * 100.0);
+        //! ```
+        cidr::aggregate_cidr(self, net)
+    }
+
+    pub(crate) fn range_count(&self, ipv6: bool) -> u32 {
+        match self {
+            Self::LocationDb(db) => db.range_count(ipv6),
+            Self::ProxyDb(db) => db.range_count(ipv6),
+        }
+    }
+
+    pub(crate) fn range_at(&self, ipv6: bool, row: u32) -> Result<(IpRange, Record<'_>), Error> {
+        match self {
+            Self::LocationDb(db) => {
+                let (range, record) = db.range_at(ipv6, row)?;
+                Ok((range, Record::LocationDb(Box::new(record))))
+            }
+            Self::ProxyDb(db) => {
+                let (range, record) = db.range_at(ipv6, row)?;
+                Ok((range, Record::ProxyDb(Box::new(record))))
+            }
+        }
+    }
+
+    pub fn verify(&self) -> VerifyReport {
+        //! Scans every row and index entry of the database for unsorted
+        //! ranges, pointers outside the file and header inconsistencies.
+        //! This reads the whole file; see `VerifyReport` for the findings.
+        //!
+        //! ## Example usage
+        //!
+        //! ```rust
+        //! use ip2location::DB;
+        //!
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! let report = db.verify();
+        //! assert!(report.is_ok(), "{}", report);
+        //! ```
+        match self {
+            Self::LocationDb(db) => db.verify(),
+            Self::ProxyDb(db) => db.verify(),
+        }
+    }
+
+    pub fn check_invariants(&self) -> Result<(), Issue> {
+        //! Checks the structure lookups rely on: a consistent header, rows
+        //! starting at strictly increasing addresses (so ranges neither
+        //! overlap nor go backwards) and index entries bracketing the rows
+        //! of their block. Unlike `verify`, strings are not followed and
+        //! the first problem is returned, so it is cheap enough to run
+        //! when a database is deployed or opened.
+        //!
+        //! ## Example usage
+        //!
+        //! ```rust
+        //! use ip2location::DB;
+        //!
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.IPV6.BIN").unwrap();
+        //! db.check_invariants().unwrap();
+        //! ```
+        match self {
+            Self::LocationDb(db) => db.check_invariants(),
+            Self::ProxyDb(db) => db.check_invariants(),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[deprecated(note = "use `info()`, which implements `Display` and `Serialize`")]
+    pub fn print_db_info(&self) {
+        //! Prints the DB Information of Ip2Location/Ip2Proxy to console.
+        //! Libraries and services should log or serve `info()` instead:
+        //!
+        //! ```rust
+        //! use ip2location::DB;
+        //!
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! let info = db.info();
+        //! assert!(info.to_string().starts_with("Path:"));
+        //! assert_eq!(serde_json::to_value(&info).unwrap()["kind"], "location");
+        //! ```
+        print!("{}", self.info());
+    }
+
+    pub fn ip_lookup(&self, ip: IpAddr) -> Result<Record<'_>, Error> {
+        //! Lookup for the given IPv4 or IPv6 and returns the
+        //! Geo information or Proxy Information
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::{DB, Record};
+        //!
+        //! let mut db = DB::from_file("data/IP2LOCATION-LITE-DB1.IPV6.BIN").unwrap();
+        //! let geo_info = db.ip_lookup("2a01:cb08:8d14::".parse().unwrap()).unwrap();
+        //! println!("{:#?}", geo_info);
+        //! let record = if let Record::LocationDb(rec) = geo_info {
+        //!   Some(rec)
+        //! } else { None };
+        //! let geo_info = record.unwrap();
+        //! assert!(!geo_info.country.is_none());
+        //! assert_eq!(geo_info.country.unwrap().short_name, "FR")
+        //!```
+        self.lookup(ip).map(|(_, record)| record)
+    }
+
+    pub fn ip_lookup_owned(&self, ip: IpAddr) -> Result<OwnedRecord, Error> {
+        //! `ip_lookup` returning a record that owns its strings, so that it
+        //! does not borrow the database, e.g. to return it from a function
+        //! that opened the database itself.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::{error::Error, OwnedRecord, DB};
+        //!
+        //! fn country_of(ip: &str) -> Result<OwnedRecord, Error> {
+        //!     let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN")?;
+        //!     db.ip_lookup_owned(ip.parse().unwrap())
+        //! }
+        //!
+        //! assert!(country_of("43.224.159.155").is_ok());
+        //!```
+        self.ip_lookup(ip).map(Record::into_owned)
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn ip_lookup_async(self: &Arc<Self>, ip: IpAddr) -> Result<OwnedRecord, Error> {
+        //! `ip_lookup_owned` run on the blocking thread pool of the tokio
+        //! runtime, so that a lookup waiting for a page of the file to be
+        //! read does not block the calling task. It is a method of a shared
+        //! `Arc<DB>`, which the lookup keeps alive until it is done.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::DB;
+        //! use std::sync::Arc;
+        //!
+        //! # #[tokio::main(flavor = "current_thread")]
+        //! # async fn main() {
+        //! let db = Arc::new(DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap());
+        //! let record = db.ip_lookup_async("43.224.159.155".parse().unwrap()).await;
+        //! assert!(record.is_ok());
+        //! # }
+        //!```
+        crate::stream::resolve_blocking(self.clone(), ip).await
+    }
+
+    pub fn country_lookup(&self, ip: IpAddr) -> Result<CountryOnlyRecord<'_>, Error> {
+        //! Looks up only the country of an address, as a record that has no
+        //! other field, so no other field is decoded, logged or stored. It
+        //! can be used wherever data minimization has to be shown, instead
+        //! of removing fields from full records.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::DB;
+        //!
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! let record = db.country_lookup("43.224.159.155".parse().unwrap()).unwrap();
+        //! assert_eq!(record.country.unwrap().short_name, "IN");
+        //!```
+        match self {
+            Self::LocationDb(db) => db.country_lookup(ip),
+            Self::ProxyDb(db) => db.country_lookup(ip),
+        }
+    }
+
+    pub fn lookup_addr<A: IntoIpAddr>(&self, addr: A) -> Result<Record<'_>, Error> {
+        //! `ip_lookup` for anything convertible to an address: `Ipv4Addr`,
+        //! `Ipv6Addr`, `SocketAddr` (the port is ignored) or a string
+        //! holding one of them. Fails with `Error::GenericError` for a
+        //! string that is not an address.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::DB;
+        //! use std::net::{Ipv4Addr, SocketAddr};
+        //!
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.IPV6.BIN").unwrap();
+        //! let peer: SocketAddr = "43.224.159.155:443".parse().unwrap();
+        //! assert_eq!(db.lookup_addr(peer), db.lookup_addr("43.224.159.155"));
+        //! assert!(db.lookup_addr(Ipv4Addr::new(1, 1, 1, 1)).is_ok());
+        //! assert!(db.lookup_addr("not an address").is_err());
+        //!```
+        self.ip_lookup(addr.into_ip_addr()?)
+    }
+
+    pub fn socket_lookup(&self, addr: SocketAddr) -> Result<Record<'_>, Error> {
+        //! Looks up the address of a peer as reported by an accept loop:
+        //! the port and IPv6 scope ID are dropped, and an IPv4-mapped
+        //! address (`::ffff:a.b.c.d`, as dual-stack sockets report IPv4
+        //! peers) is looked up, and reported in the record, as IPv4.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::{Record, DB};
+        //! use std::net::SocketAddr;
+        //!
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.IPV6.BIN").unwrap();
+        //! let peer: SocketAddr = "[::ffff:43.224.159.155]:50123".parse().unwrap();
+        //! if let Record::LocationDb(rec) = db.socket_lookup(peer).unwrap() {
+        //!     assert_eq!(rec.ip, "43.224.159.155".parse::<std::net::IpAddr>().unwrap());
+        //! }
+        //!```
+        self.ip_lookup(socket_ip(addr))
+    }
+
+    /// The address searched for when looking up `ip`, after the IPv6
+    /// translations enabled for the database.
+    #[cfg_attr(not(feature = "cache"), allow(dead_code))]
+    pub(crate) fn target(&self, ip: IpAddr) -> Target {
+        match self {
+            Self::LocationDb(db) => db.target(ip),
+            Self::ProxyDb(db) => db.target(ip),
+        }
+    }
+
+    /// Looks up the address and returns the matched range along with the
+    /// record.
+    pub(crate) fn search(&self, target: Target) -> Result<(IpRange, Record<'_>), Error> {
+        match self {
+            Self::LocationDb(db) => {
+                let (range, record) = db.search(target)?;
+                Ok((range, Record::LocationDb(Box::new(record))))
+            }
+            Self::ProxyDb(db) => {
+                let (range, record) = db.search(target)?;
+                Ok((range, Record::ProxyDb(Box::new(record))))
+            }
+        }
+    }
+
+    pub(crate) fn lookup(&self, ip: IpAddr) -> Result<(IpRange, Record<'_>), Error> {
+        match self {
+            Self::LocationDb(db) => {
+                let (range, record) = db.lookup(ip)?;
+                Ok((range, Record::LocationDb(Box::new(record))))
+            }
+            Self::ProxyDb(db) => {
+                let (range, record) = db.lookup(ip)?;
+                Ok((range, Record::ProxyDb(Box::new(record))))
+            }
+        }
+    }
+
+    /// The range of addresses around a target the search did not find.
+    /// The rows of a table are contiguous, so it is before the first row
+    /// or after the last, or the whole family for an empty table; an
+    /// address inside the table of a corrupt database is a range of its
+    /// own.
+    #[cfg_attr(not(feature = "cache"), allow(dead_code))]
+    pub(crate) fn unassigned_range(&self, target: Target) -> Result<IpRange, Error> {
+        let (ipv6, ip, max) = match target {
+            Target::V4(ipnum) => (false, u128::from(ipnum), u128::from(u32::MAX)),
+            Target::V6(ipv6) => (true, u128::from(ipv6), u128::MAX),
+        };
+        let count = self.range_count(ipv6);
+        let (start, end) = if count == 0 {
+            (0, max)
+        } else {
+            let (first, _) = self.range_at(ipv6, 0)?;
+            let (last, _) = self.range_at(ipv6, count - 1)?;
+            let bits = |ip: IpAddr| match ip {
+                IpAddr::V4(ipv4) => u128::from(u32::from(ipv4)),
+                IpAddr::V6(ipv6) => u128::from(ipv6),
+            };
+            let (first, last) = (bits(first.start), bits(last.end));
+            if ip < first {
+                (0, first - 1)
+            } else if ip > last {
+                (last + 1, max)
+            } else {
+                (ip, ip)
+            }
+        };
+        let addr = |value: u128| match ipv6 {
+            false => IpAddr::V4(Ipv4Addr::from(value as u32)),
+            true => IpAddr::V6(Ipv6Addr::from(value)),
+        };
+        Ok(IpRange {
+            start: addr(start),
+            end: addr(end),
+        })
+    }
+
+    pub fn on_lookup<F>(&mut self, hook: F)
+    where
+        F: Fn(&LookupEvent) + Send + Sync + 'static,
+    {
+        //! Registers a callback invoked after every lookup with the
+        //! address, the outcome and the time the lookup took.
+        //!
+        //! ## Example usage
+        //!
+        //! ```rust
+        //! use ip2location::{LookupOutcome, DB};
+        //!
+        //! let mut db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! db.on_lookup(|event| {
+        //!     if event.outcome != LookupOutcome::Found {
+        //!         eprintln!("{} not found after {:?}", event.ip, event.elapsed);
+        //!     }
+        //! });
+        //! db.ip_lookup("43.224.159.155".parse().unwrap()).unwrap();
+        //! ```
         match self {
-            Self::LocationDb(db) => Ok(Record::LocationDb(Box::new(db.ip_lookup(ip)?))),
-            Self::ProxyDb(db) => Ok(Record::ProxyDb(Box::new(db.ip_lookup(ip)?))),
+            Self::LocationDb(db) => db.on_lookup(hook),
+            Self::ProxyDb(db) => db.on_lookup(hook),
         }
     }
 }
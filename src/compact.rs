@@ -0,0 +1,91 @@
+//! Compacted in-memory copies of databases, for `Backend::Compact`.
+//!
+//! The copy keeps the header, the index tables and the row tables where
+//! they are, so that lookups read it like the original, and replaces
+//! everything after them with a table holding each distinct string once.
+//! String pointers of the rows are rewritten to point into that table.
+use crate::{
+    common::INDEX_ENTRIES, error::Error, offset::FileOffset, source::Source, verify::Layout,
+};
+use alloc::{collections::BTreeMap, vec::Vec};
+
+/// Size of the header, which is kept as is.
+const HEADER_LEN: usize = 64;
+
+/// Builds the compacted copy of the database laid out as `layout`. The
+/// database size of the header is updated to the length of the copy.
+pub(crate) fn compact<S: Source>(layout: &Layout<'_, S>) -> Result<Vec<u8>, Error> {
+    let source = layout.source;
+    let bytes = source.read_bytes(FileOffset::default(), source.len())?;
+    let mut out = bytes[..tables_end(layout, bytes.len())].to_vec();
+    // Strings are keyed by their bytes, length byte included, so that
+    // equal strings of any column share an entry.
+    let mut strings: BTreeMap<&[u8], u32> = BTreeMap::new();
+    for (ipv6, count, addr) in [
+        (false, layout.ipv4_count, layout.ipv4_addr),
+        (true, layout.ipv6_count, layout.ipv6_addr),
+    ] {
+        let row_size = layout.db_column as usize * 4 + if ipv6 { 12 } else { 0 };
+        let columns_start = if ipv6 { 12 } else { 0 };
+        for row in 0..count as usize {
+            // 0-based start of the columns after the first one.
+            let row_at = addr as usize - 1 + row * row_size + columns_start;
+            for column in 2..=layout.db_column as u32 {
+                if layout.float_columns.contains(&column) {
+                    continue;
+                }
+                let at = row_at + 4 * (column as usize - 1);
+                let Some(pointer) = out.get(at..at + 4) else {
+                    continue;
+                };
+                let pointer = u32::from_le_bytes(pointer.try_into()?) as usize;
+                let Some(string) = string_at(&bytes, pointer, column == layout.country_column)
+                else {
+                    continue;
+                };
+                let next = u32::try_from(out.len())
+                    .map_err(|_| Error::GenericError("compacted database too large".into()))?;
+                let moved = *strings.entry(string).or_insert(next);
+                if moved == next {
+                    out.extend_from_slice(string);
+                }
+                out[at..at + 4].copy_from_slice(&moved.to_le_bytes());
+            }
+        }
+    }
+    let size = out.len() as u32;
+    out[31..35].copy_from_slice(&size.to_le_bytes());
+    Ok(out)
+}
+
+/// End of the header, index and row tables, which come before the
+/// strings.
+fn tables_end<S: Source>(layout: &Layout<'_, S>, len: usize) -> usize {
+    let mut end = HEADER_LEN;
+    for base in [layout.ipv4_index_base, layout.ipv6_index_base] {
+        if base > 0 {
+            end = end.max(base as usize - 1 + INDEX_ENTRIES * 8);
+        }
+    }
+    for (ipv6, count, addr) in [
+        (false, layout.ipv4_count, layout.ipv4_addr),
+        (true, layout.ipv6_count, layout.ipv6_addr),
+    ] {
+        if count > 0 {
+            let row_size = layout.db_column as usize * 4 + if ipv6 { 12 } else { 0 };
+            end = end.max(addr as usize - 1 + count as usize * row_size);
+        }
+    }
+    end.min(len)
+}
+
+/// The string at the 0-based `pointer`: its length byte and contents. For
+/// the country column, the short name is followed 3 bytes later by the
+/// long name and both are kept together.
+fn string_at(bytes: &[u8], pointer: usize, country: bool) -> Option<&[u8]> {
+    let mut end = pointer + 1 + *bytes.get(pointer)? as usize;
+    if country {
+        end = end.max(pointer + 4 + *bytes.get(pointer + 3)? as usize);
+    }
+    bytes.get(pointer..end)
+}
@@ -0,0 +1,250 @@
+mod consts;
+mod names;
+mod region;
+pub use consts::{CONTINENTS, EEA_NON_EU_MEMBERS, EU_MEMBERS, ISO_3166_1};
+pub use names::CountryNames;
+pub use region::{RegionInfo, RegionInfoDB};
+
+use crate::{error::Error, ip2location::record::Country};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs::File, io::Read, path::Path};
+
+/// Metadata for a single country as published in the IP2Location
+/// country information CSV.
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct CountryInfo {
+    pub country_code: String,
+    pub country_name: String,
+    pub alpha3_code: Option<String>,
+    pub numeric_code: Option<u16>,
+    /// The continent of the CSV, or else of the built-in `CONTINENTS`.
+    pub continent: Option<String>,
+    pub capital: Option<String>,
+    pub demonym: Option<String>,
+    pub total_area: Option<f64>,
+    pub population: Option<u64>,
+    pub idd_code: Option<String>,
+    pub currency_code: Option<String>,
+    pub currency_name: Option<String>,
+    pub currency_symbol: Option<String>,
+    pub lang_code: Option<String>,
+    pub lang_name: Option<String>,
+    pub tld: Option<String>,
+}
+
+/// Row layout of the vendor CSV. Every column is read as text and
+/// converted in `CountryInfo::from`, since the file uses empty strings
+/// and "-" for missing values.
+#[derive(Deserialize)]
+struct CsvRow {
+    country_code: String,
+    country_name: String,
+    #[serde(default)]
+    country_alpha3_code: String,
+    #[serde(default)]
+    country_numeric_code: String,
+    #[serde(default, alias = "continent_name")]
+    continent: String,
+    #[serde(default)]
+    capital: String,
+    #[serde(default)]
+    country_demonym: String,
+    #[serde(default)]
+    total_area: String,
+    #[serde(default)]
+    population: String,
+    #[serde(default)]
+    idd_code: String,
+    #[serde(default)]
+    currency_code: String,
+    #[serde(default)]
+    currency_name: String,
+    #[serde(default)]
+    currency_symbol: String,
+    #[serde(default)]
+    lang_code: String,
+    #[serde(default)]
+    lang_name: String,
+    #[serde(default)]
+    cctld: String,
+}
+
+/// Returns true if the ISO 3166-1 alpha-2 code belongs to a member state of
+/// the European Union.
+pub fn is_eu(country_code: &str) -> bool {
+    EU_MEMBERS
+        .iter()
+        .any(|c| c.eq_ignore_ascii_case(country_code))
+}
+
+/// Returns true if the ISO 3166-1 alpha-2 code belongs to a member of the
+/// European Economic Area.
+pub fn is_eea(country_code: &str) -> bool {
+    is_eu(country_code)
+        || EEA_NON_EU_MEMBERS
+            .iter()
+            .any(|c| c.eq_ignore_ascii_case(country_code))
+}
+
+fn iso_entry(alpha2: &str) -> Option<&'static (&'static str, &'static str, u16)> {
+    let alpha2 = alpha2.to_ascii_uppercase();
+    ISO_3166_1
+        .binary_search_by(|(a, _, _)| (*a).cmp(alpha2.as_str()))
+        .ok()
+        .map(|i| &ISO_3166_1[i])
+}
+
+/// Converts an ISO 3166-1 alpha-2 code to its alpha-3 code ("FR" -> "FRA").
+pub fn to_alpha3(alpha2: &str) -> Option<&'static str> {
+    iso_entry(alpha2).map(|(_, a3, _)| *a3)
+}
+
+/// Converts an ISO 3166-1 alpha-2 code to its numeric code ("FR" -> 250).
+pub fn to_numeric(alpha2: &str) -> Option<u16> {
+    iso_entry(alpha2).map(|(_, _, n)| *n)
+}
+
+/// Returns the continent of an ISO 3166-1 alpha-2 code ("DE" -> "Europe").
+pub fn continent(alpha2: &str) -> Option<&'static str> {
+    CONTINENTS
+        .iter()
+        .find(|(_, codes)| codes.iter().any(|c| c.eq_ignore_ascii_case(alpha2)))
+        .map(|(name, _)| *name)
+}
+
+/// Converts an ISO 3166-1 alpha-3 code to its alpha-2 code ("FRA" -> "FR").
+pub fn from_alpha3(alpha3: &str) -> Option<&'static str> {
+    ISO_3166_1
+        .iter()
+        .find(|(_, a3, _)| a3.eq_ignore_ascii_case(alpha3))
+        .map(|(a2, _, _)| *a2)
+}
+
+/// Converts an ISO 3166-1 numeric code to its alpha-2 code (250 -> "FR").
+pub fn from_numeric(numeric: u16) -> Option<&'static str> {
+    ISO_3166_1
+        .iter()
+        .find(|(_, _, n)| *n == numeric)
+        .map(|(a2, _, _)| *a2)
+}
+
+fn non_empty(value: String) -> Option<String> {
+    let value = value.trim();
+    if value.is_empty() || value == "-" {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
+
+impl From<CsvRow> for CountryInfo {
+    fn from(row: CsvRow) -> Self {
+        let country_code = row.country_code.trim().to_ascii_uppercase();
+        CountryInfo {
+            continent: non_empty(row.continent)
+                .or_else(|| continent(&country_code).map(String::from)),
+            country_code,
+            country_name: row.country_name.trim().to_string(),
+            alpha3_code: non_empty(row.country_alpha3_code),
+            numeric_code: non_empty(row.country_numeric_code).and_then(|v| v.parse().ok()),
+            capital: non_empty(row.capital),
+            demonym: non_empty(row.country_demonym),
+            total_area: non_empty(row.total_area).and_then(|v| v.parse().ok()),
+            population: non_empty(row.population).and_then(|v| v.parse().ok()),
+            idd_code: non_empty(row.idd_code),
+            currency_code: non_empty(row.currency_code),
+            currency_name: non_empty(row.currency_name),
+            currency_symbol: non_empty(row.currency_symbol),
+            lang_code: non_empty(row.lang_code),
+            lang_name: non_empty(row.lang_name),
+            tld: non_empty(row.cctld),
+        }
+    }
+}
+
+/// In-memory table of `CountryInfo` keyed by the ISO 3166-1 alpha-2 code.
+#[derive(Debug, Default, Clone)]
+pub struct CountryInfoDB {
+    countries: HashMap<String, CountryInfo>,
+}
+
+impl CountryInfoDB {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        //! Loads the IP2Location country information CSV from path.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust,no_run
+        //! use ip2location::{country_info::CountryInfoDB, Record, DB};
+        //!
+        //! let info = CountryInfoDB::from_file("IP2LOCATION-COUNTRY-INFORMATION.CSV").unwrap();
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! if let Record::LocationDb(rec) = db.ip_lookup("43.224.159.155".parse().unwrap()).unwrap() {
+        //!     let country = rec.country.unwrap();
+        //!     println!("{:?}", country.info(&info).and_then(|i| i.capital.as_deref()));
+        //! }
+        //!```
+        if !path.as_ref().exists() {
+            return Err(Error::IoError(
+                "Error opening CSV file: No such file or directory".to_string(),
+            ));
+        }
+        Self::from_reader(File::open(path)?)
+    }
+
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, Error> {
+        //! Loads the country information CSV from any reader.
+        let mut countries = HashMap::new();
+        let mut rdr = csv::Reader::from_reader(reader);
+        for row in rdr.deserialize::<CsvRow>() {
+            let info = CountryInfo::from(row?);
+            countries.insert(info.country_code.clone(), info);
+        }
+        Ok(Self { countries })
+    }
+
+    /// Returns the metadata for the given ISO 3166-1 alpha-2 code.
+    pub fn get(&self, country_code: &str) -> Option<&CountryInfo> {
+        self.countries.get(&country_code.to_ascii_uppercase())
+    }
+
+    pub fn len(&self) -> usize {
+        self.countries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.countries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &CountryInfo> {
+        self.countries.values()
+    }
+}
+
+impl Country<'_> {
+    /// Returns the metadata for this country from the given table.
+    pub fn info<'db>(&self, db: &'db CountryInfoDB) -> Option<&'db CountryInfo> {
+        db.get(&self.short_name)
+    }
+
+    /// Returns the ISO 3166-1 alpha-3 code of the country (e.g. "FRA").
+    pub fn alpha3(&self) -> Option<&'static str> {
+        to_alpha3(&self.short_name)
+    }
+
+    /// Returns the ISO 3166-1 numeric code of the country (e.g. 250).
+    pub fn numeric(&self) -> Option<u16> {
+        to_numeric(&self.short_name)
+    }
+
+    /// Returns true if the country is a member state of the European Union.
+    pub fn is_eu(&self) -> bool {
+        is_eu(&self.short_name)
+    }
+
+    /// Returns true if the country is part of the European Economic Area,
+    /// i.e. the zone where the GDPR applies.
+    pub fn is_eea(&self) -> bool {
+        is_eea(&self.short_name)
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{error::Error, ip2location::record::Country};
+use serde::Deserialize;
+use std::{collections::HashMap, fs::File, io::Read, path::Path};
+
+/// Row layout of the IP2Location country multilingual CSV.
+#[derive(Deserialize)]
+struct CsvRow {
+    #[serde(alias = "LANG", alias = "lang_code")]
+    lang: String,
+    #[serde(alias = "CNTRY_ALPHA2_CODE", alias = "country_alpha2_code")]
+    alpha2_code: String,
+    #[serde(alias = "CNTRY_NAME", alias = "country_name")]
+    name: String,
+}
+
+/// Translation table of country names keyed by language and ISO 3166-1
+/// alpha-2 code.
+///
+/// No translations are bundled with the crate: the table is loaded from
+/// the IP2Location country multilingual CSV, or any CSV of the same
+/// columns, e.g. one exported from the Unicode CLDR, or filled with
+/// `insert`.
+#[derive(Debug, Default, Clone)]
+pub struct CountryNames {
+    names: HashMap<(String, String), String>,
+}
+
+fn key(lang: &str, country_code: &str) -> (String, String) {
+    (
+        lang.trim().to_lowercase(),
+        country_code.trim().to_ascii_uppercase(),
+    )
+}
+
+impl CountryNames {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        //! Loads the IP2Location country multilingual CSV from path.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust,no_run
+        //! use ip2location::{country_info::CountryNames, Record, DB};
+        //!
+        //! let names = CountryNames::from_file("IP2LOCATION-COUNTRY-MULTILINGUAL.CSV").unwrap();
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.IPV6.BIN").unwrap();
+        //! if let Record::LocationDb(rec) = db.ip_lookup("2a01:cb08:8d14::".parse().unwrap()).unwrap() {
+        //!     println!("{:?}", rec.country.unwrap().name_in(&names, "de"));
+        //! }
+        //!```
+        if !path.as_ref().exists() {
+            return Err(Error::IoError(
+                "Error opening CSV file: No such file or directory".to_string(),
+            ));
+        }
+        Self::from_reader(File::open(path)?)
+    }
+
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, Error> {
+        //! Loads the country multilingual CSV from any reader.
+        let mut names = HashMap::new();
+        let mut rdr = csv::Reader::from_reader(reader);
+        for row in rdr.deserialize::<CsvRow>() {
+            let row = row?;
+            names.insert(
+                key(&row.lang, &row.alpha2_code),
+                row.name.trim().to_string(),
+            );
+        }
+        Ok(Self { names })
+    }
+
+    /// Adds or replaces a single translation.
+    pub fn insert(&mut self, lang: &str, country_code: &str, name: &str) {
+        self.names.insert(key(lang, country_code), name.to_string());
+    }
+
+    /// Returns the name of the country in the given language. Regional
+    /// variants such as "de-AT" fall back to the base language.
+    pub fn get(&self, lang: &str, country_code: &str) -> Option<&str> {
+        self.names
+            .get(&key(lang, country_code))
+            .or_else(|| {
+                let base = lang.split(['-', '_']).next()?;
+                self.names.get(&key(base, country_code))
+            })
+            .map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+impl Country<'_> {
+    /// Returns the name of this country in the given language from a
+    /// table supplied by the caller, or `None` when the table has no
+    /// translation for it.
+    pub fn name_in<'db>(&self, names: &'db CountryNames, lang: &str) -> Option<&'db str> {
+        names.get(lang, &self.short_name)
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{error::Error, ip2location::record::LocationRecord};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs::File, io::Read, path::Path};
+
+/// A first-level subdivision as published in the IP2Location
+/// ISO 3166-2 region information CSV.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct RegionInfo {
+    pub country_code: String,
+    #[serde(rename(deserialize = "subdivision_name"))]
+    pub name: String,
+    pub code: String,
+}
+
+/// In-memory table of `RegionInfo` keyed by country code and region name.
+#[derive(Debug, Default, Clone)]
+pub struct RegionInfoDB {
+    regions: HashMap<(String, String), RegionInfo>,
+}
+
+fn key(country_code: &str, name: &str) -> (String, String) {
+    (
+        country_code.to_ascii_uppercase(),
+        name.trim().to_lowercase(),
+    )
+}
+
+impl RegionInfoDB {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        //! Loads the IP2Location ISO 3166-2 region information CSV from path.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust,no_run
+        //! use ip2location::{country_info::RegionInfoDB, Record, DB};
+        //!
+        //! let regions = RegionInfoDB::from_file("IP2LOCATION-ISO3166-2.CSV").unwrap();
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB3.BIN").unwrap();
+        //! if let Record::LocationDb(rec) = db.ip_lookup("2a01:cb08:8d14::".parse().unwrap()).unwrap() {
+        //!     println!("{:?}", rec.region_code(&regions));
+        //! }
+        //!```
+        if !path.as_ref().exists() {
+            return Err(Error::IoError(
+                "Error opening CSV file: No such file or directory".to_string(),
+            ));
+        }
+        Self::from_reader(File::open(path)?)
+    }
+
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, Error> {
+        //! Loads the region information CSV from any reader.
+        let mut regions = HashMap::new();
+        let mut rdr = csv::Reader::from_reader(reader);
+        for row in rdr.deserialize::<RegionInfo>() {
+            let mut region = row?;
+            region.country_code = region.country_code.trim().to_ascii_uppercase();
+            region.code = region.code.trim().to_string();
+            regions.insert(key(&region.country_code, &region.name), region);
+        }
+        Ok(Self { regions })
+    }
+
+    /// Returns the subdivision matching the given country code and region
+    /// name. Names are compared case-insensitively.
+    pub fn get(&self, country_code: &str, name: &str) -> Option<&RegionInfo> {
+        self.regions.get(&key(country_code, name))
+    }
+
+    pub fn len(&self) -> usize {
+        self.regions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &RegionInfo> {
+        self.regions.values()
+    }
+}
+
+impl LocationRecord<'_> {
+    /// Returns the ISO 3166-2 subdivision code (e.g. "FR-IDF") of the
+    /// record's region, when both the country and region are known.
+    pub fn region_code<'db>(&self, db: &'db RegionInfoDB) -> Option<&'db str> {
+        let country = self.country.as_ref()?;
+        let region = self.region.as_ref()?;
+        db.get(&country.short_name, region).map(|r| r.code.as_str())
+    }
+}
@@ -0,0 +1,78 @@
+//! Records holding only the country of an address, for data minimization.
+#[cfg(feature = "std")]
+use crate::country_info::CountryInfoDB;
+use crate::{ip2location::record::Country, LocationRecord, ProxyRecord, Record};
+use alloc::borrow::Cow;
+use core::net::IpAddr;
+use serde::Serialize;
+use serde_with::skip_serializing_none;
+
+/// The country of an address and nothing else, returned by
+/// `DB::country_lookup`. Having no other field, it cannot leak the city,
+/// coordinates or ISP of an address into logs or storage, whatever the
+/// database it comes from.
+#[skip_serializing_none]
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Serialize)]
+pub struct CountryOnlyRecord<'a> {
+    pub ip: IpAddr,
+    pub country: Option<Country<'a>>,
+    /// The continent of the country, set by `with_continent` since the
+    /// databases do not have it.
+    pub continent: Option<Cow<'a, str>>,
+}
+
+impl<'a> CountryOnlyRecord<'a> {
+    pub(crate) fn new(ip: IpAddr, country: Option<Country<'a>>) -> Self {
+        Self {
+            ip,
+            country,
+            continent: None,
+        }
+    }
+
+    /// Sets the continent of the country from the country information
+    /// CSV, if it has one.
+    #[cfg(feature = "std")]
+    pub fn with_continent<'b>(self, info: &'b CountryInfoDB) -> CountryOnlyRecord<'b>
+    where
+        'a: 'b,
+    {
+        let continent = self
+            .country
+            .as_ref()
+            .and_then(|country| info.get(&country.short_name))
+            .and_then(|info| info.continent.as_deref())
+            .map(Cow::Borrowed);
+        CountryOnlyRecord { continent, ..self }
+    }
+
+    /// Converts the record into one that owns its strings.
+    pub fn into_owned(self) -> CountryOnlyRecord<'static> {
+        CountryOnlyRecord {
+            ip: self.ip,
+            country: self.country.map(Country::into_owned),
+            continent: self.continent.map(|value| Cow::Owned(value.into_owned())),
+        }
+    }
+}
+
+impl<'a> From<LocationRecord<'a>> for CountryOnlyRecord<'a> {
+    fn from(record: LocationRecord<'a>) -> Self {
+        Self::new(record.ip, record.country)
+    }
+}
+
+impl<'a> From<ProxyRecord<'a>> for CountryOnlyRecord<'a> {
+    fn from(record: ProxyRecord<'a>) -> Self {
+        Self::new(record.ip, record.country)
+    }
+}
+
+impl<'a> From<Record<'a>> for CountryOnlyRecord<'a> {
+    fn from(record: Record<'a>) -> Self {
+        match record {
+            Record::LocationDb(rec) => (*rec).into(),
+            Record::ProxyDb(rec) => (*rec).into(),
+        }
+    }
+}
@@ -0,0 +1,94 @@
+//! Lookup results as Polars data frames.
+use crate::{
+    error::Error,
+    schema::{ColumnType, ColumnValue},
+    DbKind, LocationRecord, ProxyRecord, Record, DB,
+};
+use polars::prelude::{Column, DataFrame};
+use std::net::IpAddr;
+
+/// The values of one column, as they are collected.
+enum Values {
+    Float(Vec<Option<f32>>),
+    Text(Vec<Option<String>>),
+}
+
+impl Values {
+    fn push(&mut self, value: Option<ColumnValue<'_>>) {
+        match (self, value) {
+            (Values::Float(values), Some(ColumnValue::Float(value))) => values.push(Some(value)),
+            (Values::Text(values), Some(ColumnValue::Ip(ip))) => values.push(Some(ip.to_string())),
+            (Values::Text(values), Some(ColumnValue::Text(value))) => {
+                values.push(Some(value.to_string()))
+            }
+            (Values::Float(values), _) => values.push(None),
+            (Values::Text(values), _) => values.push(None),
+        }
+    }
+}
+
+pub fn lookups_to_dataframe(
+    ips: impl IntoIterator<Item = IpAddr>,
+    db: &DB,
+) -> Result<DataFrame, Error> {
+    //! Looks up every address into a `DataFrame` with one row per address,
+    //! in order, and the columns the database populates, in the order of
+    //! `LocationRecord::COLUMNS` or `ProxyRecord::COLUMNS`: addresses and
+    //! text as strings, coordinates as `f32`. Addresses the database does
+    //! not know get a row with only `ip` set; other lookup errors are
+    //! returned.
+    //!
+    //! ## Example usage
+    //!
+    //!```rust
+    //! use ip2location::{lookups_to_dataframe, DB};
+    //!
+    //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+    //! let ips = ["43.224.159.155", "1.1.1.1"].map(|ip| ip.parse().unwrap());
+    //! let df = lookups_to_dataframe(ips, &db).unwrap();
+    //! assert_eq!(df.height(), 2);
+    //! assert_eq!(
+    //!     df.get_column_names(),
+    //!     ["ip", "country_short_name", "country_long_name"]
+    //! );
+    //!```
+    let db_type = db.info().db_type;
+    let schema = match db.kind() {
+        DbKind::Location => LocationRecord::schema(db_type),
+        DbKind::Proxy => ProxyRecord::schema(db_type),
+    };
+    let mut columns: Vec<(&str, Values)> = schema
+        .into_iter()
+        .filter(|column| column.populated)
+        .map(|column| {
+            let values = match column.column_type {
+                ColumnType::Float => Values::Float(Vec::new()),
+                ColumnType::Ip | ColumnType::Text => Values::Text(Vec::new()),
+            };
+            (column.name, values)
+        })
+        .collect();
+    for ip in ips {
+        let record = match db.ip_lookup(ip) {
+            Ok(record) => Some(record),
+            Err(Error::RecordNotFound) => None,
+            Err(e) => return Err(e),
+        };
+        for (name, values) in &mut columns {
+            values.push(match &record {
+                Some(Record::LocationDb(record)) => record.column(name),
+                Some(Record::ProxyDb(record)) => record.column(name),
+                None if *name == "ip" => Some(ColumnValue::Ip(ip)),
+                None => None,
+            });
+        }
+    }
+    let columns = columns
+        .into_iter()
+        .map(|(name, values)| match values {
+            Values::Float(values) => Column::new(name.into(), values),
+            Values::Text(values) => Column::new(name.into(), values),
+        })
+        .collect();
+    DataFrame::new(columns).map_err(|e| Error::GenericError(e.to_string()))
+}
@@ -0,0 +1,80 @@
+use crate::{error::Error, Record, DB};
+use std::net::{IpAddr, ToSocketAddrs};
+
+/// The result of looking up each address of a host, in resolution order.
+pub type HostLookup<'a> = Vec<(IpAddr, Result<Record<'a>, Error>)>;
+
+/// Drops repeated addresses, keeping the first occurrence.
+fn unique(addrs: impl Iterator<Item = IpAddr>) -> Vec<IpAddr> {
+    let mut unique = Vec::new();
+    for ip in addrs {
+        if !unique.contains(&ip) {
+            unique.push(ip);
+        }
+    }
+    unique
+}
+
+fn resolution_error(host: &str, err: std::io::Error) -> Error {
+    Error::IoError(format!("cannot resolve {}: {}", host, err))
+}
+
+/// Resolves the A and AAAA records of `host` with the system resolver. An
+/// address is returned as is.
+pub fn resolve_host(host: &str) -> Result<Vec<IpAddr>, Error> {
+    let addrs = (host, 0)
+        .to_socket_addrs()
+        .map_err(|err| resolution_error(host, err))?;
+    Ok(unique(addrs.map(|addr| addr.ip())))
+}
+
+impl DB {
+    pub fn host_lookup(&self, host: &str) -> Result<HostLookup<'_>, Error> {
+        //! Resolves the A and AAAA records of the host and looks up each
+        //! address. Fails with `Error::IoError` when the host cannot be
+        //! resolved; addresses missing from the database get
+        //! `Err(Error::RecordNotFound)`. The call blocks on the system
+        //! resolver.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::DB;
+        //!
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.IPV6.BIN").unwrap();
+        //! for (ip, record) in db.host_lookup("localhost").unwrap() {
+        //!     println!("{}: {:?}", ip, record);
+        //! }
+        //!```
+        Ok(resolve_host(host)?
+            .into_iter()
+            .map(|ip| (ip, self.ip_lookup(ip)))
+            .collect())
+    }
+
+    #[cfg(feature = "dns-async")]
+    pub async fn host_lookup_async(&self, host: &str) -> Result<HostLookup<'_>, Error> {
+        //! `host_lookup` with the resolution run on the tokio runtime
+        //! instead of blocking the calling task.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::DB;
+        //!
+        //! # #[tokio::main(flavor = "current_thread")]
+        //! # async fn main() {
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.IPV6.BIN").unwrap();
+        //! let lookups = db.host_lookup_async("localhost").await.unwrap();
+        //! assert!(!lookups.is_empty());
+        //! # }
+        //!```
+        let addrs = tokio::net::lookup_host((host, 0))
+            .await
+            .map_err(|err| resolution_error(host, err))?;
+        Ok(unique(addrs.map(|addr| addr.ip()))
+            .into_iter()
+            .map(|ip| (ip, self.ip_lookup(ip)))
+            .collect())
+    }
+}
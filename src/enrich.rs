@@ -0,0 +1,68 @@
+//! Enriching CSV files with the records of an address column.
+use crate::{error::Error, schema::ColumnValue, Field, Record, DB};
+use std::{
+    borrow::Cow,
+    io::{Read, Write},
+    net::IpAddr,
+};
+
+fn cell(value: Option<ColumnValue<'_>>) -> Cow<'_, str> {
+    match value {
+        Some(ColumnValue::Ip(ip)) => Cow::Owned(ip.to_string()),
+        Some(ColumnValue::Float(value)) => Cow::Owned(value.to_string()),
+        Some(ColumnValue::Text(value)) => Cow::Borrowed(value),
+        None => Cow::Borrowed(""),
+    }
+}
+
+/// Copies the CSV of `reader` to `writer` row by row, appending the
+/// `fields` of the record of the address in `ip_column`. Returns the
+/// number of rows written, without the header.
+pub(crate) fn enrich_csv<R: Read, W: Write>(
+    db: &DB,
+    reader: R,
+    writer: W,
+    ip_column: &str,
+    fields: &[Field],
+) -> Result<u64, Error> {
+    let mut reader = csv::Reader::from_reader(reader);
+    let mut writer = csv::Writer::from_writer(writer);
+    let headers = reader.headers()?.clone();
+    let ip_index = headers
+        .iter()
+        .position(|header| header == ip_column)
+        .ok_or_else(|| Error::GenericError(format!("no column {:?} in the CSV", ip_column)))?;
+    writer.write_record(
+        headers
+            .iter()
+            .chain(fields.iter().map(|field| field.name())),
+    )?;
+
+    let mut row = csv::StringRecord::new();
+    let mut rows = 0;
+    while reader.read_record(&mut row)? {
+        let record = match row.get(ip_index).unwrap_or("").trim().parse::<IpAddr>() {
+            Ok(ip) => match db.ip_lookup(ip) {
+                Ok(record) => Some(record),
+                Err(Error::RecordNotFound) => None,
+                Err(e) => return Err(e),
+            },
+            Err(_) => None,
+        };
+        for value in row.iter() {
+            writer.write_field(value)?;
+        }
+        for field in fields {
+            let value = match &record {
+                Some(Record::LocationDb(rec)) => rec.column(field.name()),
+                Some(Record::ProxyDb(rec)) => rec.column(field.name()),
+                None => None,
+            };
+            writer.write_field(cell(value).as_bytes())?;
+        }
+        writer.write_record(None::<&[u8]>)?;
+        rows += 1;
+    }
+    writer.flush()?;
+    Ok(rows)
+}
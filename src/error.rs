@@ -1,14 +1,31 @@
-use std::{fmt, io};
+use alloc::string::{FromUtf8Error, String, ToString};
+use core::{array::TryFromSliceError, fmt, net::AddrParseError};
+#[cfg(feature = "std")]
+use std::io;
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum Error {
     GenericError(String),
     IoError(String),
     RecordNotFound,
+    /// No longer returned: a database of an unknown product fails to
+    /// open with `InvalidBinDatabase`, holding its year and product code.
+    #[deprecated(note = "unknown products are reported as `InvalidBinDatabase`")]
     UnknownDb,
     InvalidBinDatabase(u8, u8),
+    /// A string of the database is longer than `DbOptions::max_str_len`
+    /// allows: its 0-based offset, its length and the limit.
+    StringTooLong {
+        offset: u64,
+        len: u8,
+        max: u8,
+    },
+    /// The database points outside of the data it holds, e.g. a string
+    /// pointer past the end of the file or into the rows.
+    Corrupt(String),
 }
 
+#[cfg(feature = "std")]
 impl From<io::Error> for Error {
     fn from(err: io::Error) -> Error {
         Error::IoError(err.to_string())
@@ -16,6 +33,7 @@ impl From<io::Error> for Error {
 }
 
 // Use default implementation for `std::error::Error`
+#[cfg(feature = "std")]
 impl std::error::Error for Error {}
 
 impl From<&str> for Error {
@@ -24,35 +42,73 @@ impl From<&str> for Error {
     }
 }
 
-impl From<std::string::FromUtf8Error> for Error {
-    fn from(err: std::string::FromUtf8Error) -> Error {
+impl From<FromUtf8Error> for Error {
+    fn from(err: FromUtf8Error) -> Error {
         Error::GenericError(err.to_string())
     }
 }
 
-impl From<std::array::TryFromSliceError> for Error {
-    fn from(err: std::array::TryFromSliceError) -> Error {
+impl From<TryFromSliceError> for Error {
+    fn from(err: TryFromSliceError) -> Error {
         Error::GenericError(err.to_string())
     }
 }
 
-impl From<std::net::AddrParseError> for Error {
-    fn from(err: std::net::AddrParseError) -> Error {
+impl From<AddrParseError> for Error {
+    fn from(err: AddrParseError) -> Error {
         Error::GenericError(err.to_string())
     }
 }
 
-impl std::fmt::Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> fmt::Result {
+#[cfg(feature = "std")]
+impl From<csv::Error> for Error {
+    fn from(err: csv::Error) -> Error {
+        Error::GenericError(err.to_string())
+    }
+}
+
+#[cfg(feature = "msgpack")]
+impl From<rmp_serde::decode::Error> for Error {
+    fn from(err: rmp_serde::decode::Error) -> Error {
+        Error::GenericError(err.to_string())
+    }
+}
+
+#[cfg(feature = "maxmind")]
+impl From<maxminddb::MaxMindDbError> for Error {
+    fn from(err: maxminddb::MaxMindDbError) -> Error {
+        match err {
+            maxminddb::MaxMindDbError::Io(err) => Error::IoError(err.to_string()),
+            err => Error::GenericError(err.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "object-storage")]
+impl From<opendal::Error> for Error {
+    fn from(err: opendal::Error) -> Error {
+        Error::IoError(err.to_string())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Error::GenericError(msg) => write!(f, "GenericError: {}", msg)?,
             Error::IoError(msg) => write!(f, "IoError: {}", msg)?,
             Error::RecordNotFound => write!(f, "RecordNotFound: no record found")?,
+            #[allow(deprecated)]
             Error::UnknownDb => write!(
                 f,
                 "Unknown database: Database type should be Proxy or Location"
             )?,
             Error::InvalidBinDatabase(y, p) => write!(f, "Invalid Bin Database: {} {}", y, p)?,
+            Error::StringTooLong { offset, len, max } => write!(
+                f,
+                "String too long: {} bytes at offset {}, more than {}",
+                len, offset, max
+            )?,
+            Error::Corrupt(msg) => write!(f, "Corrupt database: {}", msg)?,
         }
         Ok(())
     }
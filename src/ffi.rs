@@ -0,0 +1,141 @@
+//! C interface to the reader, available with the `ffi` feature.
+//!
+//! Build a shared library with
+//! `cargo rustc --release --features ffi --crate-type cdylib` and declare
+//! the functions with `include/ip2location.h`. Functions that fail return
+//! `NULL`; `ip2location_last_error` then describes the failure.
+use crate::{error::Error, DB};
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CStr, CString},
+    net::IpAddr,
+    ptr,
+};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message).unwrap_or_else(|_| c"invalid error message".to_owned());
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+fn clear_last_error() {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+}
+
+unsafe fn to_str<'a>(s: *const c_char, what: &str) -> Option<&'a str> {
+    if s.is_null() {
+        set_last_error(format!("{} is NULL", what));
+        return None;
+    }
+    match CStr::from_ptr(s).to_str() {
+        Ok(s) => Some(s),
+        Err(_) => {
+            set_last_error(format!("{} is not valid UTF-8", what));
+            None
+        }
+    }
+}
+
+/// Opens an IP2Location or IP2Proxy BIN database.
+///
+/// Returns `NULL` on failure. The handle is released with
+/// `ip2location_close` and may be shared between threads.
+///
+/// # Safety
+///
+/// `path` must be `NULL` or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ip2location_open(path: *const c_char) -> *mut DB {
+    clear_last_error();
+    let Some(path) = to_str(path, "path") else {
+        return ptr::null_mut();
+    };
+    match DB::from_file(path) {
+        Ok(db) => Box::into_raw(Box::new(db)),
+        Err(err) => {
+            set_last_error(err.to_string());
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Looks up an IPv4 or IPv6 address and returns the record as a JSON
+/// object, in the format of `Record::to_json`.
+///
+/// Returns `NULL` when the address is invalid, is not in the database or
+/// the lookup fails; `ip2location_last_error` tells which. The string is
+/// released with `ip2location_free_string`.
+///
+/// # Safety
+///
+/// `db` must be `NULL` or a handle returned by `ip2location_open` that was
+/// not closed, and `ip` `NULL` or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ip2location_lookup(db: *const DB, ip: *const c_char) -> *mut c_char {
+    clear_last_error();
+    let Some(db) = db.as_ref() else {
+        set_last_error("database handle is NULL".to_string());
+        return ptr::null_mut();
+    };
+    let Some(ip) = to_str(ip, "address") else {
+        return ptr::null_mut();
+    };
+    let Ok(ip) = ip.parse::<IpAddr>() else {
+        set_last_error(format!("invalid IP address: {}", ip));
+        return ptr::null_mut();
+    };
+    let json = match db.ip_lookup(ip) {
+        Ok(record) => serde_json::to_string(&record).expect("records serialize to JSON"),
+        Err(Error::RecordNotFound) => {
+            set_last_error("no record found".to_string());
+            return ptr::null_mut();
+        }
+        Err(err) => {
+            set_last_error(err.to_string());
+            return ptr::null_mut();
+        }
+    };
+    // JSON escapes control characters, so there is no NUL to reject.
+    CString::new(json).map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// Releases a string returned by `ip2location_lookup`. `NULL` is ignored.
+///
+/// # Safety
+///
+/// `s` must be `NULL` or a string returned by `ip2location_lookup` that
+/// was not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn ip2location_free_string(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Closes a database opened with `ip2location_open`. `NULL` is ignored.
+///
+/// # Safety
+///
+/// `db` must be `NULL` or a handle returned by `ip2location_open` that
+/// was not closed yet, and no lookup on it may be running.
+#[no_mangle]
+pub unsafe extern "C" fn ip2location_close(db: *mut DB) {
+    if !db.is_null() {
+        drop(Box::from_raw(db));
+    }
+}
+
+/// Describes the last failure of a call on this thread, or `NULL` if the
+/// last call succeeded. The string is owned by the library and valid until
+/// the next call on the same thread.
+#[no_mangle]
+pub extern "C" fn ip2location_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
@@ -0,0 +1,265 @@
+//! Database files read on demand instead of mapped, for the file
+//! backends of `DbOptions::backend`.
+use crate::{error::Error, options::AccessPattern};
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+use io_uring::{opcode, types, IoUring};
+use std::{fmt, fs::File, path::Path};
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+use std::{os::fd::AsRawFd, sync::Mutex};
+
+/// Submission queue entries of a ring; larger batches are submitted in
+/// several rounds.
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+const RING_ENTRIES: u32 = 64;
+
+/// How a `FileSource` reads.
+enum Reader {
+    /// One positioned read (`pread`) per buffer.
+    Pread,
+    /// The reads of a batch submitted together to an io_uring, or with
+    /// `pread` once the ring failed.
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    IoUring(Box<Mutex<Option<IoUring>>>),
+}
+
+/// A database file read with positioned reads, so that it can be shared
+/// by concurrent lookups without a file position.
+pub(crate) struct FileSource {
+    file: File,
+    len: usize,
+    reader: Reader,
+}
+
+impl fmt::Debug for FileSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reader = match self.reader {
+            Reader::Pread => "pread",
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            Reader::IoUring(_) => "io_uring",
+        };
+        f.debug_struct("FileSource")
+            .field("file", &self.file)
+            .field("len", &self.len)
+            .field("reader", &reader)
+            .finish()
+    }
+}
+
+/// Opens the file of a database, on Windows with the cache hint of
+/// `access`.
+pub(crate) fn open(path: &Path, access: AccessPattern) -> std::io::Result<File> {
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::OpenOptionsExt;
+
+        const FILE_FLAG_RANDOM_ACCESS: u32 = 0x1000_0000;
+        const FILE_FLAG_SEQUENTIAL_SCAN: u32 = 0x0800_0000;
+        let flags = match access {
+            AccessPattern::Random => FILE_FLAG_RANDOM_ACCESS,
+            AccessPattern::Sequential => FILE_FLAG_SEQUENTIAL_SCAN,
+        };
+        std::fs::OpenOptions::new()
+            .read(true)
+            .custom_flags(flags)
+            .open(path)
+    }
+    #[cfg(not(windows))]
+    {
+        let _ = access;
+        File::open(path)
+    }
+}
+
+impl FileSource {
+    /// Opens the file for reads with `pread`.
+    pub fn open(path: &Path, access: AccessPattern) -> Result<Self, Error> {
+        let file = open(path, access)?;
+        let len = file.metadata()?.len() as usize;
+        Ok(Self {
+            file,
+            len,
+            reader: Reader::Pread,
+        })
+    }
+
+    /// Opens the file for reads with io_uring.
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    pub fn open_io_uring(path: &Path, access: AccessPattern) -> Result<Self, Error> {
+        Ok(Self {
+            reader: Reader::IoUring(Box::new(Mutex::new(Some(IoUring::new(RING_ENTRIES)?)))),
+            ..Self::open(path, access)?
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Fills each buffer from its 0-based offset, returning the number of
+    /// bytes read into each: less than its length only at the end of the
+    /// file.
+    pub fn read_batch(&self, reads: &mut [(u64, &mut [u8])]) -> Result<Vec<usize>, Error> {
+        match &self.reader {
+            Reader::Pread => reads
+                .iter_mut()
+                .map(|(offset, buf)| self.pread(*offset, buf))
+                .collect(),
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            Reader::IoUring(ring) => {
+                let mut ring = ring.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                match ring.as_mut() {
+                    Some(uring) => {
+                        let (done, broken) = self.read_io_uring(uring, reads);
+                        if broken {
+                            // Dropping the ring discards the reads it never
+                            // submitted; the ones it did have completed.
+                            *ring = None;
+                        }
+                        done
+                    }
+                    None => reads
+                        .iter_mut()
+                        .map(|(offset, buf)| self.pread(*offset, buf))
+                        .collect(),
+                }
+            }
+        }
+    }
+
+    /// Reads until `buf` is full or the end of the file. Nothing is read at
+    /// or past the end, where some offsets are rejected by the system.
+    fn pread(&self, offset: u64, buf: &mut [u8]) -> Result<usize, Error> {
+        let mut done = 0;
+        while done < buf.len() && offset.saturating_add(done as u64) < self.len as u64 {
+            #[cfg(unix)]
+            let read = std::os::unix::fs::FileExt::read_at(
+                &self.file,
+                &mut buf[done..],
+                offset + done as u64,
+            );
+            #[cfg(windows)]
+            let read = std::os::windows::fs::FileExt::seek_read(
+                &self.file,
+                &mut buf[done..],
+                offset + done as u64,
+            );
+            match read {
+                Ok(0) => break,
+                Ok(read) => done += read,
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(done)
+    }
+
+    /// Reads the batch through `ring`. Every read queued is waited for
+    /// before returning, errors included, as the kernel writes into the
+    /// buffers of the caller; the flag returned is set if the ring could
+    /// not be waited on and must not be used again.
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    pub(crate) fn read_io_uring(
+        &self,
+        ring: &mut IoUring,
+        reads: &mut [(u64, &mut [u8])],
+    ) -> (Result<Vec<usize>, Error>, bool) {
+        let mut done = vec![0; reads.len()];
+        let fd = types::Fd(self.file.as_raw_fd());
+        // Reads cut short before the end of the file are submitted again
+        // for their remainder.
+        let mut pending: Vec<usize> = (0..reads.len())
+            .filter(|&i| !reads[i].1.is_empty() && (reads[i].0 as usize) < self.len)
+            .collect();
+        while !pending.is_empty() {
+            let mut retry = Vec::new();
+            for chunk in pending.chunks(RING_ENTRIES as usize) {
+                let mut failure = None;
+                let mut queued = 0;
+                for &i in chunk {
+                    let (offset, buf) = &mut reads[i];
+                    let rest = &mut buf[done[i]..];
+                    let entry = opcode::Read::new(fd, rest.as_mut_ptr(), rest.len() as u32)
+                        .offset(*offset + done[i] as u64)
+                        .build()
+                        .user_data(i as u64);
+                    // The buffers outlive the completion of every read
+                    // queued, which is waited for below.
+                    if unsafe { ring.submission().push(&entry) }.is_err() {
+                        failure = Some(Error::IoError("io_uring submission queue full".into()));
+                        break;
+                    }
+                    queued += 1;
+                }
+                let mut completed = 0;
+                while completed < queued {
+                    match ring.submit_and_wait(queued - completed) {
+                        Ok(_) => {}
+                        // Interrupted, or completions to reap first.
+                        Err(err)
+                            if err.kind() == std::io::ErrorKind::Interrupted
+                                || err.raw_os_error() == Some(libc::EBUSY) => {}
+                        Err(err) => {
+                            let unsubmitted = {
+                                let mut submission = ring.submission();
+                                submission.sync();
+                                submission.len()
+                            };
+                            wait_in_flight(ring, queued - completed - unsubmitted);
+                            return (Err(failure.unwrap_or_else(|| err.into())), true);
+                        }
+                    }
+                    for cqe in ring.completion() {
+                        completed += 1;
+                        let i = cqe.user_data() as usize;
+                        let read = cqe.result();
+                        if read < 0 {
+                            failure.get_or_insert_with(|| {
+                                std::io::Error::from_raw_os_error(-read).into()
+                            });
+                            continue;
+                        }
+                        done[i] += read as usize;
+                        let (offset, buf) = &reads[i];
+                        if read > 0
+                            && done[i] < buf.len()
+                            && (*offset as usize + done[i]) < self.len
+                        {
+                            retry.push(i);
+                        }
+                    }
+                }
+                if let Some(err) = failure {
+                    return (Err(err), false);
+                }
+            }
+            pending = retry;
+        }
+        (Ok(done), false)
+    }
+
+    /// Fills `buf` from the 0-based offset, failing with `Error::Corrupt`
+    /// past the end of the file, like reads from memory.
+    pub fn read_exact_at(&self, offset: u64, buf: &mut [u8]) -> Result<(), Error> {
+        let len = buf.len();
+        let read = self.read_batch(&mut [(offset, buf)])?;
+        if read[0] < len {
+            return Err(Error::Corrupt(format!(
+                "read of {} bytes at {} past the end of the file",
+                len, offset
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Waits for `in_flight` reads submitted to a ring that can no longer be
+/// entered, by polling its completion queue. Completions are posted as
+/// the thread returns from system calls.
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+fn wait_in_flight(ring: &mut IoUring, in_flight: usize) {
+    let mut completed = 0;
+    while completed < in_flight {
+        completed += ring.completion().count();
+        std::thread::yield_now();
+    }
+}
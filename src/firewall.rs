@@ -0,0 +1,117 @@
+//! Per-country CIDR lists, for geo-blocking at the firewall with the same
+//! data the application uses.
+use crate::{error::Error, Record, DB};
+use ipnet::{IpNet, Ipv4Subnets, Ipv6Subnets};
+use std::{
+    io::Write,
+    net::{IpAddr, Ipv6Addr},
+};
+
+/// Output of `DB::export_firewall`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum FirewallFormat {
+    /// One CIDR per line, IPv4 first.
+    Plain,
+    /// Commands for `nft -f` declaring the interval sets `{name}-v4` and
+    /// `{name}-v6` in the `inet filter` table, emptying them and adding
+    /// the CIDRs, so that loading the file again replaces the sets.
+    Nftables,
+    /// Commands for `ipset restore` creating the `hash:net` sets
+    /// `{name}-v4` and `{name}-v6` if needed, emptying them and adding the
+    /// CIDRs.
+    Ipset,
+}
+
+/// Elements per `add element` command of the nftables output.
+const NFT_CHUNK: usize = 1000;
+
+fn country_of<'r>(record: &'r Record<'_>) -> Option<&'r str> {
+    let country = match record {
+        Record::LocationDb(rec) => &rec.country,
+        Record::ProxyDb(rec) => &rec.country,
+    };
+    country.as_ref().map(|country| country.short_name.as_ref())
+}
+
+/// IPv4-mapped addresses, whose ranges repeat the IPv4 table.
+fn is_ipv4_mapped(ip: Ipv6Addr) -> bool {
+    ip.segments()[..6] == [0, 0, 0, 0, 0, 0xffff]
+}
+
+pub(crate) fn country_cidrs(db: &DB, countries: &[&str]) -> Result<Vec<IpNet>, Error> {
+    let mut nets = Vec::new();
+    for range in db.ranges() {
+        let (range, record) = range?;
+        let wanted = country_of(&record).is_some_and(|code| {
+            countries
+                .iter()
+                .any(|wanted| wanted.eq_ignore_ascii_case(code))
+        });
+        if !wanted {
+            continue;
+        }
+        match (range.start, range.end) {
+            (IpAddr::V4(start), IpAddr::V4(end)) => {
+                nets.extend(Ipv4Subnets::new(start, end, 0).map(IpNet::V4))
+            }
+            (IpAddr::V6(start), IpAddr::V6(end)) if !is_ipv4_mapped(start) => {
+                nets.extend(Ipv6Subnets::new(start, end, 0).map(IpNet::V6))
+            }
+            _ => {}
+        }
+    }
+    Ok(IpNet::aggregate(&nets))
+}
+
+pub(crate) fn write<W: Write>(
+    nets: &[IpNet],
+    format: FirewallFormat,
+    name: &str,
+    mut out: W,
+) -> Result<(), Error> {
+    let (v4, v6): (Vec<&IpNet>, Vec<&IpNet>) =
+        nets.iter().partition(|net| matches!(net, IpNet::V4(_)));
+    for (nets, suffix, nft_type, ipset_family) in [
+        (v4, "v4", "ipv4_addr", "inet"),
+        (v6, "v6", "ipv6_addr", "inet6"),
+    ] {
+        match format {
+            FirewallFormat::Plain => {
+                for net in nets {
+                    writeln!(out, "{}", net)?;
+                }
+            }
+            FirewallFormat::Nftables => {
+                let set = format!("inet filter {}-{}", name, suffix);
+                writeln!(
+                    out,
+                    "add set {} {{ type {}; flags interval; }}",
+                    set, nft_type
+                )?;
+                writeln!(out, "flush set {}", set)?;
+                for chunk in nets.chunks(NFT_CHUNK) {
+                    let elements: Vec<String> = chunk.iter().map(|net| net.to_string()).collect();
+                    writeln!(out, "add element {} {{ {} }}", set, elements.join(", "))?;
+                }
+            }
+            FirewallFormat::Ipset => {
+                let set = format!("{}-{}", name, suffix);
+                // The default limit of 65536 entries is too low for the
+                // largest countries.
+                writeln!(
+                    out,
+                    "create {} hash:net family {} maxelem {} -exist",
+                    set,
+                    ipset_family,
+                    nets.len().max(65536)
+                )?;
+                writeln!(out, "flush {}", set)?;
+                for net in nets {
+                    writeln!(out, "add {} {}", set, net)?;
+                }
+            }
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use crate::IntoIpAddr;
+use std::net::IpAddr;
+
+/// Resolves the client address from the values of the `X-Forwarded-For`
+/// headers when `trusted_proxies` is non zero, falling back to the peer
+/// address.
+///
+/// Every proxy appends the address it received the request from, so the
+/// client is the last entry not written by a trusted proxy. Peers are
+/// expected as given by `addr::socket_ip`.
+pub(crate) fn client_ip<'a>(
+    forwarded_for: impl IntoIterator<Item = &'a str>,
+    trusted_proxies: usize,
+    peer: Option<IpAddr>,
+) -> Option<IpAddr> {
+    if trusted_proxies == 0 {
+        return peer;
+    }
+    let forwarded: Vec<&str> = forwarded_for
+        .into_iter()
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .collect();
+    if forwarded.is_empty() {
+        return peer;
+    }
+    // Some proxies write the port as well, e.g. `203.0.113.7:41234`.
+    forwarded[forwarded.len().saturating_sub(trusted_proxies)]
+        .into_ip_addr()
+        .ok()
+}
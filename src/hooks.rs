@@ -0,0 +1,85 @@
+use crate::error::Error;
+use alloc::{boxed::Box, vec::Vec};
+use core::{fmt, net::IpAddr, time::Duration};
+use serde::Serialize;
+
+/// Result of a lookup as reported to lookup hooks.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+pub enum LookupOutcome {
+    Found,
+    NotFound,
+    Error,
+}
+
+/// Passed to the callbacks registered with `DB::on_lookup` after every
+/// lookup.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+pub struct LookupEvent {
+    /// The address as given to the lookup, before any IPv6 translation.
+    pub ip: IpAddr,
+    pub outcome: LookupOutcome,
+    /// Time the lookup took; always zero without the `std` feature.
+    pub elapsed: Duration,
+}
+
+/// Times a lookup for the hooks and the metrics. Without `std` there is
+/// no clock and the elapsed time is always zero.
+pub(crate) struct Stopwatch {
+    #[cfg(feature = "std")]
+    started: Option<std::time::Instant>,
+}
+
+impl Stopwatch {
+    /// Starts timing if `enabled`, so lookups nobody times skip the clock.
+    #[cfg_attr(not(feature = "std"), allow(unused_variables))]
+    pub fn start(enabled: bool) -> Self {
+        Self {
+            #[cfg(feature = "std")]
+            started: enabled.then(std::time::Instant::now),
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        #[cfg(feature = "std")]
+        if let Some(started) = self.started {
+            return started.elapsed();
+        }
+        Duration::ZERO
+    }
+}
+
+type Hook = Box<dyn Fn(&LookupEvent) + Send + Sync>;
+
+#[derive(Default)]
+pub(crate) struct Hooks(Vec<Hook>);
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Hooks({})", self.0.len())
+    }
+}
+
+impl Hooks {
+    pub fn push(&mut self, hook: Hook) {
+        self.0.push(hook);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn fire<T>(&self, ip: IpAddr, result: &Result<T, Error>, elapsed: Duration) {
+        let event = LookupEvent {
+            ip,
+            outcome: match result {
+                Ok(_) => LookupOutcome::Found,
+                Err(Error::RecordNotFound) => LookupOutcome::NotFound,
+                Err(_) => LookupOutcome::Error,
+            },
+            elapsed,
+        };
+        for hook in &self.0 {
+            hook(&event);
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use alloc::string::String;
+use core::fmt;
+use serde::Serialize;
+
+/// Header of an opened database.
+#[derive(PartialEq, Eq, Debug, Clone, Serialize)]
+pub struct DbInfo {
+    /// "location" for IP2Location and "proxy" for IP2Proxy databases.
+    pub kind: &'static str,
+    pub path: String,
+    pub db_type: u8,
+    pub db_column: u8,
+    pub db_year: u8,
+    pub db_month: u8,
+    pub db_day: u8,
+    pub ipv4_count: u32,
+    pub ipv6_count: u32,
+    pub product_code: u8,
+    pub license_code: u8,
+    pub database_size: u32,
+    /// The acknowledgment the LITE license requires products using the
+    /// database to display, `None` for other databases.
+    pub attribution: Option<&'static str>,
+    /// The version of the serialized form of records, `SCHEMA_VERSION`.
+    pub schema_version: u32,
+}
+
+/// One field per line, e.g. for logs:
+///
+/// ```text
+/// Path:          data/IP2LOCATION-LITE-DB1.BIN
+/// Kind:          location
+/// Type:          1
+/// ...
+/// ```
+impl fmt::Display for DbInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Path:          {}", self.path)?;
+        writeln!(f, "Kind:          {}", self.kind)?;
+        writeln!(f, "Type:          {}", self.db_type)?;
+        writeln!(f, "Columns:       {}", self.db_column)?;
+        writeln!(
+            f,
+            "Date:          20{:02}-{:02}-{:02}",
+            self.db_year, self.db_month, self.db_day
+        )?;
+        writeln!(f, "IPv4 ranges:   {}", self.ipv4_count)?;
+        writeln!(f, "IPv6 ranges:   {}", self.ipv6_count)?;
+        writeln!(f, "Product code:  {}", self.product_code)?;
+        writeln!(f, "License code:  {}", self.license_code)?;
+        if let Some(attribution) = self.attribution {
+            writeln!(f, "Attribution:   {}", attribution)?;
+        }
+        writeln!(f, "Database size: {}", self.database_size)?;
+        writeln!(f, "Schema:        {}", self.schema_version)
+    }
+}
+
+/// Acknowledgment required by the IP2Location LITE license.
+pub const LOCATION_LITE_ATTRIBUTION: &str =
+    "This site or product includes IP2Location LITE data available from https://lite.ip2location.com.";
+/// Acknowledgment required by the IP2Proxy LITE license.
+pub const PROXY_LITE_ATTRIBUTION: &str =
+    "This site or product includes IP2Proxy LITE data available from https://lite.ip2location.com.";
+
+/// The attribution owed for a database of `kind` opened from `path`.
+///
+/// LITE databases are recognized by their file name, which the vendor
+/// downloads spell `IP2LOCATION-LITE-*` and `IP2PROXY-LITE-*`: the license
+/// code of the header differs between LITE files of the same edition, and
+/// databases read from memory have no name.
+pub(crate) fn attribution(kind: &str, path: &str) -> Option<&'static str> {
+    let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    if !name.to_ascii_uppercase().contains("LITE") {
+        return None;
+    }
+    Some(if kind == "proxy" {
+        PROXY_LITE_ATTRIBUTION
+    } else {
+        LOCATION_LITE_ATTRIBUTION
+    })
+}
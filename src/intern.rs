@@ -0,0 +1,100 @@
+//! Sharing the strings of owned records.
+use crate::{Country, LocationRecord, ProxyRecord, Record};
+use alloc::{borrow::Cow, boxed::Box};
+use std::{collections::HashSet, sync::Mutex};
+
+/// A set of strings shared by the records converted with
+/// `Record::into_interned`, so that records holding the same country,
+/// ISP or usage type point to one copy of it instead of each owning a
+/// `String`.
+///
+/// Interned strings are kept for the rest of the process, which is what
+/// lets records borrow them as `Cow<'static, str>` and be cloned without
+/// allocating. The set grows with the distinct values of the databases
+/// the records come from, not with the number of records.
+#[derive(Debug, Default)]
+pub struct Interner {
+    strings: Mutex<HashSet<&'static str>>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        //! Creates an empty interner, to be shared (e.g. in an `Arc`) by
+        //! everything producing owned records.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::{Interner, Record, DB};
+        //!
+        //! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! let interner = Interner::new();
+        //! let first = db.ip_lookup("43.224.159.155".parse().unwrap()).unwrap().into_interned(&interner);
+        //! let second = db.ip_lookup("43.224.159.156".parse().unwrap()).unwrap().into_interned(&interner);
+        //! if let (Record::LocationDb(first), Record::LocationDb(second)) = (first, second) {
+        //!     let (first, second) = (first.country.unwrap(), second.country.unwrap());
+        //!     assert!(std::ptr::eq(first.long_name.as_ref(), second.long_name.as_ref()));
+        //! }
+        //!```
+        Self::default()
+    }
+
+    /// Returns the shared copy of `value`, adding it on first use.
+    pub fn intern(&self, value: &str) -> &'static str {
+        let mut strings = self.strings.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(interned) = strings.get(value) {
+            return interned;
+        }
+        let interned: &'static str = Box::leak(value.into());
+        strings.insert(interned);
+        interned
+    }
+
+    /// Number of distinct strings interned.
+    pub fn len(&self) -> usize {
+        self.strings.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn cow(&self, value: Cow<'_, str>) -> Cow<'static, str> {
+        Cow::Borrowed(self.intern(&value))
+    }
+}
+
+impl Country<'_> {
+    /// Converts the country into one whose names are shared by `interner`.
+    pub fn into_interned(self, interner: &Interner) -> Country<'static> {
+        self.map_strings(|value| interner.cow(value))
+    }
+}
+
+impl LocationRecord<'_> {
+    /// Converts the record into one whose strings are shared by
+    /// `interner`, so it can outlive the database it was read from.
+    pub fn into_interned(self, interner: &Interner) -> LocationRecord<'static> {
+        self.map_strings(|value| interner.cow(value))
+    }
+}
+
+impl ProxyRecord<'_> {
+    /// Converts the record into one whose strings are shared by
+    /// `interner`, so it can outlive the database it was read from.
+    pub fn into_interned(self, interner: &Interner) -> ProxyRecord<'static> {
+        self.map_strings(|value| interner.cow(value))
+    }
+}
+
+impl Record<'_> {
+    /// Converts the record into one whose strings are shared by
+    /// `interner`, like `into_owned` without a copy of each string per
+    /// record.
+    pub fn into_interned(self, interner: &Interner) -> Record<'static> {
+        match self {
+            Record::LocationDb(rec) => Record::LocationDb(Box::new(rec.into_interned(interner))),
+            Record::ProxyDb(rec) => Record::ProxyDb(Box::new(rec.into_interned(interner))),
+        }
+    }
+}
@@ -1,21 +1,39 @@
+#[cfg(feature = "rand")]
+use crate::sample;
+#[cfg(feature = "spatial")]
+use crate::spatial::SpatialIndex;
+#[cfg(feature = "std")]
+use crate::spatial::{self, Nearest};
 use crate::{
-    common::{Source, FROM_6TO4, FROM_TEREDO, TO_6TO4, TO_TEREDO},
+    common::{DefaultSource, RowReader, RowSource, Storage, Target},
+    compact,
+    country_only::CountryOnlyRecord,
     error::Error,
+    hooks::{Hooks, LookupEvent, Stopwatch},
+    info::{self, DbInfo},
     ip2location::{
         consts::*,
         record::{self, LocationRecord},
     },
+    offset::{header, FileOffset},
+    options::{Backend, DbOptions},
+    range::IpRange,
+    raw::{IpFamily, RawRow},
+    schema::SCHEMA_VERSION,
+    source::Source,
+    stats::{LookupCounters, LookupStats, MemoryStats},
+    verify::{self, Issue, Layout, VerifyReport},
 };
-use memmap2::Mmap;
-use std::{
-    fs::File,
+use alloc::{boxed::Box, collections::BTreeMap, string::ToString, sync::Arc, vec::Vec};
+use core::{
     net::{IpAddr, Ipv6Addr},
-    path::Path,
     result::Result,
 };
+#[cfg(feature = "std")]
+use std::path::Path;
 
 #[derive(Debug)]
-pub struct LocationDB {
+pub struct LocationDB<S = DefaultSource> {
     db_type: u8,
     db_column: u8,
     db_year: u8,
@@ -30,11 +48,77 @@ pub struct LocationDB {
     product_code: u8,
     license_code: u8,
     database_size: u32,
-    source: Source,
+    ipv4_index: Vec<u32>,
+    ipv6_index: Vec<u32>,
+    options: DbOptions,
+    stats: Option<LookupCounters>,
+    hooks: Hooks,
+    source: Storage<S>,
 }
 
 impl LocationDB {
-    pub(crate) fn new(source: Source) -> Self {
+    #[cfg(feature = "std")]
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        //! Loads a Ip2Location Database .bin file from path using
+        //! mmap (memap) feature.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::DB;
+        //!
+        //! let mut db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //!```
+        Self::from_file_with_options(path, &DbOptions::default())
+    }
+
+    #[cfg(feature = "std")]
+    pub fn from_file_with_options<P: AsRef<Path>>(
+        path: P,
+        options: &DbOptions,
+    ) -> Result<Self, Error> {
+        //! Loads the database from path, applying the given options.
+        if !path.as_ref().exists() {
+            return Err(Error::IoError(
+                "Error opening DB file: No such file or directory".to_string(),
+            ));
+        }
+
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("open", db = "location", path = %path.as_ref().display())
+            .entered();
+        Self::from_storage(Storage::open(path.as_ref(), options)?, options)
+    }
+
+    pub fn from_bytes<B: Into<Arc<[u8]>>>(bytes: B) -> Result<Self, Error> {
+        //! Loads a Ip2Location database from its bytes instead of a file.
+        Self::from_bytes_with_options(bytes, &DbOptions::default())
+    }
+
+    pub fn from_bytes_with_options<B: Into<Arc<[u8]>>>(
+        bytes: B,
+        options: &DbOptions,
+    ) -> Result<Self, Error> {
+        //! Loads the database from its bytes, applying the given options.
+        Self::from_storage(Storage::from_bytes(bytes.into()), options)
+    }
+
+    pub(crate) fn from_storage(
+        source: Storage<DefaultSource>,
+        options: &DbOptions,
+    ) -> Result<Self, Error> {
+        let mut ldb = Self::open(source, options)?;
+        if ldb.options.backend == Backend::Compact {
+            let bytes = compact::compact(&ldb.layout())?;
+            ldb.source = ldb.source.with_bytes(bytes.into());
+            ldb.read_header()?;
+        }
+        ldb.finish()
+    }
+}
+
+impl<S: Source> LocationDB<S> {
+    pub(crate) fn new(source: Storage<S>, options: DbOptions) -> Self {
         Self {
             db_type: 0,
             db_column: 0,
@@ -50,55 +134,452 @@ impl LocationDB {
             product_code: 0,
             license_code: 0,
             database_size: 0,
+            ipv4_index: Vec::new(),
+            ipv6_index: Vec::new(),
+            stats: options.collect_stats.then(LookupCounters::default),
+            hooks: Hooks::default(),
+            options,
             source,
         }
     }
 
-    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
-        //! Loads a Ip2Location Database .bin file from path using
-        //! mmap (memap) feature.
+    pub fn from_source(source: S) -> Result<Self, Error> {
+        //! Loads the database from a `Source` of the caller, read through
+        //! for every lookup. The database is typed by the source, so its
+        //! reads are not dispatched dynamically.
         //!
         //! ## Example usage
         //!
         //!```rust
-        //! use ip2location::DB;
+        //! use ip2location::LocationDB;
         //!
-        //! let mut db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! let bytes = std::fs::read("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! let db: LocationDB<Vec<u8>> = LocationDB::from_source(bytes).unwrap();
+        //! let record = db.ip_lookup("43.224.159.155".parse().unwrap()).unwrap();
         //!```
-        if !path.as_ref().exists() {
-            return Err(Error::IoError(
-                "Error opening DB file: No such file or directory".to_string(),
-            ));
-        }
+        Self::from_source_with_options(source, &DbOptions::default())
+    }
+
+    pub fn from_source_with_options(source: S, options: &DbOptions) -> Result<Self, Error> {
+        //! Loads the database from a `Source` of the caller, applying the
+        //! given options. The backend of the options only applies to files
+        //! and is ignored.
+        Self::open(Storage::new(source), options)?.finish()
+    }
 
-        let db = File::open(&path)?;
-        let map = unsafe { Mmap::map(&db) }?;
-        let mut ldb = Self::new(Source::new(path.as_ref().to_path_buf(), map));
+    /// Reads and validates the header of `source`.
+    fn open(source: Storage<S>, options: &DbOptions) -> Result<Self, Error> {
+        let mut ldb = Self::new(source.with_options(options), options.clone());
         ldb.read_header()?;
+        verify::validate(&ldb.layout(), ldb.options.validation)?;
         Ok(ldb)
     }
 
+    /// Preloads the index tables where the options or the source need them.
+    fn finish(mut self) -> Result<Self, Error> {
+        if self.options.preload_index || self.source.reads_file() {
+            self.preload_index()?;
+        }
+        Ok(self)
+    }
+
+    /// Touches every page of the database so that the first lookups after
+    /// startup do not pay for page faults.
+    pub fn warm(&self) {
+        self.source.warm();
+    }
+
+    /// Reports the memory used by the mapping and the preloaded index.
+    pub fn memory_stats(&self) -> MemoryStats {
+        MemoryStats {
+            mapped_bytes: self.source.len(),
+            resident_bytes: self.source.resident_len(),
+            index_bytes: (self.ipv4_index.capacity() + self.ipv6_index.capacity())
+                * core::mem::size_of::<u32>(),
+            cache_entries: 0,
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[deprecated(note = "use `info()`, which implements `Display` and `Serialize`")]
     pub fn print_db_info(&self) {
         //! Prints the DB Information to console
+        print!("{}", self.info());
+    }
+
+    /// Product code of the header: 1 for IP2Location, 2 for IP2Proxy
+    /// databases, 0 in databases older than 2021.
+    pub fn product_code(&self) -> u8 {
+        self.product_code
+    }
+
+    /// License code of the header.
+    pub fn license_code(&self) -> u8 {
+        self.license_code
+    }
+
+    /// Number of records in the IPv4 table, i.e. of its ranges: the rows
+    /// of the header count but the last one, which only marks the end of
+    /// the address space.
+    pub fn ipv4_record_count(&self) -> u32 {
+        self.range_count(false)
+    }
+
+    /// Number of records in the IPv6 table, 0 for IPv4-only databases.
+    pub fn ipv6_record_count(&self) -> u32 {
+        self.range_count(true)
+    }
+
+    /// Size in bytes of the database as opened: the file, the buffer of
+    /// `from_bytes`, or the compacted copy of `Backend::Compact`.
+    pub fn file_size(&self) -> u64 {
+        self.source.len() as u64
+    }
+
+    /// Returns the parsed header of the database.
+    pub fn info(&self) -> DbInfo {
+        let path = self.source.to_string();
+        DbInfo {
+            kind: "location",
+            attribution: info::attribution("location", &path),
+            path,
+            db_type: self.db_type,
+            db_column: self.db_column,
+            db_year: self.db_year,
+            db_month: self.db_month,
+            db_day: self.db_day,
+            ipv4_count: self.ipv4_db_count,
+            ipv6_count: self.ipv6_db_count,
+            product_code: self.product_code,
+            license_code: self.license_code,
+            database_size: self.database_size,
+            schema_version: SCHEMA_VERSION,
+        }
+    }
+
+    /// Returns the undecoded bytes of a row of the IPv4 or IPv6 table.
+    pub fn get_raw_row(&self, index: u32, family: IpFamily) -> Result<RawRow<'_>, Error> {
+        let table = match family {
+            IpFamily::V4 => (self.ipv4_db_count, self.ipv4_db_addr),
+            IpFamily::V6 => (self.ipv6_db_count, self.ipv6_db_addr),
+        };
+        RawRow::read(&self.source, family, index, table, self.db_column)
+    }
+
+    /// Number of ranges in the IPv4 or IPv6 table. The last row of a
+    /// table only marks the end of the address space and is not counted.
+    pub(crate) fn range_count(&self, ipv6: bool) -> u32 {
+        let count = if ipv6 {
+            self.ipv6_db_count
+        } else {
+            self.ipv4_db_count
+        };
+        count.saturating_sub(1)
+    }
+
+    /// Size in bytes of a row of the IPv4 or IPv6 table.
+    fn row_size(&self, ipv6: bool) -> u64 {
+        u64::from(self.db_column) * 4 + if ipv6 { 12 } else { 0 }
+    }
+
+    /// Offset of a row of the IPv4 or IPv6 table.
+    fn row_offset(&self, ipv6: bool, row: u32) -> FileOffset {
+        let table = if ipv6 {
+            self.ipv6_db_addr
+        } else {
+            self.ipv4_db_addr
+        };
+        FileOffset::from_position(table) + u64::from(row) * self.row_size(ipv6)
+    }
+
+    /// Offset the columns of a row of the IPv4 or IPv6 table are read
+    /// from, as passed to `read_record`: column 1 is at this offset, and
+    /// IPv6 rows start 12 bytes earlier for their 16-byte address.
+    fn columns_addr(&self, ipv6: bool, row: u32) -> FileOffset {
+        self.row_offset(ipv6, row) + if ipv6 { 12 } else { 0 }
+    }
+
+    /// Reads the range of a row of the IPv4 or IPv6 table, along with the
+    /// offset its columns are read from.
+    pub(crate) fn row_range(&self, ipv6: bool, row: u32) -> Result<(IpRange, FileOffset), Error> {
+        let row_addr = self.row_offset(ipv6, row);
+        let next = row_addr + self.row_size(ipv6);
+        let range = if ipv6 {
+            let ip_from = self.source.read_ipv6(row_addr)?;
+            let ip_to = self.source.read_ipv6(next)?;
+            IpRange::from_ipv6_row(ip_from, ip_to)
+        } else {
+            let ip_from = self.source.read_u32(row_addr)?;
+            let ip_to = self.source.read_u32(next)?;
+            IpRange::from_ipv4_row(ip_from, ip_to)
+        };
+        Ok((range, self.columns_addr(ipv6, row)))
+    }
+
+    /// Reads the range and record of a row of the IPv4 or IPv6 table. The
+    /// record's `ip` is the start of the range.
+    pub(crate) fn range_at(
+        &self,
+        ipv6: bool,
+        row: u32,
+    ) -> Result<(IpRange, LocationRecord<'_>), Error> {
+        let (range, row_addr) = self.row_range(ipv6, row)?;
+        let mut record = self.read_record(row_addr)?;
+        record.ip = range.start;
+        Ok((range, record))
+    }
+
+    pub fn ranges_for_country<'a>(
+        &'a self,
+        code: &'a str,
+    ) -> impl Iterator<Item = Result<IpRange, Error>> + 'a {
+        //! Iterates over the ranges of a country, given by its ISO 3166 code
+        //! in any case: the IPv4 table first, then the IPv6 one, in address
+        //! order. Only the country column of each row is read, so this is
+        //! much faster than filtering `DB::ranges`.
         //!
         //! ## Example usage
         //!
         //! ```rust
-        //! use ip2location::DB;
+        //! use ip2location::LocationDB;
         //!
-        //! let mut db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
-        //! db.print_db_info();
+        //! let db = LocationDB::from_file("data/IP2LOCATION-LITE-DB1.IPV6.BIN").unwrap();
+        //! let (v4, v6): (Vec<_>, Vec<_>) = db
+        //!     .ranges_for_country("CN")
+        //!     .filter_map(Result::ok)
+        //!     .partition(|range| !range.is_ipv6());
+        //! assert!(!v4.is_empty() && !v6.is_empty());
         //! ```
-        println!("Db Path: {}", self.source);
-        println!(" |- Db Type: {}", self.db_type);
-        println!(" |- Db Column: {}", self.db_column);
-        println!(
-            " |- Db Date (YY/MM/DD): {}/{}/{}",
-            self.db_year, self.db_month, self.db_day
-        );
+        let position = COUNTRY_POSITION
+            .get(self.db_type as usize)
+            .copied()
+            .unwrap_or(0);
+        // Whether the country at each string pointer seen so far matches:
+        // countries are usually stored once and shared by their rows.
+        let mut matches = BTreeMap::new();
+        let rows = |ipv6| (0..self.range_count(ipv6)).map(move |row| (ipv6, row));
+        rows(false)
+            .chain(rows(true))
+            .filter(move |_| position > 0)
+            .filter_map(move |(ipv6, row)| {
+                let mut country_of = |row_addr: FileOffset| -> Result<bool, Error> {
+                    let pointer = self.source.read_u32(row_addr.column(position))?;
+                    if let Some(&matched) = matches.get(&pointer) {
+                        return Ok(matched);
+                    }
+                    let matched = self
+                        .source
+                        .read_str(pointer.into())?
+                        .eq_ignore_ascii_case(code);
+                    matches.insert(pointer, matched);
+                    Ok(matched)
+                };
+                match country_of(self.columns_addr(ipv6, row)) {
+                    Ok(true) => Some(self.row_range(ipv6, row).map(|(range, _)| range)),
+                    Ok(false) => None,
+                    Err(e) => Some(Err(e)),
+                }
+            })
+    }
+
+    pub fn ranges_in_bbox(
+        &self,
+        min_lat: f32,
+        min_lon: f32,
+        max_lat: f32,
+        max_lon: f32,
+    ) -> impl Iterator<Item = Result<IpRange, Error>> + '_ {
+        //! Iterates over the ranges whose coordinates are inside a box, the
+        //! IPv4 table first, then the IPv6 one, in address order. A box with
+        //! `min_lon` greater than `max_lon` crosses the antimeridian. Only
+        //! the coordinate columns of each row are read; databases without
+        //! coordinates (below DB5) yield nothing.
+        //!
+        //! ## Example usage
+        //!
+        //! ```rust,no_run
+        //! use ip2location::LocationDB;
+        //!
+        //! let db = LocationDB::from_file("data/IP2LOCATION-LITE-DB5.BIN").unwrap();
+        //! // Ranges located in Greater London.
+        //! for range in db.ranges_in_bbox(51.28, -0.51, 51.69, 0.33) {
+        //!     println!("{:?}", range.unwrap());
+        //! }
+        //! ```
+        let inside = move |(lat, lon): (f32, f32)| {
+            let lon_inside = if min_lon <= max_lon {
+                (min_lon..=max_lon).contains(&lon)
+            } else {
+                lon >= min_lon || lon <= max_lon
+            };
+            (min_lat..=max_lat).contains(&lat) && lon_inside
+        };
+        self.located_rows()
+            .filter_map(move |(ipv6, row)| match self.coordinates(ipv6, row) {
+                Ok(coordinates) if inside(coordinates) => {
+                    Some(self.row_range(ipv6, row).map(|(range, _)| range))
+                }
+                Ok(_) => None,
+                Err(e) => Some(Err(e)),
+            })
+    }
+
+    #[cfg(feature = "std")]
+    pub fn nearest(&self, lat: f32, lon: f32, k: usize) -> Result<Vec<Nearest<'_>>, Error> {
+        //! Finds the `k` locations nearest to a coordinate, closest first,
+        //! by great-circle distance. Ranges sharing coordinates are one
+        //! location, reported with the first of them in address order.
+        //! Every row is scanned; databases without coordinates (below DB5)
+        //! find nothing.
+        //!
+        //! ## Example usage
+        //!
+        //! ```rust,no_run
+        //! use ip2location::LocationDB;
+        //!
+        //! let db = LocationDB::from_file("data/IP2LOCATION-LITE-DB5.BIN").unwrap();
+        //! for nearest in db.nearest(48.86, 2.35, 3).unwrap() {
+        //!     println!("{:?} {:.0} km", nearest.record.city, nearest.distance_km);
+        //! }
+        //! ```
+        let mut locations = BTreeMap::new();
+        for (ipv6, row) in self.located_rows() {
+            let (row_lat, row_lon) = self.coordinates(ipv6, row)?;
+            locations
+                .entry((row_lat.to_bits(), row_lon.to_bits()))
+                .or_insert((
+                    ipv6,
+                    row,
+                    spatial::distance_km((lat, lon), (row_lat, row_lon)),
+                ));
+        }
+        let mut locations: Vec<_> = locations.into_values().collect();
+        locations.sort_by(|a, b| a.2.total_cmp(&b.2).then((a.0, a.1).cmp(&(b.0, b.1))));
+        locations
+            .into_iter()
+            .take(k)
+            .map(|(ipv6, row, distance_km)| {
+                let (range, record) = self.range_at(ipv6, row)?;
+                Ok(Nearest {
+                    range,
+                    record,
+                    distance_km,
+                })
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "spatial")]
+    pub fn build_spatial_index(&self) -> Result<SpatialIndex, Error> {
+        //! Builds an R-tree of the coordinates of every row, for
+        //! `SpatialIndex::nearest` and `SpatialIndex::ranges_in_bbox`. This
+        //! reads the whole database once; keep the index for as long as
+        //! the database instead of building it per query.
+        //!
+        //! ## Example usage
+        //!
+        //! ```rust,no_run
+        //! use ip2location::LocationDB;
+        //!
+        //! let db = LocationDB::from_file("data/IP2LOCATION-LITE-DB5.BIN").unwrap();
+        //! let index = db.build_spatial_index().unwrap();
+        //! println!("{} locations", index.len());
+        //! ```
+        SpatialIndex::build(self)
+    }
+
+    /// The rows of both tables, IPv4 first, if the database has
+    /// coordinates.
+    pub(crate) fn located_rows(&self) -> impl Iterator<Item = (bool, u32)> + '_ {
+        let db_type = self.db_type as usize;
+        let located = LATITUDE_POSITION.get(db_type).is_some_and(|&p| p > 0)
+            && LONGITUDE_POSITION.get(db_type).is_some_and(|&p| p > 0);
+        let rows = |ipv6| (0..self.range_count(ipv6)).map(move |row| (ipv6, row));
+        rows(false).chain(rows(true)).filter(move |_| located)
+    }
+
+    /// Reads the latitude and longitude of a row of a database with
+    /// coordinates.
+    pub(crate) fn coordinates(&self, ipv6: bool, row: u32) -> Result<(f32, f32), Error> {
+        let db_type = self.db_type as usize;
+        let row_addr = self.columns_addr(ipv6, row);
+        let lat = self
+            .source
+            .read_f32(row_addr.column(LATITUDE_POSITION[db_type]))?;
+        let lon = self
+            .source
+            .read_f32(row_addr.column(LONGITUDE_POSITION[db_type]))?;
+        match self.options.coordinate_precision {
+            Some(decimals) => Ok((
+                record::truncate(lat, decimals),
+                record::truncate(lon, decimals),
+            )),
+            None => Ok((lat, lon)),
+        }
     }
 
-    pub fn ip_lookup(&self, ip: IpAddr) -> Result<LocationRecord, Error> {
+    #[cfg(feature = "rand")]
+    pub fn sample_ips<R: rand_core::Rng + ?Sized>(
+        &self,
+        country: &str,
+        n: usize,
+        rng: &mut R,
+    ) -> Result<Vec<IpAddr>, Error> {
+        //! Draws `n` random addresses of a country, given by its ISO 3166
+        //! code in any case, each address of its ranges being equally
+        //! likely, e.g. to generate test traffic from one country. IPv6
+        //! ranges hold far more addresses, so on a database with an IPv6
+        //! table nearly every address drawn is IPv6. Fails with
+        //! `Error::RecordNotFound` if the country has no range.
+        //!
+        //! ## Example usage
+        //!
+        //! ```rust
+        //! use ip2location::LocationDB;
+        //!
+        //! let db = LocationDB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! let mut rng = rand::rng();
+        //! for ip in db.sample_ips("DE", 10, &mut rng).unwrap() {
+        //!     println!("{}", ip);
+        //! }
+        //! ```
+        sample::sample_ips(self.ranges_for_country(country), n, rng)
+    }
+
+    /// Scans every row and index entry of the database for unsorted
+    /// ranges, pointers outside the file and header inconsistencies.
+    pub fn verify(&self) -> VerifyReport {
+        verify::scan(&self.layout())
+    }
+
+    /// Checks the header, the order of the rows and the index tables,
+    /// stopping at the first problem; see `DB::check_invariants`.
+    pub fn check_invariants(&self) -> Result<(), Issue> {
+        verify::check(&self.layout())
+    }
+
+    fn layout(&self) -> Layout<'_, S> {
+        let db_type = self.db_type as usize;
+        Layout {
+            source: &self.source,
+            db_column: self.db_column,
+            ipv4_count: self.ipv4_db_count,
+            ipv4_addr: self.ipv4_db_addr,
+            ipv6_count: self.ipv6_db_count,
+            ipv6_addr: self.ipv6_db_addr,
+            ipv4_index_base: self.ipv4_index_base_addr,
+            ipv6_index_base: self.ipv6_index_base_addr,
+            database_size: self.database_size,
+            float_columns: [LATITUDE_POSITION, LONGITUDE_POSITION]
+                .iter()
+                .filter_map(|positions| positions.get(db_type).copied())
+                .collect(),
+            country_column: COUNTRY_POSITION.get(db_type).copied().unwrap_or(0),
+            unknown_type: (db_type >= COUNTRY_POSITION.len()).then_some(self.db_type),
+        }
+    }
+
+    pub fn ip_lookup(&self, ip: IpAddr) -> Result<LocationRecord<'_>, Error> {
         //! Lookup for the given IPv4 or IPv6 and returns the Geo information
         //!
         //! ## Example usage
@@ -116,51 +597,210 @@ impl LocationDB {
         //! assert!(!geo_info.country.is_none());
         //! assert_eq!(geo_info.country.unwrap().short_name, "FR")
         //!```
-        match ip {
-            IpAddr::V4(ipv4) => {
-                let mut record = self.ipv4_lookup(u32::from(ipv4))?;
-                record.ip = ip;
-                Ok(record)
-            }
-            IpAddr::V6(ipv6) => {
-                if let Some(converted_ip) = ipv6.to_ipv4() {
-                    let mut record = self.ipv4_lookup(u32::from(converted_ip))?;
-                    record.ip = ip;
-                    Ok(record)
-                } else if Ipv6Addr::from(FROM_6TO4) <= ipv6 && ipv6 <= Ipv6Addr::from(TO_6TO4) {
-                    let ipnum = (u128::from(ipv6) >> 80) as u32;
-                    let mut record = self.ipv4_lookup(ipnum)?;
-                    record.ip = ip;
-                    Ok(record)
-                } else if Ipv6Addr::from(FROM_TEREDO) <= ipv6 && ipv6 <= Ipv6Addr::from(TO_TEREDO) {
-                    let ipnum = !u128::from(ipv6) as u32;
-                    let mut record = self.ipv4_lookup(ipnum)?;
-                    record.ip = ip;
-                    Ok(record)
-                } else {
-                    let mut record = self.ipv6_lookup(ipv6)?;
-                    record.ip = ip;
-                    Ok(record)
-                }
-            }
+        self.lookup(ip).map(|(_, record)| record)
+    }
+
+    /// `ip_lookup` returning a record that owns its strings and does not
+    /// borrow the database.
+    pub fn ip_lookup_owned(&self, ip: IpAddr) -> Result<LocationRecord<'static>, Error> {
+        self.ip_lookup(ip).map(LocationRecord::into_owned)
+    }
+
+    /// The address searched for when looking up `ip`.
+    pub(crate) fn target(&self, ip: IpAddr) -> Target {
+        Target::from_ip(ip, &self.options.translation)
+    }
+
+    /// Searches the table of the target, without the statistics and hooks
+    /// of `lookup`.
+    pub(crate) fn search(&self, target: Target) -> Result<(IpRange, LocationRecord<'_>), Error> {
+        let (range, row_addr) = self.find_row(target)?;
+        Ok((range, self.read_record(row_addr)?))
+    }
+
+    /// Finds the range of the target and the address its columns are read
+    /// from.
+    fn find_row(&self, target: Target) -> Result<(IpRange, FileOffset), Error> {
+        match target {
+            Target::V4(ip_number) => self.ipv4_lookup(ip_number),
+            Target::V6(ipv6) => self.ipv6_lookup(ipv6),
+        }
+    }
+
+    /// Looks up the address and returns the matched range along with the
+    /// record, updating the counters and calling the lookup hooks.
+    pub(crate) fn lookup(&self, ip: IpAddr) -> Result<(IpRange, LocationRecord<'_>), Error> {
+        let mut result = self.observe(ip, |target| self.search(target));
+        if let Ok((_, record)) = &mut result {
+            record.ip = ip;
+        }
+        result
+    }
+
+    pub fn country_lookup(&self, ip: IpAddr) -> Result<CountryOnlyRecord<'_>, Error> {
+        //! Looks up only the country of an address, as a record that has no
+        //! other field: nothing else is decoded. Counted and reported to the
+        //! lookup hooks like `ip_lookup`.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::LocationDB;
+        //!
+        //! let db = LocationDB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! let record = db.country_lookup("43.224.159.155".parse().unwrap()).unwrap();
+        //! assert_eq!(record.country.unwrap().short_name, "IN");
+        //!```
+        let (_, country) = self.observe(ip, |target| {
+            let (range, row_addr) = self.find_row(target)?;
+            Ok((range, self.decode_country(&&self.source, row_addr)?))
+        })?;
+        Ok(CountryOnlyRecord::new(ip, country))
+    }
+
+    /// Runs `search` on the target of `ip`, updating the counters, the
+    /// metrics and the traces and calling the lookup hooks.
+    fn observe<T>(
+        &self,
+        ip: IpAddr,
+        search: impl FnOnce(Target) -> Result<(IpRange, T), Error>,
+    ) -> Result<(IpRange, T), Error> {
+        let target = self.target(ip);
+        let stopwatch = Stopwatch::start(cfg!(feature = "metrics") || !self.hooks.is_empty());
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!(
+            "ip_lookup",
+            db = "location",
+            target = %self.options.observed(target.to_ip()),
+            index = self.index_kind(target)
+        )
+        .entered();
+        let result = search(target);
+        if let Some(stats) = &self.stats {
+            stats.record(target, &result);
+        }
+        let elapsed = stopwatch.elapsed();
+        #[cfg(feature = "metrics")]
+        crate::telemetry::record_metrics("location", target, &result, elapsed);
+        #[cfg(feature = "tracing")]
+        crate::telemetry::trace_result(&result);
+        if !self.hooks.is_empty() {
+            self.hooks.fire(self.options.observed(ip), &result, elapsed);
+        }
+        result
+    }
+
+    pub fn on_lookup<F>(&mut self, hook: F)
+    where
+        F: Fn(&LookupEvent) + Send + Sync + 'static,
+    {
+        //! Registers a callback invoked after every lookup with the
+        //! address, the outcome and the time the lookup took.
+        self.hooks.push(Box::new(hook));
+    }
+
+    /// How the index bounds of a lookup are obtained, reported in traces.
+    #[cfg(feature = "tracing")]
+    fn index_kind(&self, target: Target) -> &'static str {
+        let (preloaded, base) = match target {
+            Target::V4(_) => (&self.ipv4_index, self.ipv4_index_base_addr),
+            Target::V6(_) => (&self.ipv6_index, self.ipv6_index_base_addr),
+        };
+        if !preloaded.is_empty() {
+            "preloaded"
+        } else if base > 0 {
+            "mmap"
+        } else {
+            "none"
+        }
+    }
+
+    /// Returns the lookup counters, or `None` unless the database was
+    /// opened with `DbOptions::collect_stats`.
+    pub fn stats(&self) -> Option<LookupStats> {
+        self.stats.as_ref().map(LookupCounters::snapshot)
+    }
+
+    /// Resets the lookup counters to zero.
+    pub fn reset_stats(&self) {
+        if let Some(stats) = &self.stats {
+            stats.reset();
+        }
+    }
+
+    fn preload_index(&mut self) -> Result<(), Error> {
+        if self.ipv4_db_count > 0 {
+            self.ipv4_index = self.source.read_index(self.ipv4_index_base_addr)?;
+        }
+        if self.ipv6_db_count > 0 {
+            self.ipv6_index = self.source.read_index(self.ipv6_index_base_addr)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the `(low, high)` row bounds of the /16 block of an IPv4
+    /// address, from the preloaded index when available.
+    fn ipv4_bounds(&self, ip_number: u32) -> Result<(u32, u32), Error> {
+        let block = (ip_number >> 16) as usize;
+        if let Some(bounds) = self.ipv4_index.get(block * 2..block * 2 + 2) {
+            return Ok((bounds[0], bounds[1]));
         }
+        let index =
+            FileOffset::from_position(self.ipv4_index_base_addr) + u64::from(ip_number >> 16) * 8;
+        Ok((
+            self.source.read_u32(index)?,
+            self.source.read_u32(index + 4)?,
+        ))
+    }
+
+    /// Returns the `(low, high)` row bounds of the first 16 bits of an
+    /// IPv6 address, from the preloaded index when available.
+    fn ipv6_bounds(&self, ipv6: Ipv6Addr) -> Result<(u32, u32), Error> {
+        let block = (ipv6.octets()[0] as usize) * 256 + (ipv6.octets()[1] as usize);
+        if let Some(bounds) = self.ipv6_index.get(block * 2..block * 2 + 2) {
+            return Ok((bounds[0], bounds[1]));
+        }
+        let index = FileOffset::from_position(self.ipv6_index_base_addr) + block as u64 * 8;
+        Ok((
+            self.source.read_u32(index)?,
+            self.source.read_u32(index + 4)?,
+        ))
     }
 
     fn read_header(&mut self) -> Result<(), Error> {
-        self.db_type = self.source.read_u8(1)?;
-        self.db_column = self.source.read_u8(2)?;
-        self.db_year = self.source.read_u8(3)?;
-        self.db_month = self.source.read_u8(4)?;
-        self.db_day = self.source.read_u8(5)?;
-        self.ipv4_db_count = self.source.read_u32(6)?;
-        self.ipv4_db_addr = self.source.read_u32(10)?;
-        self.ipv6_db_count = self.source.read_u32(14)?;
-        self.ipv6_db_addr = self.source.read_u32(18)?;
-        self.ipv4_index_base_addr = self.source.read_u32(22)?;
-        self.ipv6_index_base_addr = self.source.read_u32(26)?;
-        self.product_code = self.source.read_u8(30)?;
-        self.license_code = self.source.read_u8(31)?;
-        self.database_size = self.source.read_u32(32)?;
+        self.db_type = self.source.read_u8(header::DB_TYPE)?;
+        self.db_column = self.source.read_u8(header::DB_COLUMN)?;
+        self.db_year = self.source.read_u8(header::DB_YEAR)?;
+        self.db_month = self.source.read_u8(header::DB_MONTH)?;
+        self.db_day = self.source.read_u8(header::DB_DAY)?;
+        self.ipv4_db_count = self.source.read_u32(header::IPV4_COUNT)?;
+        self.ipv4_db_addr = self.source.read_u32(header::IPV4_ADDR)?;
+        self.ipv6_db_count = self.source.read_u32(header::IPV6_COUNT)?;
+        self.ipv6_db_addr = self.source.read_u32(header::IPV6_ADDR)?;
+        self.ipv4_index_base_addr = self.source.read_u32(header::IPV4_INDEX)?;
+        self.ipv6_index_base_addr = self.source.read_u32(header::IPV6_INDEX)?;
+        self.product_code = self.source.read_u8(header::PRODUCT_CODE)?;
+        self.license_code = self.source.read_u8(header::LICENSE_CODE)?;
+        self.database_size = self.source.read_u32(header::DATABASE_SIZE)?;
+        for ipv6 in [false, true] {
+            let count = if ipv6 {
+                self.ipv6_db_count
+            } else {
+                self.ipv4_db_count
+            };
+            self.source
+                .set_rows(ipv6, self.row_offset(ipv6, 0), count, self.row_size(ipv6));
+        }
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            db_type = self.db_type,
+            db_column = self.db_column,
+            db_date = %format_args!("{}/{}/{}", self.db_year, self.db_month, self.db_day),
+            ipv4_count = self.ipv4_db_count,
+            ipv6_count = self.ipv6_db_count,
+            product_code = self.product_code,
+            "parsed header"
+        );
         if (self.db_year <= 20 && self.product_code == 0) || self.product_code == 1 {
             Ok(())
         } else {
@@ -168,236 +808,243 @@ impl LocationDB {
         }
     }
 
-    fn ipv4_lookup(&self, mut ip_number: u32) -> Result<LocationRecord, Error> {
+    fn ipv4_lookup(&self, mut ip_number: u32) -> Result<(IpRange, FileOffset), Error> {
         if ip_number == u32::MAX {
             ip_number -= 1;
         }
         let mut low = 0;
         let mut high = self.ipv4_db_count;
         if self.ipv4_index_base_addr > 0 {
-            let index = ((ip_number >> 16) << 3) + self.ipv4_index_base_addr;
-            low = self.source.read_u32(index as u64)?;
-            high = self.source.read_u32((index + 4) as u64)?;
+            (low, high) = self.ipv4_bounds(ip_number)?;
         }
         while low <= high {
-            let mid = (low + high) >> 1;
-            let ip_from = self
-                .source
-                .read_u32((self.ipv4_db_addr + mid * (self.db_column as u32) * 4) as u64)?;
-            let ip_to = self
-                .source
-                .read_u32((self.ipv4_db_addr + (mid + 1) * (self.db_column as u32) * 4) as u64)?;
+            let mid = low + (high - low) / 2;
+            let row_addr = self.row_offset(false, mid);
+            let ip_from = self.source.read_u32(row_addr)?;
+            let ip_to = self.source.read_u32(row_addr + self.row_size(false))?;
             if (ip_number >= ip_from) && (ip_number < ip_to) {
-                return self.read_record(self.ipv4_db_addr + mid * (self.db_column as u32) * 4);
+                return Ok((IpRange::from_ipv4_row(ip_from, ip_to), row_addr));
             } else if ip_number < ip_from {
-                high = mid - 1;
+                // Bounds of a corrupt index may end the search at row 0.
+                let Some(below) = mid.checked_sub(1) else {
+                    break;
+                };
+                high = below;
             } else {
-                low = mid + 1;
+                let Some(above) = mid.checked_add(1) else {
+                    break;
+                };
+                low = above;
             }
         }
         Err(Error::RecordNotFound)
     }
 
-    fn ipv6_lookup(&self, ipv6: Ipv6Addr) -> Result<LocationRecord, Error> {
+    fn ipv6_lookup(&self, ipv6: Ipv6Addr) -> Result<(IpRange, FileOffset), Error> {
         let mut low = 0;
         let mut high = self.ipv6_db_count;
         if self.ipv6_index_base_addr > 0 {
-            let num = (ipv6.octets()[0] as u32) * 256 + (ipv6.octets()[1] as u32);
-            let index = (num << 3) + self.ipv6_index_base_addr;
-            low = self.source.read_u32(index as u64)?;
-            high = self.source.read_u32((index + 4) as u64)?;
+            (low, high) = self.ipv6_bounds(ipv6)?;
         }
         while low <= high {
-            let mid = (low + high) >> 1;
-            let ip_from = self
-                .source
-                .read_ipv6((self.ipv6_db_addr + mid * ((self.db_column as u32) * 4 + 12)) as u64)?;
-            let ip_to = self.source.read_ipv6(
-                (self.ipv6_db_addr + (mid + 1) * ((self.db_column as u32) * 4 + 12)) as u64,
-            )?;
+            let mid = low + (high - low) / 2;
+            let row_addr = self.row_offset(true, mid);
+            let ip_from = self.source.read_ipv6(row_addr)?;
+            let ip_to = self.source.read_ipv6(row_addr + self.row_size(true))?;
             if (ipv6 >= ip_from) && (ipv6 < ip_to) {
-                return self.read_record(
-                    self.ipv6_db_addr + mid * ((self.db_column as u32) * 4 + 12) + 12,
-                );
+                return Ok((IpRange::from_ipv6_row(ip_from, ip_to), row_addr + 12));
             } else if ipv6 < ip_from {
-                high = mid - 1;
+                // Bounds of a corrupt index may end the search at row 0.
+                let Some(below) = mid.checked_sub(1) else {
+                    break;
+                };
+                high = below;
             } else {
-                low = mid + 1;
+                let Some(above) = mid.checked_add(1) else {
+                    break;
+                };
+                low = above;
             }
         }
         Err(Error::RecordNotFound)
     }
 
-    fn read_record(&self, row_addr: u32) -> Result<LocationRecord, Error> {
-        let mut result = LocationRecord::default();
+    fn read_record(&self, row_addr: FileOffset) -> Result<LocationRecord<'_>, Error> {
+        if !self.source.reads_file() {
+            return self.decode_record(&&self.source, row_addr);
+        }
+        let db_type = self.db_type as usize;
+        let floats = [LATITUDE_POSITION[db_type], LONGITUDE_POSITION[db_type]];
+        let pointers: Vec<usize> = (2..=self.db_column as u32)
+            .filter(|position| !floats.contains(position))
+            .map(|position| position as usize - 1)
+            .collect();
+        let country = COUNTRY_POSITION[db_type].checked_sub(1).map(|i| i as usize);
+        let row = RowReader::new(
+            &self.source,
+            row_addr,
+            self.db_column as usize,
+            &pointers,
+            country,
+        )?;
+        self.decode_record(&row, row_addr)
+    }
 
-        if COUNTRY_POSITION[self.db_type as usize] > 0 {
-            let index = self
-                .source
-                .read_u32((row_addr + 4 * (COUNTRY_POSITION[self.db_type as usize] - 1)).into())?;
-            let short_name = self.source.read_str(index.into())?;
-            let long_name = self.source.read_str((index + 3).into())?;
-            result.country = Some(record::Country {
-                short_name,
-                long_name,
-            });
+    fn decode_country<'a>(
+        &'a self,
+        source: &impl RowSource<'a>,
+        row_addr: FileOffset,
+    ) -> Result<Option<record::Country<'a>>, Error> {
+        if COUNTRY_POSITION[self.db_type as usize] == 0 {
+            return Ok(None);
         }
+        let index = source.read_u32(row_addr.column(COUNTRY_POSITION[self.db_type as usize]))?;
+        Ok(Some(record::Country {
+            short_name: source.read_str(index.into())?,
+            long_name: source.read_str(FileOffset::from(index) + 3)?,
+        }))
+    }
+
+    fn decode_record<'a>(
+        &'a self,
+        source: &impl RowSource<'a>,
+        row_addr: FileOffset,
+    ) -> Result<LocationRecord<'a>, Error> {
+        let mut result = LocationRecord {
+            country: self.decode_country(source, row_addr)?,
+            ..LocationRecord::default()
+        };
 
         if REGION_POSITION[self.db_type as usize] > 0 {
-            let index = self
-                .source
-                .read_u32((row_addr + 4 * (REGION_POSITION[self.db_type as usize] - 1)).into())?;
-            result.region = Some(self.source.read_str(index.into())?);
+            let index = source.read_u32(row_addr.column(REGION_POSITION[self.db_type as usize]))?;
+            result.region = Some(source.read_str(index.into())?);
         }
 
         if LATITUDE_POSITION[self.db_type as usize] > 0 {
-            let index = row_addr + 4 * (LATITUDE_POSITION[self.db_type as usize] - 1);
-            result.latitude = Some(self.source.read_f32(index.into())?);
+            let index = row_addr.column(LATITUDE_POSITION[self.db_type as usize]);
+            result.latitude = Some(source.read_f32(index)?);
         }
 
         if LONGITUDE_POSITION[self.db_type as usize] > 0 {
-            let index = row_addr + 4 * (LONGITUDE_POSITION[self.db_type as usize] - 1);
-            result.longitude = Some(self.source.read_f32(index.into())?);
+            let index = row_addr.column(LONGITUDE_POSITION[self.db_type as usize]);
+            result.longitude = Some(source.read_f32(index)?);
+        }
+
+        if let Some(decimals) = self.options.coordinate_precision {
+            result.truncate_coordinates(decimals);
         }
 
         if CITY_POSITION[self.db_type as usize] > 0 {
-            let index = self
-                .source
-                .read_u32((row_addr + 4 * (CITY_POSITION[self.db_type as usize] - 1)).into())?;
-            result.city = Some(self.source.read_str(index.into())?);
+            let index = source.read_u32(row_addr.column(CITY_POSITION[self.db_type as usize]))?;
+            result.city = Some(source.read_str(index.into())?);
         }
 
         if ISP_POSITION[self.db_type as usize] > 0 {
-            let index = self
-                .source
-                .read_u32((row_addr + 4 * (ISP_POSITION[self.db_type as usize] - 1)).into())?;
-            result.isp = Some(self.source.read_str(index.into())?);
+            let index = source.read_u32(row_addr.column(ISP_POSITION[self.db_type as usize]))?;
+            result.isp = Some(source.read_str(index.into())?);
         }
 
         if DOMAIN_POSITION[self.db_type as usize] > 0 {
-            let index = self
-                .source
-                .read_u32((row_addr + 4 * (DOMAIN_POSITION[self.db_type as usize] - 1)).into())?;
-            result.domain = Some(self.source.read_str(index.into())?);
+            let index = source.read_u32(row_addr.column(DOMAIN_POSITION[self.db_type as usize]))?;
+            result.domain = Some(source.read_str(index.into())?);
         }
 
         if ZIPCODE_POSITION[self.db_type as usize] > 0 {
-            let index = self
-                .source
-                .read_u32((row_addr + 4 * (ZIPCODE_POSITION[self.db_type as usize] - 1)).into())?;
-            result.zip_code = Some(self.source.read_str(index.into())?);
+            let index =
+                source.read_u32(row_addr.column(ZIPCODE_POSITION[self.db_type as usize]))?;
+            result.zip_code = Some(source.read_str(index.into())?);
         }
 
         if TIMEZONE_POSITION[self.db_type as usize] > 0 {
-            let index = self
-                .source
-                .read_u32((row_addr + 4 * (TIMEZONE_POSITION[self.db_type as usize] - 1)).into())?;
-            result.time_zone = Some(self.source.read_str(index.into())?);
+            let index =
+                source.read_u32(row_addr.column(TIMEZONE_POSITION[self.db_type as usize]))?;
+            result.time_zone = Some(source.read_str(index.into())?);
         }
 
         if NETSPEED_POSITION[self.db_type as usize] > 0 {
-            let index = self
-                .source
-                .read_u32((row_addr + 4 * (NETSPEED_POSITION[self.db_type as usize] - 1)).into())?;
-            result.net_speed = Some(self.source.read_str(index.into())?);
+            let index =
+                source.read_u32(row_addr.column(NETSPEED_POSITION[self.db_type as usize]))?;
+            result.net_speed = Some(source.read_str(index.into())?);
         }
 
         if IDDCODE_POSITION[self.db_type as usize] > 0 {
-            let index = self
-                .source
-                .read_u32((row_addr + 4 * (IDDCODE_POSITION[self.db_type as usize] - 1)).into())?;
-            result.idd_code = Some(self.source.read_str(index.into())?);
+            let index =
+                source.read_u32(row_addr.column(IDDCODE_POSITION[self.db_type as usize]))?;
+            result.idd_code = Some(source.read_str(index.into())?);
         }
 
         if AREACODE_POSITION[self.db_type as usize] > 0 {
-            let index = self
-                .source
-                .read_u32((row_addr + 4 * (AREACODE_POSITION[self.db_type as usize] - 1)).into())?;
-            result.area_code = Some(self.source.read_str(index.into())?);
+            let index =
+                source.read_u32(row_addr.column(AREACODE_POSITION[self.db_type as usize]))?;
+            result.area_code = Some(source.read_str(index.into())?);
         }
 
         if WEATHERSTATIONCODE_POSITION[self.db_type as usize] > 0 {
-            let index = self.source.read_u32(
-                (row_addr + 4 * (WEATHERSTATIONCODE_POSITION[self.db_type as usize] - 1)).into(),
-            )?;
-            result.weather_station_code = Some(self.source.read_str(index.into())?);
+            let index = source
+                .read_u32(row_addr.column(WEATHERSTATIONCODE_POSITION[self.db_type as usize]))?;
+            result.weather_station_code = Some(source.read_str(index.into())?);
         }
 
         if WEATHERSTATIONNAME_POSITION[self.db_type as usize] > 0 {
-            let index = self.source.read_u32(
-                (row_addr + 4 * (WEATHERSTATIONNAME_POSITION[self.db_type as usize] - 1)).into(),
-            )?;
-            result.weather_station_name = Some(self.source.read_str(index.into())?);
+            let index = source
+                .read_u32(row_addr.column(WEATHERSTATIONNAME_POSITION[self.db_type as usize]))?;
+            result.weather_station_name = Some(source.read_str(index.into())?);
         }
 
         if MCC_POSITION[self.db_type as usize] > 0 {
-            let index = self
-                .source
-                .read_u32((row_addr + 4 * (MCC_POSITION[self.db_type as usize] - 1)).into())?;
-            result.mcc = Some(self.source.read_str(index.into())?);
+            let index = source.read_u32(row_addr.column(MCC_POSITION[self.db_type as usize]))?;
+            result.mcc = Some(source.read_str(index.into())?);
         }
 
         if MNC_POSITION[self.db_type as usize] > 0 {
-            let index = self
-                .source
-                .read_u32((row_addr + 4 * (MNC_POSITION[self.db_type as usize] - 1)).into())?;
-            result.mnc = Some(self.source.read_str(index.into())?);
+            let index = source.read_u32(row_addr.column(MNC_POSITION[self.db_type as usize]))?;
+            result.mnc = Some(source.read_str(index.into())?);
         }
 
         if MOBILEBRAND_POSITION[self.db_type as usize] > 0 {
-            let index = self.source.read_u32(
-                (row_addr + 4 * (MOBILEBRAND_POSITION[self.db_type as usize] - 1)).into(),
-            )?;
-            result.mobile_brand = Some(self.source.read_str(index.into())?);
+            let index =
+                source.read_u32(row_addr.column(MOBILEBRAND_POSITION[self.db_type as usize]))?;
+            result.mobile_brand = Some(source.read_str(index.into())?);
         }
 
         if ELEVATION_POSITION[self.db_type as usize] > 0 {
-            let index = self.source.read_u32(
-                (row_addr + 4 * (ELEVATION_POSITION[self.db_type as usize] - 1)).into(),
-            )?;
-            result.elevation = Some(self.source.read_str(index.into())?);
+            let index =
+                source.read_u32(row_addr.column(ELEVATION_POSITION[self.db_type as usize]))?;
+            result.elevation = Some(source.read_str(index.into())?);
         }
 
         if USAGETYPE_POSITION[self.db_type as usize] > 0 {
-            let index = self.source.read_u32(
-                (row_addr + 4 * (USAGETYPE_POSITION[self.db_type as usize] - 1)).into(),
-            )?;
-            result.usage_type = Some(self.source.read_str(index.into())?);
+            let index =
+                source.read_u32(row_addr.column(USAGETYPE_POSITION[self.db_type as usize]))?;
+            result.usage_type = Some(source.read_str(index.into())?);
         }
 
         if ADDRESSTYPE_POSITION[self.db_type as usize] > 0 {
-            let index = self.source.read_u32(
-                (row_addr + 4 * (ADDRESSTYPE_POSITION[self.db_type as usize] - 1)).into(),
-            )?;
-            result.address_type = Some(self.source.read_str(index.into())?);
+            let index =
+                source.read_u32(row_addr.column(ADDRESSTYPE_POSITION[self.db_type as usize]))?;
+            result.address_type = Some(source.read_str(index.into())?);
         }
 
         if CATEGORY_POSITION[self.db_type as usize] > 0 {
-            let index = self
-                .source
-                .read_u32((row_addr + 4 * (CATEGORY_POSITION[self.db_type as usize] - 1)).into())?;
-            result.category = Some(self.source.read_str(index.into())?);
+            let index =
+                source.read_u32(row_addr.column(CATEGORY_POSITION[self.db_type as usize]))?;
+            result.category = Some(source.read_str(index.into())?);
         }
 
         if DISTRICT_POSITION[self.db_type as usize] > 0 {
-            let index = self
-                .source
-                .read_u32((row_addr + 4 * (DISTRICT_POSITION[self.db_type as usize] - 1)).into())?;
-            result.district = Some(self.source.read_str(index.into())?);
+            let index =
+                source.read_u32(row_addr.column(DISTRICT_POSITION[self.db_type as usize]))?;
+            result.district = Some(source.read_str(index.into())?);
         }
 
         if ASN_POSITION[self.db_type as usize] > 0 {
-            let index = self
-                .source
-                .read_u32((row_addr + 4 * (ASN_POSITION[self.db_type as usize] - 1)).into())?;
-            result.asn = Some(self.source.read_str(index.into())?);
+            let index = source.read_u32(row_addr.column(ASN_POSITION[self.db_type as usize]))?;
+            result.asn = Some(source.read_str(index.into())?);
         }
 
         if AS_POSITION[self.db_type as usize] > 0 {
-            let index = self
-                .source
-                .read_u32((row_addr + 4 * (AS_POSITION[self.db_type as usize] - 1)).into())?;
-            result.as_name = Some(self.source.read_str(index.into())?);
+            let index = source.read_u32(row_addr.column(AS_POSITION[self.db_type as usize]))?;
+            result.as_name = Some(source.read_str(index.into())?);
         }
         Ok(result)
     }
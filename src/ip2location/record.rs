@@ -1,20 +1,22 @@
-#![allow(clippy::derive_partial_eq_without_eq)]
-
-use serde::Serialize;
-use serde_with::skip_serializing_none;
-use std::{
-    borrow::Cow,
+use alloc::{borrow::Cow, string::String};
+use core::{
+    hash::{Hash, Hasher},
     net::{IpAddr, Ipv6Addr},
 };
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
 
-#[derive(PartialEq, Debug, Clone, Serialize)]
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Serialize, Deserialize)]
 pub struct Country<'a> {
     pub short_name: Cow<'a, str>,
     pub long_name: Cow<'a, str>,
 }
 
+/// Records compare and hash their coordinates by their bits, so that they
+/// can be keys of hash maps: a NaN coordinate equals itself, and 0.0 and
+/// -0.0 differ. `same_location` compares coordinates with a tolerance.
 #[skip_serializing_none]
-#[derive(PartialEq, Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LocationRecord<'a> {
     pub ip: IpAddr,
     pub latitude: Option<f32>,
@@ -43,10 +45,193 @@ pub struct LocationRecord<'a> {
     pub as_name: Option<Cow<'a, str>>,
 }
 
-impl LocationRecord<'_> {
+/// A `LocationRecord` owning its strings.
+pub type OwnedLocationRecord = LocationRecord<'static>;
+
+impl<'a> Country<'a> {
+    /// Converts the country into one that owns its strings.
+    pub fn into_owned(self) -> Country<'static> {
+        self.map_strings(|value| Cow::Owned(value.into_owned()))
+    }
+
+    pub(crate) fn map_strings<F>(self, mut f: F) -> Country<'static>
+    where
+        F: FnMut(Cow<'a, str>) -> Cow<'static, str>,
+    {
+        Country {
+            short_name: f(self.short_name),
+            long_name: f(self.long_name),
+        }
+    }
+}
+
+impl<'a> LocationRecord<'a> {
     pub fn to_json(&self) -> String {
         serde_json::to_string(&self).unwrap()
     }
+
+    #[cfg(feature = "msgpack")]
+    pub fn to_msgpack(&self) -> alloc::vec::Vec<u8> {
+        //! The record as MessagePack, a map of the fields it has named as
+        //! in `to_json`, e.g. to cache it; read it back with `from_msgpack`.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::{LocationDB, LocationRecord};
+        //!
+        //! let db = LocationDB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! let record = db.ip_lookup("43.224.159.155".parse().unwrap()).unwrap();
+        //! let bytes = record.to_msgpack();
+        //! assert!(bytes.len() < record.to_json().len());
+        //! assert_eq!(LocationRecord::from_msgpack(&bytes).unwrap(), record);
+        //!```
+        rmp_serde::to_vec_named(self).unwrap()
+    }
+
+    /// Reads a record written by `to_msgpack`.
+    #[cfg(feature = "msgpack")]
+    pub fn from_msgpack(bytes: &[u8]) -> Result<LocationRecord<'static>, crate::error::Error> {
+        Ok(rmp_serde::from_slice(bytes)?)
+    }
+
+    /// Returns true if both records are in the same place: the same
+    /// country, region and city, and coordinates within about 11 metres
+    /// of each other, so that records read from two releases of a
+    /// database compare equal despite rounding.
+    pub fn same_location(&self, other: &LocationRecord<'_>) -> bool {
+        let close = |a: Option<f32>, b: Option<f32>| match (a, b) {
+            (Some(a), Some(b)) => (a - b).abs() <= COORDINATE_TOLERANCE,
+            (a, b) => a.is_none() && b.is_none(),
+        };
+        self.country.as_ref().map(|c| &c.short_name)
+            == other.country.as_ref().map(|c| &c.short_name)
+            && self.region == other.region
+            && self.city == other.city
+            && close(self.latitude, other.latitude)
+            && close(self.longitude, other.longitude)
+    }
+
+    /// Truncates the latitude and longitude toward zero to `decimals`
+    /// decimal places, e.g. before storing the record.
+    pub fn truncate_coordinates(&mut self, decimals: u8) {
+        self.latitude = self.latitude.map(|value| truncate(value, decimals));
+        self.longitude = self.longitude.map(|value| truncate(value, decimals));
+    }
+
+    /// Converts the record into one that owns all of its strings, so it
+    /// can outlive the database it was read from.
+    pub fn into_owned(self) -> LocationRecord<'static> {
+        self.map_strings(|value| Cow::Owned(value.into_owned()))
+    }
+
+    /// Converts every string of the record with `f`.
+    pub(crate) fn map_strings<F>(self, mut f: F) -> LocationRecord<'static>
+    where
+        F: FnMut(Cow<'a, str>) -> Cow<'static, str>,
+    {
+        LocationRecord {
+            ip: self.ip,
+            latitude: self.latitude,
+            longitude: self.longitude,
+            country: self.country.map(|country| country.map_strings(&mut f)),
+            region: self.region.map(&mut f),
+            city: self.city.map(&mut f),
+            isp: self.isp.map(&mut f),
+            domain: self.domain.map(&mut f),
+            zip_code: self.zip_code.map(&mut f),
+            time_zone: self.time_zone.map(&mut f),
+            net_speed: self.net_speed.map(&mut f),
+            idd_code: self.idd_code.map(&mut f),
+            area_code: self.area_code.map(&mut f),
+            weather_station_code: self.weather_station_code.map(&mut f),
+            weather_station_name: self.weather_station_name.map(&mut f),
+            mcc: self.mcc.map(&mut f),
+            mnc: self.mnc.map(&mut f),
+            mobile_brand: self.mobile_brand.map(&mut f),
+            elevation: self.elevation.map(&mut f),
+            usage_type: self.usage_type.map(&mut f),
+            address_type: self.address_type.map(&mut f),
+            category: self.category.map(&mut f),
+            district: self.district.map(&mut f),
+            asn: self.asn.map(&mut f),
+            as_name: self.as_name.map(&mut f),
+        }
+    }
+}
+
+/// Maximum difference, in degrees, between the coordinates of records at
+/// the same location: about 11 metres.
+const COORDINATE_TOLERANCE: f32 = 1e-4;
+
+impl PartialEq for LocationRecord<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.latitude.map(f32::to_bits) == other.latitude.map(f32::to_bits)
+            && self.longitude.map(f32::to_bits) == other.longitude.map(f32::to_bits)
+            && self.ip == other.ip
+            && self.country == other.country
+            && self.region == other.region
+            && self.city == other.city
+            && self.isp == other.isp
+            && self.domain == other.domain
+            && self.zip_code == other.zip_code
+            && self.time_zone == other.time_zone
+            && self.net_speed == other.net_speed
+            && self.idd_code == other.idd_code
+            && self.area_code == other.area_code
+            && self.weather_station_code == other.weather_station_code
+            && self.weather_station_name == other.weather_station_name
+            && self.mcc == other.mcc
+            && self.mnc == other.mnc
+            && self.mobile_brand == other.mobile_brand
+            && self.elevation == other.elevation
+            && self.usage_type == other.usage_type
+            && self.address_type == other.address_type
+            && self.category == other.category
+            && self.district == other.district
+            && self.asn == other.asn
+            && self.as_name == other.as_name
+    }
+}
+
+impl Eq for LocationRecord<'_> {}
+
+impl Hash for LocationRecord<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.latitude.map(f32::to_bits).hash(state);
+        self.longitude.map(f32::to_bits).hash(state);
+        self.ip.hash(state);
+        self.country.hash(state);
+        self.region.hash(state);
+        self.city.hash(state);
+        self.isp.hash(state);
+        self.domain.hash(state);
+        self.zip_code.hash(state);
+        self.time_zone.hash(state);
+        self.net_speed.hash(state);
+        self.idd_code.hash(state);
+        self.area_code.hash(state);
+        self.weather_station_code.hash(state);
+        self.weather_station_name.hash(state);
+        self.mcc.hash(state);
+        self.mnc.hash(state);
+        self.mobile_brand.hash(state);
+        self.elevation.hash(state);
+        self.usage_type.hash(state);
+        self.address_type.hash(state);
+        self.category.hash(state);
+        self.district.hash(state);
+        self.asn.hash(state);
+        self.as_name.hash(state);
+    }
+}
+
+/// Truncates `value` toward zero to `decimals` decimal places. An f32
+/// holds no more than 9 significant digits, so more decimals change
+/// nothing.
+pub(crate) fn truncate(value: f32, decimals: u8) -> f32 {
+    let scale = (0..decimals.min(9)).fold(1.0f64, |scale, _| scale * 10.0);
+    ((f64::from(value) * scale) as i64 as f64 / scale) as f32
 }
 
 impl Default for LocationRecord<'_> {
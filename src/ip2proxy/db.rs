@@ -1,21 +1,30 @@
 use crate::{
-    common::{Source, FROM_6TO4, FROM_TEREDO, TO_6TO4, TO_TEREDO},
+    common::{DefaultSource, RowReader, RowSource, Storage, Target},
+    compact,
+    country_only::CountryOnlyRecord,
     error::Error,
+    hooks::{Hooks, LookupEvent, Stopwatch},
+    info::{self, DbInfo},
     ip2proxy::{
         consts::*,
         record::{Country, Proxy, ProxyRecord},
     },
+    offset::{header, FileOffset},
+    options::{Backend, DbOptions},
+    range::IpRange,
+    raw::{IpFamily, RawRow},
+    schema::SCHEMA_VERSION,
+    source::Source,
+    stats::{LookupCounters, LookupStats, MemoryStats},
+    verify::{self, Issue, Layout, VerifyReport},
 };
-use memmap2::Mmap;
-use std::{
-    borrow::Cow,
-    fs::File,
-    net::{IpAddr, Ipv6Addr},
-    path::Path,
-};
+use alloc::{borrow::Cow, boxed::Box, string::ToString, sync::Arc, vec::Vec};
+use core::net::{IpAddr, Ipv6Addr};
+#[cfg(feature = "std")]
+use std::path::Path;
 
 #[derive(Debug)]
-pub struct ProxyDB {
+pub struct ProxyDB<S = DefaultSource> {
     //    path: PathBuf,
     db_type: u8,
     db_column: u8,
@@ -31,11 +40,77 @@ pub struct ProxyDB {
     licence_code: u8,
     product_code: u8,
     database_size: u32,
-    source: Source,
+    ipv4_index: Vec<u32>,
+    ipv6_index: Vec<u32>,
+    options: DbOptions,
+    stats: Option<LookupCounters>,
+    hooks: Hooks,
+    source: Storage<S>,
 }
 
 impl ProxyDB {
-    pub(crate) fn new(source: Source) -> Self {
+    #[cfg(feature = "std")]
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        //! Loads a Ip2Proxy Database .bin file from path using
+        //! mmap (memap) feature.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::DB;
+        //!
+        //! let mut db = DB::from_file("data/IP2PROXY-IP-COUNTRY.BIN").unwrap();
+        //!```
+        Self::from_file_with_options(path, &DbOptions::default())
+    }
+
+    #[cfg(feature = "std")]
+    pub fn from_file_with_options<P: AsRef<Path>>(
+        path: P,
+        options: &DbOptions,
+    ) -> Result<Self, Error> {
+        //! Loads the database from path, applying the given options.
+        if !path.as_ref().exists() {
+            return Err(Error::IoError(
+                "Error opening DB file: No such file or directory".to_string(),
+            ));
+        }
+
+        #[cfg(feature = "tracing")]
+        let _span =
+            tracing::debug_span!("open", db = "proxy", path = %path.as_ref().display()).entered();
+        Self::from_storage(Storage::open(path.as_ref(), options)?, options)
+    }
+
+    pub fn from_bytes<B: Into<Arc<[u8]>>>(bytes: B) -> Result<Self, Error> {
+        //! Loads a Ip2Proxy database from its bytes instead of a file.
+        Self::from_bytes_with_options(bytes, &DbOptions::default())
+    }
+
+    pub fn from_bytes_with_options<B: Into<Arc<[u8]>>>(
+        bytes: B,
+        options: &DbOptions,
+    ) -> Result<Self, Error> {
+        //! Loads the database from its bytes, applying the given options.
+        Self::from_storage(Storage::from_bytes(bytes.into()), options)
+    }
+
+    pub(crate) fn from_storage(
+        source: Storage<DefaultSource>,
+        options: &DbOptions,
+    ) -> Result<Self, Error> {
+        let mut pdb = Self::open(source, options)?;
+        if pdb.options.backend == Backend::Compact {
+            let bytes = compact::compact(&pdb.layout())?;
+            pdb.source = pdb.source.with_bytes(bytes.into());
+            pdb.read_header()?;
+        }
+        pdb.finish()
+    }
+}
+
+impl<S: Source> ProxyDB<S> {
+    pub(crate) fn new(source: Storage<S>, options: DbOptions) -> Self {
         Self {
             //            path: db.path,
             db_type: 0,
@@ -52,35 +127,195 @@ impl ProxyDB {
             licence_code: 0,
             product_code: 0,
             database_size: 0,
+            ipv4_index: Vec::new(),
+            ipv6_index: Vec::new(),
+            stats: options.collect_stats.then(LookupCounters::default),
+            hooks: Hooks::default(),
+            options,
             source,
         }
     }
 
-    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
-        //! Loads a Ip2Proxy Database .bin file from path using
-        //! mmap (memap) feature.
+    pub fn from_source(source: S) -> Result<Self, Error> {
+        //! Loads a Ip2Proxy database from a `Source` of the caller, read
+        //! through for every lookup.
         //!
         //! ## Example usage
         //!
         //!```rust
-        //! use ip2location::DB;
+        //! use ip2location::ProxyDB;
         //!
-        //! let mut db = DB::from_file("data/IP2PROXY-IP-COUNTRY.BIN").unwrap();
+        //! let bytes = std::fs::read("data/IP2PROXY-IP-COUNTRY.BIN").unwrap();
+        //! let db = ProxyDB::from_source(bytes).unwrap();
+        //! let record = db.ip_lookup("1.1.1.1".parse().unwrap()).unwrap();
         //!```
-        if !path.as_ref().exists() {
-            return Err(Error::IoError(
-                "Error opening DB file: No such file or directory".to_string(),
-            ));
-        }
+        Self::from_source_with_options(source, &DbOptions::default())
+    }
 
-        let db = File::open(&path)?;
-        let map = unsafe { Mmap::map(&db) }?;
-        let mut pdb = Self::new(Source::new(path.as_ref().to_path_buf(), map));
+    pub fn from_source_with_options(source: S, options: &DbOptions) -> Result<Self, Error> {
+        //! Loads the database from a `Source` of the caller, applying the
+        //! given options; their backend is ignored.
+        Self::open(Storage::new(source), options)?.finish()
+    }
+
+    fn open(source: Storage<S>, options: &DbOptions) -> Result<Self, Error> {
+        let mut pdb = Self::new(source.with_options(options), options.clone());
         pdb.read_header()?;
+        verify::validate(&pdb.layout(), pdb.options.validation)?;
         Ok(pdb)
     }
 
-    pub fn ip_lookup(&self, ip: IpAddr) -> Result<ProxyRecord, Error> {
+    fn finish(mut self) -> Result<Self, Error> {
+        if self.options.preload_index || self.source.reads_file() {
+            self.preload_index()?;
+        }
+        Ok(self)
+    }
+
+    /// Product code of the header: 1 for IP2Location, 2 for IP2Proxy
+    /// databases, 0 in databases older than 2021.
+    pub fn product_code(&self) -> u8 {
+        self.product_code
+    }
+
+    /// License code of the header.
+    pub fn license_code(&self) -> u8 {
+        self.licence_code
+    }
+
+    /// Number of records in the IPv4 table, i.e. of its ranges: the rows
+    /// of the header count but the last one, which only marks the end of
+    /// the address space.
+    pub fn ipv4_record_count(&self) -> u32 {
+        self.range_count(false)
+    }
+
+    /// Number of records in the IPv6 table, 0 for IPv4-only databases.
+    pub fn ipv6_record_count(&self) -> u32 {
+        self.range_count(true)
+    }
+
+    /// Size in bytes of the database as opened: the file, the buffer of
+    /// `from_bytes`, or the compacted copy of `Backend::Compact`.
+    pub fn file_size(&self) -> u64 {
+        self.source.len() as u64
+    }
+
+    /// Returns the parsed header of the database.
+    pub fn info(&self) -> DbInfo {
+        let path = self.source.to_string();
+        DbInfo {
+            kind: "proxy",
+            attribution: info::attribution("proxy", &path),
+            path,
+            db_type: self.db_type,
+            db_column: self.db_column,
+            db_year: self.db_year,
+            db_month: self.db_month,
+            db_day: self.db_day,
+            ipv4_count: self.ipv4_db_count,
+            ipv6_count: self.ipv6_db_count,
+            product_code: self.product_code,
+            license_code: self.licence_code,
+            database_size: self.database_size,
+            schema_version: SCHEMA_VERSION,
+        }
+    }
+
+    /// Returns the undecoded bytes of a row of the IPv4 or IPv6 table.
+    pub fn get_raw_row(&self, index: u32, family: IpFamily) -> Result<RawRow<'_>, Error> {
+        let table = match family {
+            IpFamily::V4 => (self.ipv4_db_count, self.ipv4_db_addr),
+            IpFamily::V6 => (self.ipv6_db_count, self.ipv6_db_addr),
+        };
+        RawRow::read(&self.source, family, index, table, self.db_column)
+    }
+
+    /// Number of ranges in the IPv4 or IPv6 table. The last row of a
+    /// table only marks the end of the address space and is not counted.
+    pub(crate) fn range_count(&self, ipv6: bool) -> u32 {
+        let count = if ipv6 {
+            self.ipv6_db_count
+        } else {
+            self.ipv4_db_count
+        };
+        count.saturating_sub(1)
+    }
+
+    /// Size in bytes of a row of the IPv4 or IPv6 table.
+    fn row_size(&self, ipv6: bool) -> u64 {
+        u64::from(self.db_column) * 4 + if ipv6 { 12 } else { 0 }
+    }
+
+    /// Offset of a row of the IPv4 or IPv6 table.
+    fn row_offset(&self, ipv6: bool, row: u32) -> FileOffset {
+        let table = if ipv6 {
+            self.ipv6_db_addr
+        } else {
+            self.ipv4_db_addr
+        };
+        FileOffset::from_position(table) + u64::from(row) * self.row_size(ipv6)
+    }
+
+    /// Reads the range and record of a row of the IPv4 or IPv6 table. The
+    /// record's `ip` is the start of the range.
+    pub(crate) fn range_at(
+        &self,
+        ipv6: bool,
+        row: u32,
+    ) -> Result<(IpRange, ProxyRecord<'_>), Error> {
+        let row_addr = self.row_offset(ipv6, row);
+        let next = row_addr + self.row_size(ipv6);
+        let (range, mut record) = if ipv6 {
+            let ip_from = self.source.read_ipv6(row_addr)?;
+            let ip_to = self.source.read_ipv6(next)?;
+            (
+                IpRange::from_ipv6_row(ip_from, ip_to),
+                self.read_record(row_addr + 12)?,
+            )
+        } else {
+            let ip_from = self.source.read_u32(row_addr)?;
+            let ip_to = self.source.read_u32(next)?;
+            (
+                IpRange::from_ipv4_row(ip_from, ip_to),
+                self.read_record(row_addr)?,
+            )
+        };
+        record.ip = range.start;
+        Ok((range, record))
+    }
+
+    /// Scans every row and index entry of the database for unsorted
+    /// ranges, pointers outside the file and header inconsistencies.
+    pub fn verify(&self) -> VerifyReport {
+        verify::scan(&self.layout())
+    }
+
+    /// Checks the header, the order of the rows and the index tables,
+    /// stopping at the first problem; see `DB::check_invariants`.
+    pub fn check_invariants(&self) -> Result<(), Issue> {
+        verify::check(&self.layout())
+    }
+
+    fn layout(&self) -> Layout<'_, S> {
+        let db_type = self.db_type as usize;
+        Layout {
+            source: &self.source,
+            db_column: self.db_column,
+            ipv4_count: self.ipv4_db_count,
+            ipv4_addr: self.ipv4_db_addr,
+            ipv6_count: self.ipv6_db_count,
+            ipv6_addr: self.ipv6_db_addr,
+            ipv4_index_base: self.ipv4_index_base_addr,
+            ipv6_index_base: self.ipv6_index_base_addr,
+            database_size: self.database_size,
+            float_columns: Vec::new(),
+            country_column: COUNTRY_POSITION.get(db_type).copied().unwrap_or(0),
+            unknown_type: (db_type >= COUNTRY_POSITION.len()).then_some(self.db_type),
+        }
+    }
+
+    pub fn ip_lookup(&self, ip: IpAddr) -> Result<ProxyRecord<'_>, Error> {
         //! Lookup for the given IPv4 or IPv6 and returns the Proxy information
         //!
         //! ## Example usage
@@ -97,61 +332,237 @@ impl ProxyDB {
         //! let geo_info = record.unwrap();
         //! assert!(!geo_info.country.is_none());
         //!```
-        match ip {
-            IpAddr::V4(ipv4) => {
-                let mut record = self.get_ipv4_record(u32::from(ipv4))?;
-                record.ip = ip;
-                Ok(record)
-            }
-            IpAddr::V6(ipv6) => {
-                if let Some(converted_ip) = ipv6.to_ipv4() {
-                    let mut record = self.get_ipv4_record(u32::from(converted_ip))?;
-                    record.ip = ip;
-                    Ok(record)
-                } else if Ipv6Addr::from(FROM_6TO4) <= ipv6 && ipv6 <= Ipv6Addr::from(TO_6TO4) {
-                    let ipnum = (u128::from(ipv6) >> 80) as u32;
-                    let mut record = self.get_ipv4_record(ipnum)?;
-                    record.ip = ip;
-                    Ok(record)
-                } else if Ipv6Addr::from(FROM_TEREDO) <= ipv6 && ipv6 <= Ipv6Addr::from(TO_TEREDO) {
-                    let ipnum = !u128::from(ipv6) as u32;
-                    let mut record = self.get_ipv4_record(ipnum)?;
-                    record.ip = ip;
-                    Ok(record)
-                } else {
-                    let mut record = self.get_ipv6_record(ipv6)?;
-                    record.ip = ip;
-                    Ok(record)
-                }
-            }
+        self.lookup(ip).map(|(_, record)| record)
+    }
+
+    /// `ip_lookup` returning a record that owns its strings and does not
+    /// borrow the database.
+    pub fn ip_lookup_owned(&self, ip: IpAddr) -> Result<ProxyRecord<'static>, Error> {
+        self.ip_lookup(ip).map(ProxyRecord::into_owned)
+    }
+
+    /// The address searched for when looking up `ip`.
+    pub(crate) fn target(&self, ip: IpAddr) -> Target {
+        Target::from_ip(ip, &self.options.translation)
+    }
+
+    /// Searches the table of the target, without the statistics and hooks
+    /// of `lookup`.
+    pub(crate) fn search(&self, target: Target) -> Result<(IpRange, ProxyRecord<'_>), Error> {
+        match self.find_row(target)? {
+            (range, Some(offset)) => Ok((range, self.read_record(offset)?)),
+            (range, None) => Ok((range, ProxyRecord::default())),
         }
     }
 
+    /// Finds the range of the target and the offset the columns of its row
+    /// are read from, or `None` for an IPv6 target of a database without
+    /// an IPv6 table, whose record is empty.
+    fn find_row(&self, target: Target) -> Result<(IpRange, Option<FileOffset>), Error> {
+        match target {
+            Target::V4(ip_number) => self.get_ipv4_row(ip_number),
+            Target::V6(ipv6) => self.get_ipv6_row(ipv6),
+        }
+    }
+
+    /// Looks up the address and returns the matched range along with the
+    /// record, updating the counters and calling the lookup hooks.
+    pub(crate) fn lookup(&self, ip: IpAddr) -> Result<(IpRange, ProxyRecord<'_>), Error> {
+        let mut result = self.observe(ip, |target| self.search(target));
+        if let Ok((_, record)) = &mut result {
+            record.ip = ip;
+        }
+        result
+    }
+
+    pub fn country_lookup(&self, ip: IpAddr) -> Result<CountryOnlyRecord<'_>, Error> {
+        //! Looks up only the country of an address, as a record that has no
+        //! other field: nothing else is decoded. Counted and reported to the
+        //! lookup hooks like `ip_lookup`.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::ProxyDB;
+        //!
+        //! let db = ProxyDB::from_file("data/IP2PROXY-IP-COUNTRY.BIN").unwrap();
+        //! let record = db.country_lookup("1.1.1.1".parse().unwrap()).unwrap();
+        //! assert!(record.country.is_some());
+        //!```
+        let (_, country) = self.observe(ip, |target| match self.find_row(target)? {
+            (range, Some(offset)) => Ok((range, self.decode_country(&&self.source, offset)?)),
+            (range, None) => Ok((range, None)),
+        })?;
+        Ok(CountryOnlyRecord::new(ip, country))
+    }
+
+    /// Runs `search` on the target of `ip`, updating the counters, the
+    /// metrics and the traces and calling the lookup hooks.
+    fn observe<T>(
+        &self,
+        ip: IpAddr,
+        search: impl FnOnce(Target) -> Result<(IpRange, T), Error>,
+    ) -> Result<(IpRange, T), Error> {
+        let target = self.target(ip);
+        let stopwatch = Stopwatch::start(cfg!(feature = "metrics") || !self.hooks.is_empty());
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!(
+            "ip_lookup",
+            db = "proxy",
+            target = %self.options.observed(target.to_ip()),
+            index = self.index_kind(target)
+        )
+        .entered();
+        let result = search(target);
+        if let Some(stats) = &self.stats {
+            stats.record(target, &result);
+        }
+        let elapsed = stopwatch.elapsed();
+        #[cfg(feature = "metrics")]
+        crate::telemetry::record_metrics("proxy", target, &result, elapsed);
+        #[cfg(feature = "tracing")]
+        crate::telemetry::trace_result(&result);
+        if !self.hooks.is_empty() {
+            self.hooks.fire(self.options.observed(ip), &result, elapsed);
+        }
+        result
+    }
+
+    pub fn on_lookup<F>(&mut self, hook: F)
+    where
+        F: Fn(&LookupEvent) + Send + Sync + 'static,
+    {
+        //! Registers a callback invoked after every lookup with the
+        //! address, the outcome and the time the lookup took.
+        self.hooks.push(Box::new(hook));
+    }
+
+    /// How the index bounds of a lookup are obtained, reported in traces.
+    #[cfg(feature = "tracing")]
+    fn index_kind(&self, target: Target) -> &'static str {
+        let (preloaded, base) = match target {
+            Target::V4(_) => (&self.ipv4_index, self.ipv4_index_base_addr),
+            Target::V6(_) => (&self.ipv6_index, self.ipv6_index_base_addr),
+        };
+        if !preloaded.is_empty() {
+            "preloaded"
+        } else if base > 0 {
+            "mmap"
+        } else {
+            "none"
+        }
+    }
+
+    /// Returns the lookup counters, or `None` unless the database was
+    /// opened with `DbOptions::collect_stats`.
+    pub fn stats(&self) -> Option<LookupStats> {
+        self.stats.as_ref().map(LookupCounters::snapshot)
+    }
+
+    /// Resets the lookup counters to zero.
+    pub fn reset_stats(&self) {
+        if let Some(stats) = &self.stats {
+            stats.reset();
+        }
+    }
+
+    /// Touches every page of the database so that the first lookups after
+    /// startup do not pay for page faults.
+    pub fn warm(&self) {
+        self.source.warm();
+    }
+
+    /// Reports the memory used by the mapping and the preloaded index.
+    pub fn memory_stats(&self) -> MemoryStats {
+        MemoryStats {
+            mapped_bytes: self.source.len(),
+            resident_bytes: self.source.resident_len(),
+            index_bytes: (self.ipv4_index.capacity() + self.ipv6_index.capacity())
+                * core::mem::size_of::<u32>(),
+            cache_entries: 0,
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[deprecated(note = "use `info()`, which implements `Display` and `Serialize`")]
     pub fn print_db_info(&self) {
-        println!("Db Path: {}", self.source);
-        println!(" |- Db Type: {}", self.db_type);
-        println!(" |- Db Column: {}", self.db_column);
-        println!(
-            " |- Db Date (YY/MM/DD): {}/{}/{}",
-            self.db_year, self.db_month, self.db_day
-        );
+        //! Prints the DB Information to console
+        print!("{}", self.info());
+    }
+
+    fn preload_index(&mut self) -> Result<(), Error> {
+        if self.ipv4_db_count > 0 {
+            self.ipv4_index = self.source.read_index(self.ipv4_index_base_addr)?;
+        }
+        if self.ipv6_db_count > 0 {
+            self.ipv6_index = self.source.read_index(self.ipv6_index_base_addr)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the `(low, high)` row bounds of the /16 block of an IPv4
+    /// address, from the preloaded index when available.
+    fn ipv4_bounds(&self, ip_number: u32) -> Result<(u32, u32), Error> {
+        let block = (ip_number >> 16) as usize;
+        if let Some(bounds) = self.ipv4_index.get(block * 2..block * 2 + 2) {
+            return Ok((bounds[0], bounds[1]));
+        }
+        let index =
+            FileOffset::from_position(self.ipv4_index_base_addr) + u64::from(ip_number >> 16) * 8;
+        Ok((
+            self.source.read_u32(index)?,
+            self.source.read_u32(index + 4)?,
+        ))
+    }
+
+    /// Returns the `(low, high)` row bounds of the first 16 bits of an
+    /// IPv6 address, from the preloaded index when available.
+    fn ipv6_bounds(&self, ipv6: Ipv6Addr) -> Result<(u32, u32), Error> {
+        let block = (ipv6.octets()[0] as usize) * 256 + (ipv6.octets()[1] as usize);
+        if let Some(bounds) = self.ipv6_index.get(block * 2..block * 2 + 2) {
+            return Ok((bounds[0], bounds[1]));
+        }
+        let index = FileOffset::from_position(self.ipv6_index_base_addr) + block as u64 * 8;
+        Ok((
+            self.source.read_u32(index)?,
+            self.source.read_u32(index + 4)?,
+        ))
     }
 
     fn read_header(&mut self) -> Result<(), Error> {
-        self.db_type = self.source.read_u8(1)?;
-        self.db_column = self.source.read_u8(2)?;
-        self.db_year = self.source.read_u8(3)?;
-        self.db_month = self.source.read_u8(4)?;
-        self.db_day = self.source.read_u8(5)?;
-        self.ipv4_db_count = self.source.read_u32(6)?;
-        self.ipv4_db_addr = self.source.read_u32(10)?;
-        self.ipv6_db_count = self.source.read_u32(14)?;
-        self.ipv6_db_addr = self.source.read_u32(18)?;
-        self.ipv4_index_base_addr = self.source.read_u32(22)?;
-        self.ipv6_index_base_addr = self.source.read_u32(26)?;
-        self.product_code = self.source.read_u8(30)?;
-        self.licence_code = self.source.read_u8(31)?;
-        self.database_size = self.source.read_u32(32)?;
+        self.db_type = self.source.read_u8(header::DB_TYPE)?;
+        self.db_column = self.source.read_u8(header::DB_COLUMN)?;
+        self.db_year = self.source.read_u8(header::DB_YEAR)?;
+        self.db_month = self.source.read_u8(header::DB_MONTH)?;
+        self.db_day = self.source.read_u8(header::DB_DAY)?;
+        self.ipv4_db_count = self.source.read_u32(header::IPV4_COUNT)?;
+        self.ipv4_db_addr = self.source.read_u32(header::IPV4_ADDR)?;
+        self.ipv6_db_count = self.source.read_u32(header::IPV6_COUNT)?;
+        self.ipv6_db_addr = self.source.read_u32(header::IPV6_ADDR)?;
+        self.ipv4_index_base_addr = self.source.read_u32(header::IPV4_INDEX)?;
+        self.ipv6_index_base_addr = self.source.read_u32(header::IPV6_INDEX)?;
+        self.product_code = self.source.read_u8(header::PRODUCT_CODE)?;
+        self.licence_code = self.source.read_u8(header::LICENSE_CODE)?;
+        self.database_size = self.source.read_u32(header::DATABASE_SIZE)?;
+        for ipv6 in [false, true] {
+            let count = if ipv6 {
+                self.ipv6_db_count
+            } else {
+                self.ipv4_db_count
+            };
+            self.source
+                .set_rows(ipv6, self.row_offset(ipv6, 0), count, self.row_size(ipv6));
+        }
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            db_type = self.db_type,
+            db_column = self.db_column,
+            db_date = %format_args!("{}/{}/{}", self.db_year, self.db_month, self.db_day),
+            ipv4_count = self.ipv4_db_count,
+            ipv6_count = self.ipv6_db_count,
+            product_code = self.product_code,
+            "parsed header"
+        );
         if (self.db_year <= 20 && self.product_code == 0) || self.product_code == 2 {
             Ok(())
         } else {
@@ -159,175 +570,161 @@ impl ProxyDB {
         }
     }
 
-    fn get_ipv4_record(&self, mut ip_number: u32) -> Result<ProxyRecord, Error> {
-        let mut ip_from: u32;
-        let mut ip_to: u32;
+    fn get_ipv4_row(&self, mut ip_number: u32) -> Result<(IpRange, Option<FileOffset>), Error> {
         if ip_number == MAX_IPV4_RANGE {
             ip_number -= 1;
         }
-        let base_address = self.ipv4_db_addr;
-        let database_column = self.db_column;
-        let ipv4_index_base_address = self.ipv4_index_base_addr;
         let mut low: u32 = 0;
         let mut high = self.ipv4_db_count;
-        let mut mid: u32;
-        let column_offset = database_column * 4;
-        let mut row_offset: u32;
-        let mut mem_offset: u32;
-        if ipv4_index_base_address > 0 {
-            let number = ip_number >> 16;
-            let index_pos = ipv4_index_base_address + (number << 3);
-            //            let index_buffer = &mut vec![0_u8; 8];
-            mem_offset = index_pos;
-            low = self.source.read_u32(mem_offset as u64)?;
-            high = self.source.read_u32((mem_offset + 4) as u64)?;
+        if self.ipv4_index_base_addr > 0 {
+            (low, high) = self.ipv4_bounds(ip_number)?;
         }
         while low <= high {
-            mid = (low + high) >> 1;
-            row_offset = base_address + (mid * column_offset as u32);
-            mem_offset = row_offset;
-            ip_from = self.source.read_u32(mem_offset as u64)?;
-            ip_to = self
-                .source
-                .read_u32(mem_offset as u64 + column_offset as u64)?;
+            let mid = low + (high - low) / 2;
+            let row_addr = self.row_offset(false, mid);
+            let ip_from = self.source.read_u32(row_addr)?;
+            let ip_to = self.source.read_u32(row_addr + self.row_size(false))?;
             if ip_number >= ip_from && ip_number < ip_to {
-                return self.read_record(mem_offset + 4);
+                return Ok((IpRange::from_ipv4_row(ip_from, ip_to), Some(row_addr)));
             } else if ip_number < ip_from {
-                high = mid - 1;
+                // Bounds of a corrupt index may end the search at row 0.
+                let Some(below) = mid.checked_sub(1) else {
+                    break;
+                };
+                high = below;
             } else {
-                low = mid + 1;
+                let Some(above) = mid.checked_add(1) else {
+                    break;
+                };
+                low = above;
             }
         }
         Err(Error::RecordNotFound)
     }
 
-    fn get_ipv6_record(&self, ip_address: Ipv6Addr) -> Result<ProxyRecord, Error> {
-        let base_address = self.ipv6_db_addr;
-        let database_column = self.db_column;
-        let ipv6_index_base_address = self.ipv4_index_base_addr;
+    fn get_ipv6_row(&self, ip_address: Ipv6Addr) -> Result<(IpRange, Option<FileOffset>), Error> {
         let mut low = 0_u32;
         let mut high = self.ipv6_db_count;
-        let mut mid;
-        let mut ip_from: Ipv6Addr;
-        let mut ip_to: Ipv6Addr;
-        let column_offset = database_column * 4 + 12;
-        let mut row_offset;
-        let mut mem_offset: u32;
         if high == 0 {
-            return Ok(ProxyRecord::default());
+            let range = IpRange::from_ipv6_row(Ipv6Addr::UNSPECIFIED, Ipv6Addr::from(u128::MAX));
+            return Ok((range, None));
         }
-        if ipv6_index_base_address > 0 {
-            let number = (ip_address.octets()[0] as u32 * 256) + ip_address.octets()[1] as u32;
-            let index_pos = ipv6_index_base_address + (number << 3);
-            //let index_buffer = &mut vec![0_u8; 8];
-            mem_offset = index_pos;
-            low = self.source.read_u32(mem_offset as u64)?;
-            high = self.source.read_u32((mem_offset + 4) as u64)?;
+        if self.ipv6_index_base_addr > 0 {
+            (low, high) = self.ipv6_bounds(ip_address)?;
         }
         while low <= high {
-            mid = (low + high) >> 1;
-            row_offset = base_address + (mid + column_offset as u32);
-
-            mem_offset = row_offset;
-            ip_from = self.source.read_ipv6(mem_offset as u64)?;
-            ip_to = self
-                .source
-                .read_ipv6((mem_offset + column_offset as u32) as u64)?;
-            if ip_address > ip_from && ip_address < ip_to {
-                return self.read_record(mem_offset + 16);
+            let mid = low + (high - low) / 2;
+            let row_addr = self.row_offset(true, mid);
+            let ip_from = self.source.read_ipv6(row_addr)?;
+            let ip_to = self.source.read_ipv6(row_addr + self.row_size(true))?;
+            if ip_address >= ip_from && ip_address < ip_to {
+                return Ok((IpRange::from_ipv6_row(ip_from, ip_to), Some(row_addr + 12)));
             } else if ip_address < ip_from {
-                high = mid - 1;
+                // Bounds of a corrupt index may end the search at row 0.
+                let Some(below) = mid.checked_sub(1) else {
+                    break;
+                };
+                high = below;
             } else {
-                low = mid + 1;
+                let Some(above) = mid.checked_add(1) else {
+                    break;
+                };
+                low = above;
             }
         }
         Err(Error::RecordNotFound)
     }
 
-    fn read_record(&self, offset: u32) -> Result<ProxyRecord, Error> {
+    /// Reads the record of the row whose column 1 is at `offset`, 12 bytes
+    /// after the start of IPv6 rows for their 16-byte address.
+    fn read_record(&self, offset: FileOffset) -> Result<ProxyRecord<'_>, Error> {
+        if !self.source.reads_file() {
+            return self.decode_record(&&self.source, offset);
+        }
+        let words = self.db_column.saturating_sub(1) as usize;
+        let pointers: Vec<usize> = (0..words).collect();
+        let country = COUNTRY_POSITION[self.db_type as usize]
+            .checked_sub(2)
+            .map(|i| i as usize);
+        let row = RowReader::new(&self.source, offset.column(2), words, &pointers, country)?;
+        self.decode_record(&row, offset)
+    }
+
+    fn decode_country<'a>(
+        &'a self,
+        source: &impl RowSource<'a>,
+        offset: FileOffset,
+    ) -> Result<Option<Country<'a>>, Error> {
+        let db_type = self.db_type as usize;
+        if COUNTRY_POSITION[db_type] == 0 {
+            return Ok(None);
+        }
+        let index = source.read_u32(offset.column(COUNTRY_POSITION[db_type]))?;
+        Ok(Some(Country {
+            short_name: source.read_str(index.into())?,
+            long_name: source.read_str(FileOffset::from(index) + 3)?,
+        }))
+    }
+
+    fn decode_record<'a>(
+        &'a self,
+        source: &impl RowSource<'a>,
+        offset: FileOffset,
+    ) -> Result<ProxyRecord<'a>, Error> {
         let db_type = self.db_type as usize;
         let mut record = ProxyRecord::default();
 
         if REGION_POSITION[db_type] != 0 && record.region.is_none() {
-            let index = self
-                .source
-                .read_u32(4 * (REGION_POSITION[db_type] - 2) as u64 + offset as u64)?;
-            record.region = Some(self.source.read_str(index as u64)?);
+            let index = source.read_u32(offset.column(REGION_POSITION[db_type]))?;
+            record.region = Some(source.read_str(index.into())?);
         }
         if CITY_POSITION[db_type] != 0 && record.city.is_none() {
-            let index = self
-                .source
-                .read_u32(4 * (CITY_POSITION[db_type] - 2) as u64 + offset as u64)?;
-            record.city = Some(self.source.read_str(index as u64)?);
+            let index = source.read_u32(offset.column(CITY_POSITION[db_type]))?;
+            record.city = Some(source.read_str(index.into())?);
         }
         if ISP_POSITION[db_type] != 0 && record.isp.is_none() {
-            let index = self
-                .source
-                .read_u32(4 * (ISP_POSITION[db_type] - 2) as u64 + offset as u64)?;
-            record.isp = Some(self.source.read_str(index as u64)?);
+            let index = source.read_u32(offset.column(ISP_POSITION[db_type]))?;
+            record.isp = Some(source.read_str(index.into())?);
         }
         if PROXY_TYPE_POSITION[db_type] != 0 && record.proxy_type.is_none() {
-            let index = self
-                .source
-                .read_u32(4 * (PROXY_TYPE_POSITION[db_type] - 2) as u64 + offset as u64)?;
-            record.proxy_type = Some(self.source.read_str(index as u64)?);
+            let index = source.read_u32(offset.column(PROXY_TYPE_POSITION[db_type]))?;
+            record.proxy_type = Some(source.read_str(index.into())?);
         }
         if DOMAIN_POSITION[db_type] != 0 && record.domain.is_none() {
-            let index = self
-                .source
-                .read_u32(4 * (DOMAIN_POSITION[db_type] - 2) as u64 + offset as u64)?;
-            record.domain = Some(self.source.read_str(index as u64)?);
+            let index = source.read_u32(offset.column(DOMAIN_POSITION[db_type]))?;
+            record.domain = Some(source.read_str(index.into())?);
         }
         if USAGE_TYPE_POSITION[db_type] != 0 && record.usage_type.is_none() {
-            let index = self
-                .source
-                .read_u32(4 * (USAGE_TYPE_POSITION[db_type] - 2) as u64 + offset as u64)?;
-            record.usage_type = Some(self.source.read_str(index as u64)?);
+            let index = source.read_u32(offset.column(USAGE_TYPE_POSITION[db_type]))?;
+            record.usage_type = Some(source.read_str(index.into())?);
         }
         if ASN_POSITION[db_type] != 0 && record.asn.is_none() {
-            let index = self
-                .source
-                .read_u32(4 * (ASN_POSITION[db_type] - 2) as u64 + offset as u64)?;
-            record.asn = Some(self.source.read_str(index as u64)?);
+            let index = source.read_u32(offset.column(ASN_POSITION[db_type]))?;
+            record.asn = Some(source.read_str(index.into())?);
         }
         if AS_POSITION[db_type] != 0 && record.as_.is_none() {
-            let index = self
-                .source
-                .read_u32(4 * (AS_POSITION[db_type] - 2) as u64 + offset as u64)?;
-            record.as_ = Some(self.source.read_str(index as u64)?);
+            let index = source.read_u32(offset.column(AS_POSITION[db_type]))?;
+            record.as_ = Some(source.read_str(index.into())?);
         }
         if LAST_SEEN_POSITION[db_type] != 0 && record.last_seen.is_none() {
-            let index = self
-                .source
-                .read_u32(4 * (LAST_SEEN_POSITION[db_type] - 2) as u64 + offset as u64)?;
-            record.last_seen = Some(self.source.read_str(index as u64)?);
+            let index = source.read_u32(offset.column(LAST_SEEN_POSITION[db_type]))?;
+            record.last_seen = Some(source.read_str(index.into())?);
         }
         if THREAT_POSITION[db_type] != 0 && record.threat.is_none() {
-            let index = self
-                .source
-                .read_u32(4 * (THREAT_POSITION[db_type] - 2) as u64 + offset as u64)?;
-            record.threat = Some(self.source.read_str(index as u64)?);
+            let index = source.read_u32(offset.column(THREAT_POSITION[db_type]))?;
+            record.threat = Some(source.read_str(index.into())?);
         }
         if PROVIDER_POSITION[db_type] != 0 && record.provider.is_none() {
-            let index = self
-                .source
-                .read_u32(4 * (PROVIDER_POSITION[db_type] - 2) as u64 + offset as u64)?;
-            record.provider = Some(self.source.read_str(index as u64)?);
-        }
-        if COUNTRY_POSITION[db_type] != 0 {
-            let index = self
-                .source
-                .read_u32(offset as u64 + 4 * (COUNTRY_POSITION[db_type] - 2) as u64)?;
-            let country_short = self.source.read_str(index as u64)?;
-            let country_long = self.source.read_str(index as u64 + 3)?;
-            if country_short == "-" {
+            let index = source.read_u32(offset.column(PROVIDER_POSITION[db_type]))?;
+            record.provider = Some(source.read_str(index.into())?);
+        }
+        if let Some(country) = self.decode_country(source, offset)? {
+            if country.short_name == "-" {
                 record.is_proxy = Some(Proxy::IsNotAProxy);
             } else {
                 if record.proxy_type.is_none() {
-                    let index = self
-                        .source
-                        .read_u32(4 * (COUNTRY_POSITION[db_type] - 2) as u64 + offset as u64)?;
-                    record.proxy_type = Some(self.source.read_str(index as u64)?);
+                    let index = source.read_u32(offset.column(COUNTRY_POSITION[db_type]))?;
+                    record.proxy_type = Some(source.read_str(index.into())?);
                 }
                 if record.proxy_type == Some(Cow::from("DCH"))
                     || record.proxy_type == Some(Cow::from("SES"))
@@ -337,10 +734,7 @@ impl ProxyDB {
                     record.is_proxy = Some(Proxy::IsAProxy);
                 }
             }
-            record.country = Some(Country {
-                short_name: country_short,
-                long_name: country_long,
-            });
+            record.country = Some(country);
         }
 
         Ok(record)
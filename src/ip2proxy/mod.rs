@@ -1,3 +1,3 @@
-mod consts;
+pub(crate) mod consts;
 pub(crate) mod db;
 pub(crate) mod record;
@@ -1,19 +1,12 @@
-#![allow(clippy::enum_variant_names, clippy::derive_partial_eq_without_eq)]
+#![allow(clippy::enum_variant_names)]
 
-use serde::Serialize;
+pub use crate::ip2location::record::Country;
+use alloc::{borrow::Cow, string::String};
+use core::net::{IpAddr, Ipv6Addr};
+use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
-use std::{
-    borrow::Cow,
-    net::{IpAddr, Ipv6Addr},
-};
 
-#[derive(PartialEq, Debug, Clone, Serialize)]
-pub struct Country<'a> {
-    pub short_name: Cow<'a, str>,
-    pub long_name: Cow<'a, str>,
-}
-
-#[derive(PartialEq, Debug, Clone, Serialize)]
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Serialize, Deserialize)]
 pub enum Proxy {
     IsAnError,
     IsNotAProxy,
@@ -21,8 +14,20 @@ pub enum Proxy {
     IsADataCenterIpAddress,
 }
 
+impl Proxy {
+    /// The name of the variant, as serialized.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Proxy::IsAnError => "IsAnError",
+            Proxy::IsNotAProxy => "IsNotAProxy",
+            Proxy::IsAProxy => "IsAProxy",
+            Proxy::IsADataCenterIpAddress => "IsADataCenterIpAddress",
+        }
+    }
+}
+
 #[skip_serializing_none]
-#[derive(PartialEq, Debug, Clone, Serialize)]
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Serialize, Deserialize)]
 pub struct ProxyRecord<'a> {
     pub ip: IpAddr,
     pub country: Option<Country<'a>>,
@@ -40,10 +45,54 @@ pub struct ProxyRecord<'a> {
     pub usage_type: Option<Cow<'a, str>>,
 }
 
-impl ProxyRecord<'_> {
+/// A `ProxyRecord` owning its strings.
+pub type OwnedProxyRecord = ProxyRecord<'static>;
+
+impl<'a> ProxyRecord<'a> {
     pub fn to_json(&self) -> String {
         serde_json::to_string(&self).unwrap()
     }
+
+    /// The record as MessagePack, like `LocationRecord::to_msgpack`.
+    #[cfg(feature = "msgpack")]
+    pub fn to_msgpack(&self) -> alloc::vec::Vec<u8> {
+        rmp_serde::to_vec_named(self).unwrap()
+    }
+
+    /// Reads a record written by `to_msgpack`.
+    #[cfg(feature = "msgpack")]
+    pub fn from_msgpack(bytes: &[u8]) -> Result<ProxyRecord<'static>, crate::error::Error> {
+        Ok(rmp_serde::from_slice(bytes)?)
+    }
+
+    /// Converts the record into one that owns all of its strings, so it
+    /// can outlive the database it was read from.
+    pub fn into_owned(self) -> ProxyRecord<'static> {
+        self.map_strings(|value| Cow::Owned(value.into_owned()))
+    }
+
+    /// Converts every string of the record with `f`.
+    pub(crate) fn map_strings<F>(self, mut f: F) -> ProxyRecord<'static>
+    where
+        F: FnMut(Cow<'a, str>) -> Cow<'static, str>,
+    {
+        ProxyRecord {
+            ip: self.ip,
+            country: self.country.map(|country| country.map_strings(&mut f)),
+            region: self.region.map(&mut f),
+            city: self.city.map(&mut f),
+            isp: self.isp.map(&mut f),
+            domain: self.domain.map(&mut f),
+            is_proxy: self.is_proxy,
+            proxy_type: self.proxy_type.map(&mut f),
+            asn: self.asn.map(&mut f),
+            as_: self.as_.map(&mut f),
+            last_seen: self.last_seen.map(&mut f),
+            threat: self.threat.map(&mut f),
+            provider: self.provider.map(&mut f),
+            usage_type: self.usage_type.map(&mut f),
+        }
+    }
 }
 
 impl Default for ProxyRecord<'_> {
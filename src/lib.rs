@@ -1,16 +1,201 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 mod common;
-pub use common::{Record, DB};
+pub use common::{DbKind, DefaultSource, OwnedRecord, Record, DB};
+
+mod addr;
+pub use addr::IntoIpAddr;
+
+#[cfg(feature = "actix")]
+pub mod actix;
+
+#[cfg(feature = "arrow")]
+mod arrow;
+#[cfg(feature = "arrow")]
+pub use arrow::records_to_arrow;
+#[cfg(feature = "arrow")]
+pub use {arrow_array, arrow_schema};
+
+#[cfg(feature = "std")]
+mod asn;
+#[cfg(feature = "std")]
+pub use asn::{AsnDB, AsnRecord};
+#[cfg(feature = "axum")]
+pub mod axum;
 
+#[cfg(feature = "std")]
+mod bench;
+#[cfg(feature = "std")]
+pub use bench::BenchReport;
+
+#[cfg(feature = "cache")]
+pub mod cache;
+
+mod cidr;
+pub use cidr::{CidrAggregate, CidrLookup, Dominant};
+mod compact;
+mod country_only;
+pub use country_only::CountryOnlyRecord;
+#[cfg(feature = "std")]
+pub mod country_info;
+#[cfg(feature = "polars")]
+mod dataframe;
+#[cfg(feature = "polars")]
+pub use {dataframe::lookups_to_dataframe, polars};
+#[cfg(feature = "dns")]
+pub mod dns;
+#[cfg(feature = "std")]
+mod enrich;
 pub mod error;
 
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+#[cfg(all(feature = "std", any(unix, windows)))]
+mod file;
+
+#[cfg(feature = "std")]
+mod firewall;
+#[cfg(feature = "std")]
+pub use firewall::FirewallFormat;
+
+#[cfg(any(feature = "actix", feature = "axum"))]
+mod forwarded;
+
+mod hooks;
+pub use hooks::{LookupEvent, LookupOutcome};
+
+mod info;
+pub use info::{DbInfo, LOCATION_LITE_ATTRIBUTION, PROXY_LITE_ATTRIBUTION};
+
+#[cfg(feature = "std")]
+mod intern;
+#[cfg(feature = "std")]
+pub use intern::Interner;
+
 mod ip2location;
-pub use self::ip2location::{db::LocationDB, record::LocationRecord};
+pub use self::ip2location::{
+    db::LocationDB,
+    record::{Country, LocationRecord, OwnedLocationRecord},
+};
 
 mod ip2proxy;
 pub use self::ip2proxy::{
     db::ProxyDB,
-    record::{Proxy, ProxyRecord},
+    record::{OwnedProxyRecord, Proxy, ProxyRecord},
 };
 
-#[cfg(test)]
+#[cfg(feature = "async")]
+mod lookup_service;
+#[cfg(feature = "async")]
+pub use lookup_service::LookupService;
+
+#[cfg(feature = "maxmind")]
+pub mod maxmind;
+
+#[cfg(feature = "uniffi")]
+pub mod mobile;
+#[cfg(feature = "uniffi")]
+uniffi::setup_scaffolding!();
+
+#[cfg(feature = "object-storage")]
+pub use opendal;
+#[cfg(feature = "object-storage")]
+mod storage;
+
+mod offset;
+pub use offset::FileOffset;
+
+mod options;
+#[cfg(unix)]
+pub use options::MmapAdvice;
+pub use options::{AccessPattern, Backend, DbOptions, Ipv4MappedMode, Nat64Prefix, Validation};
+
+#[cfg(feature = "std")]
+pub mod pipeline;
+
+#[cfg(feature = "proto")]
+pub mod proto;
+
+mod pseudonym;
+pub use pseudonym::truncate_ip;
+#[cfg(feature = "pseudonymize")]
+pub use pseudonym::IpHasher;
+
+#[cfg(feature = "python")]
+pub mod python;
+
+#[cfg(feature = "rand")]
+pub use rand_core;
+#[cfg(feature = "rand")]
+mod sample;
+
+mod range;
+pub use range::{IpRange, Ranges};
+
+#[cfg(feature = "std")]
+mod reload;
+#[cfg(feature = "std")]
+pub use reload::ReloadableDb;
+
+mod raw;
+pub use raw::{IpFamily, RawRow};
+
+mod resolver;
+pub use resolver::IpResolver;
+
+mod schema;
+pub use schema::{Column, ColumnType, Field, SCHEMA_VERSION};
+
+#[cfg(feature = "http-server")]
+pub mod server;
+#[cfg(feature = "tower")]
+pub mod service;
+
+mod shared;
+pub use shared::{SharedCountry, SharedLocationRecord, SharedProxyRecord, SharedRecord};
+
+mod source;
+pub use source::Source;
+
+#[cfg(feature = "std")]
+mod spatial;
+#[cfg(feature = "std")]
+pub use spatial::Nearest;
+#[cfg(feature = "spatial")]
+pub use spatial::SpatialIndex;
+
+mod statistics;
+pub use statistics::DbStatistics;
+
+mod stats;
+pub use stats::{LookupStats, MemoryStats};
+
+#[cfg(feature = "async")]
+mod stream;
+#[cfg(feature = "async")]
+pub use stream::GeoLookupExt;
+
+mod suite;
+pub use suite::{EnrichedRecord, GeoSuite};
+
+#[cfg(any(feature = "metrics", feature = "tracing"))]
+mod telemetry;
+#[cfg(feature = "metrics")]
+pub use telemetry::describe_metrics;
+
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+
+mod verify;
+pub use verify::{Issue, VerifyReport, MAX_ISSUES};
+
+pub use ipnet;
+
+#[cfg(feature = "std")]
+pub mod writer;
+
+#[cfg(all(test, feature = "std"))]
 mod tests;
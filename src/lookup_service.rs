@@ -0,0 +1,85 @@
+//! Lookups answered by threads owning the database, available with the
+//! `async` feature.
+use crate::{error::Error, IpResolver, OwnedRecord};
+use std::{
+    net::IpAddr,
+    sync::{
+        mpsc::{self, Sender},
+        Arc, Mutex,
+    },
+    thread,
+};
+use tokio::sync::oneshot;
+
+type Request = (IpAddr, oneshot::Sender<Result<OwnedRecord, Error>>);
+
+/// A cheaply cloneable handle to threads owning a database and answering
+/// its lookups.
+///
+/// The database is only ever touched by the service threads, so the tasks
+/// of any runtime can share the handle without sharing the mapped file.
+/// The threads stop once every handle is dropped and the pending lookups
+/// are answered.
+///
+/// ## Example usage
+///
+///```rust
+/// use ip2location::{LookupService, DB};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let service = LookupService::spawn(DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap());
+/// let record = service.lookup("43.224.159.155".parse().unwrap()).await.unwrap();
+/// assert!(record.is_ok());
+/// # }
+///```
+#[derive(Debug, Clone)]
+pub struct LookupService {
+    requests: Sender<Request>,
+}
+
+impl LookupService {
+    pub fn spawn<R>(resolver: R) -> Self
+    where
+        R: IpResolver + Send + Sync + 'static,
+    {
+        //! Moves `resolver` to one service thread per available CPU.
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        Self::spawn_with_threads(resolver, threads)
+    }
+
+    pub fn spawn_with_threads<R>(resolver: R, threads: usize) -> Self
+    where
+        R: IpResolver + Send + Sync + 'static,
+    {
+        //! Moves `resolver` to `threads` (at least one) service threads.
+        let (requests, queue) = mpsc::channel::<Request>();
+        let resolver = Arc::new(resolver);
+        // As in `pipeline`, the threads take turns receiving requests and
+        // only hold the lock while waiting for the next one.
+        let queue = Arc::new(Mutex::new(queue));
+        for _ in 0..threads.max(1) {
+            let (resolver, queue) = (resolver.clone(), queue.clone());
+            thread::spawn(move || loop {
+                let request = queue.lock().unwrap_or_else(|e| e.into_inner()).recv();
+                let Ok((ip, reply)) = request else {
+                    return;
+                };
+                // The caller may have stopped waiting for the answer.
+                let _ = reply.send(resolver.resolve(ip).map(|record| record.into_owned()));
+            });
+        }
+        Self { requests }
+    }
+
+    pub fn lookup(&self, ip: IpAddr) -> oneshot::Receiver<Result<OwnedRecord, Error>> {
+        //! Queues a lookup of `ip` and returns the receiver of its record,
+        //! which can be awaited or, outside of a runtime, waited for with
+        //! `blocking_recv`. The receiver fails if the service threads are
+        //! gone, i.e. a lookup panicked.
+        let (reply, record) = oneshot::channel();
+        // If the threads are gone, `reply` is dropped and `record` fails.
+        let _ = self.requests.send((ip, reply));
+        record
+    }
+}
@@ -0,0 +1,81 @@
+use crate::{error::Error, Country, LocationRecord};
+use maxminddb::{geoip2, Reader};
+use std::{borrow::Cow, fmt, net::IpAddr, path::Path};
+
+/// A MaxMind GeoIP2 / GeoLite2 City (or Country) database answering
+/// lookups with the same `LocationRecord` as the IP2Location databases.
+///
+/// Country, region (the largest subdivision), city, coordinates, zip code
+/// and time zone are filled from the English names; the other fields have
+/// no MaxMind counterpart and stay `None`.
+pub struct MaxMindDb {
+    reader: Reader<Vec<u8>>,
+}
+
+impl MaxMindDb {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        //! Opens a `.mmdb` file
+        //!
+        //! ## Example usage
+        //!
+        //!```ignore
+        //! use ip2location::maxmind::MaxMindDb;
+        //!
+        //! let db = MaxMindDb::from_file("GeoLite2-City.mmdb").unwrap();
+        //! let record = db.ip_lookup("8.8.8.8".parse().unwrap()).unwrap();
+        //! println!("{:?}", record.country);
+        //!```
+        Ok(Self {
+            reader: Reader::open_readfile(path)?,
+        })
+    }
+
+    /// Reads a `.mmdb` database from a buffer.
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, Error> {
+        Ok(Self {
+            reader: Reader::from_source(bytes)?,
+        })
+    }
+
+    /// The `database_type` of the metadata, e.g. `GeoLite2-City`.
+    pub fn database_type(&self) -> &str {
+        &self.reader.metadata().database_type
+    }
+
+    /// Looks up the address; `Error::RecordNotFound` when the database has
+    /// no data for it.
+    pub fn ip_lookup(&self, ip: IpAddr) -> Result<LocationRecord<'_>, Error> {
+        let city = self
+            .reader
+            .lookup(ip)?
+            .decode::<geoip2::City>()?
+            .ok_or(Error::RecordNotFound)?;
+        let country = city.country.iso_code.map(|code| Country {
+            short_name: Cow::Borrowed(code),
+            long_name: Cow::Borrowed(city.country.names.english.unwrap_or(code)),
+        });
+        Ok(LocationRecord {
+            ip,
+            latitude: city.location.latitude.map(|lat| lat as f32),
+            longitude: city.location.longitude.map(|lon| lon as f32),
+            country,
+            region: city
+                .subdivisions
+                .first()
+                .and_then(|subdivision| subdivision.names.english)
+                .map(Cow::Borrowed),
+            city: city.city.names.english.map(Cow::Borrowed),
+            zip_code: city.postal.code.map(Cow::Borrowed),
+            time_zone: city.location.time_zone.map(Cow::Borrowed),
+            ..LocationRecord::default()
+        })
+    }
+}
+
+impl fmt::Debug for MaxMindDb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MaxMindDb")
+            .field("database_type", &self.database_type())
+            .finish()
+    }
+}
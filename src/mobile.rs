@@ -0,0 +1,159 @@
+//! UniFFI bindings for Kotlin and Swift, available with the `uniffi`
+//! feature.
+//!
+//! Build the library as a `cdylib` or `staticlib` and generate the
+//! bindings from it with the bundled `uniffi-bindgen` binary:
+//!
+//! ```text
+//! cargo rustc --release --lib --features uniffi --crate-type cdylib
+//! cargo run --features uniffi-bindgen --bin uniffi-bindgen -- \
+//!     generate --library target/release/libip2location.so --language kotlin --out-dir out
+//! ```
+//!
+//! Databases are read from bytes, e.g. a LITE BIN bundled with the app.
+use crate::{error::Error, LocationRecord, Record, DB};
+use std::{fmt, net::IpAddr, sync::Arc};
+
+/// Errors raised to Kotlin and Swift.
+#[derive(Debug, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum GeoError {
+    /// The bytes are not an IP2Location or IP2Proxy database.
+    InvalidDatabase(String),
+    /// The address could not be parsed.
+    InvalidAddress(String),
+    /// `lookup` was called on an IP2Proxy database.
+    NotLocationDb,
+    Lookup(String),
+}
+
+impl fmt::Display for GeoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GeoError::InvalidDatabase(msg) => write!(f, "invalid database: {}", msg),
+            GeoError::InvalidAddress(ip) => write!(f, "invalid IP address: {}", ip),
+            GeoError::NotLocationDb => write!(f, "not an IP2Location database"),
+            GeoError::Lookup(msg) => write!(f, "lookup failed: {}", msg),
+        }
+    }
+}
+
+/// Location of an address; fields the database does not have are `null`.
+#[derive(PartialEq, Debug, Clone, Default, uniffi::Record)]
+pub struct GeoRecord {
+    pub ip: String,
+    pub country_code: Option<String>,
+    pub country_name: Option<String>,
+    pub region: Option<String>,
+    pub city: Option<String>,
+    pub latitude: Option<f32>,
+    pub longitude: Option<f32>,
+    pub zip_code: Option<String>,
+    pub time_zone: Option<String>,
+    pub isp: Option<String>,
+    pub domain: Option<String>,
+    pub net_speed: Option<String>,
+    pub idd_code: Option<String>,
+    pub area_code: Option<String>,
+    pub weather_station_code: Option<String>,
+    pub weather_station_name: Option<String>,
+    pub mcc: Option<String>,
+    pub mnc: Option<String>,
+    pub mobile_brand: Option<String>,
+    pub elevation: Option<String>,
+    pub usage_type: Option<String>,
+    pub address_type: Option<String>,
+    pub category: Option<String>,
+    pub district: Option<String>,
+    pub asn: Option<String>,
+    pub as_name: Option<String>,
+}
+
+impl From<LocationRecord<'_>> for GeoRecord {
+    fn from(record: LocationRecord<'_>) -> Self {
+        let text = |field: Option<std::borrow::Cow<'_, str>>| field.map(|s| s.into_owned());
+        let (country_code, country_name) = match record.country {
+            Some(country) => (
+                Some(country.short_name.into_owned()),
+                Some(country.long_name.into_owned()),
+            ),
+            None => (None, None),
+        };
+        Self {
+            ip: record.ip.to_string(),
+            country_code,
+            country_name,
+            region: text(record.region),
+            city: text(record.city),
+            latitude: record.latitude,
+            longitude: record.longitude,
+            zip_code: text(record.zip_code),
+            time_zone: text(record.time_zone),
+            isp: text(record.isp),
+            domain: text(record.domain),
+            net_speed: text(record.net_speed),
+            idd_code: text(record.idd_code),
+            area_code: text(record.area_code),
+            weather_station_code: text(record.weather_station_code),
+            weather_station_name: text(record.weather_station_name),
+            mcc: text(record.mcc),
+            mnc: text(record.mnc),
+            mobile_brand: text(record.mobile_brand),
+            elevation: text(record.elevation),
+            usage_type: text(record.usage_type),
+            address_type: text(record.address_type),
+            category: text(record.category),
+            district: text(record.district),
+            asn: text(record.asn),
+            as_name: text(record.as_name),
+        }
+    }
+}
+
+/// A database shared with Kotlin or Swift; lookups may run on any thread.
+#[derive(Debug, uniffi::Object)]
+pub struct GeoDb {
+    db: DB,
+}
+
+impl GeoDb {
+    fn search(&self, ip: &str) -> Result<Option<Record<'_>>, GeoError> {
+        let ip: IpAddr = ip
+            .parse()
+            .map_err(|_| GeoError::InvalidAddress(ip.to_string()))?;
+        match self.db.ip_lookup(ip) {
+            Ok(record) => Ok(Some(record)),
+            Err(Error::RecordNotFound) => Ok(None),
+            Err(err) => Err(GeoError::Lookup(err.to_string())),
+        }
+    }
+}
+
+#[uniffi::export]
+impl GeoDb {
+    /// Reads a database from its bytes.
+    #[uniffi::constructor]
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Arc<Self>, GeoError> {
+        let db = DB::from_bytes(bytes).map_err(|e| GeoError::InvalidDatabase(e.to_string()))?;
+        Ok(Arc::new(Self { db }))
+    }
+
+    /// Returns the location of `ip`, or `null` when the address is not in
+    /// the database.
+    pub fn ip_lookup(&self, ip: String) -> Result<Option<GeoRecord>, GeoError> {
+        match self.search(&ip)? {
+            Some(Record::LocationDb(record)) => Ok(Some((*record).into())),
+            Some(Record::ProxyDb(_)) => Err(GeoError::NotLocationDb),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the record of `ip` as JSON, for IP2Proxy databases and
+    /// fields `GeoRecord` does not have, or `null` when the address is not
+    /// in the database.
+    pub fn ip_lookup_json(&self, ip: String) -> Result<Option<String>, GeoError> {
+        Ok(self
+            .search(&ip)?
+            .map(|record| serde_json::to_string(&record).expect("records serialize to JSON")))
+    }
+}
@@ -0,0 +1,82 @@
+//! 0-based byte offsets into a database, the only offsets a `Source`
+//! reads at.
+//!
+//! The header gives the tables and index tables at 1-based positions,
+//! while the string pointers of the rows are already 0-based; both are
+//! turned into a `FileOffset` where they are read, so that no reader
+//! adjusts offsets itself.
+use core::{fmt, ops::Add};
+
+/// A 0-based byte offset into a database, at which a `Source` is read.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy, Default)]
+pub struct FileOffset(u64);
+
+impl FileOffset {
+    pub const fn new(offset: u64) -> Self {
+        FileOffset(offset)
+    }
+
+    /// The offset of a 1-based position of the header. Position 0, which
+    /// the header gives for a missing index table, is offset 0 as well;
+    /// callers check for it first.
+    pub(crate) const fn from_position(position: u32) -> Self {
+        FileOffset((position as u64).saturating_sub(1))
+    }
+
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+
+    /// The offset of the 4-byte word of the 1-based column `position`, for
+    /// a row whose words start here with column 1.
+    pub(crate) const fn column(self, position: u32) -> Self {
+        FileOffset(self.0 + 4 * (position as u64).saturating_sub(1))
+    }
+
+    /// The number of bytes from `start` to this offset, if it is not
+    /// before `start`.
+    pub(crate) fn since(self, start: FileOffset) -> Option<u64> {
+        self.0.checked_sub(start.0)
+    }
+}
+
+/// String pointers of the rows are 0-based offsets.
+impl From<u32> for FileOffset {
+    fn from(pointer: u32) -> Self {
+        FileOffset(pointer as u64)
+    }
+}
+
+impl Add<u64> for FileOffset {
+    type Output = FileOffset;
+
+    fn add(self, len: u64) -> FileOffset {
+        FileOffset(self.0 + len)
+    }
+}
+
+impl fmt::Display for FileOffset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Offsets of the fields of the header.
+pub(crate) mod header {
+    use super::FileOffset;
+
+    pub const DB_TYPE: FileOffset = FileOffset(0);
+    pub const DB_COLUMN: FileOffset = FileOffset(1);
+    pub const DB_YEAR: FileOffset = FileOffset(2);
+    pub const DB_MONTH: FileOffset = FileOffset(3);
+    pub const DB_DAY: FileOffset = FileOffset(4);
+    pub const IPV4_COUNT: FileOffset = FileOffset(5);
+    pub const IPV4_ADDR: FileOffset = FileOffset(9);
+    pub const IPV6_COUNT: FileOffset = FileOffset(13);
+    pub const IPV6_ADDR: FileOffset = FileOffset(17);
+    pub const IPV4_INDEX: FileOffset = FileOffset(21);
+    pub const IPV6_INDEX: FileOffset = FileOffset(25);
+    pub const PRODUCT_CODE: FileOffset = FileOffset(29);
+    pub const LICENSE_CODE: FileOffset = FileOffset(30);
+    pub const DATABASE_SIZE: FileOffset = FileOffset(31);
+}
@@ -0,0 +1,469 @@
+#[cfg(feature = "cache")]
+use crate::cache::CachedDb;
+use crate::{common::DbKind, error::Error, pseudonym::truncate_ip, DB};
+use alloc::{sync::Arc, vec::Vec};
+use core::{
+    fmt,
+    net::{IpAddr, Ipv6Addr},
+};
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
+
+/// Settings applied when opening a database.
+///
+/// The options are passed to `DB::from_file_with_options` and the like,
+/// or, started with `DB::options()`, also say where the database is and
+/// open it.
+///
+/// ## Example usage
+///
+///```rust
+/// use ip2location::{DbOptions, DB};
+///
+/// let options = DbOptions::new().preload_index(true);
+/// let db = DB::from_file_with_options("data/IP2LOCATION-LITE-DB1.IPV6.BIN", &options).unwrap();
+/// let db = DB::options()
+///     .path("data/IP2LOCATION-LITE-DB1.IPV6.BIN")
+///     .preload_index(true)
+///     .open()
+///     .unwrap();
+///```
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct DbOptions {
+    pub(crate) source: Option<DbSource>,
+    pub(crate) backend: Backend,
+    pub(crate) kind: Option<DbKind>,
+    pub(crate) validation: Validation,
+    pub(crate) preload_index: bool,
+    pub(crate) collect_stats: bool,
+    pub(crate) coordinate_precision: Option<u8>,
+    pub(crate) max_str_len: Option<u8>,
+    pub(crate) pseudonymize_hooks: bool,
+    pub(crate) translation: Ipv6Translation,
+    pub(crate) access: AccessPattern,
+    #[cfg(unix)]
+    pub(crate) advice: Vec<MmapAdvice>,
+    #[cfg(unix)]
+    pub(crate) mlock: bool,
+    pub(crate) populate: bool,
+}
+
+/// Where `DbOptions::open` reads the database from.
+#[derive(PartialEq, Eq, Clone)]
+pub(crate) enum DbSource {
+    #[cfg(feature = "std")]
+    Path(PathBuf),
+    Bytes(Arc<[u8]>),
+}
+
+impl fmt::Debug for DbSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            DbSource::Path(path) => f.debug_tuple("Path").field(path).finish(),
+            DbSource::Bytes(bytes) => write!(f, "Bytes(<{} bytes>)", bytes.len()),
+        }
+    }
+}
+
+/// How the file of a database is held in memory.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum Backend {
+    /// Maps the file, so pages are read on demand and shared with other
+    /// processes mapping it. Without the `mmap` feature the file is read
+    /// into memory instead.
+    #[default]
+    Mmap,
+    /// Reads the whole file into memory when opening, after which the
+    /// file can be replaced or removed. The memory hints do not apply.
+    InMemory,
+    /// Reads the file into memory as a compacted copy: the rows are kept
+    /// and the strings are moved to a table holding each distinct string
+    /// once. Opening takes longer than with `InMemory`, for a smaller
+    /// footprint when rows share strings. The memory hints do not apply.
+    Compact,
+    /// Keeps the header and the index tables in memory and reads the rest
+    /// of the file with positioned reads (`pread`), for environments where
+    /// `mmap` is not allowed or not reliable, such as seccomp sandboxes and
+    /// some FUSE mounts. The memory hints do not apply.
+    #[cfg(all(feature = "std", any(unix, windows)))]
+    Pread,
+    /// Keeps the header and the index tables in memory and reads the rest
+    /// of the file with io_uring, submitting the reads of the row and of
+    /// the strings of a record together. The memory hints do not apply.
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    IoUring,
+}
+
+/// How the file of a database will be read, told to the OS when opening
+/// it so that its caching suits the reads.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum AccessPattern {
+    /// Lookups read rows and strings scattered over the file. On Windows
+    /// the file is opened with `FILE_FLAG_RANDOM_ACCESS`, so the cache
+    /// manager does not read ahead of every lookup.
+    #[default]
+    Random,
+    /// The file is read through in order, as by exports iterating
+    /// `DB::ranges`. On Windows the file is opened with
+    /// `FILE_FLAG_SEQUENTIAL_SCAN`; on Unix a mapping is advised with
+    /// `MADV_SEQUENTIAL` before the hints of `advise`.
+    Sequential,
+}
+
+/// How much of a database is checked when opening it, trading opening
+/// time for the assurance that lookups will not run into a corrupt file.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum Validation {
+    /// Only the header is read, as needed to use the database, and its
+    /// tables and index areas are checked to end within the file.
+    #[default]
+    HeaderOnly,
+    /// The header and the index tables are checked along with the first
+    /// and last rows of each table. Reads about 1 MiB.
+    Quick,
+    /// Every row and index entry is checked, as by `DB::verify`. Reads
+    /// the whole file.
+    Full,
+}
+
+/// Which IPv6 addresses embedding an IPv4 address are looked up in the
+/// IPv4 table rather than the IPv6 one.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub(crate) struct Ipv6Translation {
+    pub(crate) ipv4_mapped: Ipv4MappedMode,
+    /// 6to4, `2002::/16`, embedding the address in bits 16 to 48.
+    pub(crate) six_to_four: bool,
+    /// Teredo, `2001::/32`, embedding the client address inverted in the
+    /// last 32 bits.
+    pub(crate) teredo: bool,
+    /// NAT64 with the well-known prefix, `64:ff9b::/96`.
+    pub(crate) nat64: bool,
+    /// Network-specific NAT64 prefixes, tried after the well-known one.
+    pub(crate) nat64_prefixes: Vec<Nat64Prefix>,
+}
+
+impl Default for Ipv6Translation {
+    fn default() -> Self {
+        Self {
+            ipv4_mapped: Ipv4MappedMode::Full,
+            six_to_four: true,
+            teredo: true,
+            nat64: true,
+            nat64_prefixes: Vec::new(),
+        }
+    }
+}
+
+impl Ipv6Translation {
+    /// The IPv4 address of an IPv4-mapped or IPv4-compatible `ip`, as
+    /// selected by the mode.
+    pub(crate) fn ipv4_mapped(&self, ip: Ipv6Addr) -> Option<u32> {
+        let ipv4 = match self.ipv4_mapped {
+            Ipv4MappedMode::Full => ip.to_ipv4(),
+            Ipv4MappedMode::MappedOnly => ip.to_ipv4_mapped(),
+            Ipv4MappedMode::Native => None,
+        };
+        ipv4.map(u32::from)
+    }
+
+    /// The IPv4 address embedded by a NAT64 prefix in `ip`.
+    pub(crate) fn nat64(&self, ip: Ipv6Addr) -> Option<u32> {
+        self.nat64
+            .then(|| Nat64Prefix::WELL_KNOWN.extract(ip))
+            .flatten()
+            .or_else(|| {
+                self.nat64_prefixes
+                    .iter()
+                    .find_map(|prefix| prefix.extract(ip))
+            })
+    }
+}
+
+/// Which IPv6 addresses are looked up in the IPv4 table as the IPv4
+/// address in their last 32 bits, see `DbOptions::ipv4_mapped`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum Ipv4MappedMode {
+    /// IPv4-mapped (`::ffff:1.2.3.4`) and the deprecated IPv4-compatible
+    /// (`::1.2.3.4`) addresses, as `Ipv6Addr::to_ipv4`. This includes `::`
+    /// and `::1`, looked up as `0.0.0.0` and `0.0.0.1`.
+    #[default]
+    Full,
+    /// Only IPv4-mapped addresses, as `Ipv6Addr::to_ipv4_mapped`.
+    MappedOnly,
+    /// None: both are searched for in the IPv6 table.
+    Native,
+}
+
+/// A NAT64 prefix, under which IPv6 addresses embed the IPv4 address they
+/// are translated to, as laid out by RFC 6052.
+///
+/// ## Example usage
+///
+///```rust
+/// use ip2location::{DbOptions, Nat64Prefix};
+///
+/// let prefix = Nat64Prefix::new("2001:db8:64::".parse().unwrap(), 48).unwrap();
+/// let options = DbOptions::new().nat64_prefix(prefix);
+///```
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Nat64Prefix {
+    prefix: u128,
+    len: u8,
+}
+
+impl Nat64Prefix {
+    /// The well-known prefix, `64:ff9b::/96`.
+    pub const WELL_KNOWN: Nat64Prefix = Nat64Prefix {
+        prefix: 0x0064_ff9b_0000_0000_0000_0000_0000_0000,
+        len: 96,
+    };
+
+    /// The prefix of length `len`, which must be one of the lengths of
+    /// RFC 6052: 32, 40, 48, 56, 64 or 96. Bits past the length are
+    /// ignored.
+    pub fn new(prefix: Ipv6Addr, len: u8) -> Result<Self, Error> {
+        if ![32, 40, 48, 56, 64, 96].contains(&len) {
+            return Err(Error::GenericError(alloc::format!(
+                "invalid NAT64 prefix length {}",
+                len
+            )));
+        }
+        Ok(Self {
+            prefix: u128::from(prefix) & (u128::MAX << (128 - len)),
+            len,
+        })
+    }
+
+    pub fn prefix(&self) -> Ipv6Addr {
+        Ipv6Addr::from(self.prefix)
+    }
+
+    pub fn prefix_len(&self) -> u8 {
+        self.len
+    }
+
+    /// The IPv4 address embedded in `ip` if it is under the prefix. The
+    /// address follows the prefix, skipping bits 64 to 72, which RFC 6052
+    /// reserves.
+    pub(crate) fn extract(&self, ip: Ipv6Addr) -> Option<u32> {
+        if (u128::from(ip) ^ self.prefix) >> (128 - self.len) != 0 {
+            return None;
+        }
+        let octets = ip.octets();
+        let mut ipv4 = 0;
+        for octet in (usize::from(self.len / 8)..16).filter(|&i| i != 8).take(4) {
+            ipv4 = (ipv4 << 8) | u32::from(octets[octet]);
+        }
+        Some(ipv4)
+    }
+}
+
+/// Access pattern hints passed to `madvise` for the mapped database.
+#[cfg(unix)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum MmapAdvice {
+    Normal,
+    /// Lookups touch pages in random order; disables read-ahead.
+    Random,
+    /// Pages are read in order, as in full scans and exports.
+    Sequential,
+    /// The whole mapping will be needed soon; starts reading it in.
+    WillNeed,
+}
+
+#[cfg(all(unix, feature = "mmap"))]
+impl MmapAdvice {
+    pub(crate) fn to_memmap(self) -> memmap2::Advice {
+        match self {
+            MmapAdvice::Normal => memmap2::Advice::Normal,
+            MmapAdvice::Random => memmap2::Advice::Random,
+            MmapAdvice::Sequential => memmap2::Advice::Sequential,
+            MmapAdvice::WillNeed => memmap2::Advice::WillNeed,
+        }
+    }
+}
+
+impl DbOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The form of an address handed to the hooks and the traces.
+    pub(crate) fn observed(&self, ip: IpAddr) -> IpAddr {
+        if self.pseudonymize_hooks {
+            truncate_ip(ip)
+        } else {
+            ip
+        }
+    }
+
+    /// Opens the database file at path, replacing any bytes given before.
+    #[cfg(feature = "std")]
+    pub fn path<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.source = Some(DbSource::Path(path.as_ref().to_path_buf()));
+        self
+    }
+
+    /// Opens the database from its bytes, replacing any path given before.
+    pub fn bytes<B: Into<Arc<[u8]>>>(mut self, bytes: B) -> Self {
+        self.source = Some(DbSource::Bytes(bytes.into()));
+        self
+    }
+
+    /// How the file is held in memory, mapped by default. Ignored for
+    /// databases opened from bytes.
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Opens the database as the given kind instead of detecting it, and
+    /// fails if the header names another product.
+    pub fn kind(mut self, kind: DbKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    /// How much of the database is checked when opening, only the header
+    /// by default. Opening fails with the first problem found.
+    pub fn validate(mut self, validation: Validation) -> Self {
+        self.validation = validation;
+        self
+    }
+
+    /// Opens the database given by `path` or `bytes` with these options.
+    pub fn open(&self) -> Result<DB, Error> {
+        match &self.source {
+            #[cfg(feature = "std")]
+            Some(DbSource::Path(path)) => DB::from_file_with_options(path, self),
+            Some(DbSource::Bytes(bytes)) => DB::from_bytes_with_options(bytes.clone(), self),
+            None => Err(Error::GenericError(
+                "no database path or bytes to open".into(),
+            )),
+        }
+    }
+
+    /// Opens the database as `open` does, wrapped in a `CachedDb` holding
+    /// up to `capacity` ranges.
+    #[cfg(feature = "cache")]
+    pub fn open_cached(&self, capacity: usize) -> Result<CachedDb, Error> {
+        Ok(CachedDb::new(self.open()?, capacity))
+    }
+
+    /// Decodes the IPv4 and IPv6 index tables into memory when opening,
+    /// so the index reads of every lookup are plain array accesses.
+    pub fn preload_index(mut self, preload: bool) -> Self {
+        self.preload_index = preload;
+        self
+    }
+
+    /// Counts lookups, hits, misses and errors, reported by `DB::stats`.
+    pub fn collect_stats(mut self, collect: bool) -> Self {
+        self.collect_stats = collect;
+        self
+    }
+
+    /// Truncates the latitude and longitude of every record to `decimals`
+    /// decimal places, e.g. 1 for about 11 km, so no coordinate finer than
+    /// that leaves the database, coordinate queries included. The
+    /// coordinates of IP2Location databases are those of a city already;
+    /// this bounds their precision. Ignored for IP2Proxy databases.
+    pub fn coordinate_precision(mut self, decimals: u8) -> Self {
+        self.coordinate_precision = Some(decimals);
+        self
+    }
+
+    /// Fails lookups reading a string longer than `len` bytes with
+    /// `Error::StringTooLong` instead of decoding it, e.g. to bound what a
+    /// corrupt or hostile database can make a lookup read and return.
+    /// Strings are at most 255 bytes, their length being one byte, and are
+    /// not limited otherwise.
+    pub fn max_str_len(mut self, len: u8) -> Self {
+        self.max_str_len = Some(len);
+        self
+    }
+
+    /// Hands the lookup hooks, and the traces of the `tracing` feature,
+    /// the address truncated by `truncate_ip` instead of the address
+    /// looked up, so audit logs fed by them never hold full addresses.
+    pub fn pseudonymize_hooks(mut self, pseudonymize: bool) -> Self {
+        self.pseudonymize_hooks = pseudonymize;
+        self
+    }
+
+    /// Selects which IPv6 addresses embedding an IPv4 address in their
+    /// last 32 bits are looked up in the IPv4 table, by default both the
+    /// IPv4-mapped and the IPv4-compatible ones.
+    pub fn ipv4_mapped(mut self, mode: Ipv4MappedMode) -> Self {
+        self.translation.ipv4_mapped = mode;
+        self
+    }
+
+    /// Looks up 6to4 addresses (`2002::/16`) as the IPv4 address they
+    /// embed, the default. When disabled they are searched for in the
+    /// IPv6 table like any other IPv6 address.
+    pub fn translate_6to4(mut self, translate: bool) -> Self {
+        self.translation.six_to_four = translate;
+        self
+    }
+
+    /// Looks up Teredo addresses (`2001::/32`) as the IPv4 address of the
+    /// Teredo client, the default. The client address is the last 32 bits
+    /// with every bit inverted, so any address of the prefix maps to some
+    /// IPv4 address, including ones that are not real Teredo addresses.
+    /// When disabled they are searched for in the IPv6 table like any
+    /// other IPv6 address.
+    pub fn translate_teredo(mut self, translate: bool) -> Self {
+        self.translation.teredo = translate;
+        self
+    }
+
+    /// Looks up addresses of the well-known NAT64 prefix, `64:ff9b::/96`,
+    /// as the IPv4 address they embed, the default. When disabled they
+    /// are searched for in the IPv6 table like any other IPv6 address.
+    pub fn translate_nat64(mut self, translate: bool) -> Self {
+        self.translation.nat64 = translate;
+        self
+    }
+
+    /// Also looks up addresses of a network-specific NAT64 prefix, such as
+    /// the one of a mobile carrier, as the IPv4 address they embed.
+    pub fn nat64_prefix(mut self, prefix: Nat64Prefix) -> Self {
+        self.translation.nat64_prefixes.push(prefix);
+        self
+    }
+
+    /// Sets how the file will be read, for the hints given to the OS when
+    /// opening it. Lookups are `AccessPattern::Random`, the default.
+    pub fn access_pattern(mut self, access: AccessPattern) -> Self {
+        self.access = access;
+        self
+    }
+
+    /// Adds an `madvise` hint applied to the mapping on open. Hints are
+    /// applied in the order given, e.g. `Random` then `WillNeed`.
+    #[cfg(unix)]
+    pub fn advise(mut self, advice: MmapAdvice) -> Self {
+        self.advice.push(advice);
+        self
+    }
+
+    /// Locks the mapping into RAM with `mlock` on open, so lookups never
+    /// wait for pages reclaimed under memory pressure. Opening fails if
+    /// the lock is refused, e.g. because of `RLIMIT_MEMLOCK`.
+    #[cfg(unix)]
+    pub fn mlock(mut self, lock: bool) -> Self {
+        self.mlock = lock;
+        self
+    }
+
+    /// Faults the whole mapping in on open, so the first lookups after a
+    /// deploy do not wait for page faults. Maps with `MAP_POPULATE` on
+    /// Linux and Android; elsewhere every page is read once after mapping,
+    /// as by `DB::warm`. Only applies to `Backend::Mmap`.
+    pub fn populate(mut self, populate: bool) -> Self {
+        self.populate = populate;
+        self
+    }
+}
@@ -0,0 +1,119 @@
+//! Lookups of a stream of addresses on a pool of worker threads.
+//!
+//! `spawn` starts the workers and returns the two ends of the pipeline: a
+//! `SyncSender` taking `(tag, address)` jobs and `Results`, an iterator of
+//! `(tag, record)` results. Both channels are bounded, so a slow consumer
+//! blocks the workers and busy workers block the producers instead of
+//! queueing without limit. The tag travels with the address, e.g. the log
+//! line it was read from, since results come in the order the workers
+//! finish them rather than the order of the jobs.
+//!
+//! ## Example usage
+//!
+//!```rust
+//! use ip2location::{pipeline, DB};
+//! use std::{sync::Arc, thread};
+//!
+//! let db = Arc::new(DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap());
+//! let (jobs, results) = pipeline::spawn(db, 4, 1024);
+//! let producer = thread::spawn(move || {
+//!     for (line, ip) in ["43.224.159.155", "1.1.1.1"].iter().enumerate() {
+//!         jobs.send((line, ip.parse().unwrap())).unwrap();
+//!     }
+//!     // Dropping the last sender ends the pipeline once the jobs are done.
+//! });
+//! let mut lines: Vec<usize> = results.map(|(line, record)| {
+//!     assert!(record.is_ok());
+//!     line
+//! }).collect();
+//! producer.join().unwrap();
+//! lines.sort();
+//! assert_eq!(lines, [0, 1]);
+//!```
+use crate::{error::Error, IpResolver, OwnedRecord};
+use std::{
+    net::IpAddr,
+    sync::{
+        mpsc::{self, Receiver, SyncSender},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+/// The result of a job: its tag and the record of its address.
+pub type Output<T> = (T, Result<OwnedRecord, Error>);
+
+/// Starts `workers` threads (at least one) looking up the addresses sent
+/// to the returned sender with `resolver`, with room for `capacity` jobs
+/// waiting for a worker and as many results waiting for the consumer.
+///
+/// The pipeline ends when every clone of the sender is dropped: the
+/// workers finish the queued jobs, and `Results` stops after the last
+/// result. Dropping `Results` stops the workers at their next result.
+pub fn spawn<T, R>(
+    resolver: Arc<R>,
+    workers: usize,
+    capacity: usize,
+) -> (SyncSender<(T, IpAddr)>, Results<T>)
+where
+    T: Send + 'static,
+    R: IpResolver + Send + Sync + ?Sized + 'static,
+{
+    let (jobs, queue) = mpsc::sync_channel::<(T, IpAddr)>(capacity);
+    let (sender, output) = mpsc::sync_channel(capacity);
+    // Workers take turns receiving from the queue; the lock is only held
+    // while waiting for the next job, not during the lookup.
+    let queue = Arc::new(Mutex::new(queue));
+    let workers = (0..workers.max(1))
+        .map(|_| {
+            let (resolver, queue, sender) = (resolver.clone(), queue.clone(), sender.clone());
+            thread::spawn(move || loop {
+                let job = queue.lock().unwrap_or_else(|e| e.into_inner()).recv();
+                let Ok((tag, ip)) = job else {
+                    return;
+                };
+                let record = resolver.resolve(ip).map(|record| record.into_owned());
+                if sender.send((tag, record)).is_err() {
+                    return;
+                }
+            })
+        })
+        .collect();
+    (jobs, Results { output, workers })
+}
+
+/// The results of a pipeline, in the order they are done.
+///
+/// Iterating blocks until the next result is ready, and ends when the
+/// jobs are all done and the senders dropped. A worker that panicked is
+/// reported by panicking once the results end.
+#[derive(Debug)]
+pub struct Results<T> {
+    output: Receiver<Output<T>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl<T> Results<T> {
+    /// The next result if one is ready, without blocking.
+    pub fn try_next(&mut self) -> Option<Output<T>> {
+        self.output.try_recv().ok()
+    }
+}
+
+impl<T> Iterator for Results<T> {
+    type Item = Output<T>;
+
+    fn next(&mut self) -> Option<Output<T>> {
+        match self.output.recv() {
+            Ok(output) => Some(output),
+            Err(_) => {
+                for worker in self.workers.drain(..) {
+                    if let Err(panic) = worker.join() {
+                        std::panic::resume_unwind(panic);
+                    }
+                }
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//! Protobuf messages of records, generated with prost from
+//! `proto/records.proto`, with conversions from the records of lookups:
+//!
+//!```rust
+//! use ip2location::{proto, DB};
+//! use prost::Message;
+//!
+//! let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+//! let record = db.ip_lookup("43.224.159.155".parse().unwrap()).unwrap();
+//! let bytes = proto::Record::from(record).encode_to_vec();
+//! let decoded = proto::Record::decode(bytes.as_slice()).unwrap();
+//! let Some(proto::record::Record::Location(location)) = decoded.record else {
+//!     panic!("not a location record");
+//! };
+//! assert_eq!(location.country.unwrap().short_name, "IN");
+//!```
+#![allow(clippy::large_enum_variant)]
+// The messages are named like the records they are built from, which are
+// written with their `crate::` path here.
+include!(concat!(env!("OUT_DIR"), "/ip2location.v1.rs"));
+
+impl From<crate::Record<'_>> for Record {
+    fn from(record: crate::Record<'_>) -> Self {
+        let record = match record {
+            crate::Record::LocationDb(rec) => record::Record::Location((*rec).into()),
+            crate::Record::ProxyDb(rec) => record::Record::Proxy((*rec).into()),
+        };
+        Record {
+            record: Some(record),
+        }
+    }
+}
+
+impl From<crate::Country<'_>> for Country {
+    fn from(country: crate::Country<'_>) -> Self {
+        Country {
+            short_name: country.short_name.into_owned(),
+            long_name: country.long_name.into_owned(),
+        }
+    }
+}
+
+impl From<crate::LocationRecord<'_>> for LocationRecord {
+    fn from(rec: crate::LocationRecord<'_>) -> Self {
+        LocationRecord {
+            ip: rec.ip.to_string(),
+            latitude: rec.latitude,
+            longitude: rec.longitude,
+            country: rec.country.map(Into::into),
+            region: rec.region.map(Into::into),
+            city: rec.city.map(Into::into),
+            isp: rec.isp.map(Into::into),
+            domain: rec.domain.map(Into::into),
+            zip_code: rec.zip_code.map(Into::into),
+            time_zone: rec.time_zone.map(Into::into),
+            net_speed: rec.net_speed.map(Into::into),
+            idd_code: rec.idd_code.map(Into::into),
+            area_code: rec.area_code.map(Into::into),
+            weather_station_code: rec.weather_station_code.map(Into::into),
+            weather_station_name: rec.weather_station_name.map(Into::into),
+            mcc: rec.mcc.map(Into::into),
+            mnc: rec.mnc.map(Into::into),
+            mobile_brand: rec.mobile_brand.map(Into::into),
+            elevation: rec.elevation.map(Into::into),
+            usage_type: rec.usage_type.map(Into::into),
+            address_type: rec.address_type.map(Into::into),
+            category: rec.category.map(Into::into),
+            district: rec.district.map(Into::into),
+            asn: rec.asn.map(Into::into),
+            as_name: rec.as_name.map(Into::into),
+        }
+    }
+}
+
+impl From<Option<crate::Proxy>> for ProxyStatus {
+    fn from(proxy: Option<crate::Proxy>) -> Self {
+        match proxy {
+            None => ProxyStatus::Unspecified,
+            Some(crate::Proxy::IsAnError) => ProxyStatus::Error,
+            Some(crate::Proxy::IsNotAProxy) => ProxyStatus::NotAProxy,
+            Some(crate::Proxy::IsAProxy) => ProxyStatus::Proxy,
+            Some(crate::Proxy::IsADataCenterIpAddress) => ProxyStatus::DataCenter,
+        }
+    }
+}
+
+impl From<crate::ProxyRecord<'_>> for ProxyRecord {
+    fn from(rec: crate::ProxyRecord<'_>) -> Self {
+        ProxyRecord {
+            ip: rec.ip.to_string(),
+            country: rec.country.map(Into::into),
+            region: rec.region.map(Into::into),
+            city: rec.city.map(Into::into),
+            isp: rec.isp.map(Into::into),
+            domain: rec.domain.map(Into::into),
+            is_proxy: ProxyStatus::from(rec.is_proxy).into(),
+            proxy_type: rec.proxy_type.map(Into::into),
+            asn: rec.asn.map(Into::into),
+            as_name: rec.as_.map(Into::into),
+            last_seen: rec.last_seen.map(Into::into),
+            threat: rec.threat.map(Into::into),
+            provider: rec.provider.map(Into::into),
+            usage_type: rec.usage_type.map(Into::into),
+        }
+    }
+}
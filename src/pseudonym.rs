@@ -0,0 +1,87 @@
+//! Pseudonymized forms of addresses, to store or log next to the result of
+//! a lookup instead of the address itself.
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+#[cfg(feature = "pseudonymize")]
+use {
+    alloc::string::String,
+    core::fmt::Write,
+    hmac::{Hmac, Mac},
+    sha2::Sha256,
+};
+
+pub fn truncate_ip(ip: IpAddr) -> IpAddr {
+    //! Zeroes the host part of an address, keeping the /24 of an IPv4
+    //! address and the /48 of an IPv6 one. IPv4-mapped addresses are
+    //! truncated as the IPv4 address they hold.
+    //!
+    //! ## Example usage
+    //!
+    //!```rust
+    //! use ip2location::truncate_ip;
+    //!
+    //! assert_eq!(truncate_ip("203.0.113.7".parse().unwrap()).to_string(), "203.0.113.0");
+    //! assert_eq!(truncate_ip("2001:db8:1:2::7".parse().unwrap()).to_string(), "2001:db8:1::");
+    //!```
+    match ip.to_canonical() {
+        IpAddr::V4(ip) => IpAddr::V4(Ipv4Addr::from(u32::from(ip) & !0xff)),
+        IpAddr::V6(ip) => IpAddr::V6(Ipv6Addr::from(u128::from(ip) & !(u128::MAX >> 48))),
+    }
+}
+
+/// Replaces addresses with a keyed hash, HMAC-SHA256, so the same address
+/// always gives the same pseudonym under a key, e.g. to count distinct
+/// clients, while the address cannot be recovered without the key.
+/// IPv4-mapped addresses hash as the IPv4 address they hold.
+///
+/// ## Example usage
+///
+///```rust
+/// use ip2location::IpHasher;
+///
+/// let hasher = IpHasher::new(b"a secret key, rotated daily");
+/// let pseudonym = hasher.hash_hex("203.0.113.7".parse().unwrap());
+/// assert_eq!(pseudonym, hasher.hash_hex("::ffff:203.0.113.7".parse().unwrap()));
+/// assert_eq!(pseudonym.len(), 64);
+///```
+#[cfg(feature = "pseudonymize")]
+#[derive(Clone)]
+pub struct IpHasher {
+    mac: Hmac<Sha256>,
+}
+
+#[cfg(feature = "pseudonymize")]
+impl IpHasher {
+    /// A hasher keyed with `key`, of any length.
+    pub fn new(key: &[u8]) -> Self {
+        Self {
+            mac: Hmac::new_from_slice(key).expect("HMAC takes keys of any length"),
+        }
+    }
+
+    /// The HMAC-SHA256 of the octets of the address.
+    pub fn hash(&self, ip: IpAddr) -> [u8; 32] {
+        let mut mac = self.mac.clone();
+        match ip.to_canonical() {
+            IpAddr::V4(ip) => mac.update(&ip.octets()),
+            IpAddr::V6(ip) => mac.update(&ip.octets()),
+        }
+        mac.finalize().into_bytes().into()
+    }
+
+    /// `hash` as lowercase hexadecimal.
+    pub fn hash_hex(&self, ip: IpAddr) -> String {
+        self.hash(ip)
+            .iter()
+            .fold(String::with_capacity(64), |mut hex, byte| {
+                let _ = write!(hex, "{:02x}", byte);
+                hex
+            })
+    }
+}
+
+#[cfg(feature = "pseudonymize")]
+impl core::fmt::Debug for IpHasher {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str("IpHasher(<key>)")
+    }
+}
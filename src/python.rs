@@ -0,0 +1,115 @@
+//! Python bindings, available with the `python` feature.
+//!
+//! Build the extension module with [maturin](https://www.maturin.rs)
+//! (`maturin develop`, see `pyproject.toml`), then:
+//!
+//! ```python
+//! import ip2location
+//!
+//! db = ip2location.DB("data/IP2LOCATION-LITE-DB1.IPV6.BIN")
+//! db.ip_lookup("43.224.159.155")
+//! # {'ip': '43.224.159.155', 'country': {'short_name': 'IN', 'long_name': 'India'}}
+//! ```
+//!
+//! Records are the dicts of their JSON form (`Record::to_json`); fields
+//! the database does not have are left out.
+use crate::{error::Error, DB};
+use pyo3::{
+    exceptions::{PyOSError, PyValueError},
+    prelude::*,
+    types::{PyBool, PyDict, PyList, PyString},
+};
+use serde_json::Value;
+use std::{net::IpAddr, path::PathBuf};
+
+fn to_py_err(err: Error) -> PyErr {
+    match err {
+        Error::IoError(msg) => PyOSError::new_err(msg),
+        err => PyValueError::new_err(err.to_string()),
+    }
+}
+
+fn to_py<'py>(py: Python<'py>, value: &Value) -> PyResult<Bound<'py, PyAny>> {
+    Ok(match value {
+        Value::Null => py.None().into_bound(py),
+        Value::Bool(b) => PyBool::new(py, *b).to_owned().into_any(),
+        Value::Number(n) => match n.as_i64() {
+            Some(n) => n.into_pyobject(py)?.into_any(),
+            None => n.as_f64().unwrap_or(f64::NAN).into_pyobject(py)?.into_any(),
+        },
+        Value::String(s) => PyString::new(py, s).into_any(),
+        Value::Array(items) => {
+            let list = PyList::empty(py);
+            for item in items {
+                list.append(to_py(py, item)?)?;
+            }
+            list.into_any()
+        }
+        Value::Object(fields) => {
+            let dict = PyDict::new(py);
+            for (key, value) in fields {
+                dict.set_item(key, to_py(py, value)?)?;
+            }
+            dict.into_any()
+        }
+    })
+}
+
+/// An IP2Location or IP2Proxy BIN database.
+#[pyclass(name = "DB", module = "ip2location", frozen)]
+pub struct PyDb {
+    db: DB,
+}
+
+#[pymethods]
+impl PyDb {
+    /// Opens the database at `path`.
+    #[new]
+    fn new(path: PathBuf) -> PyResult<Self> {
+        let db = DB::from_file(path).map_err(to_py_err)?;
+        Ok(Self { db })
+    }
+
+    /// Reads a database from its bytes.
+    #[staticmethod]
+    fn from_bytes(bytes: &[u8]) -> PyResult<Self> {
+        let db = DB::from_bytes(bytes).map_err(to_py_err)?;
+        Ok(Self { db })
+    }
+
+    /// Returns the record of `ip` as a dict, or `None` when the address is
+    /// not in the database. Raises `ValueError` for invalid addresses.
+    fn ip_lookup<'py>(&self, py: Python<'py>, ip: &str) -> PyResult<Option<Bound<'py, PyAny>>> {
+        let ip: IpAddr = ip
+            .parse()
+            .map_err(|_| PyValueError::new_err(format!("invalid IP address: {}", ip)))?;
+        match self.db.ip_lookup(ip) {
+            Ok(record) => {
+                let value = serde_json::to_value(record).expect("records serialize to JSON");
+                to_py(py, &value).map(Some)
+            }
+            Err(Error::RecordNotFound) => Ok(None),
+            Err(err) => Err(to_py_err(err)),
+        }
+    }
+
+    /// Returns the header of the database as a dict.
+    fn info<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let value = serde_json::to_value(self.db.info()).expect("info serializes to JSON");
+        to_py(py, &value)
+    }
+
+    fn __repr__(&self) -> String {
+        let info = self.db.info();
+        format!(
+            "DB(path={:?}, kind={:?}, type={})",
+            info.path, info.kind, info.db_type
+        )
+    }
+}
+
+#[pymodule(name = "ip2location")]
+fn init_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyDb>()?;
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use crate::{error::Error, Record, DB};
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use serde::Serialize;
+
+/// An inclusive range of addresses sharing a single row in the database.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy, Serialize)]
+pub struct IpRange {
+    pub start: IpAddr,
+    pub end: IpAddr,
+}
+
+impl IpRange {
+    /// Builds a range from the `ip_from`/`ip_to` columns of an IPv4 row,
+    /// where `ip_to` is exclusive. A range ending at 255.255.255.255 includes
+    /// it, since lookups of that address are answered by the last row.
+    pub(crate) fn from_ipv4_row(ip_from: u32, ip_to: u32) -> Self {
+        let end = if ip_to == u32::MAX {
+            ip_to
+        } else {
+            ip_to.saturating_sub(1).max(ip_from)
+        };
+        Self {
+            start: IpAddr::V4(Ipv4Addr::from(ip_from)),
+            end: IpAddr::V4(Ipv4Addr::from(end)),
+        }
+    }
+
+    /// Builds a range from the `ip_from`/`ip_to` columns of an IPv6 row,
+    /// where `ip_to` is exclusive.
+    pub(crate) fn from_ipv6_row(ip_from: Ipv6Addr, ip_to: Ipv6Addr) -> Self {
+        let (from, to) = (u128::from(ip_from), u128::from(ip_to));
+        Self {
+            start: IpAddr::V6(ip_from),
+            end: IpAddr::V6(Ipv6Addr::from(to.saturating_sub(1).max(from))),
+        }
+    }
+
+    /// Returns true if the address is inside the range. Addresses of the
+    /// other family are never contained.
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.start, self.end, ip) {
+            (IpAddr::V4(start), IpAddr::V4(end), IpAddr::V4(ip)) => start <= ip && ip <= end,
+            (IpAddr::V6(start), IpAddr::V6(end), IpAddr::V6(ip)) => start <= ip && ip <= end,
+            _ => false,
+        }
+    }
+
+    /// Returns true for a range of the IPv6 table.
+    pub fn is_ipv6(&self) -> bool {
+        self.start.is_ipv6()
+    }
+}
+
+/// Iterator over every range of a database with its record, IPv4 table
+/// first, in address order. Created by `DB::ranges`.
+pub struct Ranges<'a> {
+    db: &'a DB,
+    ipv6: bool,
+    row: u32,
+}
+
+impl<'a> Ranges<'a> {
+    pub(crate) fn new(db: &'a DB) -> Self {
+        Self {
+            db,
+            ipv6: false,
+            row: 0,
+        }
+    }
+
+    fn remaining(&self) -> usize {
+        let ipv6 = self.db.range_count(true) as usize;
+        if self.ipv6 {
+            ipv6 - self.row as usize
+        } else {
+            self.db.range_count(false) as usize - self.row as usize + ipv6
+        }
+    }
+}
+
+impl<'a> Iterator for Ranges<'a> {
+    type Item = Result<(IpRange, Record<'a>), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.ipv6 && self.row >= self.db.range_count(false) {
+            self.ipv6 = true;
+            self.row = 0;
+        }
+        if self.ipv6 && self.row >= self.db.range_count(true) {
+            return None;
+        }
+        let item = self.db.range_at(self.ipv6, self.row);
+        self.row += 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining();
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Ranges<'_> {}
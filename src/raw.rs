@@ -0,0 +1,91 @@
+//! Undecoded rows, for callers building their own structures from the
+//! tables of a database.
+use crate::{common::Storage, error::Error, offset::FileOffset, source::Source};
+use alloc::borrow::Cow;
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use serde::Serialize;
+
+/// The table of a database: IPv4 or IPv6 rows.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IpFamily {
+    V4,
+    V6,
+}
+
+/// The bytes of a database row as stored in the file, returned by
+/// `DB::get_raw_row`.
+///
+/// A row starts with its first address (`ip_from`, 4 bytes in the IPv4
+/// table and 16 in the IPv6 one), followed by one little-endian 4-byte
+/// word per other column: a string pointer, or a float for the latitude
+/// and longitude. A row's range ends where the next row starts; the last
+/// row of a table only marks the end of the address space.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct RawRow<'a> {
+    pub family: IpFamily,
+    /// 0-based index of the row in its table.
+    pub index: u32,
+    /// 1-based offset of the row in the file, as used by the header.
+    pub offset: u64,
+    /// Number of columns, `ip_from` included.
+    pub columns: u8,
+    pub bytes: Cow<'a, [u8]>,
+}
+
+impl RawRow<'_> {
+    pub(crate) fn read<'a, S: Source>(
+        source: &'a Storage<S>,
+        family: IpFamily,
+        index: u32,
+        table: (u32, u32),
+        columns: u8,
+    ) -> Result<RawRow<'a>, Error> {
+        let (count, addr) = table;
+        if index >= count {
+            return Err(Error::RecordNotFound);
+        }
+        let row_size = columns as u64 * 4 + if family == IpFamily::V6 { 12 } else { 0 };
+        let offset = FileOffset::from_position(addr) + index as u64 * row_size;
+        Ok(RawRow {
+            family,
+            index,
+            offset: offset.get() + 1,
+            columns,
+            bytes: source.read_bytes(offset, row_size as usize)?,
+        })
+    }
+
+    /// Byte offset in `bytes` of a 1-based column, `ip_from` being column
+    /// 1, or `None` past the last column.
+    pub fn column_offset(&self, column: u32) -> Option<usize> {
+        match column {
+            0 => None,
+            1 => Some(0),
+            column if column <= self.columns as u32 => {
+                let ip_len = if self.family == IpFamily::V6 { 16 } else { 4 };
+                Some(ip_len + 4 * (column as usize - 2))
+            }
+            _ => None,
+        }
+    }
+
+    /// The 4-byte word of a 1-based column after `ip_from`: a string
+    /// pointer (0-based offset of its length byte) or the bits of a float.
+    pub fn word(&self, column: u32) -> Option<u32> {
+        let at = self.column_offset(column).filter(|_| column > 1)?;
+        Some(u32::from_le_bytes(self.bytes[at..at + 4].try_into().ok()?))
+    }
+
+    /// The first address of the row.
+    pub fn ip_from(&self) -> IpAddr {
+        match self.family {
+            IpFamily::V4 => IpAddr::V4(Ipv4Addr::from(u32::from_le_bytes(
+                self.bytes[..4].try_into().unwrap_or_default(),
+            ))),
+            IpFamily::V6 => IpAddr::V6(Ipv6Addr::from(u128::from_le_bytes(
+                self.bytes[..16].try_into().unwrap_or_default(),
+            ))),
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{error::Error, DbOptions, DB};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError, RwLock, Weak},
+    thread::{self, JoinHandle},
+    time::{Duration, SystemTime},
+};
+
+/// A database that can be swapped for a newer copy of its file while it is
+/// being queried.
+///
+/// Lookups go through `current()`, which hands out the database in use at
+/// that moment; a reload only affects later calls, so in-flight lookups
+/// keep reading the previous file. Replace the file with an atomic rename
+/// rather than overwriting it in place, since the old copy stays mapped
+/// until its last user is done.
+///
+/// Hooks registered on a database are not carried over to the reloaded
+/// copy.
+#[derive(Debug)]
+pub struct ReloadableDb {
+    path: PathBuf,
+    options: DbOptions,
+    current: RwLock<Arc<DB>>,
+    modified: Mutex<Option<SystemTime>>,
+}
+
+impl ReloadableDb {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        //! Opens the database at path.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::ReloadableDb;
+        //! use std::{sync::Arc, time::Duration};
+        //!
+        //! let db = Arc::new(ReloadableDb::open("data/IP2LOCATION-LITE-DB1.BIN").unwrap());
+        //! let _watcher = db.watch(Duration::from_secs(30));
+        //! let current = db.current();
+        //! let record = current.ip_lookup("43.224.159.155".parse().unwrap());
+        //! assert!(record.is_ok());
+        //!```
+        Self::open_with_options(path, &DbOptions::default())
+    }
+
+    /// Opens the database at path; the options are used again for every
+    /// reload.
+    pub fn open_with_options<P: AsRef<Path>>(path: P, options: &DbOptions) -> Result<Self, Error> {
+        let path = path.as_ref().to_path_buf();
+        let modified = modified(&path);
+        let db = DB::from_file_with_options(&path, options)?;
+        Ok(Self {
+            path,
+            options: options.clone(),
+            current: RwLock::new(Arc::new(db)),
+            modified: Mutex::new(modified),
+        })
+    }
+
+    /// Returns the database currently in use.
+    pub fn current(&self) -> Arc<DB> {
+        self.current
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reopens the file and swaps it in. On error the database in use is
+    /// kept.
+    pub fn reload(&self) -> Result<(), Error> {
+        let modified = modified(&self.path);
+        let db = match DB::from_file_with_options(&self.path, &self.options) {
+            Ok(db) => db,
+            Err(err) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(path = %self.path.display(), error = %err, "reload failed");
+                return Err(err);
+            }
+        };
+        *self.current.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(db);
+        *self.modified.lock().unwrap_or_else(PoisonError::into_inner) = modified;
+        #[cfg(feature = "tracing")]
+        tracing::info!(path = %self.path.display(), "reloaded database");
+        Ok(())
+    }
+
+    /// Reloads the database if the modification time of its file changed
+    /// since it was last loaded. Returns true if it was reloaded.
+    pub fn reload_if_modified(&self) -> Result<bool, Error> {
+        let modified = modified(&self.path);
+        if modified.is_none()
+            || modified == *self.modified.lock().unwrap_or_else(PoisonError::into_inner)
+        {
+            return Ok(false);
+        }
+        self.reload().map(|_| true)
+    }
+
+    /// Starts a thread checking the file every `interval` and reloading it
+    /// when it changes. The thread exits once the database is dropped.
+    pub fn watch(self: &Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        let db: Weak<Self> = Arc::downgrade(self);
+        thread::spawn(move || loop {
+            thread::sleep(interval);
+            match db.upgrade() {
+                // A failed reload keeps the current database and is retried
+                // on the next tick.
+                Some(db) => {
+                    let _ = db.reload_if_modified();
+                }
+                None => break,
+            }
+        })
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
@@ -0,0 +1,100 @@
+use crate::{error::Error, LocationDB, ProxyDB, Record, Source, DB};
+use alloc::{boxed::Box, sync::Arc};
+use core::net::IpAddr;
+
+/// Anything that answers lookups with a `Record`: the databases, their
+/// cached and reloadable wrappers and handles to any of them.
+///
+/// Code that takes an `impl IpResolver` (or `&dyn IpResolver`) works with
+/// every backend and can be handed a mock in tests.
+///
+/// ## Example usage
+///
+///```rust
+/// use ip2location::{IpResolver, DB};
+/// use std::net::IpAddr;
+///
+/// fn country(resolver: &dyn IpResolver, ip: IpAddr) -> Option<String> {
+///     let record = resolver.resolve(ip).ok()?;
+///     match record {
+///         ip2location::Record::LocationDb(rec) => rec.country.map(|c| c.short_name.into_owned()),
+///         ip2location::Record::ProxyDb(rec) => rec.country.map(|c| c.short_name.into_owned()),
+///     }
+/// }
+///
+/// let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+/// assert_eq!(country(&db, "43.224.159.155".parse().unwrap()).as_deref(), Some("IN"));
+///```
+pub trait IpResolver {
+    /// Looks up the address; `Error::RecordNotFound` when it is not known.
+    fn resolve(&self, ip: IpAddr) -> Result<Record<'_>, Error>;
+}
+
+impl IpResolver for DB {
+    fn resolve(&self, ip: IpAddr) -> Result<Record<'_>, Error> {
+        self.ip_lookup(ip)
+    }
+}
+
+impl<S: Source> IpResolver for LocationDB<S> {
+    fn resolve(&self, ip: IpAddr) -> Result<Record<'_>, Error> {
+        self.ip_lookup(ip)
+            .map(|record| Record::LocationDb(Box::new(record)))
+    }
+}
+
+impl<S: Source> IpResolver for ProxyDB<S> {
+    fn resolve(&self, ip: IpAddr) -> Result<Record<'_>, Error> {
+        self.ip_lookup(ip)
+            .map(|record| Record::ProxyDb(Box::new(record)))
+    }
+}
+
+#[cfg(feature = "std")]
+impl IpResolver for crate::ReloadableDb {
+    /// Looks up in the current database; the record is owned since a
+    /// reload may drop that database.
+    fn resolve(&self, ip: IpAddr) -> Result<Record<'_>, Error> {
+        self.current().ip_lookup(ip).map(Record::into_owned)
+    }
+}
+
+#[cfg(feature = "cache")]
+impl<C: crate::cache::LookupCache> IpResolver for crate::cache::CachedDb<C> {
+    fn resolve(&self, ip: IpAddr) -> Result<Record<'_>, Error> {
+        self.ip_lookup(ip)
+    }
+}
+
+#[cfg(feature = "maxmind")]
+impl IpResolver for crate::maxmind::MaxMindDb {
+    fn resolve(&self, ip: IpAddr) -> Result<Record<'_>, Error> {
+        self.ip_lookup(ip)
+            .map(|record| Record::LocationDb(Box::new(record)))
+    }
+}
+
+#[cfg(feature = "tower")]
+impl IpResolver for crate::service::DbService {
+    fn resolve(&self, ip: IpAddr) -> Result<Record<'_>, Error> {
+        self.db().ip_lookup(ip)
+    }
+}
+
+impl<R: IpResolver + ?Sized> IpResolver for &R {
+    fn resolve(&self, ip: IpAddr) -> Result<Record<'_>, Error> {
+        (**self).resolve(ip)
+    }
+}
+
+impl<R: IpResolver + ?Sized> IpResolver for Box<R> {
+    fn resolve(&self, ip: IpAddr) -> Result<Record<'_>, Error> {
+        (**self).resolve(ip)
+    }
+}
+
+impl<R: IpResolver + ?Sized> IpResolver for Arc<R> {
+    fn resolve(&self, ip: IpAddr) -> Result<Record<'_>, Error> {
+        (**self).resolve(ip)
+    }
+}
@@ -0,0 +1,62 @@
+//! Random addresses of a country, for test traffic.
+use crate::{error::Error, IpRange};
+use alloc::vec::Vec;
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use rand_core::Rng;
+
+/// A random number below `bound`, which is not zero, without modulo bias.
+fn below<R: Rng + ?Sized>(rng: &mut R, bound: u128) -> u128 {
+    // Draws from the largest multiple of `bound` fitting in a u128.
+    let threshold = 0u128.wrapping_sub(bound) % bound;
+    loop {
+        let draw = (u128::from(rng.next_u64()) << 64) | u128::from(rng.next_u64());
+        if draw >= threshold {
+            return draw % bound;
+        }
+    }
+}
+
+/// Draws `n` addresses uniformly from `ranges`, skipping IPv4-mapped
+/// ranges, which repeat the IPv4 table.
+pub(crate) fn sample_ips<R: Rng + ?Sized>(
+    ranges: impl Iterator<Item = Result<IpRange, Error>>,
+    n: usize,
+    rng: &mut R,
+) -> Result<Vec<IpAddr>, Error> {
+    // Ranges with the number of addresses up to and including them.
+    let mut ranges_by_total: Vec<(IpRange, u128)> = Vec::new();
+    let mut total = 0u128;
+    for range in ranges {
+        let range = range?;
+        let size = match (range.start, range.end) {
+            (IpAddr::V4(start), IpAddr::V4(end)) => {
+                u128::from(u32::from(end) - u32::from(start)) + 1
+            }
+            (IpAddr::V6(start), IpAddr::V6(end)) if start.to_ipv4_mapped().is_none() => {
+                (u128::from(end) - u128::from(start)).saturating_add(1)
+            }
+            _ => continue,
+        };
+        total = total.saturating_add(size);
+        ranges_by_total.push((range, total));
+    }
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+    if total == 0 {
+        return Err(Error::RecordNotFound);
+    }
+    let sample = (0..n)
+        .map(|_| {
+            let draw = below(rng, total);
+            let index = ranges_by_total.partition_point(|&(_, upto)| upto <= draw);
+            let before = index.checked_sub(1).map_or(0, |i| ranges_by_total[i].1);
+            let (range, offset) = (ranges_by_total[index].0, draw - before);
+            match range.start {
+                IpAddr::V4(start) => IpAddr::V4(Ipv4Addr::from(u32::from(start) + offset as u32)),
+                IpAddr::V6(start) => IpAddr::V6(Ipv6Addr::from(u128::from(start) + offset)),
+            }
+        })
+        .collect();
+    Ok(sample)
+}
@@ -0,0 +1,430 @@
+//! The serialized form of records, which downstream tables are built from.
+use crate::{
+    ip2location::consts as location, ip2proxy::consts as proxy, LocationRecord, ProxyRecord,
+};
+use alloc::{borrow::Cow, vec::Vec};
+use core::net::IpAddr;
+use serde::{Deserialize, Serialize};
+
+/// Version of the serialized form of records: the names of the fields,
+/// their order and their types. Records serialize their fields in the
+/// order of `LocationRecord::COLUMNS` and `ProxyRecord::COLUMNS`, leaving
+/// out the ones a database does not have. New fields are only ever added
+/// at the end; renaming, removing, reordering or retyping a field bumps
+/// this version. It is reported in `DbInfo::schema_version`.
+pub const SCHEMA_VERSION: u32 = 1;
+
+impl LocationRecord<'_> {
+    /// The fields of a serialized record in order, with `country` flattened
+    /// into `country_short_name` and `country_long_name` as in CSV output.
+    pub const COLUMNS: &'static [&'static str] = &[
+        "ip",
+        "latitude",
+        "longitude",
+        "country_short_name",
+        "country_long_name",
+        "region",
+        "city",
+        "isp",
+        "domain",
+        "zip_code",
+        "time_zone",
+        "net_speed",
+        "idd_code",
+        "area_code",
+        "weather_station_code",
+        "weather_station_name",
+        "mcc",
+        "mnc",
+        "mobile_brand",
+        "elevation",
+        "usage_type",
+        "address_type",
+        "category",
+        "district",
+        "asn",
+        "as_name",
+    ];
+}
+
+impl ProxyRecord<'_> {
+    /// The fields of a serialized record in order, with `country` flattened
+    /// into `country_short_name` and `country_long_name` as in CSV output.
+    pub const COLUMNS: &'static [&'static str] = &[
+        "ip",
+        "country_short_name",
+        "country_long_name",
+        "region",
+        "city",
+        "isp",
+        "domain",
+        "is_proxy",
+        "proxy_type",
+        "asn",
+        "as_",
+        "last_seen",
+        "threat",
+        "provider",
+        "usage_type",
+    ];
+}
+
+/// Type of the values of a column.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+pub enum ColumnType {
+    /// An IPv4 or IPv6 address in its text form.
+    Ip,
+    /// A 32-bit float.
+    Float,
+    /// A string; `is_proxy` is the name of a `Proxy` variant.
+    Text,
+}
+
+/// A column of the serialized form of records.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+pub struct Column {
+    pub name: &'static str,
+    pub column_type: ColumnType,
+    /// Whether the database type has the field. Records of other types
+    /// leave it out, so it is always null in their rows.
+    pub populated: bool,
+}
+
+/// The columns of `names` in order, typed by `column_type` and populated
+/// as `populated` says.
+fn columns(
+    names: &[&'static str],
+    column_type: impl Fn(&str) -> ColumnType,
+    populated: impl Fn(&str) -> bool,
+) -> Vec<Column> {
+    names
+        .iter()
+        .map(|&name| Column {
+            name,
+            column_type: column_type(name),
+            populated: populated(name),
+        })
+        .collect()
+}
+
+/// Whether `positions` has a column for `db_type`.
+fn has(positions: &[u32], db_type: u8) -> bool {
+    positions.get(db_type as usize).is_some_and(|&p| p > 0)
+}
+
+impl LocationRecord<'_> {
+    pub fn schema(db_type: u8) -> Vec<Column> {
+        //! The columns of records read from databases of type `db_type`, the
+        //! `db_type` of `DbInfo`, in the order of `COLUMNS`.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::{ColumnType, LocationRecord};
+        //!
+        //! // DB5 has coordinates but no ISP.
+        //! let schema = LocationRecord::schema(5);
+        //! assert_eq!(schema[1].name, "latitude");
+        //! assert_eq!(schema[1].column_type, ColumnType::Float);
+        //! assert!(schema[1].populated);
+        //! assert!(!schema.iter().any(|c| c.name == "isp" && c.populated));
+        //!```
+
+        use location::*;
+        columns(
+            Self::COLUMNS,
+            |name| match name {
+                "ip" => ColumnType::Ip,
+                "latitude" | "longitude" => ColumnType::Float,
+                _ => ColumnType::Text,
+            },
+            |name| {
+                let positions: &[u32] = match name {
+                    "ip" => return true,
+                    "latitude" => &LATITUDE_POSITION,
+                    "longitude" => &LONGITUDE_POSITION,
+                    "country_short_name" | "country_long_name" => &COUNTRY_POSITION,
+                    "region" => &REGION_POSITION,
+                    "city" => &CITY_POSITION,
+                    "isp" => &ISP_POSITION,
+                    "domain" => &DOMAIN_POSITION,
+                    "zip_code" => &ZIPCODE_POSITION,
+                    "time_zone" => &TIMEZONE_POSITION,
+                    "net_speed" => &NETSPEED_POSITION,
+                    "idd_code" => &IDDCODE_POSITION,
+                    "area_code" => &AREACODE_POSITION,
+                    "weather_station_code" => &WEATHERSTATIONCODE_POSITION,
+                    "weather_station_name" => &WEATHERSTATIONNAME_POSITION,
+                    "mcc" => &MCC_POSITION,
+                    "mnc" => &MNC_POSITION,
+                    "mobile_brand" => &MOBILEBRAND_POSITION,
+                    "elevation" => &ELEVATION_POSITION,
+                    "usage_type" => &USAGETYPE_POSITION,
+                    "address_type" => &ADDRESSTYPE_POSITION,
+                    "category" => &CATEGORY_POSITION,
+                    "district" => &DISTRICT_POSITION,
+                    "asn" => &ASN_POSITION,
+                    _ => &AS_POSITION,
+                };
+                has(positions, db_type)
+            },
+        )
+    }
+}
+
+impl ProxyRecord<'_> {
+    pub fn schema(db_type: u8) -> Vec<Column> {
+        //! The columns of records read from databases of type `db_type`, the
+        //! `db_type` of `DbInfo`, in the order of `COLUMNS`.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::ProxyRecord;
+        //!
+        //! // PX1 only has the country, from which `is_proxy` is derived.
+        //! let populated: Vec<_> = ProxyRecord::schema(1)
+        //!     .into_iter()
+        //!     .filter(|c| c.populated)
+        //!     .map(|c| c.name)
+        //!     .collect();
+        //! assert_eq!(
+        //!     populated,
+        //!     ["ip", "country_short_name", "country_long_name", "is_proxy", "proxy_type"]
+        //! );
+        //!```
+
+        use proxy::*;
+        columns(
+            Self::COLUMNS,
+            |name| match name {
+                "ip" => ColumnType::Ip,
+                _ => ColumnType::Text,
+            },
+            |name| {
+                let positions: &[u32] = match name {
+                    "ip" => return true,
+                    // Both are derived from the country of proxies, and
+                    // the proxy type is read from the country column when
+                    // the database has no column of its own.
+                    "country_short_name" | "country_long_name" | "is_proxy" | "proxy_type" => {
+                        &COUNTRY_POSITION
+                    }
+                    "region" => &REGION_POSITION,
+                    "city" => &CITY_POSITION,
+                    "isp" => &ISP_POSITION,
+                    "domain" => &DOMAIN_POSITION,
+                    "asn" => &ASN_POSITION,
+                    "as_" => &AS_POSITION,
+                    "last_seen" => &LAST_SEEN_POSITION,
+                    "threat" => &THREAT_POSITION,
+                    "provider" => &PROVIDER_POSITION,
+                    _ => &USAGE_TYPE_POSITION,
+                };
+                has(positions, db_type)
+            },
+        )
+    }
+}
+
+/// A column of `LocationRecord::COLUMNS` or `ProxyRecord::COLUMNS`, e.g.
+/// to select the fields of `Record::to_value`. Serialized as its name, so
+/// selections can be read from configuration.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Field {
+    Ip,
+    Latitude,
+    Longitude,
+    CountryShortName,
+    CountryLongName,
+    Region,
+    City,
+    Isp,
+    Domain,
+    ZipCode,
+    TimeZone,
+    NetSpeed,
+    IddCode,
+    AreaCode,
+    WeatherStationCode,
+    WeatherStationName,
+    Mcc,
+    Mnc,
+    MobileBrand,
+    Elevation,
+    UsageType,
+    AddressType,
+    Category,
+    District,
+    Asn,
+    AsName,
+    IsProxy,
+    ProxyType,
+    #[serde(rename = "as_")]
+    As,
+    LastSeen,
+    Threat,
+    Provider,
+}
+
+impl Field {
+    /// Every field, location columns first.
+    pub const ALL: [Field; 32] = [
+        Field::Ip,
+        Field::Latitude,
+        Field::Longitude,
+        Field::CountryShortName,
+        Field::CountryLongName,
+        Field::Region,
+        Field::City,
+        Field::Isp,
+        Field::Domain,
+        Field::ZipCode,
+        Field::TimeZone,
+        Field::NetSpeed,
+        Field::IddCode,
+        Field::AreaCode,
+        Field::WeatherStationCode,
+        Field::WeatherStationName,
+        Field::Mcc,
+        Field::Mnc,
+        Field::MobileBrand,
+        Field::Elevation,
+        Field::UsageType,
+        Field::AddressType,
+        Field::Category,
+        Field::District,
+        Field::Asn,
+        Field::AsName,
+        Field::IsProxy,
+        Field::ProxyType,
+        Field::As,
+        Field::LastSeen,
+        Field::Threat,
+        Field::Provider,
+    ];
+
+    /// The name of the column, as in `COLUMNS`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Field::Ip => "ip",
+            Field::Latitude => "latitude",
+            Field::Longitude => "longitude",
+            Field::CountryShortName => "country_short_name",
+            Field::CountryLongName => "country_long_name",
+            Field::Region => "region",
+            Field::City => "city",
+            Field::Isp => "isp",
+            Field::Domain => "domain",
+            Field::ZipCode => "zip_code",
+            Field::TimeZone => "time_zone",
+            Field::NetSpeed => "net_speed",
+            Field::IddCode => "idd_code",
+            Field::AreaCode => "area_code",
+            Field::WeatherStationCode => "weather_station_code",
+            Field::WeatherStationName => "weather_station_name",
+            Field::Mcc => "mcc",
+            Field::Mnc => "mnc",
+            Field::MobileBrand => "mobile_brand",
+            Field::Elevation => "elevation",
+            Field::UsageType => "usage_type",
+            Field::AddressType => "address_type",
+            Field::Category => "category",
+            Field::District => "district",
+            Field::Asn => "asn",
+            Field::AsName => "as_name",
+            Field::IsProxy => "is_proxy",
+            Field::ProxyType => "proxy_type",
+            Field::As => "as_",
+            Field::LastSeen => "last_seen",
+            Field::Threat => "threat",
+            Field::Provider => "provider",
+        }
+    }
+}
+
+/// The value of a column of a record, of its `ColumnType`.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub(crate) enum ColumnValue<'r> {
+    Ip(IpAddr),
+    Float(f32),
+    Text(&'r str),
+}
+
+fn text<'r>(value: &'r Option<Cow<'_, str>>) -> Option<ColumnValue<'r>> {
+    value.as_deref().map(ColumnValue::Text)
+}
+
+impl LocationRecord<'_> {
+    /// The value of the column `name` of `COLUMNS`, `None` for fields the
+    /// record does not have and for unknown names.
+    pub(crate) fn column(&self, name: &str) -> Option<ColumnValue<'_>> {
+        match name {
+            "ip" => Some(ColumnValue::Ip(self.ip)),
+            "latitude" => self.latitude.map(ColumnValue::Float),
+            "longitude" => self.longitude.map(ColumnValue::Float),
+            "country_short_name" => self
+                .country
+                .as_ref()
+                .map(|c| ColumnValue::Text(&c.short_name)),
+            "country_long_name" => self
+                .country
+                .as_ref()
+                .map(|c| ColumnValue::Text(&c.long_name)),
+            "region" => text(&self.region),
+            "city" => text(&self.city),
+            "isp" => text(&self.isp),
+            "domain" => text(&self.domain),
+            "zip_code" => text(&self.zip_code),
+            "time_zone" => text(&self.time_zone),
+            "net_speed" => text(&self.net_speed),
+            "idd_code" => text(&self.idd_code),
+            "area_code" => text(&self.area_code),
+            "weather_station_code" => text(&self.weather_station_code),
+            "weather_station_name" => text(&self.weather_station_name),
+            "mcc" => text(&self.mcc),
+            "mnc" => text(&self.mnc),
+            "mobile_brand" => text(&self.mobile_brand),
+            "elevation" => text(&self.elevation),
+            "usage_type" => text(&self.usage_type),
+            "address_type" => text(&self.address_type),
+            "category" => text(&self.category),
+            "district" => text(&self.district),
+            "asn" => text(&self.asn),
+            "as_name" => text(&self.as_name),
+            _ => None,
+        }
+    }
+}
+
+impl ProxyRecord<'_> {
+    /// The value of the column `name` of `COLUMNS`, `None` for fields the
+    /// record does not have and for unknown names.
+    pub(crate) fn column(&self, name: &str) -> Option<ColumnValue<'_>> {
+        match name {
+            "ip" => Some(ColumnValue::Ip(self.ip)),
+            "country_short_name" => self
+                .country
+                .as_ref()
+                .map(|c| ColumnValue::Text(&c.short_name)),
+            "country_long_name" => self
+                .country
+                .as_ref()
+                .map(|c| ColumnValue::Text(&c.long_name)),
+            "region" => text(&self.region),
+            "city" => text(&self.city),
+            "isp" => text(&self.isp),
+            "domain" => text(&self.domain),
+            "is_proxy" => self.is_proxy.as_ref().map(|p| ColumnValue::Text(p.name())),
+            "proxy_type" => text(&self.proxy_type),
+            "asn" => text(&self.asn),
+            "as_" => text(&self.as_),
+            "last_seen" => text(&self.last_seen),
+            "threat" => text(&self.threat),
+            "provider" => text(&self.provider),
+            "usage_type" => text(&self.usage_type),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! Embedded HTTP/JSON lookup server, available with the `http-server`
+//! feature.
+//!
+//! Routes:
+//! - `GET /v1/lookup/{ip}` returns the record of the address as JSON, with
+//!   400 for an invalid address and 404 when it is not in the database.
+//! - `GET /v1/info` returns the `DbInfo` of the database being served.
+//!
+//! Errors are returned as `{"error": "..."}`.
+use crate::{error::Error, ReloadableDb};
+use ::axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use std::{io, net::IpAddr, net::SocketAddr, sync::Arc};
+
+/// Builds the router serving lookups from the database.
+pub fn router(db: Arc<ReloadableDb>) -> Router {
+    Router::new()
+        .route("/v1/lookup/{ip}", get(lookup))
+        .route("/v1/info", get(info))
+        .with_state(db)
+}
+
+pub async fn serve(db: Arc<ReloadableDb>, addr: SocketAddr) -> io::Result<()> {
+    //! Serves the router on addr until the process is stopped.
+    //!
+    //! ## Example usage
+    //!
+    //!```rust,no_run
+    //! use ip2location::{server, ReloadableDb};
+    //! use std::{sync::Arc, time::Duration};
+    //!
+    //! #[tokio::main]
+    //! async fn main() -> std::io::Result<()> {
+    //!     let db = Arc::new(ReloadableDb::open("data/IP2LOCATION-LITE-DB1.BIN").unwrap());
+    //!     let _watcher = db.watch(Duration::from_secs(30));
+    //!     server::serve(db, "127.0.0.1:8080".parse().unwrap()).await
+    //! }
+    //!```
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    ::axum::serve(listener, router(db)).await
+}
+
+fn error(status: StatusCode, message: impl ToString) -> Response {
+    let body = serde_json::json!({ "error": message.to_string() });
+    (status, Json(body)).into_response()
+}
+
+async fn lookup(State(db): State<Arc<ReloadableDb>>, Path(ip): Path<String>) -> Response {
+    let ip: IpAddr = match ip.parse() {
+        Ok(ip) => ip,
+        Err(_) => {
+            return error(
+                StatusCode::BAD_REQUEST,
+                format!("invalid IP address: {}", ip),
+            )
+        }
+    };
+    let db = db.current();
+    match db.ip_lookup(ip) {
+        Ok(record) => Json(record).into_response(),
+        Err(Error::RecordNotFound) => error(StatusCode::NOT_FOUND, format!("no record for {}", ip)),
+        Err(err) => error(StatusCode::INTERNAL_SERVER_ERROR, err),
+    }
+}
+
+async fn info(State(db): State<Arc<ReloadableDb>>) -> Json<crate::DbInfo> {
+    Json(db.current().info())
+}
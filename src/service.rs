@@ -0,0 +1,75 @@
+//! `tower::Service` implementation for lookups, available with the `tower`
+//! feature.
+use crate::{error::Error, Record, DB};
+use std::{
+    future::{ready, Ready},
+    net::IpAddr,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower_service::Service;
+
+/// A cheaply cloneable handle to a shared `DB` answering lookups as a
+/// `tower::Service<IpAddr>`.
+///
+/// Lookups are served from the memory map and never block, so the service
+/// is always ready and its future resolves immediately. Records are
+/// returned owned so they can outlive the call.
+#[derive(Debug, Clone)]
+pub struct DbService {
+    db: Arc<DB>,
+}
+
+impl DbService {
+    pub fn new(db: DB) -> Self {
+        //! Wraps the database in a service.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::{service::DbService, DB};
+        //! use tower_service::Service;
+        //!
+        //! let mut service = DbService::new(DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap());
+        //! let record = service.call("43.224.159.155".parse().unwrap()).into_inner().unwrap();
+        //! println!("{:#?}", record);
+        //!```
+        Self::from_shared(Arc::new(db))
+    }
+
+    /// Builds a service on top of an already shared database.
+    pub fn from_shared(db: Arc<DB>) -> Self {
+        Self { db }
+    }
+
+    /// Returns the underlying database.
+    pub fn db(&self) -> &Arc<DB> {
+        &self.db
+    }
+}
+
+impl From<DB> for DbService {
+    fn from(db: DB) -> Self {
+        Self::new(db)
+    }
+}
+
+impl From<Arc<DB>> for DbService {
+    fn from(db: Arc<DB>) -> Self {
+        Self::from_shared(db)
+    }
+}
+
+impl Service<IpAddr> for DbService {
+    type Response = Record<'static>;
+    type Error = Error;
+    type Future = Ready<Result<Record<'static>, Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, ip: IpAddr) -> Self::Future {
+        ready(self.db.ip_lookup(ip).map(Record::into_owned))
+    }
+}
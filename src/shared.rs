@@ -0,0 +1,248 @@
+//! Records whose strings are reference counted.
+use crate::{Country, LocationRecord, Proxy, ProxyRecord, Record};
+use alloc::{borrow::Cow, boxed::Box, sync::Arc};
+use core::net::IpAddr;
+use serde::{Serialize, Serializer};
+
+/// A `Country` with reference counted names.
+#[derive(PartialEq, Debug, Clone)]
+pub struct SharedCountry {
+    pub short_name: Arc<str>,
+    pub long_name: Arc<str>,
+}
+
+/// A `LocationRecord` whose strings are `Arc<str>`: it does not borrow
+/// from the database, and clones share the strings instead of copying
+/// them, so it can be cloned and sent to other threads cheaply.
+#[derive(PartialEq, Debug, Clone)]
+pub struct SharedLocationRecord {
+    pub ip: IpAddr,
+    pub latitude: Option<f32>,
+    pub longitude: Option<f32>,
+    pub country: Option<SharedCountry>,
+    pub region: Option<Arc<str>>,
+    pub city: Option<Arc<str>>,
+    pub isp: Option<Arc<str>>,
+    pub domain: Option<Arc<str>>,
+    pub zip_code: Option<Arc<str>>,
+    pub time_zone: Option<Arc<str>>,
+    pub net_speed: Option<Arc<str>>,
+    pub idd_code: Option<Arc<str>>,
+    pub area_code: Option<Arc<str>>,
+    pub weather_station_code: Option<Arc<str>>,
+    pub weather_station_name: Option<Arc<str>>,
+    pub mcc: Option<Arc<str>>,
+    pub mnc: Option<Arc<str>>,
+    pub mobile_brand: Option<Arc<str>>,
+    pub elevation: Option<Arc<str>>,
+    pub usage_type: Option<Arc<str>>,
+    pub address_type: Option<Arc<str>>,
+    pub category: Option<Arc<str>>,
+    pub district: Option<Arc<str>>,
+    pub asn: Option<Arc<str>>,
+    pub as_name: Option<Arc<str>>,
+}
+
+/// A `ProxyRecord` whose strings are `Arc<str>`, like
+/// `SharedLocationRecord`.
+#[derive(PartialEq, Debug, Clone)]
+pub struct SharedProxyRecord {
+    pub ip: IpAddr,
+    pub country: Option<SharedCountry>,
+    pub region: Option<Arc<str>>,
+    pub city: Option<Arc<str>>,
+    pub isp: Option<Arc<str>>,
+    pub domain: Option<Arc<str>>,
+    pub is_proxy: Option<Proxy>,
+    pub proxy_type: Option<Arc<str>>,
+    pub asn: Option<Arc<str>>,
+    pub as_: Option<Arc<str>>,
+    pub last_seen: Option<Arc<str>>,
+    pub threat: Option<Arc<str>>,
+    pub provider: Option<Arc<str>>,
+    pub usage_type: Option<Arc<str>>,
+}
+
+/// A `Record` whose strings are `Arc<str>`, as returned by
+/// `Record::into_shared`.
+#[derive(PartialEq, Debug, Clone)]
+pub enum SharedRecord {
+    LocationDb(Box<SharedLocationRecord>),
+    ProxyDb(Box<SharedProxyRecord>),
+}
+
+fn shared(value: Cow<'_, str>) -> Arc<str> {
+    Arc::from(value.as_ref())
+}
+
+impl From<Country<'_>> for SharedCountry {
+    fn from(country: Country<'_>) -> Self {
+        SharedCountry {
+            short_name: shared(country.short_name),
+            long_name: shared(country.long_name),
+        }
+    }
+}
+
+impl SharedCountry {
+    /// Borrows the names as a `Country`.
+    pub fn as_country(&self) -> Country<'_> {
+        Country {
+            short_name: Cow::Borrowed(&self.short_name),
+            long_name: Cow::Borrowed(&self.long_name),
+        }
+    }
+}
+
+impl From<LocationRecord<'_>> for SharedLocationRecord {
+    fn from(record: LocationRecord<'_>) -> Self {
+        SharedLocationRecord {
+            ip: record.ip,
+            latitude: record.latitude,
+            longitude: record.longitude,
+            country: record.country.map(SharedCountry::from),
+            region: record.region.map(shared),
+            city: record.city.map(shared),
+            isp: record.isp.map(shared),
+            domain: record.domain.map(shared),
+            zip_code: record.zip_code.map(shared),
+            time_zone: record.time_zone.map(shared),
+            net_speed: record.net_speed.map(shared),
+            idd_code: record.idd_code.map(shared),
+            area_code: record.area_code.map(shared),
+            weather_station_code: record.weather_station_code.map(shared),
+            weather_station_name: record.weather_station_name.map(shared),
+            mcc: record.mcc.map(shared),
+            mnc: record.mnc.map(shared),
+            mobile_brand: record.mobile_brand.map(shared),
+            elevation: record.elevation.map(shared),
+            usage_type: record.usage_type.map(shared),
+            address_type: record.address_type.map(shared),
+            category: record.category.map(shared),
+            district: record.district.map(shared),
+            asn: record.asn.map(shared),
+            as_name: record.as_name.map(shared),
+        }
+    }
+}
+
+impl SharedLocationRecord {
+    /// Borrows the strings as a `LocationRecord`, e.g. to pass the record
+    /// to code taking the borrowed flavor.
+    pub fn as_record(&self) -> LocationRecord<'_> {
+        LocationRecord {
+            ip: self.ip,
+            latitude: self.latitude,
+            longitude: self.longitude,
+            country: self.country.as_ref().map(SharedCountry::as_country),
+            region: self.region.as_deref().map(Cow::Borrowed),
+            city: self.city.as_deref().map(Cow::Borrowed),
+            isp: self.isp.as_deref().map(Cow::Borrowed),
+            domain: self.domain.as_deref().map(Cow::Borrowed),
+            zip_code: self.zip_code.as_deref().map(Cow::Borrowed),
+            time_zone: self.time_zone.as_deref().map(Cow::Borrowed),
+            net_speed: self.net_speed.as_deref().map(Cow::Borrowed),
+            idd_code: self.idd_code.as_deref().map(Cow::Borrowed),
+            area_code: self.area_code.as_deref().map(Cow::Borrowed),
+            weather_station_code: self.weather_station_code.as_deref().map(Cow::Borrowed),
+            weather_station_name: self.weather_station_name.as_deref().map(Cow::Borrowed),
+            mcc: self.mcc.as_deref().map(Cow::Borrowed),
+            mnc: self.mnc.as_deref().map(Cow::Borrowed),
+            mobile_brand: self.mobile_brand.as_deref().map(Cow::Borrowed),
+            elevation: self.elevation.as_deref().map(Cow::Borrowed),
+            usage_type: self.usage_type.as_deref().map(Cow::Borrowed),
+            address_type: self.address_type.as_deref().map(Cow::Borrowed),
+            category: self.category.as_deref().map(Cow::Borrowed),
+            district: self.district.as_deref().map(Cow::Borrowed),
+            asn: self.asn.as_deref().map(Cow::Borrowed),
+            as_name: self.as_name.as_deref().map(Cow::Borrowed),
+        }
+    }
+}
+
+impl From<ProxyRecord<'_>> for SharedProxyRecord {
+    fn from(record: ProxyRecord<'_>) -> Self {
+        SharedProxyRecord {
+            ip: record.ip,
+            country: record.country.map(SharedCountry::from),
+            region: record.region.map(shared),
+            city: record.city.map(shared),
+            isp: record.isp.map(shared),
+            domain: record.domain.map(shared),
+            is_proxy: record.is_proxy,
+            proxy_type: record.proxy_type.map(shared),
+            asn: record.asn.map(shared),
+            as_: record.as_.map(shared),
+            last_seen: record.last_seen.map(shared),
+            threat: record.threat.map(shared),
+            provider: record.provider.map(shared),
+            usage_type: record.usage_type.map(shared),
+        }
+    }
+}
+
+impl SharedProxyRecord {
+    /// Borrows the strings as a `ProxyRecord`.
+    pub fn as_record(&self) -> ProxyRecord<'_> {
+        ProxyRecord {
+            ip: self.ip,
+            country: self.country.as_ref().map(SharedCountry::as_country),
+            region: self.region.as_deref().map(Cow::Borrowed),
+            city: self.city.as_deref().map(Cow::Borrowed),
+            isp: self.isp.as_deref().map(Cow::Borrowed),
+            domain: self.domain.as_deref().map(Cow::Borrowed),
+            is_proxy: self.is_proxy.clone(),
+            proxy_type: self.proxy_type.as_deref().map(Cow::Borrowed),
+            asn: self.asn.as_deref().map(Cow::Borrowed),
+            as_: self.as_.as_deref().map(Cow::Borrowed),
+            last_seen: self.last_seen.as_deref().map(Cow::Borrowed),
+            threat: self.threat.as_deref().map(Cow::Borrowed),
+            provider: self.provider.as_deref().map(Cow::Borrowed),
+            usage_type: self.usage_type.as_deref().map(Cow::Borrowed),
+        }
+    }
+}
+
+impl From<Record<'_>> for SharedRecord {
+    fn from(record: Record<'_>) -> Self {
+        match record {
+            Record::LocationDb(rec) => SharedRecord::LocationDb(Box::new((*rec).into())),
+            Record::ProxyDb(rec) => SharedRecord::ProxyDb(Box::new((*rec).into())),
+        }
+    }
+}
+
+impl SharedRecord {
+    /// Borrows the strings as a `Record`.
+    pub fn as_record(&self) -> Record<'_> {
+        match self {
+            SharedRecord::LocationDb(rec) => Record::LocationDb(Box::new(rec.as_record())),
+            SharedRecord::ProxyDb(rec) => Record::ProxyDb(Box::new(rec.as_record())),
+        }
+    }
+}
+
+// Serialized like the borrowed records.
+impl Serialize for SharedCountry {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_country().serialize(serializer)
+    }
+}
+
+impl Serialize for SharedLocationRecord {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_record().serialize(serializer)
+    }
+}
+
+impl Serialize for SharedProxyRecord {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_record().serialize(serializer)
+    }
+}
+
+impl Serialize for SharedRecord {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_record().serialize(serializer)
+    }
+}
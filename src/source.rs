@@ -0,0 +1,167 @@
+//! Storage a database can be read from besides files and buffers, for
+//! callers supplying their own: encrypted files, caches, device memory.
+use crate::{error::Error, offset::FileOffset};
+use alloc::{format, vec::Vec};
+
+/// Random-access reads of the bytes of a database.
+///
+/// Implementations provide the length and `read_exact_at`; the numbers
+/// and strings of the records are decoded from the bytes they read.
+/// Storage holding the bytes in memory returns them from `as_slice`, and
+/// storage able to serve several reads at once overrides `read_batch`.
+/// Offsets are 0-based, as in the files.
+///
+/// A database opened with `DB::from_source` reads through the source for
+/// every lookup, checking its reads against `len` first, so a source only
+/// ever sees reads that fit.
+///
+/// ## Example usage
+///
+///```rust
+/// use ip2location::{error::Error, FileOffset, Source, DB};
+///
+/// /// A database stored with every byte XOR-ed with a key.
+/// struct Xored {
+///     bytes: Vec<u8>,
+///     key: u8,
+/// }
+///
+/// impl Source for Xored {
+///     fn len(&self) -> u64 {
+///         self.bytes.len() as u64
+///     }
+///
+///     fn read_exact_at(&self, offset: FileOffset, buf: &mut [u8]) -> Result<(), Error> {
+///         let bytes = usize::try_from(offset.get())
+///             .ok()
+///             .and_then(|start| self.bytes.get(start..start.checked_add(buf.len())?))
+///             .ok_or_else(|| Error::Corrupt(format!("read past the end at {}", offset)))?;
+///         for (out, byte) in buf.iter_mut().zip(bytes) {
+///             *out = byte ^ self.key;
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// let bytes = std::fs::read("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+/// let xored = Xored { bytes: bytes.iter().map(|b| b ^ 0x5a).collect(), key: 0x5a };
+/// let mut buf = [0; 4];
+/// assert!(xored.read_exact_at(FileOffset::new(xored.len() - 2), &mut buf).is_err());
+/// let db = DB::from_source(xored).unwrap();
+/// let record = db.ip_lookup("43.224.159.155".parse().unwrap()).unwrap();
+///```
+pub trait Source: Send + Sync {
+    /// Size of the database in bytes.
+    fn len(&self) -> u64;
+
+    /// Fills `buf` with the bytes at `offset`, failing if they are not
+    /// all in the database.
+    fn read_exact_at(&self, offset: FileOffset, buf: &mut [u8]) -> Result<(), Error>;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// All the bytes, for storage holding them in memory, so that reads
+    /// borrow them instead of copying.
+    fn as_slice(&self) -> Option<&[u8]> {
+        None
+    }
+
+    /// Fills each buffer from its offset, returning the number of bytes
+    /// read into each: less than its length only at the end of the
+    /// database. The strings of a record are read in one batch, so storage
+    /// that can serve several reads at once should override it.
+    fn read_batch(&self, reads: &mut [(FileOffset, &mut [u8])]) -> Result<Vec<usize>, Error> {
+        reads
+            .iter_mut()
+            .map(|(offset, buf)| {
+                let len = self
+                    .len()
+                    .saturating_sub(offset.get())
+                    .min(buf.len() as u64) as usize;
+                if len > 0 {
+                    self.read_exact_at(*offset, &mut buf[..len])?;
+                }
+                Ok(len)
+            })
+            .collect()
+    }
+
+    /// Number of bytes held in RAM, if known.
+    fn resident_len(&self) -> Option<u64> {
+        self.as_slice().map(|bytes| bytes.len() as u64)
+    }
+}
+
+impl Source for [u8] {
+    fn len(&self) -> u64 {
+        <[u8]>::len(self) as u64
+    }
+
+    fn read_exact_at(&self, offset: FileOffset, buf: &mut [u8]) -> Result<(), Error> {
+        buf.copy_from_slice(slice(self, offset, buf.len())?);
+        Ok(())
+    }
+
+    fn as_slice(&self) -> Option<&[u8]> {
+        Some(self)
+    }
+}
+
+impl Source for Vec<u8> {
+    fn len(&self) -> u64 {
+        Source::len(self.as_slice())
+    }
+
+    fn read_exact_at(&self, offset: FileOffset, buf: &mut [u8]) -> Result<(), Error> {
+        self.as_slice().read_exact_at(offset, buf)
+    }
+
+    fn as_slice(&self) -> Option<&[u8]> {
+        Some(self)
+    }
+}
+
+#[cfg(all(feature = "std", any(unix, windows)))]
+impl Source for std::fs::File {
+    fn len(&self) -> u64 {
+        self.metadata().map_or(0, |metadata| metadata.len())
+    }
+
+    fn read_exact_at(&self, offset: FileOffset, buf: &mut [u8]) -> Result<(), Error> {
+        #[cfg(unix)]
+        std::os::unix::fs::FileExt::read_exact_at(self, buf, offset.get())?;
+        #[cfg(windows)]
+        {
+            let mut done = 0;
+            while done < buf.len() {
+                let read = std::os::windows::fs::FileExt::seek_read(
+                    self,
+                    &mut buf[done..],
+                    offset.get() + done as u64,
+                )?;
+                if read == 0 {
+                    return Err(past_end(offset, buf.len()));
+                }
+                done += read;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The `len` bytes of `bytes` at `offset`, or `Error::Corrupt`.
+pub(crate) fn slice(bytes: &[u8], offset: FileOffset, len: usize) -> Result<&[u8], Error> {
+    usize::try_from(offset.get())
+        .ok()
+        .and_then(|start| bytes.get(start..start.checked_add(len)?))
+        .ok_or_else(|| past_end(offset, len))
+}
+
+pub(crate) fn past_end(offset: FileOffset, len: usize) -> Error {
+    Error::Corrupt(format!(
+        "read of {} bytes at {} past the end of the file",
+        len, offset
+    ))
+}
@@ -0,0 +1,225 @@
+//! Queries over the coordinates of the rows of DB5 and above.
+#[cfg(feature = "spatial")]
+use crate::{error::Error, LocationDB, Source};
+use crate::{IpRange, LocationRecord};
+#[cfg(feature = "spatial")]
+use rstar::{primitives::GeomWithData, RTree, AABB};
+#[cfg(feature = "spatial")]
+use std::collections::BTreeMap;
+
+/// Mean radius of the Earth, in kilometres.
+const EARTH_RADIUS_KM: f64 = 6371.0088;
+
+/// A location found by `LocationDB::nearest`.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Nearest<'a> {
+    /// The first range at the location, in address order.
+    pub range: IpRange,
+    /// The record of the range, whose `ip` is the start of the range.
+    pub record: LocationRecord<'a>,
+    /// The great-circle distance to the location.
+    pub distance_km: f64,
+}
+
+/// The great-circle distance between two coordinates, in degrees, by the
+/// haversine formula.
+pub(crate) fn distance_km(from: (f32, f32), to: (f32, f32)) -> f64 {
+    let (lat1, lon1) = (
+        f64::from(from.0).to_radians(),
+        f64::from(from.1).to_radians(),
+    );
+    let (lat2, lon2) = (f64::from(to.0).to_radians(), f64::from(to.1).to_radians());
+    let a = ((lat2 - lat1) / 2.0).sin().powi(2)
+        + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()
+}
+
+/// An R-tree of the locations of a database, built once by
+/// `LocationDB::build_spatial_index` and queried without scanning the
+/// rows. It holds no reference to the database: keep it next to the
+/// database it was built from, e.g. to rebuild both on reload.
+#[cfg(feature = "spatial")]
+#[derive(Debug, Clone)]
+pub struct SpatialIndex {
+    /// Rows of the database, `(ipv6, row)`, grouped by location and in
+    /// address order within a location.
+    rows: Vec<(bool, u32)>,
+    /// Locations by latitude and longitude, for boxes.
+    planar: RTree<GeomWithData<[f32; 2], Location>>,
+    /// Locations on the unit sphere, for nearest neighbours: the distance
+    /// through the sphere grows with the distance along it.
+    sphere: RTree<GeomWithData<[f64; 3], Location>>,
+    /// The shape of the database, to refuse queries against another one.
+    shape: (u64, u32, u32),
+}
+
+/// A location with the rows having it, `SpatialIndex::rows[start..end]`.
+#[cfg(feature = "spatial")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Location {
+    lat: f32,
+    lon: f32,
+    start: u32,
+    end: u32,
+}
+
+#[cfg(feature = "spatial")]
+impl SpatialIndex {
+    /// Indexes the rows of `db` with their coordinates.
+    pub(crate) fn build<S: Source>(db: &LocationDB<S>) -> Result<Self, Error> {
+        let mut grouped: BTreeMap<(u32, u32), Vec<(bool, u32)>> = BTreeMap::new();
+        for (ipv6, row) in db.located_rows() {
+            let (lat, lon) = db.coordinates(ipv6, row)?;
+            if lat.is_finite() && lon.is_finite() {
+                grouped
+                    .entry((lat.to_bits(), lon.to_bits()))
+                    .or_default()
+                    .push((ipv6, row));
+            }
+        }
+        let mut rows = Vec::new();
+        let mut locations = Vec::with_capacity(grouped.len());
+        for ((lat, lon), group) in grouped {
+            let start = rows.len() as u32;
+            rows.extend(group);
+            locations.push(Location {
+                lat: f32::from_bits(lat),
+                lon: f32::from_bits(lon),
+                start,
+                end: rows.len() as u32,
+            });
+        }
+        let planar = locations
+            .iter()
+            .map(|&location| GeomWithData::new([location.lat, location.lon], location))
+            .collect();
+        let sphere = locations
+            .iter()
+            .map(|&location| GeomWithData::new(on_sphere(location.lat, location.lon), location))
+            .collect();
+        Ok(Self {
+            rows,
+            planar: RTree::bulk_load(planar),
+            sphere: RTree::bulk_load(sphere),
+            shape: shape(db),
+        })
+    }
+
+    /// The number of distinct locations indexed.
+    pub fn len(&self) -> usize {
+        self.sphere.size()
+    }
+
+    /// Returns true if the database had no coordinates to index.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn nearest<'a, S: Source>(
+        &self,
+        db: &'a LocationDB<S>,
+        lat: f32,
+        lon: f32,
+        k: usize,
+    ) -> Result<Vec<Nearest<'a>>, Error> {
+        //! Finds the `k` locations nearest to a coordinate, as
+        //! `LocationDB::nearest` does, reading only the rows returned.
+        //! `db` must be the database the index was built from.
+        //!
+        //! ## Example usage
+        //!
+        //! ```rust,no_run
+        //! use ip2location::LocationDB;
+        //!
+        //! let db = LocationDB::from_file("data/IP2LOCATION-LITE-DB5.BIN").unwrap();
+        //! let index = db.build_spatial_index().unwrap();
+        //! for nearest in index.nearest(&db, 48.86, 2.35, 3).unwrap() {
+        //!     println!("{:?} {:.0} km", nearest.record.city, nearest.distance_km);
+        //! }
+        //! ```
+        self.check(db)?;
+        self.sphere
+            .nearest_neighbor_iter(&on_sphere(lat, lon))
+            .take(k)
+            .map(|point| {
+                let location = point.data;
+                let (ipv6, row) = self.rows[location.start as usize];
+                let (range, record) = db.range_at(ipv6, row)?;
+                Ok(Nearest {
+                    range,
+                    record,
+                    distance_km: distance_km((lat, lon), (location.lat, location.lon)),
+                })
+            })
+            .collect()
+    }
+
+    pub fn ranges_in_bbox<S: Source>(
+        &self,
+        db: &LocationDB<S>,
+        min_lat: f32,
+        min_lon: f32,
+        max_lat: f32,
+        max_lon: f32,
+    ) -> Result<Vec<IpRange>, Error> {
+        //! Finds the ranges whose coordinates are inside a box, in the
+        //! order of `LocationDB::ranges_in_bbox`, reading only their rows.
+        //! `db` must be the database the index was built from.
+        //!
+        //! ## Example usage
+        //!
+        //! ```rust,no_run
+        //! use ip2location::LocationDB;
+        //!
+        //! let db = LocationDB::from_file("data/IP2LOCATION-LITE-DB5.BIN").unwrap();
+        //! let index = db.build_spatial_index().unwrap();
+        //! // Ranges located in Greater London.
+        //! let ranges = index.ranges_in_bbox(&db, 51.28, -0.51, 51.69, 0.33).unwrap();
+        //! println!("{} ranges", ranges.len());
+        //! ```
+        self.check(db)?;
+        // A box crossing the antimeridian is the union of two boxes.
+        let boxes = if min_lon <= max_lon {
+            vec![(min_lon, max_lon)]
+        } else {
+            vec![(min_lon, 180.0), (-180.0, max_lon)]
+        };
+        let mut rows: Vec<(bool, u32)> = boxes
+            .into_iter()
+            .flat_map(|(min_lon, max_lon)| {
+                let envelope = AABB::from_corners([min_lat, min_lon], [max_lat, max_lon]);
+                self.planar.locate_in_envelope(&envelope)
+            })
+            .flat_map(|point| &self.rows[point.data.start as usize..point.data.end as usize])
+            .copied()
+            .collect();
+        rows.sort_unstable();
+        rows.into_iter()
+            .map(|(ipv6, row)| db.row_range(ipv6, row).map(|(range, _)| range))
+            .collect()
+    }
+
+    fn check<S: Source>(&self, db: &LocationDB<S>) -> Result<(), Error> {
+        if shape(db) == self.shape {
+            Ok(())
+        } else {
+            Err("spatial index built from another database".into())
+        }
+    }
+}
+
+#[cfg(feature = "spatial")]
+fn shape<S: Source>(db: &LocationDB<S>) -> (u64, u32, u32) {
+    (
+        db.file_size(),
+        db.ipv4_record_count(),
+        db.ipv6_record_count(),
+    )
+}
+
+/// The point of the unit sphere at a coordinate.
+#[cfg(feature = "spatial")]
+fn on_sphere(lat: f32, lon: f32) -> [f64; 3] {
+    let (lat, lon) = (f64::from(lat).to_radians(), f64::from(lon).to_radians());
+    [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]
+}
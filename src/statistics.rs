@@ -0,0 +1,135 @@
+//! Whole-database statistics, for checking a release before deploying it.
+use crate::{error::Error, Country, Record, DB};
+use alloc::{collections::BTreeMap, string::String, vec, vec::Vec};
+use core::net::IpAddr;
+use serde::Serialize;
+
+/// Summary of every range of a database, returned by `DB::statistics`.
+#[derive(PartialEq, Debug, Clone, Default, Serialize)]
+pub struct DbStatistics {
+    pub ipv4_records: u64,
+    pub ipv6_records: u64,
+    /// Records per country short name, `-` counting the ranges without a
+    /// country.
+    pub countries: BTreeMap<String, u64>,
+    /// Percentage of the records with each column populated, i.e. neither
+    /// empty nor `-`, for the columns the database has.
+    pub fill_rates: BTreeMap<&'static str, f64>,
+    /// Percentage of the IPv4 address space in ranges with a country.
+    pub ipv4_coverage: f64,
+    /// Percentage of the IPv6 address space in ranges with a country.
+    pub ipv6_coverage: f64,
+}
+
+/// Returns true for a value actually provided by the database.
+fn filled(value: &str) -> bool {
+    !value.is_empty() && value != "-"
+}
+
+fn text(name: &'static str, value: &Option<impl AsRef<str>>) -> (&'static str, Option<bool>) {
+    (name, value.as_ref().map(|value| filled(value.as_ref())))
+}
+
+/// The columns of a record, with whether each is populated, `None` for
+/// columns the database does not have.
+fn columns(record: &Record<'_>) -> Vec<(&'static str, Option<bool>)> {
+    let country = |country: &Option<Country<'_>>| {
+        ("country", country.as_ref().map(|c| filled(&c.short_name)))
+    };
+    match record {
+        Record::LocationDb(rec) => vec![
+            country(&rec.country),
+            ("latitude", rec.latitude.map(|_| true)),
+            ("longitude", rec.longitude.map(|_| true)),
+            text("region", &rec.region),
+            text("city", &rec.city),
+            text("isp", &rec.isp),
+            text("domain", &rec.domain),
+            text("zip_code", &rec.zip_code),
+            text("time_zone", &rec.time_zone),
+            text("net_speed", &rec.net_speed),
+            text("idd_code", &rec.idd_code),
+            text("area_code", &rec.area_code),
+            text("weather_station_code", &rec.weather_station_code),
+            text("weather_station_name", &rec.weather_station_name),
+            text("mcc", &rec.mcc),
+            text("mnc", &rec.mnc),
+            text("mobile_brand", &rec.mobile_brand),
+            text("elevation", &rec.elevation),
+            text("usage_type", &rec.usage_type),
+            text("address_type", &rec.address_type),
+            text("category", &rec.category),
+            text("district", &rec.district),
+            text("asn", &rec.asn),
+            text("as_name", &rec.as_name),
+        ],
+        Record::ProxyDb(rec) => vec![
+            country(&rec.country),
+            text("region", &rec.region),
+            text("city", &rec.city),
+            text("isp", &rec.isp),
+            text("domain", &rec.domain),
+            text("proxy_type", &rec.proxy_type),
+            text("asn", &rec.asn),
+            text("as", &rec.as_),
+            text("last_seen", &rec.last_seen),
+            text("threat", &rec.threat),
+            text("provider", &rec.provider),
+            text("usage_type", &rec.usage_type),
+        ],
+    }
+}
+
+fn country_code<'r>(record: &'r Record<'_>) -> &'r str {
+    let country = match record {
+        Record::LocationDb(rec) => &rec.country,
+        Record::ProxyDb(rec) => &rec.country,
+    };
+    country.as_ref().map_or("-", |country| &country.short_name)
+}
+
+/// Number of addresses in a range, as a float since the IPv6 address
+/// space does not fit in any integer.
+fn size(start: IpAddr, end: IpAddr) -> f64 {
+    match (start, end) {
+        (IpAddr::V4(start), IpAddr::V4(end)) => (u32::from(end) - u32::from(start)) as f64 + 1.0,
+        (IpAddr::V6(start), IpAddr::V6(end)) => (u128::from(end) - u128::from(start)) as f64 + 1.0,
+        _ => 0.0,
+    }
+}
+
+pub(crate) fn scan(db: &DB) -> Result<DbStatistics, Error> {
+    let mut stats = DbStatistics::default();
+    // Rows having each column, and rows where it is populated.
+    let mut fill: BTreeMap<&'static str, (u64, u64)> = BTreeMap::new();
+    let mut covered = [0.0; 2];
+    for range in db.ranges() {
+        let (range, record) = range?;
+        let ipv6 = range.is_ipv6();
+        if ipv6 {
+            stats.ipv6_records += 1;
+        } else {
+            stats.ipv4_records += 1;
+        }
+        let code = country_code(&record);
+        if filled(code) {
+            covered[ipv6 as usize] += size(range.start, range.end);
+        }
+        *stats.countries.entry(String::from(code)).or_default() += 1;
+        for (name, populated) in columns(&record) {
+            if let Some(populated) = populated {
+                let (present, populated_count) = fill.entry(name).or_default();
+                *present += 1;
+                *populated_count += populated as u64;
+            }
+        }
+    }
+    let records = stats.ipv4_records + stats.ipv6_records;
+    stats.fill_rates = fill
+        .into_iter()
+        .map(|(name, (_, populated))| (name, populated as f64 * 100.0 / records as f64))
+        .collect();
+    stats.ipv4_coverage = covered[0] * 100.0 / (u32::MAX as f64 + 1.0);
+    stats.ipv6_coverage = covered[1] * 100.0 / (u128::MAX as f64 + 1.0);
+    Ok(stats)
+}
@@ -0,0 +1,95 @@
+use crate::{common::Target, error::Error};
+use core::sync::atomic::Ordering;
+use serde::Serialize;
+
+// Targets without 64-bit atomics count in 32 bits.
+#[cfg(target_has_atomic = "64")]
+type Counter = core::sync::atomic::AtomicU64;
+#[cfg(not(target_has_atomic = "64"))]
+type Counter = core::sync::atomic::AtomicU32;
+
+/// Memory used by an open database.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default, Serialize)]
+pub struct MemoryStats {
+    /// Size of the file mapping.
+    pub mapped_bytes: usize,
+    /// Part of the mapping currently resident in RAM, when the platform
+    /// can report it (Linux and Android).
+    pub resident_bytes: Option<usize>,
+    /// Size of the index tables decoded into memory.
+    pub index_bytes: usize,
+    /// Number of ranges held by a lookup cache.
+    pub cache_entries: usize,
+}
+
+/// Counters of the lookups performed on a database.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default, Serialize)]
+pub struct LookupStats {
+    pub lookups: u64,
+    /// Lookups that returned a record.
+    pub hits: u64,
+    /// Lookups that failed with `Error::RecordNotFound`.
+    pub misses: u64,
+    /// Lookups that failed with any other error.
+    pub errors: u64,
+    /// Lookups searched in the IPv4 table, including IPv6 addresses
+    /// translated to IPv4.
+    pub ipv4_lookups: u64,
+    /// Lookups searched in the IPv6 table.
+    pub ipv6_lookups: u64,
+}
+
+/// Reads a counter, widening 32-bit ones.
+#[allow(clippy::useless_conversion)]
+fn load(counter: &Counter) -> u64 {
+    counter.load(Ordering::Relaxed).into()
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct LookupCounters {
+    lookups: Counter,
+    hits: Counter,
+    misses: Counter,
+    errors: Counter,
+    ipv4_lookups: Counter,
+    ipv6_lookups: Counter,
+}
+
+impl LookupCounters {
+    pub fn record<T>(&self, target: Target, result: &Result<T, Error>) {
+        self.lookups.fetch_add(1, Ordering::Relaxed);
+        match target {
+            Target::V4(_) => self.ipv4_lookups.fetch_add(1, Ordering::Relaxed),
+            Target::V6(_) => self.ipv6_lookups.fetch_add(1, Ordering::Relaxed),
+        };
+        match result {
+            Ok(_) => self.hits.fetch_add(1, Ordering::Relaxed),
+            Err(Error::RecordNotFound) => self.misses.fetch_add(1, Ordering::Relaxed),
+            Err(_) => self.errors.fetch_add(1, Ordering::Relaxed),
+        };
+    }
+
+    pub fn snapshot(&self) -> LookupStats {
+        LookupStats {
+            lookups: load(&self.lookups),
+            hits: load(&self.hits),
+            misses: load(&self.misses),
+            errors: load(&self.errors),
+            ipv4_lookups: load(&self.ipv4_lookups),
+            ipv6_lookups: load(&self.ipv6_lookups),
+        }
+    }
+
+    pub fn reset(&self) {
+        for counter in [
+            &self.lookups,
+            &self.hits,
+            &self.misses,
+            &self.errors,
+            &self.ipv4_lookups,
+            &self.ipv6_lookups,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{error::Error, options::DbOptions, DB};
+use opendal::Operator;
+
+impl DB {
+    pub async fn from_operator(
+        operator: &Operator,
+        path: &str,
+        options: &DbOptions,
+    ) -> Result<DB, Error> {
+        //! Loads the database at `path` of an OpenDAL operator into memory,
+        //! applying the given options. The operator can be any storage
+        //! OpenDAL supports, configured with its credentials.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::{opendal::{services::Memory, Operator}, DbOptions, DB};
+        //!
+        //! # #[tokio::main(flavor = "current_thread")]
+        //! # async fn main() {
+        //! let operator = Operator::new(Memory::default()).unwrap();
+        //! let bytes = std::fs::read("data/IP2LOCATION-LITE-DB1.BIN").unwrap();
+        //! operator.write("geo/DB1.BIN", bytes).await.unwrap();
+        //! let db = DB::from_operator(&operator, "geo/DB1.BIN", &DbOptions::new())
+        //!     .await
+        //!     .unwrap();
+        //! # }
+        //!```
+        let bytes = operator.read(path).await?.to_vec();
+        DB::from_bytes_with_options(bytes, options)
+    }
+
+    pub async fn from_url<I>(url: &str, config: I) -> Result<DB, Error>
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        //! Loads the database object at `url` into memory, e.g.
+        //! `s3://bucket/geo/IP2LOCATION-LITE-DB1.BIN`, `gcs://bucket/...`
+        //! or `azblob://container/...`. `config` holds the settings of the
+        //! OpenDAL service, such as `region`, `access_key_id` and
+        //! `secret_access_key` for S3; settings missing from it are read
+        //! from the environment as each service does.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust,no_run
+        //! use ip2location::DB;
+        //!
+        //! # #[tokio::main(flavor = "current_thread")]
+        //! # async fn main() {
+        //! let config = [("region".to_string(), "eu-west-1".to_string())];
+        //! let db = DB::from_url("s3://geo-data/IP2LOCATION-LITE-DB1.BIN", config)
+        //!     .await
+        //!     .unwrap();
+        //! # }
+        //!```
+        let (root, path) = split_url(url)?;
+        let operator = Operator::from_uri((root, config))?;
+        DB::from_operator(&operator, path, &DbOptions::default()).await
+    }
+}
+
+/// Splits `scheme://name/path/to/object` into the URL of the bucket or
+/// container and the path of the object in it.
+fn split_url(url: &str) -> Result<(&str, &str), Error> {
+    let invalid = || Error::GenericError(format!("invalid object URL: {}", url));
+    let authority = url.find("://").ok_or_else(invalid)? + 3;
+    let slash = url[authority..].find('/').ok_or_else(invalid)? + authority;
+    let path = &url[slash + 1..];
+    if slash == authority || path.is_empty() {
+        return Err(invalid());
+    }
+    Ok((&url[..slash], path))
+}
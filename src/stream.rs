@@ -0,0 +1,63 @@
+//! Lookups of a `Stream` of addresses and awaitable lookups, available
+//! with the `async` feature.
+use crate::{error::Error, IpResolver, OwnedRecord};
+use futures_util::stream::{Stream, StreamExt};
+use std::{net::IpAddr, sync::Arc};
+
+/// Looks up the addresses of any `Stream<Item = IpAddr>`.
+///
+/// ## Example usage
+///
+///```rust
+/// use futures_util::{stream, StreamExt};
+/// use ip2location::{GeoLookupExt, DB};
+/// use std::sync::Arc;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let db = Arc::new(DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap());
+/// let ips = stream::iter(["43.224.159.155".parse().unwrap(), "1.1.1.1".parse().unwrap()]);
+/// let mut records = ips.geo_lookup(&db, 8);
+/// while let Some((ip, record)) = records.next().await {
+///     println!("{}: {:?}", ip, record);
+/// }
+/// # }
+///```
+pub trait GeoLookupExt: Stream<Item = IpAddr> + Sized {
+    fn geo_lookup<R>(
+        self,
+        resolver: &Arc<R>,
+        concurrency: usize,
+    ) -> impl Stream<Item = (IpAddr, Result<OwnedRecord, Error>)> + use<Self, R>
+    where
+        R: IpResolver + Send + Sync + ?Sized + 'static,
+    {
+        //! Yields every address with its record, in the order of the
+        //! addresses. The lookups run on the blocking thread pool of the
+        //! tokio runtime, `concurrency` (at least one) at a time, so the
+        //! binary search never blocks the executor; a lookup that panics
+        //! panics the stream.
+        let resolver = resolver.clone();
+        self.map(move |ip| {
+            let resolver = resolver.clone();
+            async move { (ip, resolve_blocking(resolver, ip).await) }
+        })
+        .buffered(concurrency.max(1))
+    }
+}
+
+impl<S: Stream<Item = IpAddr>> GeoLookupExt for S {}
+
+/// Looks `ip` up on the blocking thread pool of the tokio runtime,
+/// panicking if the lookup panicked.
+pub(crate) async fn resolve_blocking<R>(resolver: Arc<R>, ip: IpAddr) -> Result<OwnedRecord, Error>
+where
+    R: IpResolver + Send + Sync + ?Sized + 'static,
+{
+    let lookup =
+        tokio::task::spawn_blocking(move || resolver.resolve(ip).map(|record| record.into_owned()));
+    match lookup.await {
+        Ok(record) => record,
+        Err(err) => std::panic::resume_unwind(err.into_panic()),
+    }
+}
@@ -0,0 +1,167 @@
+use crate::{error::Error, Country, LocationDB, LocationRecord, Proxy, ProxyDB, ProxyRecord, DB};
+#[cfg(feature = "std")]
+use crate::{AsnDB, AsnRecord};
+use core::net::IpAddr;
+use serde::Serialize;
+use serde_with::skip_serializing_none;
+
+/// The records of every database of a `GeoSuite` for one address.
+#[skip_serializing_none]
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct EnrichedRecord<'a> {
+    pub ip: IpAddr,
+    /// The IP2Location record, when the suite has a location database
+    /// knowing the address.
+    pub location: Option<LocationRecord<'a>>,
+    /// The IP2Proxy record, when the suite has a proxy database knowing
+    /// the address.
+    pub proxy: Option<ProxyRecord<'a>>,
+    /// The autonomous system, when the suite has an ASN database knowing
+    /// the address.
+    #[cfg(feature = "std")]
+    pub asn: Option<AsnRecord<'a>>,
+}
+
+impl EnrichedRecord<'_> {
+    /// The country of the location record, or else of the proxy record.
+    pub fn country(&self) -> Option<&Country<'_>> {
+        self.location
+            .as_ref()
+            .and_then(|rec| rec.country.as_ref())
+            .or_else(|| self.proxy.as_ref().and_then(|rec| rec.country.as_ref()))
+    }
+
+    /// Whether the proxy database flags the address, `None` without one.
+    pub fn is_proxy(&self) -> Option<&Proxy> {
+        self.proxy.as_ref().and_then(|rec| rec.is_proxy.as_ref())
+    }
+
+    /// Converts the record into one that owns all of its strings.
+    pub fn into_owned(self) -> EnrichedRecord<'static> {
+        EnrichedRecord {
+            ip: self.ip,
+            location: self.location.map(LocationRecord::into_owned),
+            proxy: self.proxy.map(ProxyRecord::into_owned),
+            #[cfg(feature = "std")]
+            asn: self.asn.map(AsnRecord::into_owned),
+        }
+    }
+}
+
+/// An IP2Location, an IP2Proxy and an ASN database queried together, for
+/// deployments running several of them. Each is optional.
+///
+/// ## Example usage
+///
+///```rust
+/// use ip2location::{GeoSuite, DB};
+///
+/// let suite = GeoSuite::new()
+///     .with_db(DB::from_file("data/IP2LOCATION-LITE-DB1.IPV6.BIN").unwrap())
+///     .with_db(DB::from_file("data/IP2PROXY-IP-COUNTRY.BIN").unwrap());
+/// let record = suite.lookup("1.1.1.1".parse().unwrap()).unwrap();
+/// assert_eq!(record.country().unwrap().short_name, "US");
+/// assert!(record.is_proxy().is_some());
+///```
+#[derive(Debug, Default)]
+pub struct GeoSuite {
+    location: Option<LocationDB>,
+    proxy: Option<ProxyDB>,
+    #[cfg(feature = "std")]
+    asn: Option<AsnDB>,
+}
+
+impl GeoSuite {
+    /// A suite without databases; add them with the `with_*` methods.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the location database, replacing any previous one.
+    pub fn with_location(mut self, db: LocationDB) -> Self {
+        self.location = Some(db);
+        self
+    }
+
+    /// Sets the proxy database, replacing any previous one.
+    pub fn with_proxy(mut self, db: ProxyDB) -> Self {
+        self.proxy = Some(db);
+        self
+    }
+
+    /// Sets the ASN database, replacing any previous one.
+    #[cfg(feature = "std")]
+    pub fn with_asn(mut self, db: AsnDB) -> Self {
+        self.asn = Some(db);
+        self
+    }
+
+    /// Sets the location or proxy database, depending on what `db` is.
+    pub fn with_db(self, db: DB) -> Self {
+        match db {
+            DB::LocationDb(db) => self.with_location(db),
+            DB::ProxyDb(db) => self.with_proxy(db),
+        }
+    }
+
+    pub fn location(&self) -> Option<&LocationDB> {
+        self.location.as_ref()
+    }
+
+    pub fn proxy(&self) -> Option<&ProxyDB> {
+        self.proxy.as_ref()
+    }
+
+    #[cfg(feature = "std")]
+    pub fn asn(&self) -> Option<&AsnDB> {
+        self.asn.as_ref()
+    }
+
+    /// Looks up the address in every database. A database not knowing the
+    /// address leaves its part of the record empty; other errors are
+    /// returned, and so is `Error::RecordNotFound` when no database knows
+    /// the address.
+    pub fn lookup(&self, ip: IpAddr) -> Result<EnrichedRecord<'_>, Error> {
+        let location = self
+            .location
+            .as_ref()
+            .map(|db| db.ip_lookup(ip))
+            .transpose()
+            .or_else(not_found)?;
+        let proxy = self
+            .proxy
+            .as_ref()
+            .map(|db| db.ip_lookup(ip))
+            .transpose()
+            .or_else(not_found)?;
+        #[cfg(feature = "std")]
+        let asn = self
+            .asn
+            .as_ref()
+            .map(|db| db.asn_lookup(ip))
+            .transpose()
+            .or_else(not_found)?;
+        #[cfg(feature = "std")]
+        let found = asn.is_some();
+        #[cfg(not(feature = "std"))]
+        let found = false;
+        if location.is_none() && proxy.is_none() && !found {
+            return Err(Error::RecordNotFound);
+        }
+        Ok(EnrichedRecord {
+            ip,
+            location,
+            proxy,
+            #[cfg(feature = "std")]
+            asn,
+        })
+    }
+}
+
+/// Treats a missing record as no record.
+fn not_found<T>(err: Error) -> Result<Option<T>, Error> {
+    match err {
+        Error::RecordNotFound => Ok(None),
+        err => Err(err),
+    }
+}
@@ -0,0 +1,58 @@
+#[cfg(feature = "metrics")]
+pub use self::lookup_metrics::*;
+
+#[cfg(feature = "metrics")]
+mod lookup_metrics {
+    use crate::{common::Target, error::Error};
+    use std::time::Duration;
+
+    pub const LOOKUPS_TOTAL: &str = "ip2location_lookups_total";
+    pub const LOOKUP_DURATION_SECONDS: &str = "ip2location_lookup_duration_seconds";
+
+    /// Registers the descriptions of the metrics emitted by lookups with the
+    /// installed `metrics` recorder.
+    ///
+    /// `ip2location_lookups_total` counts lookups and
+    /// `ip2location_lookup_duration_seconds` records their latency, both
+    /// labelled with `db` (location or proxy), `family` (ipv4 or ipv6, after
+    /// translation) and `outcome` (hit, miss or error).
+    pub fn describe_metrics() {
+        metrics::describe_counter!(LOOKUPS_TOTAL, "Number of IP2Location/IP2Proxy lookups");
+        metrics::describe_histogram!(
+            LOOKUP_DURATION_SECONDS,
+            metrics::Unit::Seconds,
+            "Latency of IP2Location/IP2Proxy lookups"
+        );
+    }
+
+    pub(crate) fn record_metrics<T>(
+        db: &'static str,
+        target: Target,
+        result: &Result<T, Error>,
+        elapsed: Duration,
+    ) {
+        let family = match target {
+            Target::V4(_) => "ipv4",
+            Target::V6(_) => "ipv6",
+        };
+        let outcome = match result {
+            Ok(_) => "hit",
+            Err(Error::RecordNotFound) => "miss",
+            Err(_) => "error",
+        };
+        let labels = [("db", db), ("family", family), ("outcome", outcome)];
+        metrics::counter!(LOOKUPS_TOTAL, &labels).increment(1);
+        metrics::histogram!(LOOKUP_DURATION_SECONDS, &labels).record(elapsed.as_secs_f64());
+    }
+}
+
+#[cfg(feature = "tracing")]
+pub(crate) fn trace_result<T>(result: &Result<(crate::range::IpRange, T), crate::error::Error>) {
+    use crate::error::Error;
+
+    match result {
+        Ok((range, _)) => tracing::trace!(start = %range.start, end = %range.end, "found"),
+        Err(Error::RecordNotFound) => tracing::trace!("not found"),
+        Err(e) => tracing::debug!(error = %e, "lookup failed"),
+    }
+}
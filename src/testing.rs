@@ -0,0 +1,228 @@
+//! Test doubles, available with the `testing` feature: `MockResolver` for
+//! code that looks up addresses through `IpResolver`, and `BinBuilder` for
+//! small BIN fixtures generated in memory.
+
+use crate::{error::Error, Country, IpRange, IpResolver, LocationRecord, Record};
+use alloc::{borrow::Cow, boxed::Box, string::String, vec::Vec};
+use core::net::IpAddr;
+
+/// What a `MockResolver` answers for an address without a mapping.
+enum Miss {
+    NotFound,
+    Record(Record<'static>),
+    Error(fn(IpAddr) -> Error),
+}
+
+/// An `IpResolver` answering from `ip -> record` mappings registered by the
+/// test, so business logic can be unit tested without shipping BIN files.
+///
+/// Addresses without a mapping fail with `Error::RecordNotFound` unless
+/// `or_record` or `or_error` says otherwise. The `ip` of the returned
+/// record is the address looked up, as with a real database.
+///
+/// ## Example usage
+///
+///```rust
+/// use ip2location::{error::Error, testing::MockResolver, IpResolver};
+///
+/// let resolver = MockResolver::new()
+///     .with_country("1.1.1.1".parse().unwrap(), "AU", "Australia")
+///     .or_error(|_| Error::IoError("offline".into()));
+/// assert!(resolver.resolve("1.1.1.1".parse().unwrap()).is_ok());
+/// assert!(matches!(
+///     resolver.resolve("8.8.8.8".parse().unwrap()),
+///     Err(Error::IoError(_))
+/// ));
+///```
+pub struct MockResolver {
+    /// Registered ranges, the latest registration first.
+    entries: Vec<(IpRange, Record<'static>)>,
+    miss: Miss,
+}
+
+/// `MockResolver` under the name used for the databases it stands in for.
+pub type MockDb = MockResolver;
+
+impl MockResolver {
+    /// A resolver without mappings, answering `Error::RecordNotFound`.
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            miss: Miss::NotFound,
+        }
+    }
+
+    /// Answers `record` for `ip`.
+    pub fn with_record(self, ip: IpAddr, record: Record<'static>) -> Self {
+        self.with_range(IpRange { start: ip, end: ip }, record)
+    }
+
+    /// Answers `record` for every address of `range`. Later registrations
+    /// take precedence over overlapping earlier ones.
+    pub fn with_range(mut self, range: IpRange, record: Record<'static>) -> Self {
+        self.insert(range, record);
+        self
+    }
+
+    /// Answers a `LocationRecord` with only the country set for `ip`.
+    pub fn with_country(self, ip: IpAddr, short_name: &str, long_name: &str) -> Self {
+        let record = LocationRecord {
+            country: Some(Country {
+                short_name: Cow::Owned(String::from(short_name)),
+                long_name: Cow::Owned(String::from(long_name)),
+            }),
+            ..LocationRecord::default()
+        };
+        self.with_record(ip, Record::LocationDb(Box::new(record)))
+    }
+
+    /// Answers `record` for addresses without a mapping.
+    pub fn or_record(mut self, record: Record<'static>) -> Self {
+        self.miss = Miss::Record(record);
+        self
+    }
+
+    /// Fails with the error built by `error` for addresses without a
+    /// mapping, e.g. to exercise the handling of I/O errors.
+    pub fn or_error(mut self, error: fn(IpAddr) -> Error) -> Self {
+        self.miss = Miss::Error(error);
+        self
+    }
+
+    /// Registers `record` for `range` on an existing resolver.
+    pub fn insert(&mut self, range: IpRange, record: Record<'static>) {
+        self.entries.insert(0, (range, record));
+    }
+
+    /// Number of registered mappings.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl Default for MockResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IpResolver for MockResolver {
+    fn resolve(&self, ip: IpAddr) -> Result<Record<'_>, Error> {
+        let mut record = match self.entries.iter().find(|(range, _)| range.contains(ip)) {
+            Some((_, record)) => record.clone(),
+            None => match &self.miss {
+                Miss::NotFound => return Err(Error::RecordNotFound),
+                Miss::Record(record) => record.clone(),
+                Miss::Error(error) => return Err(error(ip)),
+            },
+        };
+        match &mut record {
+            Record::LocationDb(rec) => rec.ip = ip,
+            Record::ProxyDb(rec) => rec.ip = ip,
+        }
+        Ok(record)
+    }
+}
+
+/// Generates small, valid BIN databases in memory for tests, on top of
+/// `writer::BinWriter`.
+///
+/// Addresses not covered by a range are answered with a record whose
+/// fields are all "-", as in the vendor databases. Besides the ranges,
+/// the layout can be stretched to exercise edge cases: an IPv6 table
+/// without IPv6 ranges, or strings at large offsets.
+///
+/// ## Example usage
+///
+///```rust
+/// use ip2location::{testing::BinBuilder, Record};
+///
+/// let db = BinBuilder::new(1)
+///     .country_range("1.0.0.0", "1.0.0.255", "AU", "Australia")
+///     .country_range("2001:db8::", "2001:db8::ffff", "FR", "France")
+///     .open();
+/// match db.ip_lookup("2001:db8::1".parse().unwrap()).unwrap() {
+///     Record::LocationDb(rec) => assert_eq!(rec.country.unwrap().short_name, "FR"),
+///     Record::ProxyDb(_) => unreachable!(),
+/// }
+///```
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct BinBuilder {
+    writer: crate::writer::BinWriter,
+}
+
+#[cfg(feature = "std")]
+impl BinBuilder {
+    /// Starts an empty database of the given type (1 to 26).
+    ///
+    /// Panics on other types, as befits a fixture.
+    pub fn new(db_type: u8) -> Self {
+        Self {
+            writer: crate::writer::BinWriter::new(db_type).unwrap(),
+        }
+    }
+
+    /// Sets the date of the header, as a two-digit year, month and day.
+    pub fn date(mut self, year: u8, month: u8, day: u8) -> Self {
+        self.writer = self.writer.date(year, month, day);
+        self
+    }
+
+    /// Adds the inclusive range `start..=end` with its record; only the
+    /// fields of the database type are kept. The last address of a table
+    /// (255.255.255.255 or ffff:..:ffff) is answered by the range ending
+    /// on it, so a range starting there is dropped.
+    ///
+    /// Panics on addresses that do not parse or on a reversed range.
+    pub fn range(mut self, start: &str, end: &str, record: LocationRecord<'_>) -> Self {
+        let range = IpRange {
+            start: start.parse().unwrap(),
+            end: end.parse().unwrap(),
+        };
+        self.writer.push(range, &record).unwrap();
+        self
+    }
+
+    /// Adds a range with only the country set.
+    pub fn country_range(self, start: &str, end: &str, short_name: &str, long_name: &str) -> Self {
+        let record = LocationRecord {
+            country: Some(Country {
+                short_name: Cow::Borrowed(short_name),
+                long_name: Cow::Borrowed(long_name),
+            }),
+            ..LocationRecord::default()
+        };
+        self.range(start, end, record)
+    }
+
+    /// Writes an IPv6 table and its index even when no IPv6 range was
+    /// added, so IPv6 lookups find the gap record.
+    pub fn ipv6_table(mut self) -> Self {
+        self.writer.ipv6_table = true;
+        self
+    }
+
+    /// Inserts `bytes` zero bytes before the strings, moving every string
+    /// offset that far into the file.
+    pub fn padding(mut self, bytes: usize) -> Self {
+        self.writer.padding = bytes;
+        self
+    }
+
+    /// The database file.
+    ///
+    /// Panics if ranges overlap.
+    pub fn build(&self) -> Vec<u8> {
+        self.writer.to_bytes().unwrap()
+    }
+
+    /// The database, opened with `DB::from_bytes`.
+    pub fn open(&self) -> crate::DB {
+        crate::DB::from_bytes(self.build()).unwrap()
+    }
+}
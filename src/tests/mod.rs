@@ -1,2 +1,47 @@
+#[cfg(feature = "actix")]
+mod tests_actix;
+#[cfg(feature = "arrow")]
+mod tests_arrow;
+#[cfg(feature = "axum")]
+mod tests_axum;
+#[cfg(feature = "cache")]
+mod tests_cache;
+mod tests_country_info;
+#[cfg(feature = "polars")]
+mod tests_dataframe;
+#[cfg(feature = "dns")]
+mod tests_dns;
 mod tests_error;
+#[cfg(feature = "ffi")]
+mod tests_ffi;
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+mod tests_io_uring;
 mod tests_lib;
+#[cfg(feature = "maxmind")]
+mod tests_maxmind;
+#[cfg(feature = "metrics")]
+mod tests_metrics;
+#[cfg(feature = "uniffi")]
+mod tests_mobile;
+#[cfg(feature = "msgpack")]
+mod tests_msgpack;
+#[cfg(feature = "proto")]
+mod tests_proto;
+#[cfg(feature = "pseudonymize")]
+mod tests_pseudonym;
+#[cfg(feature = "python")]
+mod tests_python;
+#[cfg(feature = "rand")]
+mod tests_sample;
+#[cfg(feature = "http-server")]
+mod tests_server;
+#[cfg(feature = "tower")]
+mod tests_service;
+mod tests_source;
+#[cfg(feature = "spatial")]
+mod tests_spatial;
+#[cfg(feature = "object-storage")]
+mod tests_storage;
+#[cfg(feature = "async")]
+mod tests_stream;
+mod tests_testing;
@@ -0,0 +1,63 @@
+use crate::{
+    actix::{GeoIpMiddleware, OnFailure},
+    Record, DB,
+};
+use actix_web::{http::StatusCode, rt::System, test, web, App};
+use std::sync::Arc;
+
+async fn country(record: Option<web::ReqData<Record<'static>>>) -> String {
+    match record.map(|r| r.into_inner()) {
+        Some(Record::LocationDb(rec)) => rec.country.unwrap().short_name.into_owned(),
+        _ => "-".to_string(),
+    }
+}
+
+#[test]
+fn test_geoip_middleware() {
+    System::new().block_on(async {
+        let db = Arc::new(DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap());
+        let app = test::init_service(
+            App::new()
+                .wrap(GeoIpMiddleware::new(db.clone()).trusted_proxies(1))
+                .route("/", web::get().to(country)),
+        )
+        .await;
+        let req = test::TestRequest::get()
+            .uri("/")
+            .insert_header(("x-forwarded-for", "43.224.159.155"))
+            .to_request();
+        assert_eq!(test::call_and_read_body(&app, req).await, "IN");
+
+        // Lookup failures are passed on without a record by default.
+        let req = test::TestRequest::get()
+            .uri("/")
+            .insert_header(("x-forwarded-for", "2a01:b600:8001::"))
+            .to_request();
+        assert_eq!(test::call_and_read_body(&app, req).await, "-");
+
+        let app = test::init_service(
+            App::new()
+                .wrap(
+                    GeoIpMiddleware::new(db)
+                        .trusted_proxies(1)
+                        .on_failure(OnFailure::Reject),
+                )
+                .route("/", web::get().to(country)),
+        )
+        .await;
+        let req = test::TestRequest::get()
+            .uri("/")
+            .insert_header(("x-forwarded-for", "2a01:b600:8001::"))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::NOT_FOUND
+        );
+
+        let req = test::TestRequest::get().uri("/").to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::BAD_REQUEST
+        );
+    });
+}
@@ -0,0 +1,63 @@
+use crate::{arrow_array::cast::AsArray, arrow_schema::DataType, records_to_arrow, DB};
+use crate::{arrow_array::types::Float32Type, error::Error, testing::BinBuilder, LocationRecord};
+
+const IPV4BIN: &str = "data/IP2LOCATION-LITE-DB1.BIN";
+const IP2PROXYBIN: &str = "data/IP2PROXY-IP-COUNTRY.BIN";
+
+#[test]
+fn test_records_to_arrow() -> Result<(), Error> {
+    let db = BinBuilder::new(5)
+        .range(
+            "1.0.0.0",
+            "1.0.0.255",
+            LocationRecord {
+                latitude: Some(48.5),
+                longitude: Some(2.25),
+                ..LocationRecord::default()
+            },
+        )
+        .country_range("1.0.1.0", "1.0.1.255", "FR", "France")
+        .open();
+    let records = ["1.0.0.1", "1.0.1.1"]
+        .iter()
+        .map(|ip| db.ip_lookup(ip.parse().unwrap()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let batch = records_to_arrow(records)?;
+
+    assert_eq!(batch.num_rows(), 2);
+    let names: Vec<_> = batch
+        .schema()
+        .fields()
+        .iter()
+        .map(|f| f.name().clone())
+        .collect();
+    assert_eq!(names, LocationRecord::COLUMNS);
+    let ips = batch.column_by_name("ip").unwrap().as_string::<i32>();
+    assert_eq!(ips.value(1), "1.0.1.1");
+    let latitude = batch.column_by_name("latitude").unwrap();
+    assert_eq!(latitude.data_type(), &DataType::Float32);
+    assert_eq!(latitude.as_primitive::<Float32Type>().value(0), 48.5);
+    let countries = batch
+        .column_by_name("country_long_name")
+        .unwrap()
+        .as_string::<i32>();
+    assert_eq!(countries.value(1), "France");
+    // DB5 has no ISP column.
+    assert_eq!(batch.column_by_name("isp").unwrap().null_count(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_records_to_arrow_kinds() -> Result<(), Error> {
+    let location = DB::from_file(IPV4BIN)?;
+    let proxy = DB::from_file(IP2PROXYBIN)?;
+    let ip = "43.224.159.155".parse().unwrap();
+
+    let batch = records_to_arrow([proxy.ip_lookup(ip)?])?;
+    assert!(batch.column_by_name("is_proxy").is_some());
+    assert!(records_to_arrow([location.ip_lookup(ip)?, proxy.ip_lookup(ip)?]).is_err());
+
+    let empty = records_to_arrow([])?;
+    assert_eq!((empty.num_rows(), empty.num_columns()), (0, 0));
+    Ok(())
+}
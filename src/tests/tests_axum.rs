@@ -0,0 +1,82 @@
+use crate::{
+    axum::{GeoIp, GeoIpConfig, GeoIpRejection},
+    DB,
+};
+use axum::{
+    body::Body,
+    extract::connect_info::MockConnectInfo,
+    http::{Request, StatusCode},
+    routing::get,
+    Extension, Router,
+};
+use std::{net::SocketAddr, sync::Arc};
+use tower::ServiceExt;
+
+async fn country(GeoIp(record): GeoIp) -> String {
+    format!("{} {}", record.ip, record.country.unwrap().short_name)
+}
+
+fn app(trusted_proxies: usize) -> Router {
+    let db = Arc::new(DB::from_file("data/IP2LOCATION-LITE-DB1.IPV6.BIN").unwrap());
+    let peer: SocketAddr = "43.224.159.155:4000".parse().unwrap();
+    Router::new()
+        .route("/", get(country))
+        .layer(Extension(
+            GeoIpConfig::new(db).trusted_proxies(trusted_proxies),
+        ))
+        .layer(MockConnectInfo(peer))
+}
+
+async fn get_body(app: Router, forwarded_for: Option<&str>) -> (StatusCode, String) {
+    let mut request = Request::builder().uri("/");
+    if let Some(value) = forwarded_for {
+        request = request.header("x-forwarded-for", value);
+    }
+    let response = app
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn test_geoip_extractor() {
+    // Without trusted proxies the header is ignored.
+    let (status, body) = get_body(app(0), Some("2a01:cb08:8d14::")).await;
+    assert_eq!(
+        (status, body.as_str()),
+        (StatusCode::OK, "43.224.159.155 IN")
+    );
+
+    let (status, body) = get_body(app(1), Some("10.0.0.1, 2a01:cb08:8d14::")).await;
+    assert_eq!(
+        (status, body.as_str()),
+        (StatusCode::OK, "2a01:cb08:8d14:: FR")
+    );
+
+    let (status, body) = get_body(app(2), Some("2a01:cb08:8d14::, 10.0.0.1")).await;
+    assert_eq!(
+        (status, body.as_str()),
+        (StatusCode::OK, "2a01:cb08:8d14:: FR")
+    );
+
+    // Ports written by some proxies are ignored.
+    let (_, body) = get_body(app(1), Some("[2a01:cb08:8d14::]:443")).await;
+    assert_eq!(body, "2a01:cb08:8d14:: FR");
+
+    // Falls back to the peer address when the header is missing.
+    let (_, body) = get_body(app(1), None).await;
+    assert_eq!(body, "43.224.159.155 IN");
+
+    let (status, _) = get_body(app(1), Some("not-an-ip")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let missing = Router::new().route("/", get(country));
+    let (status, body) = get_body(missing, None).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(body, GeoIpRejection::MissingConfig.to_string());
+}
@@ -0,0 +1,165 @@
+use crate::{cache::CachedDb, error, Record, DB};
+
+const IPV6BIN: &str = "data/IP2LOCATION-LITE-DB1.IPV6.BIN";
+
+#[test]
+fn test_cached_lookup() -> Result<(), error::Error> {
+    let db = DB::from_file(IPV6BIN)?;
+    let expected = db
+        .ip_lookup("43.224.159.155".parse().unwrap())?
+        .into_owned();
+    let cached = CachedDb::new(db, 2);
+
+    assert_eq!(
+        cached.ip_lookup("43.224.159.155".parse().unwrap())?,
+        expected
+    );
+    assert_eq!(
+        cached.ip_lookup("43.224.159.155".parse().unwrap())?,
+        expected
+    );
+    let stats = cached.stats();
+    assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+
+    // another address in the same range is a hit, with its own ip
+    let record = cached.ip_lookup("::ffff:43.224.159.156".parse().unwrap())?;
+    let record = match record {
+        Record::LocationDb(rec) => rec,
+        Record::ProxyDb(_) => unreachable!(),
+    };
+    assert_eq!(
+        record.ip,
+        "::ffff:43.224.159.156".parse::<std::net::IpAddr>().unwrap()
+    );
+    assert_eq!(record.country.unwrap().short_name, "IN");
+    assert_eq!(cached.stats().hits, 2);
+
+    // ipv6 ranges do not collide with ipv4 ranges
+    cached.ip_lookup("2a01:b600:8001::".parse().unwrap())?;
+    cached.ip_lookup("2a01:cb08:8d14::".parse().unwrap())?;
+    let stats = cached.stats();
+    assert_eq!((stats.misses, stats.entries, stats.capacity), (3, 2, 2));
+
+    cached.clear();
+    assert_eq!(cached.stats().entries, 0);
+    Ok(())
+}
+
+#[test]
+fn test_custom_lookup_cache() -> Result<(), error::Error> {
+    use crate::{cache::LookupCache, IpRange};
+    use std::{net::IpAddr, sync::Mutex};
+
+    #[derive(Default)]
+    struct VecCache(Mutex<Vec<(IpRange, Record<'static>)>>);
+
+    impl LookupCache for VecCache {
+        fn get(&self, ip: IpAddr) -> Option<Record<'static>> {
+            let entries = self.0.lock().unwrap();
+            entries
+                .iter()
+                .find(|(range, _)| range.contains(ip))
+                .map(|(_, record)| record.clone())
+        }
+
+        fn put(&self, range: IpRange, record: Record<'static>) {
+            self.0.lock().unwrap().push((range, record));
+        }
+
+        fn len(&self) -> usize {
+            self.0.lock().unwrap().len()
+        }
+
+        fn capacity(&self) -> usize {
+            usize::MAX
+        }
+
+        fn clear(&self) {
+            self.0.lock().unwrap().clear();
+        }
+    }
+
+    let cached = CachedDb::with_cache(DB::from_file(IPV6BIN)?, VecCache::default());
+    cached.ip_lookup("43.224.159.155".parse().unwrap())?;
+    cached.ip_lookup("2002:2be0:9f9b::".parse().unwrap())?;
+    let stats = cached.stats();
+    assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+    let entries = cached.cache().0.lock().unwrap();
+    assert!(entries[0].0.contains("43.224.159.155".parse().unwrap()));
+    Ok(())
+}
+
+#[test]
+fn test_negative_cache() -> Result<(), error::Error> {
+    use std::time::Duration;
+
+    let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN")?;
+    let ip = "2a01:b600:8001::".parse().unwrap();
+    let cached = CachedDb::new(db, 8).with_negative_ttl(Duration::from_secs(60));
+    assert_eq!(cached.ip_lookup(ip), Err(error::Error::RecordNotFound));
+    assert_eq!(cached.ip_lookup(ip), Err(error::Error::RecordNotFound));
+    // The database has no IPv6 table: the miss is cached for all of it.
+    let other = "2001:db8::1".parse().unwrap();
+    assert_eq!(cached.ip_lookup(other), Err(error::Error::RecordNotFound));
+    let stats = cached.stats();
+    assert_eq!(
+        (stats.misses, stats.negative_hits, stats.entries),
+        (1, 2, 1)
+    );
+    // Found ranges are cached next to it.
+    cached.ip_lookup("43.224.159.155".parse().unwrap())?;
+    assert!(cached.ip_lookup(other).is_err());
+    assert_eq!(cached.stats().negative_hits, 3);
+
+    let cached = CachedDb::new(cached.into_inner(), 8).with_negative_ttl(Duration::ZERO);
+    assert!(cached.ip_lookup(ip).is_err());
+    assert!(cached.ip_lookup(ip).is_err());
+    let stats = cached.stats();
+    assert_eq!((stats.misses, stats.negative_hits), (2, 0));
+    Ok(())
+}
+
+#[test]
+fn test_open_cached() -> Result<(), error::Error> {
+    let cached = DB::options().path(IPV6BIN).open_cached(8)?;
+    cached.ip_lookup("43.224.159.155".parse().unwrap())?;
+    cached.ip_lookup("43.224.159.155".parse().unwrap())?;
+    let stats = cached.stats();
+    assert_eq!((stats.hits, stats.misses, stats.capacity), (1, 1, 8));
+    Ok(())
+}
+
+#[test]
+fn test_interned_cache() -> Result<(), error::Error> {
+    use crate::Interner;
+    use std::{collections::HashSet, sync::Arc};
+
+    let db = DB::from_file(IPV6BIN)?;
+    let starts: Vec<_> = db
+        .ranges()
+        .take(200)
+        .map(|range| range.map(|(range, _)| range.start))
+        .collect::<Result<_, _>>()?;
+    let interner = Arc::new(Interner::new());
+    let cached = CachedDb::new(db, 1000).with_interner(interner.clone());
+
+    let mut names = HashSet::new();
+    for ip in starts {
+        let expected = cached.db().ip_lookup(ip)?.into_owned();
+        let record = cached.ip_lookup(ip)?;
+        assert_eq!(record, expected);
+        let Record::LocationDb(record) = record else {
+            unreachable!()
+        };
+        let country = record.country.unwrap();
+        // Equal names are the same interned string.
+        assert!(std::ptr::eq(
+            country.long_name.as_ref(),
+            interner.intern(&country.long_name)
+        ));
+        names.insert(country.short_name.into_owned());
+        names.insert(country.long_name.into_owned());
+    }
+    assert_eq!(interner.len(), names.len());
+    Ok(())
+}
@@ -0,0 +1,149 @@
+use crate::{country_info::CountryInfoDB, error, Record, DB};
+
+const IPV4BIN: &str = "data/IP2LOCATION-LITE-DB1.BIN";
+const COUNTRY_CSV: &str = r#""country_code","country_name","country_alpha3_code","country_numeric_code","capital","country_demonym","total_area","population","idd_code","currency_code","currency_name","currency_symbol","lang_code","lang_name","cctld"
+"FR","France","FRA","250","Paris","French","551500","67848156","33","EUR","Euro","€","FR","French",".fr"
+"IN","India","IND","356","New Delhi","Indian","3287263","1380004385","91","INR","Indian Rupee","₹","HI","Hindi",".in"
+"#;
+
+#[test]
+fn test_country_info_from_reader() -> Result<(), error::Error> {
+    let info = CountryInfoDB::from_reader(COUNTRY_CSV.as_bytes())?;
+    assert_eq!(info.len(), 2);
+    let france = info.get("fr").unwrap();
+    assert_eq!(france.alpha3_code.as_deref(), Some("FRA"));
+    assert_eq!(france.numeric_code, Some(250));
+    assert_eq!(france.capital.as_deref(), Some("Paris"));
+    assert_eq!(france.currency_code.as_deref(), Some("EUR"));
+    assert_eq!(france.population, Some(67848156));
+    assert_eq!(france.tld.as_deref(), Some(".fr"));
+    // The CSV has no continent column: it comes from the built-in table.
+    assert_eq!(france.continent.as_deref(), Some("Europe"));
+    Ok(())
+}
+
+#[test]
+fn test_country_info_for_record() -> Result<(), error::Error> {
+    let info = CountryInfoDB::from_reader(COUNTRY_CSV.as_bytes())?;
+    let db = DB::from_file(IPV4BIN)?;
+    let record = db.ip_lookup("43.224.159.155".parse().unwrap())?;
+    let country = match record {
+        Record::LocationDb(rec) => rec.country.unwrap(),
+        Record::ProxyDb(_) => unreachable!(),
+    };
+    let india = country.info(&info).unwrap();
+    assert_eq!(india.capital.as_deref(), Some("New Delhi"));
+    assert_eq!(india.currency_code.as_deref(), Some("INR"));
+    Ok(())
+}
+
+#[test]
+fn test_country_only_continent() -> Result<(), error::Error> {
+    let csv = "country_code,country_name,continent\nIN,India,Asia\n";
+    let info = CountryInfoDB::from_reader(csv.as_bytes())?;
+    let db = DB::from_file(IPV4BIN)?;
+    let record = db.country_lookup("43.224.159.155".parse().unwrap())?;
+    assert_eq!(record.continent, None);
+    let record = record.with_continent(&info);
+    assert_eq!(record.continent.as_deref(), Some("Asia"));
+    let record = db.country_lookup("1.1.1.1".parse().unwrap())?;
+    assert_eq!(record.with_continent(&info).continent, None);
+    Ok(())
+}
+
+#[test]
+fn test_region_code() -> Result<(), error::Error> {
+    use crate::{country_info::RegionInfoDB, Country, LocationRecord};
+
+    let regions = RegionInfoDB::from_reader(
+        r#""country_code","subdivision_name","code"
+"FR","Ile-de-France","FR-IDF"
+"US","California","US-CA"
+"#
+        .as_bytes(),
+    )?;
+    assert_eq!(regions.len(), 2);
+    assert_eq!(regions.get("us", "CALIFORNIA").unwrap().code, "US-CA");
+
+    let record = LocationRecord {
+        country: Some(Country {
+            short_name: "FR".into(),
+            long_name: "France".into(),
+        }),
+        region: Some("Ile-de-France".into()),
+        ..Default::default()
+    };
+    assert_eq!(record.region_code(&regions), Some("FR-IDF"));
+    assert_eq!(LocationRecord::default().region_code(&regions), None);
+    Ok(())
+}
+
+#[test]
+fn test_eu_membership() {
+    use crate::{country_info, Country};
+
+    let country = |code: &'static str| Country {
+        short_name: code.into(),
+        long_name: "".into(),
+    };
+    assert!(country("FR").is_eu());
+    assert!(country("FR").is_eea());
+    assert!(!country("NO").is_eu());
+    assert!(country("NO").is_eea());
+    assert!(!country("GB").is_eu());
+    assert!(!country("CH").is_eea());
+    assert!(!country("-").is_eea());
+    assert!(country_info::is_eu("de"));
+}
+
+#[test]
+fn test_iso_codes() -> Result<(), error::Error> {
+    use crate::country_info;
+
+    let db = DB::from_file(IPV4BIN)?;
+    let record = db.ip_lookup("43.224.159.155".parse().unwrap())?;
+    let country = match record {
+        Record::LocationDb(rec) => rec.country.unwrap(),
+        Record::ProxyDb(_) => unreachable!(),
+    };
+    assert_eq!(country.alpha3(), Some("IND"));
+    assert_eq!(country.numeric(), Some(356));
+
+    assert_eq!(country_info::to_alpha3("fr"), Some("FRA"));
+    assert_eq!(country_info::to_numeric("FR"), Some(250));
+    assert_eq!(country_info::from_alpha3("fra"), Some("FR"));
+    assert_eq!(country_info::from_numeric(840), Some("US"));
+    assert_eq!(country_info::continent("DE"), Some("Europe"));
+    assert_eq!(country_info::continent("br"), Some("South America"));
+    assert_eq!(country_info::continent("-"), None);
+    assert_eq!(country_info::to_alpha3("-"), None);
+    assert_eq!(country_info::from_numeric(0), None);
+    Ok(())
+}
+
+#[test]
+fn test_localized_names() -> Result<(), error::Error> {
+    use crate::country_info::CountryNames;
+
+    let mut names = CountryNames::from_reader(
+        r#""LANG","LANG_NAME","CNTRY_ALPHA2_CODE","CNTRY_ALPHA3_CODE","CNTRY_NUMERIC_CODE","CNTRY_NAME"
+"DE","GERMAN","FR","FRA","250","Frankreich"
+"FR","FRENCH","FR","FRA","250","France"
+"#
+        .as_bytes(),
+    )?;
+    names.insert("es", "FR", "Francia");
+    assert_eq!(names.len(), 3);
+
+    let db = DB::from_file("data/IP2LOCATION-LITE-DB1.IPV6.BIN")?;
+    let record = db.ip_lookup("2a01:cb08:8d14::".parse().unwrap())?;
+    let country = match record {
+        Record::LocationDb(rec) => rec.country.unwrap(),
+        Record::ProxyDb(_) => unreachable!(),
+    };
+    assert_eq!(country.name_in(&names, "de"), Some("Frankreich"));
+    assert_eq!(country.name_in(&names, "de-AT"), Some("Frankreich"));
+    assert_eq!(country.name_in(&names, "es"), Some("Francia"));
+    assert_eq!(country.name_in(&names, "ja"), None);
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use crate::{error::Error, lookups_to_dataframe, testing::BinBuilder, LocationRecord, DB};
+use std::net::IpAddr;
+
+const IP2PROXYBIN: &str = "data/IP2PROXY-IP-COUNTRY.BIN";
+
+#[test]
+fn test_lookups_to_dataframe() -> Result<(), Error> {
+    let db = BinBuilder::new(5)
+        .range(
+            "1.0.0.0",
+            "1.0.0.255",
+            LocationRecord {
+                latitude: Some(48.5),
+                longitude: Some(2.25),
+                ..LocationRecord::default()
+            },
+        )
+        .country_range("1.0.1.0", "1.0.1.255", "FR", "France")
+        .open();
+    let ips: Vec<IpAddr> = ["1.0.1.1", "1.0.0.1", "1.0.2.1"]
+        .iter()
+        .map(|ip| ip.parse().unwrap())
+        .collect();
+    let df = lookups_to_dataframe(ips, &db).unwrap();
+
+    assert_eq!(df.height(), 3);
+    let populated: Vec<_> = LocationRecord::schema(5)
+        .into_iter()
+        .filter(|column| column.populated)
+        .map(|column| column.name)
+        .collect();
+    assert_eq!(df.get_column_names(), populated);
+    let ip = df.column("ip").unwrap().str().unwrap();
+    assert_eq!(ip.get(0), Some("1.0.1.1"));
+    assert_eq!(ip.get(2), Some("1.0.2.1"));
+    let latitude = df.column("latitude").unwrap().f32().unwrap();
+    assert_eq!(latitude.get(1), Some(48.5));
+    let country = df.column("country_long_name").unwrap().str().unwrap();
+    assert_eq!(country.get(0), Some("France"));
+    assert_eq!(country.get(2), Some("-"));
+    Ok(())
+}
+
+#[test]
+fn test_lookups_to_dataframe_proxy() -> Result<(), Error> {
+    let db = DB::from_file(IP2PROXYBIN)?;
+    let df = lookups_to_dataframe(["1.1.1.1".parse().unwrap()], &db).unwrap();
+    assert_eq!(df.height(), 1);
+    assert!(df.column("is_proxy").is_ok());
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use crate::{dns::resolve_host, error::Error, Record, DB};
+use std::net::IpAddr;
+
+const IPV6BIN: &str = "data/IP2LOCATION-LITE-DB1.IPV6.BIN";
+
+#[test]
+fn test_host_lookup() -> Result<(), Error> {
+    let db = DB::from_file(IPV6BIN)?;
+    let loopback: IpAddr = "127.0.0.1".parse().unwrap();
+    let lookups = db.host_lookup("localhost")?;
+    let (_, record) = lookups
+        .iter()
+        .find(|(ip, _)| *ip == loopback)
+        .expect("localhost resolves to 127.0.0.1");
+    match record {
+        Ok(Record::LocationDb(rec)) => {
+            assert_eq!(rec.country.as_ref().unwrap().short_name, "-")
+        }
+        other => panic!("unexpected lookup {:?}", other),
+    }
+
+    // Addresses are not resolved.
+    assert_eq!(
+        resolve_host("43.224.159.155")?,
+        ["43.224.159.155".parse::<IpAddr>().unwrap()]
+    );
+    assert!(matches!(
+        db.host_lookup("host.invalid"),
+        Err(Error::IoError(_))
+    ));
+    Ok(())
+}
+
+#[cfg(feature = "dns-async")]
+#[tokio::test]
+async fn test_host_lookup_async() -> Result<(), Error> {
+    let db = DB::from_file(IPV6BIN)?;
+    let lookups = db.host_lookup_async("43.224.159.155").await?;
+    assert_eq!(lookups.len(), 1);
+    match &lookups[0].1 {
+        Ok(Record::LocationDb(rec)) => {
+            assert_eq!(rec.country.as_ref().unwrap().short_name, "IN")
+        }
+        other => panic!("unexpected lookup {:?}", other),
+    }
+    assert!(db.host_lookup_async("host.invalid").await.is_err());
+    Ok(())
+}
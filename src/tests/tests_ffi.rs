@@ -0,0 +1,58 @@
+use crate::ffi::*;
+use std::{
+    ffi::{CStr, CString},
+    ptr,
+};
+
+fn last_error() -> Option<String> {
+    let error = ip2location_last_error();
+    (!error.is_null()).then(|| {
+        unsafe { CStr::from_ptr(error) }
+            .to_str()
+            .unwrap()
+            .to_string()
+    })
+}
+
+#[test]
+fn test_ffi_lookup() {
+    let path = CString::new("data/IP2LOCATION-LITE-DB1.IPV6.BIN").unwrap();
+    unsafe {
+        let db = ip2location_open(path.as_ptr());
+        assert!(!db.is_null());
+        assert_eq!(last_error(), None);
+
+        let ip = CString::new("43.224.159.155").unwrap();
+        let json = ip2location_lookup(db, ip.as_ptr());
+        assert!(!json.is_null());
+        let value: serde_json::Value =
+            serde_json::from_str(CStr::from_ptr(json).to_str().unwrap()).unwrap();
+        assert_eq!(value["country"]["short_name"], "IN");
+        ip2location_free_string(json);
+
+        let ip = CString::new("not an ip").unwrap();
+        assert!(ip2location_lookup(db, ip.as_ptr()).is_null());
+        assert_eq!(
+            last_error().as_deref(),
+            Some("invalid IP address: not an ip")
+        );
+        assert!(ip2location_lookup(db, ptr::null()).is_null());
+        assert_eq!(last_error().as_deref(), Some("address is NULL"));
+
+        ip2location_close(db);
+        ip2location_close(ptr::null_mut());
+        ip2location_free_string(ptr::null_mut());
+    }
+}
+
+#[test]
+fn test_ffi_open_errors() {
+    let path = CString::new("data/missing.BIN").unwrap();
+    unsafe {
+        assert!(ip2location_open(path.as_ptr()).is_null());
+        assert!(last_error().unwrap().contains("No such file"));
+        assert!(ip2location_open(ptr::null()).is_null());
+        assert_eq!(last_error().as_deref(), Some("path is NULL"));
+        assert!(ip2location_lookup(ptr::null(), path.as_ptr()).is_null());
+    }
+}
@@ -0,0 +1,109 @@
+use crate::{
+    error, file::FileSource, testing::BinBuilder, AccessPattern, Backend, LocationRecord, Record,
+    DB,
+};
+use io_uring::IoUring;
+use std::borrow::Cow;
+
+const DATABASES: [&str; 3] = [
+    "data/IP2LOCATION-LITE-DB1.BIN",
+    "data/IP2LOCATION-LITE-DB1.IPV6.BIN",
+    "data/IP2PROXY-IP-COUNTRY.BIN",
+];
+
+/// Looks up the start and the end of every 97th range in both databases.
+fn assert_same_lookups(mapped: &DB, read: &DB) -> Result<(), error::Error> {
+    for (n, range) in mapped.ranges().enumerate() {
+        if n % 97 != 0 {
+            continue;
+        }
+        let (range, _) = range?;
+        for ip in [range.start, range.end] {
+            assert_eq!(mapped.ip_lookup(ip), read.ip_lookup(ip), "{}", ip);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_io_uring_backend() -> Result<(), error::Error> {
+    for path in DATABASES {
+        let mapped = DB::from_file(path)?;
+        let read = DB::options().path(path).backend(Backend::IoUring).open()?;
+        assert_eq!(mapped.info().ipv4_count, read.info().ipv4_count);
+        assert_same_lookups(&mapped, &read)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_io_uring_every_field() -> Result<(), error::Error> {
+    // DB26 has every column, floats included, and strings up to 255 bytes.
+    let record = LocationRecord {
+        country: Some(crate::Country {
+            short_name: Cow::Borrowed("FR"),
+            long_name: Cow::Borrowed("France"),
+        }),
+        region: Some(Cow::Borrowed("Ile-de-France")),
+        city: Some(Cow::Owned("P".repeat(255))),
+        latitude: Some(48.85),
+        longitude: Some(2.35),
+        isp: Some(Cow::Borrowed("Example ISP")),
+        time_zone: Some(Cow::Borrowed("+01:00")),
+        ..LocationRecord::default()
+    };
+    let bytes = BinBuilder::new(26)
+        .range("1.0.0.0", "1.0.0.255", record.clone())
+        .range("2001:db8::", "2001:db8::ff", record)
+        .build();
+    let path = std::env::temp_dir().join(format!("ip2location-uring-{}.BIN", std::process::id()));
+    std::fs::write(&path, &bytes)?;
+    let read = DB::options().path(&path).backend(Backend::IoUring).open();
+    std::fs::remove_file(&path)?;
+    let read = read?;
+    let mapped = DB::from_bytes(bytes)?;
+    for ip in ["1.0.0.1", "2001:db8::1", "8.8.8.8"] {
+        let ip = ip.parse().unwrap();
+        assert_eq!(mapped.ip_lookup(ip), read.ip_lookup(ip));
+    }
+    match read.ip_lookup("1.0.0.1".parse().unwrap())? {
+        Record::LocationDb(rec) => {
+            assert_eq!(rec.city.unwrap().len(), 255);
+            assert_eq!(rec.latitude, Some(48.85));
+        }
+        Record::ProxyDb(_) => unreachable!(),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_io_uring_failed_reads() -> Result<(), error::Error> {
+    // Reads of a directory fail with EISDIR once submitted.
+    let dir = std::env::temp_dir().join(format!("ip2location-uring-dir-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    let failing = FileSource::open(&dir, AccessPattern::Random);
+    std::fs::remove_dir(&dir)?;
+    let failing = failing?;
+    let file = FileSource::open(DATABASES[0].as_ref(), AccessPattern::Random)?;
+    let mut ring = IoUring::new(64)?;
+    let mut expected = [0_u8; 4];
+    file.read_exact_at(0, &mut expected)?;
+
+    // More reads than the ring holds, so failures span several rounds.
+    for count in [100, 1, 65] {
+        let mut bufs = vec![[0_u8; 4]; count];
+        let mut reads: Vec<(u64, &mut [u8])> =
+            bufs.iter_mut().map(|buf| (0, &mut buf[..])).collect();
+        let (read, broken) = failing.read_io_uring(&mut ring, &mut reads);
+        assert!(failing.len() == 0 || read.is_err(), "{:?}", read);
+        assert!(!broken);
+
+        // Every read of the failed batch completed, none is left for the
+        // next batch on the ring.
+        let mut buf = [0_u8; 4];
+        let (read, _) = file.read_io_uring(&mut ring, &mut [(0, &mut buf[..])]);
+        assert_eq!(read?, vec![4]);
+        assert_eq!(buf, expected);
+    }
+    Ok(())
+}
@@ -80,3 +80,1904 @@ fn test_ip_lookup_in_proxy_bin() -> Result<(), error::Error> {
     assert!(record.country.is_some());
     Ok(())
 }
+
+#[test]
+fn test_ipv6_lookup_in_proxy_bin() -> Result<(), error::Error> {
+    let db = DB::from_file(IP2PROXYBIN)?;
+    // A range of a single address, found at its first address.
+    for (ip, short_name, long_name) in [
+        ("2001:ac8:21:12:c074:851a:99c2:f14", "DE", "Germany"),
+        (
+            "2406:da00:ff00::3456:c9a0",
+            "US",
+            "United States of America",
+        ),
+    ] {
+        let record = match db.ip_lookup(ip.parse().unwrap())? {
+            Record::ProxyDb(rec) => rec,
+            Record::LocationDb(_) => panic!("not a proxy record"),
+        };
+        let country = record.country.unwrap();
+        assert_eq!(country.short_name, short_name);
+        assert_eq!(country.long_name, long_name);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_preload_index() -> Result<(), error::Error> {
+    let options = crate::DbOptions::new().preload_index(true);
+    for path in [IPV4BIN, IPV6BIN, IP2PROXYBIN] {
+        let db = DB::from_file(path)?;
+        let preloaded = DB::from_file_with_options(path, &options)?;
+        for ip in [
+            "43.224.159.155",
+            "1.1.1.1",
+            "2a01:b600:8001::",
+            "2001:4860:4860::8888",
+        ] {
+            let ip = ip.parse().unwrap();
+            assert_eq!(db.ip_lookup(ip), preloaded.ip_lookup(ip));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_mmap_advice() -> Result<(), error::Error> {
+    use crate::{DbOptions, MmapAdvice};
+
+    let options = DbOptions::new()
+        .advise(MmapAdvice::Random)
+        .advise(MmapAdvice::WillNeed);
+    let db = DB::from_file_with_options(IPV4BIN, &options)?;
+    let record = db.ip_lookup("43.224.159.155".parse().unwrap())?;
+    assert_eq!(
+        record,
+        DB::from_file(IPV4BIN)?.ip_lookup("43.224.159.155".parse().unwrap())?
+    );
+    Ok(())
+}
+
+#[test]
+fn test_access_pattern() -> Result<(), error::Error> {
+    use crate::{AccessPattern, Backend, DbOptions};
+
+    let expected = DB::from_file(IPV6BIN)?;
+    for backend in [Backend::Mmap, Backend::InMemory, Backend::Pread] {
+        for access in [AccessPattern::Random, AccessPattern::Sequential] {
+            let options = DbOptions::new().backend(backend).access_pattern(access);
+            let db = DB::from_file_with_options(IPV6BIN, &options)?;
+            for ip in ["43.224.159.155", "2a01:b600:8001::"] {
+                let ip = ip.parse().unwrap();
+                assert_eq!(db.ip_lookup(ip), expected.ip_lookup(ip), "{:?}", options);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_populate() -> Result<(), error::Error> {
+    use crate::DbOptions;
+
+    let db = DB::from_file_with_options(IPV6BIN, &DbOptions::new().populate(true))?;
+    let ip = "2a01:b600:8001::".parse().unwrap();
+    assert_eq!(db.ip_lookup(ip), DB::from_file(IPV6BIN)?.ip_lookup(ip));
+    #[cfg(target_os = "linux")]
+    {
+        let stats = db.memory_stats();
+        assert_eq!(stats.resident_bytes, Some(stats.mapped_bytes));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_warm() -> Result<(), error::Error> {
+    let db = DB::from_file(IPV6BIN)?;
+    db.warm();
+    // Every page of the mapping is faulted in.
+    #[cfg(target_os = "linux")]
+    {
+        let stats = db.memory_stats();
+        assert_eq!(stats.resident_bytes, Some(stats.mapped_bytes));
+    }
+    let ip = "2a01:b600:8001::".parse().unwrap();
+    assert_eq!(db.ip_lookup(ip), DB::from_file(IPV6BIN)?.ip_lookup(ip));
+    Ok(())
+}
+
+#[test]
+fn test_memory_stats() -> Result<(), error::Error> {
+    let db = DB::from_file(IPV4BIN)?;
+    let stats = db.memory_stats();
+    assert_eq!(stats.mapped_bytes, 2037500);
+    assert_eq!(stats.index_bytes, 0);
+    if cfg!(target_os = "linux") {
+        db.warm();
+        assert!(db.memory_stats().resident_bytes.unwrap() > 0);
+    }
+
+    let options = crate::DbOptions::new().preload_index(true);
+    let db = DB::from_file_with_options(IPV4BIN, &options)?;
+    assert_eq!(db.memory_stats().index_bytes, 65536 * 8);
+    Ok(())
+}
+
+#[test]
+fn test_bench() -> Result<(), error::Error> {
+    let db = DB::from_file(IPV4BIN)?;
+    let ips = ["43.224.159.155", "1.1.1.1", "2a01:b600:8001::"]
+        .iter()
+        .map(|ip| ip.parse().unwrap());
+    let report = db.bench(ips);
+    assert_eq!(report.lookups, 3);
+    assert_eq!(report.errors, 1);
+    assert!(report.min <= report.p50 && report.p50 <= report.p99 && report.p99 <= report.max);
+
+    let empty = db.bench(std::iter::empty());
+    assert_eq!(empty.lookups, 0);
+
+    let ips: Vec<_> = ["43.224.159.155", "1.1.1.1", "2a01:b600:8001::"]
+        .iter()
+        .cycle()
+        .take(100)
+        .map(|ip| ip.parse().unwrap())
+        .collect();
+    let report = db.bench_threads(&ips, 3);
+    assert_eq!(report.lookups, 100);
+    assert_eq!(report.errors, 33);
+    assert_eq!(db.bench_threads(&[], 3).lookups, 0);
+    Ok(())
+}
+
+#[test]
+fn test_lookup_stats() -> Result<(), error::Error> {
+    assert!(DB::from_file(IPV4BIN)?.stats().is_none());
+
+    let options = crate::DbOptions::new().collect_stats(true);
+    let db = DB::from_file_with_options(IPV4BIN, &options)?;
+    for ip in ["43.224.159.155", "::ffff:1.1.1.1", "2a01:b600:8001::"] {
+        let _ = db.ip_lookup(ip.parse().unwrap());
+    }
+    let stats = db.stats().unwrap();
+    assert_eq!(
+        (stats.lookups, stats.hits, stats.misses, stats.errors),
+        (3, 2, 1, 0)
+    );
+    assert_eq!((stats.ipv4_lookups, stats.ipv6_lookups), (2, 1));
+
+    db.reset_stats();
+    assert_eq!(db.stats().unwrap(), crate::LookupStats::default());
+    Ok(())
+}
+
+#[test]
+fn test_options_open() -> Result<(), error::Error> {
+    use crate::Backend;
+
+    let ip = "43.224.159.155".parse().unwrap();
+    for backend in [Backend::Mmap, Backend::InMemory] {
+        let db = DB::options().path(IPV4BIN).backend(backend).open()?;
+        assert!(db.ip_lookup(ip).is_ok());
+    }
+    let db = DB::options()
+        .bytes(std::fs::read(IP2PROXYBIN)?)
+        .collect_stats(true)
+        .open()?;
+    assert!(matches!(db, DB::ProxyDb(_)));
+    assert!(db.stats().is_some());
+
+    assert!(DB::options().open().is_err());
+    assert!(DB::options().path("data/missing.BIN").open().is_err());
+    Ok(())
+}
+
+#[test]
+fn test_pread_backend() -> Result<(), error::Error> {
+    use crate::Backend;
+
+    for path in [IPV4BIN, IPV6BIN, IP2PROXYBIN] {
+        let mapped = DB::from_file(path)?;
+        let read = DB::options().path(path).backend(Backend::Pread).open()?;
+        assert_eq!(read.info().ipv4_count, mapped.info().ipv4_count);
+        for (n, range) in mapped.ranges().enumerate() {
+            if n % 97 != 0 {
+                continue;
+            }
+            let (range, _) = range?;
+            for ip in [range.start, range.end] {
+                assert_eq!(read.ip_lookup(ip), mapped.ip_lookup(ip), "{}", ip);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_compact_backend() -> Result<(), error::Error> {
+    use crate::{testing::BinBuilder, Backend, LocationRecord};
+
+    for path in [IPV4BIN, IPV6BIN, IP2PROXYBIN] {
+        let mapped = DB::from_file(path)?;
+        let compact = DB::options().path(path).backend(Backend::Compact).open()?;
+        assert!(compact.memory_stats().mapped_bytes <= mapped.memory_stats().mapped_bytes);
+        assert_eq!(compact.verify().issues, vec![]);
+        for (n, range) in mapped.ranges().enumerate() {
+            if n % 97 != 0 {
+                continue;
+            }
+            let (range, _) = range?;
+            for ip in [range.start, range.end] {
+                assert_eq!(compact.ip_lookup(ip), mapped.ip_lookup(ip), "{}", ip);
+            }
+        }
+    }
+
+    // Bytes after the tables that no row points to are dropped.
+    let record = LocationRecord {
+        city: Some("Paris".into()),
+        region: Some("Paris".into()),
+        ..LocationRecord::default()
+    };
+    let bytes = BinBuilder::new(3)
+        .range("1.0.0.0", "1.0.0.255", record)
+        .padding(4096)
+        .build();
+    let db = DB::options()
+        .bytes(bytes.clone())
+        .backend(Backend::Compact)
+        .open()?;
+    assert!(db.memory_stats().mapped_bytes + 4096 <= bytes.len());
+    let Record::LocationDb(found) = db.ip_lookup("1.0.0.1".parse().unwrap())? else {
+        panic!("not a location record");
+    };
+    assert_eq!(found.city.as_deref(), Some("Paris"));
+    assert_eq!(found.region.as_deref(), Some("Paris"));
+    Ok(())
+}
+
+#[test]
+fn test_shared_record() -> Result<(), error::Error> {
+    use crate::SharedRecord;
+    use std::sync::Arc;
+
+    for (path, ip) in [(IPV4BIN, "43.224.159.155"), (IP2PROXYBIN, "1.0.0.8")] {
+        let db = DB::from_file(path)?;
+        let record = db.ip_lookup(ip.parse().unwrap())?;
+        let shared = record.clone().into_shared();
+        assert_eq!(shared.as_record(), record);
+        assert_eq!(
+            serde_json::to_string(&shared).unwrap(),
+            serde_json::to_string(&record).unwrap()
+        );
+
+        let clone = shared.clone();
+        let country = |record: &SharedRecord| match record {
+            SharedRecord::LocationDb(rec) => rec.country.clone().unwrap(),
+            SharedRecord::ProxyDb(rec) => rec.country.clone().unwrap(),
+        };
+        let clone = std::thread::spawn(move || clone).join().unwrap();
+        assert!(Arc::ptr_eq(
+            &country(&shared).long_name,
+            &country(&clone).long_name
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_ip_lookup_owned() -> Result<(), error::Error> {
+    use crate::{LocationDB, OwnedLocationRecord, OwnedRecord};
+
+    let ip = "43.224.159.155".parse().unwrap();
+    let lookup =
+        |path| -> Result<OwnedRecord, error::Error> { DB::from_file(path)?.ip_lookup_owned(ip) };
+    let expected = DB::from_file(IPV4BIN)?.ip_lookup(ip)?.into_owned();
+    assert_eq!(lookup(IPV4BIN)?, expected);
+
+    let record: OwnedLocationRecord = LocationDB::from_file(IPV4BIN)?.ip_lookup_owned(ip)?;
+    assert_eq!(Record::LocationDb(Box::new(record)), expected);
+    Ok(())
+}
+
+#[test]
+fn test_get_raw_row() -> Result<(), error::Error> {
+    use crate::IpFamily;
+
+    for path in [IPV6BIN, IP2PROXYBIN] {
+        let db = DB::from_file(path)?;
+        for (n, range) in db.ranges().enumerate().step_by(1009) {
+            let (range, _) = range?;
+            let (family, index) = match range.is_ipv6() {
+                false => (IpFamily::V4, n as u32),
+                true => (IpFamily::V6, (n - db.range_count(false) as usize) as u32),
+            };
+            let row = db.get_raw_row(index, family)?;
+            assert_eq!(row.ip_from(), range.start);
+            assert_eq!(row.bytes.len(), row.column_offset(2).unwrap() + 4);
+            assert_eq!(row.column_offset(3), None);
+        }
+        let last = db.get_raw_row(db.range_count(false), IpFamily::V4)?;
+        assert_eq!(
+            last.ip_from(),
+            "255.255.255.255".parse::<std::net::IpAddr>().unwrap()
+        );
+        assert!(matches!(
+            db.get_raw_row(db.range_count(false) + 1, IpFamily::V4),
+            Err(error::Error::RecordNotFound)
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_record_counts() -> Result<(), error::Error> {
+    for path in [IPV4BIN, IPV6BIN] {
+        let db = DB::from_file(path)?;
+        let ranges = db.ranges().collect::<Result<Vec<_>, _>>()?;
+        let ipv6 = ranges.iter().filter(|(range, _)| range.is_ipv6()).count() as u32;
+        assert_eq!(db.ipv4_record_count(), ranges.len() as u32 - ipv6);
+        assert_eq!(db.ipv6_record_count(), ipv6);
+        assert_eq!(db.info().ipv4_count, db.ipv4_record_count() + 1);
+        assert_eq!(db.file_size(), std::fs::metadata(path)?.len());
+    }
+    assert_eq!(DB::from_file(IPV4BIN)?.ipv6_record_count(), 0);
+    Ok(())
+}
+
+#[test]
+fn test_statistics() -> Result<(), error::Error> {
+    use crate::{testing::BinBuilder, LocationRecord};
+
+    let paris = LocationRecord {
+        city: Some("Paris".into()),
+        ..LocationRecord::default()
+    };
+    let db = BinBuilder::new(3)
+        .range("1.0.0.0", "1.0.0.255", paris)
+        .country_range("2.0.0.0", "2.0.1.255", "DE", "Germany")
+        .open();
+    let stats = db.statistics()?;
+    // Gaps between the ranges are records without a country.
+    assert_eq!((stats.ipv4_records, stats.ipv6_records), (5, 0));
+    assert_eq!(stats.countries["DE"], 1);
+    assert_eq!(stats.countries["-"], 4);
+    assert_eq!(stats.fill_rates["city"], 20.0);
+    assert_eq!(stats.fill_rates["country"], 20.0);
+    assert_eq!(stats.fill_rates["region"], 0.0);
+    assert!(!stats.fill_rates.contains_key("isp"));
+    assert_eq!(stats.ipv4_coverage, 512.0 * 100.0 / 2f64.powi(32));
+
+    let stats = DB::from_file(IP2PROXYBIN)?.statistics()?;
+    assert!(stats.ipv6_records > 0);
+    assert!(stats.countries.len() > 100);
+    Ok(())
+}
+
+#[test]
+fn test_export_firewall() -> Result<(), error::Error> {
+    use crate::{testing::BinBuilder, FirewallFormat};
+
+    let db = BinBuilder::new(1)
+        .country_range("1.0.0.0", "1.0.0.255", "FR", "France")
+        .country_range("2.0.0.0", "2.0.0.255", "DE", "Germany")
+        .country_range("2.0.1.0", "2.0.2.255", "DE", "Germany")
+        .country_range("2001:db8::", "2001:db8::ffff", "DE", "Germany")
+        .open();
+    let export = |format| -> Result<String, error::Error> {
+        let mut out = Vec::new();
+        db.export_firewall(&["de"], format, "geo", &mut out)?;
+        Ok(String::from_utf8(out).unwrap())
+    };
+    assert_eq!(
+        export(FirewallFormat::Plain)?,
+        "2.0.0.0/23\n2.0.2.0/24\n2001:db8::/112\n"
+    );
+    assert_eq!(
+        export(FirewallFormat::Nftables)?,
+        "add set inet filter geo-v4 { type ipv4_addr; flags interval; }\n\
+         flush set inet filter geo-v4\n\
+         add element inet filter geo-v4 { 2.0.0.0/23, 2.0.2.0/24 }\n\
+         add set inet filter geo-v6 { type ipv6_addr; flags interval; }\n\
+         flush set inet filter geo-v6\n\
+         add element inet filter geo-v6 { 2001:db8::/112 }\n"
+    );
+    assert_eq!(
+        export(FirewallFormat::Ipset)?,
+        "create geo-v4 hash:net family inet maxelem 65536 -exist\n\
+         flush geo-v4\n\
+         add geo-v4 2.0.0.0/23\n\
+         add geo-v4 2.0.2.0/24\n\
+         create geo-v6 hash:net family inet6 maxelem 65536 -exist\n\
+         flush geo-v6\n\
+         add geo-v6 2001:db8::/112\n"
+    );
+    assert!(db.country_cidrs(&["US"])?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_ranges_for_country() -> Result<(), error::Error> {
+    use crate::LocationDB;
+
+    let db = DB::from_file(IPV6BIN)?;
+    let expected: Vec<_> = db
+        .ranges()
+        .filter_map(Result::ok)
+        .filter(|(_, record)| match record {
+            Record::LocationDb(rec) => rec.country.as_ref().is_some_and(|c| c.short_name == "CN"),
+            Record::ProxyDb(_) => false,
+        })
+        .map(|(range, _)| range)
+        .collect();
+    let db = LocationDB::from_file(IPV6BIN)?;
+    let ranges = db.ranges_for_country("cn").collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(ranges, expected);
+    assert!(ranges.iter().any(|range| range.is_ipv6()));
+    assert_eq!(db.ranges_for_country("XX").count(), 0);
+    Ok(())
+}
+
+#[test]
+fn test_ranges_in_bbox() -> Result<(), error::Error> {
+    use crate::{testing::BinBuilder, IpRange, LocationDB, LocationRecord};
+
+    let located = |latitude, longitude| LocationRecord {
+        latitude: Some(latitude),
+        longitude: Some(longitude),
+        ..LocationRecord::default()
+    };
+    let bytes = BinBuilder::new(5)
+        .range("1.0.0.0", "1.0.0.255", located(51.5, -0.12))
+        .range("1.0.1.0", "1.0.1.255", located(35.68, 139.69))
+        .range("1.0.2.0", "1.0.2.255", located(-18.14, 178.44))
+        .build();
+    let db = LocationDB::from_bytes(bytes)?;
+    let range = |start: &str, end: &str| IpRange {
+        start: start.parse().unwrap(),
+        end: end.parse().unwrap(),
+    };
+
+    let london = db.ranges_in_bbox(51.28, -0.51, 51.69, 0.33);
+    assert_eq!(
+        london.collect::<Result<Vec<_>, _>>()?,
+        vec![range("1.0.0.0", "1.0.0.255")]
+    );
+    let pacific = db.ranges_in_bbox(-60.0, 170.0, 60.0, -170.0);
+    assert_eq!(
+        pacific.collect::<Result<Vec<_>, _>>()?,
+        vec![range("1.0.2.0", "1.0.2.255")]
+    );
+    assert_eq!(db.ranges_in_bbox(60.0, 0.0, 70.0, 10.0).count(), 0);
+
+    let db = LocationDB::from_file(IPV4BIN)?;
+    assert_eq!(db.ranges_in_bbox(-90.0, -180.0, 90.0, 180.0).count(), 0);
+    Ok(())
+}
+
+#[test]
+fn test_nearest() -> Result<(), error::Error> {
+    use crate::{testing::BinBuilder, LocationDB, LocationRecord};
+    use std::{borrow::Cow, net::IpAddr};
+
+    let city = |name, latitude, longitude| LocationRecord {
+        city: Some(Cow::Borrowed(name)),
+        latitude: Some(latitude),
+        longitude: Some(longitude),
+        ..LocationRecord::default()
+    };
+    let bytes = BinBuilder::new(5)
+        .range("1.0.0.0", "1.0.0.255", city("London", 51.5, -0.12))
+        .range("1.0.1.0", "1.0.1.255", city("Paris", 48.86, 2.35))
+        .range("1.0.2.0", "1.0.2.255", city("London", 51.5, -0.12))
+        .range("1.0.3.0", "1.0.3.255", city("Tokyo", 35.68, 139.69))
+        .build();
+    let db = LocationDB::from_bytes(bytes)?;
+
+    let nearest = db.nearest(50.85, 4.35, 2)?;
+    let cities: Vec<_> = nearest
+        .iter()
+        .map(|n| n.record.city.as_deref().unwrap())
+        .collect();
+    assert_eq!(cities, ["Paris", "London"]);
+    assert!((nearest[0].distance_km - 264.0).abs() < 1.0);
+    assert_eq!(nearest[1].range.start, "1.0.0.0".parse::<IpAddr>().unwrap());
+    assert_eq!(db.nearest(50.85, 4.35, 10)?.len(), 4);
+
+    let db = LocationDB::from_file(IPV4BIN)?;
+    assert!(db.nearest(0.0, 0.0, 1)?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_coordinate_precision() -> Result<(), error::Error> {
+    use crate::{testing::BinBuilder, DbOptions, LocationDB, LocationRecord};
+
+    let london = LocationRecord {
+        latitude: Some(51.5074),
+        longitude: Some(-0.1278),
+        ..LocationRecord::default()
+    };
+    let bytes = BinBuilder::new(5)
+        .range("1.0.0.0", "1.0.0.255", london.clone())
+        .build();
+    let options = DbOptions::new().coordinate_precision(1);
+    let db = LocationDB::from_bytes_with_options(bytes, &options)?;
+    let record = db.ip_lookup("1.0.0.1".parse().unwrap())?;
+    assert_eq!(
+        (record.latitude, record.longitude),
+        (Some(51.5), Some(-0.1))
+    );
+    assert_eq!(db.ranges_in_bbox(51.45, -0.13, 51.55, -0.11).count(), 0);
+    assert_eq!(db.ranges_in_bbox(51.45, -0.1, 51.55, 0.0).count(), 1);
+
+    let mut record = london;
+    record.truncate_coordinates(2);
+    assert_eq!(
+        (record.latitude, record.longitude),
+        (Some(51.5), Some(-0.12))
+    );
+    record.truncate_coordinates(0);
+    assert_eq!((record.latitude, record.longitude), (Some(51.0), Some(0.0)));
+    Ok(())
+}
+
+#[test]
+fn test_country_lookup() -> Result<(), error::Error> {
+    use crate::{CountryOnlyRecord, DbOptions};
+
+    let options = DbOptions::new().collect_stats(true);
+    for path in [IPV4BIN, IPV6BIN, IP2PROXYBIN] {
+        let db = DB::from_file_with_options(path, &options)?;
+        for ip in [
+            "43.224.159.155",
+            "1.1.1.1",
+            "2a01:cb08:8d14::",
+            "::ffff:2.2.2.2",
+        ] {
+            let ip = ip.parse().unwrap();
+            let expected = db.ip_lookup(ip).map(CountryOnlyRecord::from);
+            assert_eq!(db.country_lookup(ip), expected);
+        }
+        assert_eq!(db.stats().unwrap().lookups, 8);
+    }
+
+    let db = DB::from_file(IPV4BIN)?;
+    let record = db.country_lookup("43.224.159.155".parse().unwrap())?;
+    assert_eq!(
+        serde_json::to_string(&record).unwrap(),
+        r#"{"ip":"43.224.159.155","country":{"short_name":"IN","long_name":"India"}}"#
+    );
+    Ok(())
+}
+
+/// The keys of a serialized record in order, with the keys of `country`
+/// prefixed as in `COLUMNS`. Values must not contain `":`.
+fn serialized_columns(json: &str) -> Vec<String> {
+    let mut columns = Vec::new();
+    let mut nested = 0;
+    for (end, _) in json.match_indices("\":") {
+        let key = &json[json[..end].rfind('"').unwrap() + 1..end];
+        if nested > 0 {
+            columns.push(format!("country_{}", key));
+            nested -= 1;
+        } else if key == "country" {
+            nested = 2;
+        } else {
+            columns.push(key.to_string());
+        }
+    }
+    columns
+}
+
+#[test]
+fn test_serialized_field_order() {
+    use crate::{Country, LocationRecord, Proxy, ProxyRecord, SCHEMA_VERSION};
+    use std::borrow::Cow;
+
+    let text = |value: &'static str| Some(Cow::Borrowed(value));
+    let country = Some(Country {
+        short_name: Cow::Borrowed("FR"),
+        long_name: Cow::Borrowed("France"),
+    });
+    let location = LocationRecord {
+        ip: "1.0.0.1".parse().unwrap(),
+        latitude: Some(48.5),
+        longitude: Some(2.25),
+        country: country.clone(),
+        region: text("Ile-de-France"),
+        city: text("Paris"),
+        isp: text("ISP"),
+        domain: text("example.fr"),
+        zip_code: text("75001"),
+        time_zone: text("+01:00"),
+        net_speed: text("DSL"),
+        idd_code: text("33"),
+        area_code: text("01"),
+        weather_station_code: text("FRXX0076"),
+        weather_station_name: text("Paris"),
+        mcc: text("208"),
+        mnc: text("01"),
+        mobile_brand: text("Orange"),
+        elevation: text("35"),
+        usage_type: text("ISP"),
+        address_type: text("U"),
+        category: text("IAB19"),
+        district: text("Paris"),
+        asn: text("3215"),
+        as_name: text("Orange"),
+    };
+    let json = location.to_json();
+    assert_eq!(
+        json,
+        r#"{"ip":"1.0.0.1","latitude":48.5,"longitude":2.25,"country":{"short_name":"FR","long_name":"France"},"region":"Ile-de-France","city":"Paris","isp":"ISP","domain":"example.fr","zip_code":"75001","time_zone":"+01:00","net_speed":"DSL","idd_code":"33","area_code":"01","weather_station_code":"FRXX0076","weather_station_name":"Paris","mcc":"208","mnc":"01","mobile_brand":"Orange","elevation":"35","usage_type":"ISP","address_type":"U","category":"IAB19","district":"Paris","asn":"3215","as_name":"Orange"}"#
+    );
+
+    let proxy = ProxyRecord {
+        ip: "1.0.0.1".parse().unwrap(),
+        country,
+        region: text("Ile-de-France"),
+        city: text("Paris"),
+        isp: text("ISP"),
+        domain: text("example.fr"),
+        is_proxy: Some(Proxy::IsAProxy),
+        proxy_type: text("VPN"),
+        asn: text("3215"),
+        as_: text("Orange"),
+        last_seen: text("3"),
+        threat: text("SPAM"),
+        provider: text("Provider"),
+        usage_type: text("ISP"),
+    };
+    let proxy_json = proxy.to_json();
+    assert_eq!(
+        proxy_json,
+        r#"{"ip":"1.0.0.1","country":{"short_name":"FR","long_name":"France"},"region":"Ile-de-France","city":"Paris","isp":"ISP","domain":"example.fr","is_proxy":"IsAProxy","proxy_type":"VPN","asn":"3215","as_":"Orange","last_seen":"3","threat":"SPAM","provider":"Provider","usage_type":"ISP"}"#
+    );
+
+    assert_eq!(serialized_columns(&json), LocationRecord::COLUMNS);
+    assert_eq!(serialized_columns(&proxy_json), ProxyRecord::COLUMNS);
+    assert_eq!(
+        DB::from_file(IPV4BIN).unwrap().info().schema_version,
+        SCHEMA_VERSION
+    );
+}
+
+#[test]
+fn test_schema() -> Result<(), error::Error> {
+    use crate::{testing::BinBuilder, LocationRecord, ProxyRecord};
+
+    let populated = |schema: Vec<crate::Column>| {
+        schema
+            .into_iter()
+            .filter(|column| column.populated)
+            .map(|column| column.name.to_string())
+            .collect::<Vec<_>>()
+    };
+    for db_type in 1..=26 {
+        let db = BinBuilder::new(db_type)
+            .country_range("1.0.0.0", "1.0.0.255", "FR", "France")
+            .open();
+        let json = match db.ip_lookup("1.0.0.1".parse().unwrap())? {
+            Record::LocationDb(record) => record.to_json(),
+            Record::ProxyDb(_) => unreachable!(),
+        };
+        assert_eq!(
+            serialized_columns(&json),
+            populated(LocationRecord::schema(db_type)),
+            "DB{}",
+            db_type
+        );
+    }
+    assert_eq!(populated(LocationRecord::schema(0)), ["ip"]);
+    assert_eq!(
+        LocationRecord::schema(0).len(),
+        LocationRecord::COLUMNS.len()
+    );
+
+    let db = DB::from_file(IP2PROXYBIN)?;
+    let schema = populated(ProxyRecord::schema(db.info().db_type));
+    for ip in ["1.1.1.1", "43.224.159.155"] {
+        let json = match db.ip_lookup(ip.parse().unwrap())? {
+            Record::ProxyDb(record) => record.to_json(),
+            Record::LocationDb(_) => unreachable!(),
+        };
+        assert!(serialized_columns(&json)
+            .iter()
+            .all(|column| schema.contains(column)));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_to_value() -> Result<(), error::Error> {
+    use crate::{testing::BinBuilder, Field, LocationRecord, ProxyRecord};
+
+    let db = BinBuilder::new(5)
+        .range(
+            "1.0.0.0",
+            "1.0.0.255",
+            LocationRecord {
+                latitude: Some(48.8534),
+                longitude: Some(2.3488),
+                ..LocationRecord::default()
+            },
+        )
+        .open();
+    let record = db.ip_lookup("1.0.0.1".parse().unwrap())?;
+    let fields: Vec<Field> =
+        serde_json::from_str(r#"["latitude", "longitude", "region", "isp", "is_proxy"]"#).unwrap();
+    assert_eq!(
+        record.to_value(&fields).to_string(),
+        r#"{"latitude":48.8534,"longitude":2.3488,"region":"-"}"#
+    );
+    assert_eq!(record.to_value(&[]), serde_json::json!({}));
+
+    let db = DB::from_file(IP2PROXYBIN)?;
+    let proxy = db.ip_lookup("1.1.1.1".parse().unwrap())?;
+    let value = proxy.to_value(&[Field::Ip, Field::IsProxy, Field::Latitude]);
+    assert_eq!(value["ip"], "1.1.1.1");
+    assert!(value["is_proxy"].is_string());
+    assert!(value.get("latitude").is_none());
+
+    // Every column is a field, under the same name.
+    let names: Vec<_> = Field::ALL.iter().map(|field| field.name()).collect();
+    for column in LocationRecord::COLUMNS.iter().chain(ProxyRecord::COLUMNS) {
+        assert!(names.contains(column), "{}", column);
+    }
+    for field in Field::ALL {
+        assert_eq!(serde_json::to_value(field).unwrap(), field.name());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_enrich_csv() -> Result<(), error::Error> {
+    use crate::{testing::BinBuilder, Field, LocationRecord};
+
+    let db = BinBuilder::new(5)
+        .range(
+            "1.0.0.0",
+            "1.0.0.255",
+            LocationRecord {
+                latitude: Some(48.8534),
+                longitude: Some(2.3488),
+                ..LocationRecord::default()
+            },
+        )
+        .country_range("1.0.1.0", "1.0.1.255", "FR", "France")
+        .open();
+    let input = "id,note,ip\n1,\"a, b\", 1.0.0.7 \n2,,1.0.1.1\n3,x,\n";
+    let mut output = Vec::new();
+    let fields = [Field::CountryLongName, Field::Latitude, Field::Isp];
+    let rows = db.enrich_csv(input.as_bytes(), &mut output, "ip", &fields)?;
+    assert_eq!(rows, 3);
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "id,note,ip,country_long_name,latitude,isp\n\
+         1,\"a, b\", 1.0.0.7 ,-,48.8534,\n\
+         2,,1.0.1.1,France,0,\n\
+         3,x,,,,\n"
+    );
+
+    let missing = db.enrich_csv(input.as_bytes(), Vec::new(), "addr", &fields);
+    assert!(matches!(missing, Err(error::Error::GenericError(_))));
+    let ragged = db.enrich_csv("ip\n1.0.0.1,extra\n".as_bytes(), Vec::new(), "ip", &fields);
+    assert!(ragged.is_err());
+
+    let proxy = DB::from_file(IP2PROXYBIN)?;
+    let mut output = Vec::new();
+    proxy.enrich_csv(
+        "ip\n1.1.1.1\n".as_bytes(),
+        &mut output,
+        "ip",
+        &[Field::IsProxy],
+    )?;
+    assert!(String::from_utf8(output)
+        .unwrap()
+        .starts_with("ip,is_proxy\n1.1.1.1,Is"));
+    Ok(())
+}
+
+#[test]
+fn test_pipeline() -> Result<(), error::Error> {
+    use crate::pipeline;
+    use std::{net::IpAddr, sync::mpsc::TrySendError, sync::Arc, thread};
+
+    let db = Arc::new(DB::from_file(IPV4BIN)?);
+    let ips: Vec<IpAddr> = (0..500u32)
+        .map(|i| IpAddr::from((i * 8_589_934).to_be_bytes()))
+        .collect();
+    let (jobs, results) = pipeline::spawn(db.clone(), 3, 4);
+    let producer = {
+        let ips = ips.clone();
+        thread::spawn(move || {
+            for (tag, ip) in ips.into_iter().enumerate() {
+                jobs.send((tag, ip)).unwrap();
+            }
+        })
+    };
+    let mut seen = vec![false; ips.len()];
+    for (tag, record) in results {
+        assert!(!seen[tag]);
+        seen[tag] = true;
+        assert_eq!(record?, db.ip_lookup_owned(ips[tag])?);
+    }
+    producer.join().unwrap();
+    assert!(seen.iter().all(|&seen| seen));
+
+    // Nobody reads the results: one job per worker, the results and the
+    // queue fill up, then senders are turned away.
+    let (jobs, mut results) = pipeline::spawn(db, 1, 2);
+    let ip: IpAddr = "1.1.1.1".parse().unwrap();
+    let mut sent = 0;
+    let full = loop {
+        match jobs.try_send(((), ip)) {
+            Ok(()) => sent += 1,
+            Err(TrySendError::Full(_)) => break true,
+            Err(TrySendError::Disconnected(_)) => break false,
+        }
+        if sent > 100 {
+            break false;
+        }
+        thread::sleep(std::time::Duration::from_millis(5));
+    };
+    assert!(full, "{} jobs queued", sent);
+    drop(jobs);
+    assert_eq!(results.by_ref().count(), sent);
+    assert!(results.try_next().is_none());
+    Ok(())
+}
+
+#[test]
+fn test_concurrent_lookups() -> Result<(), error::Error> {
+    use crate::{Backend, DbOptions};
+    use std::{net::IpAddr, thread};
+
+    let ips: Vec<IpAddr> = (0..2_000u32)
+        .map(|i| IpAddr::from(i.wrapping_mul(2_654_435_761).to_be_bytes()))
+        .chain((0..2_000u128).map(|i| IpAddr::from((i << 100 | i).to_be_bytes())))
+        .collect();
+    let mut backends = vec![Backend::Mmap, Backend::InMemory, Backend::Compact];
+    #[cfg(any(unix, windows))]
+    backends.push(Backend::Pread);
+    for path in [IPV4BIN, IPV6BIN, IP2PROXYBIN] {
+        for &backend in &backends {
+            let db = DbOptions::new().path(path).backend(backend).open()?;
+            let expected: Vec<_> = ips.iter().map(|&ip| db.ip_lookup(ip)).collect();
+            thread::scope(|scope| {
+                for offset in 0..8 {
+                    let (db, ips, expected) = (&db, &ips, &expected);
+                    scope.spawn(move || {
+                        // Each thread walks the addresses from another
+                        // start, so the threads read different rows.
+                        for i in (0..ips.len()).map(|i| (i + offset * 499) % ips.len()) {
+                            assert_eq!(db.ip_lookup(ips[i]), expected[i], "{:?}", backend);
+                        }
+                    });
+                }
+            });
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_max_str_len() -> Result<(), error::Error> {
+    use crate::{Backend, DbOptions};
+    use std::net::{IpAddr, Ipv4Addr};
+
+    // Corrupts the length byte of the country of the range of row 10.
+    let mut bytes = std::fs::read(IPV4BIN)?;
+    let ipv4_addr = u32::from_le_bytes(bytes[9..13].try_into().unwrap()) as usize - 1;
+    let row = ipv4_addr + 10 * 8;
+    let ip = IpAddr::V4(Ipv4Addr::from(u32::from_le_bytes(
+        bytes[row..row + 4].try_into().unwrap(),
+    )));
+    let pointer = u32::from_le_bytes(bytes[row + 4..row + 8].try_into().unwrap()) as usize;
+    bytes[pointer] = 200;
+
+    assert!(DB::from_bytes(bytes.clone())?.ip_lookup(ip).is_ok());
+    let options = DbOptions::new().max_str_len(64);
+    let db = DB::from_bytes_with_options(bytes.clone(), &options)?;
+    assert_eq!(
+        db.ip_lookup(ip).unwrap_err(),
+        error::Error::StringTooLong {
+            offset: pointer as u64,
+            len: 200,
+            max: 64
+        }
+    );
+
+    let path = std::env::temp_dir().join(format!(
+        "ip2location-max-str-len-{}.BIN",
+        std::process::id()
+    ));
+    std::fs::write(&path, &bytes)?;
+    let db = DB::from_file_with_options(&path, &options.clone().backend(Backend::Pread));
+    let _ = std::fs::remove_file(&path);
+    assert!(matches!(
+        db?.ip_lookup(ip),
+        Err(error::Error::StringTooLong { len: 200, .. })
+    ));
+
+    // Sane strings are read as before.
+    let db = DB::from_file_with_options(IPV4BIN, &DbOptions::new().max_str_len(64))?;
+    assert!(db.ip_lookup("43.224.159.155".parse().unwrap()).is_ok());
+    let db = DB::from_file_with_options(IPV4BIN, &DbOptions::new().max_str_len(1))?;
+    assert!(matches!(
+        db.ip_lookup("43.224.159.155".parse().unwrap()),
+        Err(error::Error::StringTooLong { len: 2, max: 1, .. })
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_corrupt_string_pointers() -> Result<(), error::Error> {
+    use crate::{Backend, DbOptions};
+    use std::net::{IpAddr, Ipv4Addr};
+
+    let bytes = std::fs::read(IPV4BIN)?;
+    let ipv4_addr = u32::from_le_bytes(bytes[9..13].try_into().unwrap()) as usize - 1;
+    let row = ipv4_addr + 10 * 8;
+    let ip = IpAddr::V4(Ipv4Addr::from(u32::from_le_bytes(
+        bytes[row..row + 4].try_into().unwrap(),
+    )));
+    let path = std::env::temp_dir().join(format!("ip2location-corrupt-{}.BIN", std::process::id()));
+    // Past the end of the file, at a string running past it and into the
+    // rows.
+    for pointer in [u32::MAX, bytes.len() as u32 - 1, ipv4_addr as u32 + 40] {
+        let mut bytes = bytes.clone();
+        bytes[row + 4..row + 8].copy_from_slice(&pointer.to_le_bytes());
+        std::fs::write(&path, &bytes)?;
+        let pread = DbOptions::new().path(&path).backend(Backend::Pread).open();
+        for db in [DB::from_bytes(bytes)?, pread?] {
+            assert!(
+                matches!(db.ip_lookup(ip), Err(error::Error::Corrupt(_))),
+                "{}",
+                pointer
+            );
+        }
+    }
+    let _ = std::fs::remove_file(&path);
+    Ok(())
+}
+
+#[test]
+fn test_header_bounds() -> Result<(), error::Error> {
+    let corrupt = |bytes: Vec<u8>| match DB::from_bytes(bytes) {
+        Err(error::Error::Corrupt(message)) => message,
+        other => panic!("{:?}", other.map(|_| ())),
+    };
+    let bytes = std::fs::read(IPV4BIN)?;
+    let set = |at: usize, value: u32| {
+        let mut bytes = bytes.clone();
+        bytes[at..at + 4].copy_from_slice(&value.to_le_bytes());
+        bytes
+    };
+
+    let count = u32::from_le_bytes(bytes[5..9].try_into().unwrap());
+    assert!(corrupt(set(5, count * 2)).starts_with("IPv4 table of"));
+    assert!(corrupt(set(9, u32::MAX - 64)).starts_with("IPv4 table of"));
+    assert!(corrupt(set(21, bytes.len() as u32 - 100)).starts_with("IPv4 index at"));
+    assert!(corrupt(bytes[..bytes.len() / 2].to_vec()).contains("past the end"));
+    // A database size smaller than the tables is caught as well.
+    assert!(corrupt(set(31, 1000)).contains("database size 1000"));
+    DB::from_bytes(bytes)?;
+    Ok(())
+}
+
+#[test]
+fn test_unknown_db_type() -> Result<(), error::Error> {
+    for path in [IPV4BIN, IP2PROXYBIN] {
+        let mut bytes = std::fs::read(path)?;
+        bytes[0] = 200;
+        match DB::from_bytes(bytes) {
+            Err(error::Error::Corrupt(message)) => {
+                assert_eq!(message, "unknown database type 200")
+            }
+            other => panic!("{}: {:?}", path, other.map(|_| ())),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_index_bounds_at_first_row() -> Result<(), error::Error> {
+    // An index entry of (0, 0) for a block whose first row starts above the
+    // address sends the search below row 0.
+    let patch = |path: &str, v6: bool| -> Result<Vec<u8>, error::Error> {
+        let mut bytes = std::fs::read(path)?;
+        let word = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap()) as usize;
+        let (table, index, block, width) = match v6 {
+            false => (word(9) - 1, word(21) - 1, 0, 4),
+            true => (word(17) - 1, word(25) - 1, 0x2001, 16),
+        };
+        bytes[index + block * 8..index + block * 8 + 8].fill(0);
+        bytes[table..table + width].fill(0xff);
+        Ok(bytes)
+    };
+    for (path, v6, ip) in [
+        (IPV4BIN, false, "0.0.0.1"),
+        (IP2PROXYBIN, false, "0.0.0.1"),
+        (IPV6BIN, true, "2001:db8::1"),
+    ] {
+        let db = DB::from_bytes(patch(path, v6)?)?;
+        match db.ip_lookup(ip.parse().unwrap()) {
+            Err(error::Error::RecordNotFound) => {}
+            other => panic!("{}: {:?}", path, other.map(|_| ())),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_records_as_hash_keys() -> Result<(), error::Error> {
+    use crate::LocationRecord;
+    use std::{borrow::Cow, collections::HashSet};
+
+    let (mut seen, mut unique) = (HashSet::new(), Vec::new());
+    for path in [IPV4BIN, IP2PROXYBIN] {
+        let db = DB::from_file(path)?;
+        for ip in ["43.224.159.155", "43.224.159.156", "1.1.1.1", "8.8.8.8"] {
+            let mut record = db.ip_lookup(ip.parse().unwrap())?.into_owned();
+            record.set_ip("0.0.0.0".parse().unwrap());
+            if !unique.contains(&record) {
+                unique.push(record.clone());
+            }
+            seen.insert(record);
+        }
+    }
+    assert_eq!(seen.len(), unique.len());
+    assert!(seen.len() < 8);
+
+    let paris = LocationRecord {
+        latitude: Some(48.8566),
+        longitude: Some(2.3522),
+        city: Some(Cow::Borrowed("Paris")),
+        ..LocationRecord::default()
+    };
+    let nan = LocationRecord {
+        latitude: Some(f32::NAN),
+        ..paris.clone()
+    };
+    assert_eq!(nan, nan.clone());
+    let moved = LocationRecord {
+        latitude: Some(48.85665),
+        ..paris.clone()
+    };
+    assert_ne!(paris, moved);
+    assert!(paris.same_location(&moved));
+    let far = LocationRecord {
+        longitude: Some(2.36),
+        ..paris.clone()
+    };
+    assert!(!paris.same_location(&far));
+    let elsewhere = LocationRecord {
+        city: Some(Cow::Borrowed("Lyon")),
+        ..paris.clone()
+    };
+    assert!(!paris.same_location(&elsewhere));
+    Ok(())
+}
+
+#[test]
+fn test_lookup_cidr() -> Result<(), error::Error> {
+    use crate::{testing::BinBuilder, CidrLookup, IpRange};
+
+    let db = BinBuilder::new(1)
+        .country_range("1.0.0.0", "1.0.0.255", "FR", "France")
+        .country_range("1.0.1.0", "1.0.1.255", "DE", "Germany")
+        .country_range("1.0.2.0", "1.0.2.255", "FR", "France")
+        .open();
+    let country = |record: &Record| match record {
+        Record::LocationDb(rec) => rec.country.clone().unwrap().short_name.into_owned(),
+        Record::ProxyDb(_) => unreachable!(),
+    };
+    let range = |start: &str, end: &str| IpRange {
+        start: start.parse().unwrap(),
+        end: end.parse().unwrap(),
+    };
+
+    let CidrLookup::Uniform(record) = db.lookup_cidr("1.0.0.128/25".parse().unwrap())? else {
+        panic!("not uniform");
+    };
+    assert_eq!(country(&record), "FR");
+    assert_eq!(record, db.ip_lookup("1.0.0.128".parse().unwrap())?);
+
+    let CidrLookup::Mixed(records) = db.lookup_cidr("1.0.0.0/22".parse().unwrap())? else {
+        panic!("not mixed");
+    };
+    let records: Vec<_> = records
+        .iter()
+        .map(|(record, parts)| (country(record), parts.clone()))
+        .collect();
+    assert_eq!(
+        records,
+        vec![
+            (
+                "FR".to_string(),
+                vec![range("1.0.0.0", "1.0.0.255"), range("1.0.2.0", "1.0.2.255")]
+            ),
+            ("DE".to_string(), vec![range("1.0.1.0", "1.0.1.255")]),
+            ("-".to_string(), vec![range("1.0.3.0", "1.0.3.255")]),
+        ]
+    );
+    assert!(db.lookup_cidr("0.0.0.0/0".parse().unwrap()).is_ok());
+    assert!(matches!(
+        db.lookup_cidr("2001:db8::/32".parse().unwrap()),
+        Err(error::Error::RecordNotFound)
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_aggregate_cidr() -> Result<(), error::Error> {
+    use crate::testing::BinBuilder;
+
+    let db = BinBuilder::new(1)
+        .country_range("1.0.0.0", "1.0.0.255", "FR", "France")
+        .country_range("1.0.1.0", "1.0.1.127", "DE", "Germany")
+        .country_range("1.0.1.128", "1.0.2.255", "FR", "France")
+        .open();
+    let aggregate = db.aggregate_cidr("1.0.0.0/22".parse().unwrap())?;
+    assert_eq!(aggregate.addresses, 1024);
+    let country = aggregate.country.unwrap();
+    assert_eq!(country.value.short_name, "FR");
+    assert_eq!(country.addresses, 640);
+    assert_eq!(country.share, 0.625);
+    assert_eq!(aggregate.isp, None);
+
+    let aggregate = db.aggregate_cidr("1.0.1.0/25".parse().unwrap())?;
+    assert_eq!(aggregate.country.unwrap().value.short_name, "DE");
+    Ok(())
+}
+
+#[test]
+fn test_open_as_kind() -> Result<(), error::Error> {
+    use crate::DbKind;
+
+    assert_eq!(
+        DB::from_file_as(DbKind::Location, IPV4BIN)?.kind(),
+        DbKind::Location
+    );
+    assert_eq!(
+        DB::from_file_as(DbKind::Proxy, IP2PROXYBIN)?.kind(),
+        DbKind::Proxy
+    );
+    assert_eq!(
+        DB::from_file_as(DbKind::Location, IP2PROXYBIN).unwrap_err(),
+        error::Error::GenericError(
+            "not an IP2Location database: the header has product code 2".into()
+        )
+    );
+    let bytes = std::fs::read(IPV6BIN)?;
+    assert!(DB::from_bytes_as(DbKind::Proxy, bytes.clone()).is_err());
+    let db = DB::options().bytes(bytes).kind(DbKind::Location).open()?;
+    assert_eq!(db.kind(), DbKind::Location);
+    Ok(())
+}
+
+#[test]
+fn test_open_detects_kind() -> Result<(), error::Error> {
+    use crate::{DbKind, DbOptions, Validation};
+
+    assert_eq!(DB::from_file(IPV4BIN)?.kind(), DbKind::Location);
+    assert_eq!(DB::from_file(IP2PROXYBIN)?.kind(), DbKind::Proxy);
+
+    // The errors of the product the header names are returned.
+    let mut bytes = std::fs::read(IP2PROXYBIN)?;
+    bytes[29] = 7;
+    let year = bytes[2];
+    assert_eq!(
+        DB::from_bytes(bytes).unwrap_err(),
+        error::Error::InvalidBinDatabase(year, 7)
+    );
+    let bytes = std::fs::read(IP2PROXYBIN)?;
+    assert!(matches!(
+        DB::from_bytes(bytes[..20].to_vec()),
+        Err(error::Error::Corrupt(_))
+    ));
+    let mut bytes = std::fs::read(IP2PROXYBIN)?;
+    let ipv4_addr = u32::from_le_bytes(bytes[9..13].try_into().unwrap()) as usize - 1;
+    let row_size = bytes[1] as usize * 4;
+    let row = |n: usize| ipv4_addr + n * row_size;
+    bytes.copy_within(row(0)..row(0) + 4, row(20));
+    let options = DbOptions::new().validate(Validation::Full);
+    let err = DB::from_bytes_with_options(bytes, &options).unwrap_err();
+    assert!(err.to_string().contains("row 20"), "{}", err);
+    Ok(())
+}
+
+#[test]
+fn test_probe() -> Result<(), error::Error> {
+    use crate::DbKind;
+
+    for path in [IPV4BIN, IPV6BIN, IP2PROXYBIN] {
+        assert_eq!(DB::probe(path)?, DB::from_file(path)?.info());
+        assert_eq!(DB::detect_kind(path)?, DB::from_file(path)?.kind());
+    }
+    assert_eq!(DB::detect_kind(IP2PROXYBIN)?, DbKind::Proxy);
+
+    // Only the header has to be there.
+    let path = std::env::temp_dir().join(format!("ip2location-probe-{}.BIN", std::process::id()));
+    std::fs::write(&path, &std::fs::read(IPV4BIN)?[..64])?;
+    let probed = DB::probe(&path);
+    std::fs::write(&path, &std::fs::read(IPV4BIN)?[..20])?;
+    let short = DB::probe(&path);
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(
+        probed?.ipv4_count,
+        DB::from_file(IPV4BIN)?.info().ipv4_count
+    );
+    assert!(matches!(short, Err(error::Error::Corrupt(_))));
+    assert!(matches!(
+        DB::probe("data/missing.BIN"),
+        Err(error::Error::IoError(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_ipv6_translation() -> Result<(), error::Error> {
+    use crate::{testing::BinBuilder, DbOptions};
+
+    let bytes = BinBuilder::new(1)
+        .country_range("43.224.0.0", "43.224.255.255", "IN", "India")
+        .country_range(
+            "2001::",
+            "2001:0:ffff:ffff:ffff:ffff:ffff:ffff",
+            "TE",
+            "Teredo",
+        )
+        .country_range(
+            "2002::",
+            "2002:ffff:ffff:ffff:ffff:ffff:ffff:ffff",
+            "6T",
+            "6to4",
+        )
+        .build();
+    let country = |options: &DbOptions, ip: &str| {
+        let db = DB::from_bytes_with_options(bytes.clone(), options).unwrap();
+        match db.ip_lookup(ip.parse().unwrap()).unwrap() {
+            Record::LocationDb(rec) => rec.country.unwrap().short_name.into_owned(),
+            Record::ProxyDb(_) => unreachable!(),
+        }
+    };
+    // 6to4 embeds 43.224.159.155; the Teredo client is 43.224.159.27.
+    let six_to_four = "2002:2be0:9f9b::";
+    let teredo = "2001:0:4136:e378:8000:63bf:d41f:60e4";
+
+    // By default both are looked up as the IPv4 address they embed.
+    let options = DbOptions::new();
+    assert_eq!(country(&options, six_to_four), "IN");
+    assert_eq!(country(&options, teredo), "IN");
+
+    // Disabled, they fall through to the ranges of the IPv6 table.
+    let options = DbOptions::new().translate_6to4(false);
+    assert_eq!(country(&options, six_to_four), "6T");
+    assert_eq!(country(&options, teredo), "IN");
+
+    let options = DbOptions::new().translate_teredo(false);
+    assert_eq!(country(&options, six_to_four), "IN");
+    assert_eq!(country(&options, teredo), "TE");
+
+    // IPv4-mapped addresses are always looked up in the IPv4 table.
+    let options = DbOptions::new()
+        .translate_6to4(false)
+        .translate_teredo(false);
+    assert_eq!(country(&options, "::ffff:43.224.159.155"), "IN");
+    Ok(())
+}
+
+#[test]
+fn test_nat64() -> Result<(), error::Error> {
+    use crate::{testing::BinBuilder, DbOptions, Nat64Prefix};
+
+    let bytes = BinBuilder::new(1)
+        .country_range("43.224.0.0", "43.224.255.255", "IN", "India")
+        .country_range(
+            "::",
+            "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff",
+            "V6",
+            "IPv6",
+        )
+        .build();
+    let country = |options: &DbOptions, ip: &str| {
+        let db = DB::from_bytes_with_options(bytes.clone(), options).unwrap();
+        match db.ip_lookup(ip.parse().unwrap()).unwrap() {
+            Record::LocationDb(rec) => rec.country.unwrap().short_name.into_owned(),
+            Record::ProxyDb(_) => unreachable!(),
+        }
+    };
+
+    // The well-known prefix embeds 43.224.159.155 in its last 32 bits.
+    assert_eq!(country(&DbOptions::new(), "64:ff9b::2be0:9f9b"), "IN");
+    let options = DbOptions::new().translate_nat64(false);
+    assert_eq!(country(&options, "64:ff9b::2be0:9f9b"), "V6");
+
+    // A /48 prefix embeds it in bits 48 to 64 and 72 to 88.
+    let prefix = Nat64Prefix::new("2001:db8:64::".parse().unwrap(), 48)?;
+    assert_eq!(
+        country(&DbOptions::new(), "2001:db8:64:2be0:9f:9b00::"),
+        "V6"
+    );
+    let options = DbOptions::new().nat64_prefix(prefix);
+    assert_eq!(country(&options, "2001:db8:64:2be0:9f:9b00::"), "IN");
+    assert_eq!(country(&options, "2001:db8:65:2be0:9f:9b00::"), "V6");
+
+    assert!(Nat64Prefix::new("2001:db8::".parse().unwrap(), 33).is_err());
+    assert_eq!(prefix.prefix_len(), 48);
+    Ok(())
+}
+
+#[test]
+fn test_ipv4_mapped_mode() -> Result<(), error::Error> {
+    use crate::{testing::BinBuilder, DbOptions, Ipv4MappedMode};
+
+    let bytes = BinBuilder::new(1)
+        .country_range("43.224.0.0", "43.224.255.255", "IN", "India")
+        .country_range(
+            "::",
+            "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff",
+            "V6",
+            "IPv6",
+        )
+        .build();
+    let countries = |mode| {
+        let options = DbOptions::new().ipv4_mapped(mode);
+        let db = DB::from_bytes_with_options(bytes.clone(), &options).unwrap();
+        ["::ffff:43.224.159.155", "::43.224.159.155"].map(|ip| {
+            match db.ip_lookup(ip.parse().unwrap()).unwrap() {
+                Record::LocationDb(rec) => rec.country.unwrap().short_name.into_owned(),
+                Record::ProxyDb(_) => unreachable!(),
+            }
+        })
+    };
+    assert_eq!(countries(Ipv4MappedMode::default()), ["IN", "IN"]);
+    assert_eq!(countries(Ipv4MappedMode::Full), ["IN", "IN"]);
+    assert_eq!(countries(Ipv4MappedMode::MappedOnly), ["IN", "V6"]);
+    assert_eq!(countries(Ipv4MappedMode::Native), ["V6", "V6"]);
+    Ok(())
+}
+
+#[test]
+fn test_on_lookup() -> Result<(), error::Error> {
+    use crate::LookupOutcome;
+    use std::sync::{Arc, Mutex};
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let mut db = DB::from_file(IPV4BIN)?;
+    let seen = events.clone();
+    db.on_lookup(move |event| seen.lock().unwrap().push((event.ip, event.outcome)));
+    for ip in ["43.224.159.155", "2a01:b600:8001::"] {
+        let _ = db.ip_lookup(ip.parse().unwrap());
+    }
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![
+            ("43.224.159.155".parse().unwrap(), LookupOutcome::Found),
+            ("2a01:b600:8001::".parse().unwrap(), LookupOutcome::NotFound),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_pseudonymized_hooks() -> Result<(), error::Error> {
+    use crate::{truncate_ip, DbOptions};
+    use std::{
+        net::IpAddr,
+        sync::{Arc, Mutex},
+    };
+
+    assert_eq!(
+        truncate_ip("::ffff:43.224.159.155".parse().unwrap()),
+        "43.224.159.0".parse::<IpAddr>().unwrap()
+    );
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let options = DbOptions::new().pseudonymize_hooks(true);
+    let mut db = DB::from_file_with_options(IPV6BIN, &options)?;
+    let seen = events.clone();
+    db.on_lookup(move |event| seen.lock().unwrap().push(event.ip));
+    for ip in ["43.224.159.155", "2a01:b600:8001:1:2::3"] {
+        let record = db.ip_lookup(ip.parse().unwrap())?;
+        assert_eq!(record, db.ip_lookup(ip.parse().unwrap())?);
+    }
+    let truncated: Vec<IpAddr> = [
+        "43.224.159.0",
+        "43.224.159.0",
+        "2a01:b600:8001::",
+        "2a01:b600:8001::",
+    ]
+    .iter()
+    .map(|ip| ip.parse().unwrap())
+    .collect();
+    assert_eq!(*events.lock().unwrap(), truncated);
+    Ok(())
+}
+
+#[test]
+fn test_reloadable_db() -> Result<(), error::Error> {
+    let path = std::env::temp_dir().join(format!("ip2location-reload-{}.BIN", std::process::id()));
+    std::fs::copy(IPV4BIN, &path)?;
+    let db = crate::ReloadableDb::open(&path)?;
+    assert!(db
+        .current()
+        .ip_lookup("2a01:cb08:8d14::".parse().unwrap())
+        .is_err());
+    assert!(!db.reload_if_modified()?);
+
+    // Swap in the IPv6 database with an atomic rename.
+    let next = path.with_extension("next");
+    std::fs::copy(IPV6BIN, &next)?;
+    let mtime = std::fs::metadata(&path)?.modified()?;
+    std::fs::File::options()
+        .write(true)
+        .open(&next)?
+        .set_modified(mtime + std::time::Duration::from_secs(1))?;
+    std::fs::rename(&next, &path)?;
+    let previous = db.current();
+    assert!(db.reload_if_modified()?);
+    assert!(db
+        .current()
+        .ip_lookup("2a01:cb08:8d14::".parse().unwrap())
+        .is_ok());
+    assert!(previous
+        .ip_lookup("43.224.159.155".parse().unwrap())
+        .is_ok());
+
+    std::fs::remove_file(&path)?;
+    assert!(db.reload().is_err());
+    assert!(db
+        .current()
+        .ip_lookup("2a01:cb08:8d14::".parse().unwrap())
+        .is_ok());
+    Ok(())
+}
+
+#[test]
+fn test_db_info() -> Result<(), error::Error> {
+    let info = DB::from_file(IP2PROXYBIN)?.info();
+    assert_eq!(
+        (info.kind, info.db_type, info.product_code),
+        ("proxy", 1, 2)
+    );
+    assert_eq!((info.ipv4_count, info.ipv6_count), (7747691, 976091));
+    let text = info.to_string();
+    assert!(text.contains("Kind:          proxy\n"));
+    assert!(text.contains("IPv4 ranges:   7747691\n"));
+    assert!(!text.contains("Attribution"));
+    Ok(())
+}
+
+#[test]
+fn test_attribution() -> Result<(), error::Error> {
+    use crate::LOCATION_LITE_ATTRIBUTION;
+
+    for path in [IPV4BIN, IPV6BIN] {
+        let db = DB::from_file(path)?;
+        assert_eq!(db.product_code(), 1);
+        assert_eq!(db.attribution(), Some(LOCATION_LITE_ATTRIBUTION));
+        assert!(db.attribution().unwrap().contains("IP2Location LITE data"));
+    }
+    assert_eq!(DB::from_file(IPV4BIN)?.license_code(), 2);
+
+    let proxy = DB::from_file(IP2PROXYBIN)?;
+    assert_eq!((proxy.product_code(), proxy.license_code()), (2, 1));
+    assert_eq!(proxy.attribution(), None);
+    assert_eq!(DB::from_bytes(std::fs::read(IPV4BIN)?)?.attribution(), None);
+    Ok(())
+}
+
+#[test]
+fn test_verify() -> Result<(), error::Error> {
+    use crate::Issue;
+
+    for path in [IPV4BIN, IPV6BIN] {
+        let report = DB::from_file(path)?.verify();
+        assert!(report.is_ok(), "{}", report);
+    }
+
+    // Corrupt a copy: a pointer past the end of the file and a row
+    // starting before the previous one.
+    let mut bytes = std::fs::read(IPV4BIN)?;
+    let ipv4_addr = u32::from_le_bytes(bytes[9..13].try_into().unwrap()) as usize - 1;
+    let row = |n: usize| ipv4_addr + n * 8;
+    bytes[row(10) + 4..row(10) + 8].copy_from_slice(&u32::MAX.to_le_bytes());
+    bytes[row(20)..row(20) + 4].copy_from_slice(&0u32.to_le_bytes());
+    let path = std::env::temp_dir().join(format!("ip2location-verify-{}.BIN", std::process::id()));
+    std::fs::write(&path, bytes)?;
+    let report = DB::from_file(&path)?.verify();
+    std::fs::remove_file(&path)?;
+
+    assert!(!report.is_ok());
+    assert_eq!(report.issue_count, 2);
+    assert_eq!(
+        report.issues[0],
+        Issue::OutOfBoundsPointer {
+            ipv6: false,
+            row: 10,
+            column: 2,
+            pointer: u32::MAX
+        }
+    );
+    assert!(matches!(
+        report.issues[1],
+        Issue::UnsortedRange { row: 20, .. }
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_validation() -> Result<(), error::Error> {
+    use crate::Validation;
+
+    let levels = [Validation::HeaderOnly, Validation::Quick, Validation::Full];
+    for path in [IPV4BIN, IPV6BIN, IP2PROXYBIN] {
+        for level in levels {
+            DB::options().path(path).validate(level).open()?;
+        }
+    }
+    let opens = |bytes: &Vec<u8>| {
+        levels.map(|level| {
+            DB::options()
+                .bytes(bytes.clone())
+                .validate(level)
+                .open()
+                .is_ok()
+        })
+    };
+
+    // A bad pointer in a middle row is only seen by a full scan, one in
+    // the last row by the quick check too.
+    let mut bytes = std::fs::read(IPV4BIN)?;
+    let ipv4_count = u32::from_le_bytes(bytes[5..9].try_into().unwrap()) as usize;
+    let ipv4_addr = u32::from_le_bytes(bytes[9..13].try_into().unwrap()) as usize - 1;
+    let row = |n: usize| ipv4_addr + n * 8;
+    bytes[row(10) + 4..row(10) + 8].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert_eq!(opens(&bytes), [true, true, false]);
+    let last = row(ipv4_count - 1);
+    bytes[last + 4..last + 8].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert_eq!(opens(&bytes), [true, false, false]);
+
+    let err = DB::options()
+        .bytes(bytes)
+        .validate(Validation::Full)
+        .open()
+        .unwrap_err();
+    assert_eq!(
+        err,
+        error::Error::GenericError(
+            "invalid database: IPv4 row 10 column 2 points outside the file (4294967295) \
+             (and 1 more issues)"
+                .into()
+        )
+    );
+    Ok(())
+}
+
+#[test]
+fn test_check_invariants() -> Result<(), error::Error> {
+    use crate::{testing::BinBuilder, Issue};
+
+    for path in [IPV4BIN, IPV6BIN, "data/IP2PROXY-IP-COUNTRY.BIN"] {
+        DB::from_file(path)?.check_invariants().unwrap();
+    }
+    BinBuilder::new(1)
+        .ipv6_table()
+        .open()
+        .check_invariants()
+        .unwrap();
+
+    // A bad pointer is left to `verify`; unsorted rows and index entries
+    // are not.
+    let mut bytes = std::fs::read(IPV4BIN)?;
+    let ipv4_addr = u32::from_le_bytes(bytes[9..13].try_into().unwrap()) as usize - 1;
+    let row = |n: usize| ipv4_addr + n * 8;
+    bytes[row(10) + 4..row(10) + 8].copy_from_slice(&u32::MAX.to_le_bytes());
+    let db = DB::from_bytes(bytes.clone())?;
+    db.check_invariants().unwrap();
+    assert!(!db.verify().is_ok());
+
+    bytes[row(20)..row(20) + 4].copy_from_slice(&0u32.to_le_bytes());
+    assert!(matches!(
+        DB::from_bytes(bytes)?.check_invariants(),
+        Err(Issue::UnsortedRange { row: 20, .. })
+    ));
+
+    let mut bytes = std::fs::read(IPV4BIN)?;
+    let index = u32::from_le_bytes(bytes[21..25].try_into().unwrap()) as usize - 1;
+    let entry = index + 100 * 8;
+    let low = u32::from_le_bytes(bytes[entry..entry + 4].try_into().unwrap());
+    bytes[entry..entry + 4].copy_from_slice(&(low + 1).to_le_bytes());
+    assert!(matches!(
+        DB::from_bytes(bytes)?.check_invariants(),
+        Err(Issue::BadIndexEntry {
+            ipv6: false,
+            block: 100,
+            ..
+        })
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_lookup_addr() -> Result<(), error::Error> {
+    use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV6};
+
+    let db = DB::from_file(IPV6BIN)?;
+    let expected = db.ip_lookup("43.224.159.155".parse().unwrap())?;
+    assert_eq!(db.lookup_addr(Ipv4Addr::new(43, 224, 159, 155))?, expected);
+    assert_eq!(db.lookup_addr("43.224.159.155")?, expected);
+    assert_eq!(
+        db.lookup_addr(" 43.224.159.155:8080".to_string())?,
+        expected
+    );
+    let peer: SocketAddr = "43.224.159.155:443".parse().unwrap();
+    assert_eq!(db.lookup_addr(peer)?, expected);
+
+    let ip: Ipv6Addr = "2a01:cb08:8d14::".parse().unwrap();
+    let expected = db.ip_lookup(ip.into())?;
+    assert_eq!(db.lookup_addr(ip)?, expected);
+    assert_eq!(db.lookup_addr(SocketAddrV6::new(ip, 80, 0, 0))?, expected);
+    assert_eq!(db.lookup_addr("[2a01:cb08:8d14::]:80")?, expected);
+    assert!(matches!(
+        db.lookup_addr("2a01:cb08:8d14::zz"),
+        Err(error::Error::GenericError(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_socket_lookup() -> Result<(), error::Error> {
+    use std::net::{IpAddr, SocketAddr, SocketAddrV6};
+
+    let db = DB::from_file(IPV6BIN)?;
+    let ipv4: IpAddr = "43.224.159.155".parse().unwrap();
+    let expected = db.ip_lookup(ipv4)?;
+    for peer in ["43.224.159.155:443", "[::ffff:43.224.159.155]:50123"] {
+        let record = db.socket_lookup(peer.parse().unwrap())?;
+        assert_eq!(record, expected, "{}", peer);
+    }
+    assert_eq!(db.lookup_addr("[::ffff:43.224.159.155]:50123")?, expected);
+
+    let ipv6: IpAddr = "2a01:cb08:8d14::1".parse().unwrap();
+    let expected = db.ip_lookup(ipv6)?;
+    let IpAddr::V6(addr) = ipv6 else {
+        unreachable!()
+    };
+    let scoped = SocketAddr::V6(SocketAddrV6::new(addr, 443, 0, 3));
+    assert_eq!(db.socket_lookup(scoped)?, expected);
+    assert_eq!(db.lookup_addr("2a01:cb08:8d14::1%eth0")?, expected);
+    assert_eq!(db.lookup_addr("[2a01:cb08:8d14::1%3]:443")?, expected);
+    Ok(())
+}
+
+#[test]
+fn test_geo_suite() -> Result<(), error::Error> {
+    use crate::{EnrichedRecord, GeoSuite, LocationDB};
+
+    let suite = GeoSuite::new()
+        .with_location(LocationDB::from_file(IPV4BIN)?)
+        .with_db(DB::from_file("data/IP2PROXY-IP-COUNTRY.BIN")?);
+    assert!(suite.location().is_some() && suite.proxy().is_some());
+
+    let ip = "1.1.1.1".parse().unwrap();
+    let record = suite.lookup(ip)?;
+    assert_eq!(record.ip, ip);
+    assert_eq!(record.location.as_ref().unwrap().ip, ip);
+    assert_eq!(record.proxy.as_ref().unwrap().ip, ip);
+    assert_eq!(record.country().unwrap().short_name, "US");
+    let json = serde_json::to_value(record.into_owned()).unwrap();
+    assert!(json["location"]["country"].is_object());
+    assert!(json["proxy"].is_object());
+
+    // The IPv4 database does not know IPv6 addresses.
+    let record = suite.lookup("2a01:cb08:8d14::".parse().unwrap())?;
+    assert!(matches!(
+        record,
+        EnrichedRecord {
+            location: None,
+            proxy: Some(_),
+            ..
+        }
+    ));
+
+    assert_eq!(
+        GeoSuite::new().lookup(ip),
+        Err(error::Error::RecordNotFound)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_asn_db() -> Result<(), error::Error> {
+    use crate::{AsnDB, GeoSuite};
+
+    // Rows in the format of the IPv6 CSV, out of order.
+    let csv = "\
+\"281470698520576\",\"281470698520831\",\"1.0.0.0/24\",\"13335\",\"CloudFlare Inc\"
+\"0\",\"281470681743359\",\"-\",\"-\",\"-\"
+\"58569107296622255421594597096899477504\",\"58569107375850417935858934690443427839\",\"2c0f:fff0::/32\",\"37125\",\"Layer3 Systems Limited\"
+";
+    let db = AsnDB::from_csv(csv.as_bytes())?;
+    assert_eq!(db.len(), 3);
+
+    let ip = "1.0.0.1".parse().unwrap();
+    let record = db.asn_lookup(ip)?;
+    assert_eq!(record.ip, ip);
+    assert_eq!(record.asn, "13335");
+    assert_eq!(record.as_name, "CloudFlare Inc");
+    assert_eq!(record.cidr, "1.0.0.0/24");
+    assert_eq!(
+        db.asn_lookup("::ffff:1.0.0.200".parse().unwrap())?.asn,
+        "13335"
+    );
+    assert_eq!(db.asn_lookup("2c0f:fff0::1".parse().unwrap())?.asn, "37125");
+    assert_eq!(db.asn_lookup("::1".parse().unwrap())?.asn, "-");
+    assert_eq!(
+        db.asn_lookup("1.0.1.0".parse().unwrap()),
+        Err(error::Error::RecordNotFound)
+    );
+    assert!(AsnDB::from_csv("\"1\",\"2\"\n".as_bytes()).is_err());
+
+    let suite = GeoSuite::new()
+        .with_db(DB::from_file(IPV4BIN)?)
+        .with_asn(db);
+    let record = suite.lookup(ip)?;
+    assert_eq!(record.asn.unwrap().as_name, "CloudFlare Inc");
+    assert!(record.location.is_some());
+    // Only the ASN database knows IPv6 addresses.
+    let record = suite.lookup("2c0f:fff0::1".parse().unwrap())?;
+    assert!(record.location.is_none());
+    assert_eq!(record.asn.unwrap().asn, "37125");
+    Ok(())
+}
+
+#[test]
+fn test_ranges() -> Result<(), error::Error> {
+    let db = DB::from_file(IPV6BIN)?;
+    let ranges = db.ranges();
+    assert_eq!(ranges.len(), 188686 + 138875);
+
+    let mut previous: Option<crate::IpRange> = None;
+    for (i, item) in ranges.enumerate() {
+        let (range, record) = item?;
+        if let Some(previous) = previous.filter(|p| p.is_ipv6() == range.is_ipv6()) {
+            assert!(previous.end < range.start);
+        }
+        if i % 997 == 0 {
+            let mut expected = db.ip_lookup(range.end)?;
+            expected.set_ip(range.start);
+            assert_eq!(record, expected);
+        }
+        previous = Some(range);
+    }
+    assert_eq!(
+        previous.map(|p| p.end),
+        Some("ffff:ffff:ffff:ffff:ffff:ffff:ffff:fffe".parse().unwrap())
+    );
+    Ok(())
+}
+
+#[test]
+fn test_bin_writer() -> Result<(), error::Error> {
+    use crate::writer::BinWriter;
+
+    let db = DB::from_file(IPV6BIN)?;
+    let mut writer = BinWriter::new(1)?.date(20, 12, 28);
+    for item in db.ranges() {
+        let (range, record) = item?;
+        if let Record::LocationDb(record) = record {
+            writer.push(range, &record)?;
+        }
+    }
+    let path = std::env::temp_dir().join(format!("ip2location-writer-{}.BIN", std::process::id()));
+    writer.write_to(&path)?;
+
+    let written = DB::from_file(&path)?;
+    assert!(written.verify().is_ok());
+    assert_eq!(written.ranges().len(), db.ranges().len());
+    for (original, copy) in db.ranges().zip(written.ranges()).step_by(101) {
+        assert_eq!(original?, copy?);
+    }
+    for ip in [
+        "43.224.159.155",
+        "1.1.1.1",
+        "2a01:cb08:8d14::",
+        "::ffff:8.8.8.8",
+    ] {
+        let ip = ip.parse().unwrap();
+        assert_eq!(written.ip_lookup(ip)?, db.ip_lookup(ip)?);
+    }
+    std::fs::remove_file(&path)?;
+
+    let mut writer = BinWriter::new(5)?;
+    writer.read_csv(
+        "\"16777216\",\"16777471\",\"AU\",\"Australia\",\"Queensland\",\"South Brisbane\",\"-27.4767\",\"153.0170\"\n\
+         \"1.0.4.0\",\"1.0.7.255\",\"AU\",\"Australia\",\"Victoria\",\"Melbourne\",\"-37.8140\",\"144.9633\"\n"
+            .as_bytes(),
+    )?;
+    assert_eq!(writer.len(), 2);
+    let path =
+        std::env::temp_dir().join(format!("ip2location-writer-csv-{}.BIN", std::process::id()));
+    writer.write_to(&path)?;
+    let written = DB::from_file(&path)?;
+    assert!(written.verify().is_ok());
+    if let Record::LocationDb(record) = written.ip_lookup("1.0.5.1".parse().unwrap())? {
+        assert_eq!(record.city.as_deref(), Some("Melbourne"));
+        assert_eq!(record.latitude, Some(-37.814));
+        assert_eq!(record.country.unwrap().long_name, "Australia");
+    } else {
+        panic!("expected a location record");
+    }
+    if let Record::LocationDb(record) = written.ip_lookup("1.0.2.1".parse().unwrap())? {
+        assert_eq!(record.city.as_deref(), Some("-"));
+    }
+    std::fs::remove_file(&path)?;
+    assert!(BinWriter::new(27).is_err());
+    assert!(writer.read_csv("\"1\",\"2\",\"AU\"\n".as_bytes()).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_from_bytes() -> Result<(), error::Error> {
+    let file = DB::from_file(IPV6BIN)?;
+    let db = DB::from_bytes(std::fs::read(IPV6BIN)?)?;
+    for ip in ["43.224.159.155", "2a01:cb08:8d14::", "::ffff:1.1.1.1"] {
+        let ip = ip.parse().unwrap();
+        assert_eq!(db.ip_lookup(ip)?, file.ip_lookup(ip)?);
+    }
+    assert_eq!(db.info().path, "<memory>");
+    let stats = db.memory_stats();
+    assert_eq!(stats.resident_bytes, Some(stats.mapped_bytes));
+
+    let db = DB::from_bytes(std::fs::read(IP2PROXYBIN)?)?;
+    assert!(matches!(db, DB::ProxyDb(_)));
+    Ok(())
+}
+
+#[test]
+fn test_ip_resolver() -> Result<(), error::Error> {
+    use crate::{IpResolver, LocationDB, LocationRecord};
+    use std::{net::IpAddr, sync::Arc};
+
+    struct Fixed;
+    impl IpResolver for Fixed {
+        fn resolve(&self, ip: IpAddr) -> Result<Record<'_>, error::Error> {
+            Ok(Record::LocationDb(Box::new(LocationRecord {
+                ip,
+                ..Default::default()
+            })))
+        }
+    }
+
+    let ip: IpAddr = "43.224.159.155".parse().unwrap();
+    let db = DB::from_file(IPV4BIN)?;
+    let expected = db.ip_lookup(ip)?;
+    let resolvers: Vec<Box<dyn IpResolver>> = vec![
+        Box::new(DB::from_file(IPV4BIN)?),
+        Box::new(LocationDB::from_file(IPV4BIN)?),
+        Box::new(Arc::new(crate::ReloadableDb::open(IPV4BIN)?)),
+    ];
+    for resolver in &resolvers {
+        assert_eq!(resolver.resolve(ip)?, expected);
+    }
+    let by_ref: &dyn IpResolver = &db;
+    assert_eq!(by_ref.resolve(ip)?, expected);
+    assert!(matches!(
+        DB::from_file(IP2PROXYBIN)?.resolve("1.1.1.1".parse().unwrap())?,
+        Record::ProxyDb(_)
+    ));
+    assert_eq!(
+        Fixed.resolve(ip)?,
+        Record::LocationDb(Box::new(LocationRecord {
+            ip,
+            ..Default::default()
+        }))
+    );
+    Ok(())
+}
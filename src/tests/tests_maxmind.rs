@@ -0,0 +1,61 @@
+use crate::{error::Error, maxmind::MaxMindDb, IpResolver, Record};
+use maxminddb_writer::{metadata::IpVersion, paths::IpAddrWithMask, Database};
+
+/// A GeoLite2-City shaped database with a single IPv4 /24.
+fn city_db() -> Vec<u8> {
+    let mut db = Database::default();
+    db.metadata.ip_version = IpVersion::V6;
+    db.metadata.database_type = "GeoLite2-City".to_string();
+    db.metadata.binary_format_major_version = 2;
+    db.metadata.languages = vec!["en".to_string()];
+    let data = db
+        .insert_value(serde_json::json!({
+            "city": {"names": {"en": "Mumbai"}},
+            "country": {"iso_code": "IN", "names": {"en": "India"}},
+            "location": {"latitude": 19.07, "longitude": 72.88, "time_zone": "Asia/Kolkata"},
+            "postal": {"code": "400001"},
+            "subdivisions": [{"iso_code": "MH", "names": {"en": "Maharashtra"}}],
+        }))
+        .unwrap();
+    db.insert_node("::2be0:9f00/120".parse::<IpAddrWithMask>().unwrap(), data);
+    let mut bytes = Vec::new();
+    db.write_to(&mut bytes).unwrap();
+    bytes
+}
+
+#[test]
+fn test_maxmind_lookup() {
+    let db = MaxMindDb::from_bytes(city_db()).unwrap();
+    assert_eq!(db.database_type(), "GeoLite2-City");
+    let ip = "43.224.159.155".parse().unwrap();
+    let record = db.ip_lookup(ip).unwrap();
+    assert_eq!(record.ip, ip);
+    let country = record.country.unwrap();
+    assert_eq!(country.short_name, "IN");
+    assert_eq!(country.long_name, "India");
+    assert_eq!(record.region.as_deref(), Some("Maharashtra"));
+    assert_eq!(record.city.as_deref(), Some("Mumbai"));
+    assert_eq!(record.zip_code.as_deref(), Some("400001"));
+    assert_eq!(record.time_zone.as_deref(), Some("Asia/Kolkata"));
+    assert_eq!(record.latitude, Some(19.07));
+    assert_eq!(record.isp, None);
+
+    assert_eq!(
+        db.ip_lookup("43.224.160.1".parse().unwrap()),
+        Err(Error::RecordNotFound)
+    );
+    assert!(matches!(
+        MaxMindDb::from_bytes(vec![0; 64]),
+        Err(Error::GenericError(_))
+    ));
+}
+
+#[test]
+fn test_maxmind_resolver() {
+    let db = MaxMindDb::from_bytes(city_db()).unwrap();
+    let resolver: &dyn IpResolver = &db;
+    match resolver.resolve("43.224.159.1".parse().unwrap()).unwrap() {
+        Record::LocationDb(record) => assert_eq!(record.city.as_deref(), Some("Mumbai")),
+        Record::ProxyDb(_) => unreachable!(),
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{error, DB};
+use metrics::{
+    Counter, CounterFn, Gauge, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder,
+    SharedString, Unit,
+};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
+
+#[derive(Default)]
+struct Handle(AtomicU64);
+
+impl CounterFn for Handle {
+    fn increment(&self, value: u64) {
+        self.0.fetch_add(value, Ordering::Relaxed);
+    }
+
+    fn absolute(&self, value: u64) {
+        self.0.store(value, Ordering::Relaxed);
+    }
+}
+
+impl HistogramFn for Handle {
+    fn record(&self, _value: f64) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Default)]
+struct TestRecorder {
+    handles: Mutex<Vec<(Key, Arc<Handle>)>>,
+}
+
+impl TestRecorder {
+    fn handle(&self, key: &Key) -> Arc<Handle> {
+        let mut handles = self.handles.lock().unwrap();
+        if let Some((_, handle)) = handles.iter().find(|(k, _)| k == key) {
+            return handle.clone();
+        }
+        let handle = Arc::new(Handle::default());
+        handles.push((key.clone(), handle.clone()));
+        handle
+    }
+
+    fn value(&self, name: &str, outcome: &str) -> u64 {
+        let handles = self.handles.lock().unwrap();
+        handles
+            .iter()
+            .filter(|(k, _)| k.name() == name)
+            .filter(|(k, _)| {
+                k.labels()
+                    .any(|l| l.key() == "outcome" && l.value() == outcome)
+            })
+            .map(|(_, h)| h.0.load(Ordering::Relaxed))
+            .sum()
+    }
+}
+
+impl Recorder for TestRecorder {
+    fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+    fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+    fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+        Counter::from_arc(self.handle(key))
+    }
+
+    fn register_gauge(&self, _: &Key, _: &Metadata<'_>) -> Gauge {
+        Gauge::noop()
+    }
+
+    fn register_histogram(&self, key: &Key, _: &Metadata<'_>) -> Histogram {
+        Histogram::from_arc(self.handle(key))
+    }
+}
+
+#[test]
+fn test_lookup_metrics() -> Result<(), error::Error> {
+    let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN")?;
+    let recorder = TestRecorder::default();
+    metrics::with_local_recorder(&recorder, || {
+        crate::describe_metrics();
+        for ip in ["43.224.159.155", "1.1.1.1", "2a01:b600:8001::"] {
+            let _ = db.ip_lookup(ip.parse().unwrap());
+        }
+    });
+    assert_eq!(recorder.value("ip2location_lookups_total", "hit"), 2);
+    assert_eq!(recorder.value("ip2location_lookups_total", "miss"), 1);
+    assert_eq!(
+        recorder.value("ip2location_lookup_duration_seconds", "hit"),
+        2
+    );
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use crate::mobile::{GeoDb, GeoError};
+
+#[test]
+fn test_mobile_lookup() {
+    let bytes = std::fs::read("data/IP2LOCATION-LITE-DB1.IPV6.BIN").unwrap();
+    let db = GeoDb::from_bytes(bytes).unwrap();
+    let record = db.ip_lookup("43.224.159.155".to_string()).unwrap().unwrap();
+    assert_eq!(record.ip, "43.224.159.155");
+    assert_eq!(record.country_code.as_deref(), Some("IN"));
+    assert_eq!(record.country_name.as_deref(), Some("India"));
+    assert_eq!(record.city, None);
+    assert!(matches!(
+        db.ip_lookup("nope".to_string()),
+        Err(GeoError::InvalidAddress(_))
+    ));
+    let json = db
+        .ip_lookup_json("2a01:cb08:8d14::".to_string())
+        .unwrap()
+        .unwrap();
+    assert!(json.contains("\"FR\""));
+
+    let proxy = GeoDb::from_bytes(std::fs::read("data/IP2PROXY-IP-COUNTRY.BIN").unwrap()).unwrap();
+    assert!(matches!(
+        proxy.ip_lookup("1.1.1.1".to_string()),
+        Err(GeoError::NotLocationDb)
+    ));
+    assert!(proxy
+        .ip_lookup_json("1.1.1.1".to_string())
+        .unwrap()
+        .is_some());
+}
@@ -0,0 +1,45 @@
+use crate::{error::Error, testing::BinBuilder, LocationRecord, ProxyRecord, Record, DB};
+
+const IP2PROXYBIN: &str = "data/IP2PROXY-IP-COUNTRY.BIN";
+
+#[test]
+fn test_location_msgpack() -> Result<(), Error> {
+    let db = BinBuilder::new(5)
+        .range(
+            "1.0.0.0",
+            "1.0.0.255",
+            LocationRecord {
+                latitude: Some(48.8534),
+                longitude: Some(2.3488),
+                ..LocationRecord::default()
+            },
+        )
+        .open();
+    let record = db.ip_lookup("1.0.0.1".parse().unwrap())?;
+    let Record::LocationDb(location) = &record else {
+        unreachable!()
+    };
+
+    let bytes = record.to_msgpack();
+    assert_eq!(bytes, location.to_msgpack());
+    assert!(bytes.len() < location.to_json().len());
+    let decoded = LocationRecord::from_msgpack(&bytes)?;
+    assert_eq!(&decoded, &**location);
+    // Fields the database does not have stay unset.
+    assert_eq!(decoded.isp, None);
+
+    assert!(LocationRecord::from_msgpack(&bytes[..bytes.len() / 2]).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_proxy_msgpack() -> Result<(), Error> {
+    let db = DB::from_file(IP2PROXYBIN)?;
+    for ip in ["1.1.1.1", "43.224.159.155"] {
+        let Record::ProxyDb(record) = db.ip_lookup(ip.parse().unwrap())? else {
+            unreachable!()
+        };
+        assert_eq!(ProxyRecord::from_msgpack(&record.to_msgpack())?, *record);
+    }
+    Ok(())
+}
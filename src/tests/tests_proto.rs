@@ -0,0 +1,44 @@
+use crate::{error::Error, proto, DB};
+use prost::Message;
+
+const IPV4BIN: &str = "data/IP2LOCATION-LITE-DB1.BIN";
+const IP2PROXYBIN: &str = "data/IP2PROXY-IP-COUNTRY.BIN";
+
+#[test]
+fn test_proto_location_record() -> Result<(), Error> {
+    let db = DB::from_file(IPV4BIN)?;
+    let record = db.ip_lookup("43.224.159.155".parse().unwrap())?;
+    let message = proto::Record::from(record);
+    let decoded = proto::Record::decode(message.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(decoded, message);
+
+    let Some(proto::record::Record::Location(location)) = decoded.record else {
+        panic!("not a location record: {:?}", decoded);
+    };
+    assert_eq!(location.ip, "43.224.159.155");
+    let country = location.country.unwrap();
+    assert_eq!(
+        (country.short_name.as_str(), country.long_name.as_str()),
+        ("IN", "India")
+    );
+    // DB1 has no coordinates nor city, which stay unset rather than empty.
+    assert_eq!((location.latitude, location.city), (None, None));
+    Ok(())
+}
+
+#[test]
+fn test_proto_proxy_record() -> Result<(), Error> {
+    let db = DB::from_file(IP2PROXYBIN)?;
+    let record = db.ip_lookup("1.1.1.1".parse().unwrap())?;
+    let is_proxy = match &record {
+        crate::Record::ProxyDb(rec) => rec.is_proxy.clone(),
+        crate::Record::LocationDb(_) => unreachable!(),
+    };
+    let Some(proto::record::Record::Proxy(proxy)) = proto::Record::from(record).record else {
+        panic!("not a proxy record");
+    };
+    assert_eq!(proxy.ip, "1.1.1.1");
+    assert_eq!(proxy.is_proxy(), proto::ProxyStatus::from(is_proxy));
+    assert_ne!(proxy.is_proxy(), proto::ProxyStatus::Unspecified);
+    Ok(())
+}
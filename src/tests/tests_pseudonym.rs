@@ -0,0 +1,22 @@
+use crate::IpHasher;
+
+#[test]
+fn test_ip_hasher() {
+    let hasher = IpHasher::new(b"key");
+    assert_eq!(
+        hasher.hash_hex("203.0.113.7".parse().unwrap()),
+        "8e827c2c1c9420f5ebd4d4a2fd3f75c454369763fe48fe879c24e668e272a93c"
+    );
+    assert_eq!(
+        hasher.hash("::ffff:203.0.113.7".parse().unwrap()),
+        hasher.hash("203.0.113.7".parse().unwrap())
+    );
+    assert_ne!(
+        hasher.hash("203.0.113.8".parse().unwrap()),
+        hasher.hash("203.0.113.7".parse().unwrap())
+    );
+    assert_ne!(
+        IpHasher::new(b"other key").hash("203.0.113.7".parse().unwrap()),
+        hasher.hash("203.0.113.7".parse().unwrap())
+    );
+}
@@ -0,0 +1,34 @@
+use crate::python::PyDb;
+use pyo3::{prelude::*, types::PyDict};
+
+#[test]
+fn test_python_lookup() {
+    Python::initialize();
+    Python::attach(|py| {
+        let db = py
+            .get_type::<PyDb>()
+            .call1(("data/IP2LOCATION-LITE-DB1.IPV6.BIN",))
+            .unwrap();
+        let record = db.call_method1("ip_lookup", ("43.224.159.155",)).unwrap();
+        let record = record.cast::<PyDict>().unwrap();
+        let country = record.get_item("country").unwrap().unwrap();
+        assert_eq!(
+            country
+                .get_item("short_name")
+                .unwrap()
+                .extract::<String>()
+                .unwrap(),
+            "IN"
+        );
+        assert!(record.get_item("city").unwrap().is_none());
+
+        assert!(db.call_method1("ip_lookup", ("nope",)).is_err());
+        let info = db.call_method0("info").unwrap();
+        assert_eq!(
+            info.get_item("db_type").unwrap().extract::<u8>().unwrap(),
+            1
+        );
+        assert!(db.repr().unwrap().to_string().starts_with("DB(path="));
+        assert!(py.get_type::<PyDb>().call1(("data/missing.BIN",)).is_err());
+    });
+}
@@ -0,0 +1,49 @@
+use crate::{error::Error, testing::BinBuilder, LocationDB};
+use rand::{rngs::StdRng, SeedableRng};
+use std::net::IpAddr;
+
+const IPV6BIN: &str = "data/IP2LOCATION-LITE-DB1.IPV6.BIN";
+
+#[test]
+fn test_sample_ips() -> Result<(), Error> {
+    let bytes = BinBuilder::new(1)
+        .country_range("1.0.0.0", "1.0.0.255", "FR", "France")
+        .country_range("1.0.1.0", "1.0.1.255", "DE", "Germany")
+        .country_range("1.0.2.0", "1.0.3.255", "FR", "France")
+        .build();
+    let db = LocationDB::from_bytes(bytes)?;
+    let mut rng = StdRng::seed_from_u64(7);
+
+    let sample = db.sample_ips("fr", 1000, &mut rng)?;
+    assert_eq!(sample.len(), 1000);
+    let first: IpAddr = "1.0.0.0".parse().unwrap();
+    let last: IpAddr = "1.0.3.255".parse().unwrap();
+    assert!(sample.iter().all(|ip| (first..=last).contains(ip)));
+    let in_first = sample
+        .iter()
+        .filter(|ip| **ip <= "1.0.0.255".parse::<IpAddr>().unwrap())
+        .count();
+    assert!((250..420).contains(&in_first), "{in_first}");
+    for ip in sample {
+        let record = db.ip_lookup(ip)?;
+        assert_eq!(record.country.unwrap().short_name, "FR");
+    }
+
+    assert!(db.sample_ips("DE", 0, &mut rng)?.is_empty());
+    assert_eq!(db.sample_ips("XX", 1, &mut rng), Err(Error::RecordNotFound));
+    Ok(())
+}
+
+#[test]
+fn test_sample_ips_skips_ipv4_mapped() -> Result<(), Error> {
+    let db = LocationDB::from_file(IPV6BIN)?;
+    let mut rng = StdRng::seed_from_u64(7);
+    for ip in db.sample_ips("CN", 100, &mut rng)? {
+        if let IpAddr::V6(ip) = ip {
+            assert!(ip.to_ipv4_mapped().is_none());
+        }
+        let record = db.ip_lookup(ip)?;
+        assert_eq!(record.country.unwrap().short_name, "CN");
+    }
+    Ok(())
+}
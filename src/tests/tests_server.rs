@@ -0,0 +1,37 @@
+use crate::{server, ReloadableDb};
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+async fn get(uri: &str) -> (StatusCode, serde_json::Value) {
+    let db = Arc::new(ReloadableDb::open("data/IP2LOCATION-LITE-DB1.IPV6.BIN").unwrap());
+    let response = server::router(db)
+        .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_server_routes() {
+    let (status, body) = get("/v1/lookup/2a01:cb08:8d14::").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["ip"], "2a01:cb08:8d14::");
+    assert_eq!(body["country"]["short_name"], "FR");
+
+    let (status, body) = get("/v1/lookup/nope").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "invalid IP address: nope");
+
+    let (status, body) = get("/v1/info").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["kind"], "location");
+    assert_eq!(body["ipv6_count"], 138876);
+}
@@ -0,0 +1,37 @@
+use crate::{error, service::DbService, Record, DB};
+use std::time::Duration;
+use tower::{ServiceBuilder, ServiceExt};
+
+#[tokio::test]
+async fn test_service_lookup() -> Result<(), error::Error> {
+    let service = DbService::new(DB::from_file("data/IP2LOCATION-LITE-DB1.BIN")?);
+    let record = service
+        .clone()
+        .oneshot("43.224.159.155".parse().unwrap())
+        .await?;
+    match record {
+        Record::LocationDb(rec) => assert_eq!(rec.country.unwrap().short_name, "IN"),
+        Record::ProxyDb(_) => unreachable!(),
+    }
+
+    let err = service
+        .oneshot("2a01:b600:8001::".parse().unwrap())
+        .await
+        .unwrap_err();
+    assert_eq!(err, error::Error::RecordNotFound);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_service_layers() {
+    let service = ServiceBuilder::new()
+        .timeout(Duration::from_secs(1))
+        .service(DbService::new(
+            DB::from_file("data/IP2LOCATION-LITE-DB1.BIN").unwrap(),
+        ));
+    let record = service
+        .oneshot("43.224.159.155".parse().unwrap())
+        .await
+        .unwrap();
+    assert!(matches!(record, Record::LocationDb(_)));
+}
@@ -0,0 +1,409 @@
+use crate::{
+    common::{DefaultSource, RowReader, RowSource, Storage},
+    error::Error,
+    offset::FileOffset,
+    Backend, DbOptions, IpResolver, LocationDB, ProxyDB, Source, DB,
+};
+use std::{
+    net::{IpAddr, Ipv6Addr},
+    path::PathBuf,
+    sync::Arc,
+};
+
+const IPV6: u128 = 0x2001_0db8_0000_0000_0000_0000_0000_0001;
+
+/// Length of the buffer of `primitives`.
+const LEN: u64 = 40;
+
+/// A buffer with one value of each primitive: a byte at 0, a `u32` at 1,
+/// an `f32` at 5, an IPv6 address at 9, strings at 25 and 29, a pointer to
+/// the second string at 35 and a last byte at 39.
+fn primitives() -> Vec<u8> {
+    let mut bytes = vec![0x11];
+    bytes.extend_from_slice(&0x0403_0201_u32.to_le_bytes());
+    bytes.extend_from_slice(&1.5_f32.to_le_bytes());
+    bytes.extend_from_slice(&IPV6.to_le_bytes());
+    bytes.extend_from_slice(b"\x03abc");
+    bytes.extend_from_slice(b"\x05hello");
+    bytes.extend_from_slice(&29_u32.to_le_bytes());
+    bytes.push(0x7f);
+    assert_eq!(bytes.len() as u64, LEN);
+    bytes
+}
+
+type Storages = Vec<(Backend, Storage<DefaultSource>)>;
+
+/// The primitives read from memory, from a mapping and from the file.
+fn sources(name: &str) -> Result<(PathBuf, Storages), Error> {
+    let bytes = primitives();
+    let path = std::env::temp_dir().join(format!(
+        "ip2location-source-{}-{}.BIN",
+        name,
+        std::process::id()
+    ));
+    std::fs::write(&path, &bytes)?;
+    let mut sources = vec![(Backend::InMemory, Storage::from_bytes(Arc::from(bytes)))];
+    for backend in [Backend::Mmap, Backend::Pread] {
+        let options = DbOptions::new().backend(backend);
+        sources.push((backend, Storage::open(&path, &options)?));
+    }
+    Ok((path, sources))
+}
+
+fn is_corrupt<T: core::fmt::Debug>(result: Result<T, Error>) -> bool {
+    matches!(result, Err(Error::Corrupt(_)))
+}
+
+#[test]
+fn test_file_offset() {
+    assert_eq!(FileOffset::from_position(1), FileOffset::new(0));
+    assert_eq!(FileOffset::from_position(65), FileOffset::new(64));
+    assert_eq!(FileOffset::from_position(0), FileOffset::new(0));
+    assert_eq!(FileOffset::from(7_u32), FileOffset::new(7));
+    assert_eq!(FileOffset::new(10) + 6, FileOffset::new(16));
+    assert_eq!(FileOffset::new(10).column(1), FileOffset::new(10));
+    assert_eq!(FileOffset::new(10).column(3), FileOffset::new(18));
+    assert_eq!(FileOffset::new(10).since(FileOffset::new(4)), Some(6));
+    assert_eq!(FileOffset::new(4).since(FileOffset::new(10)), None);
+    assert_eq!(FileOffset::new(42).to_string(), "42");
+}
+
+#[test]
+fn test_read_u8() -> Result<(), Error> {
+    let (path, sources) = sources("u8")?;
+    for (backend, source) in &sources {
+        assert_eq!(source.read_u8(FileOffset::new(0))?, 0x11, "{:?}", backend);
+        assert_eq!(source.read_u8(FileOffset::new(1))?, 0x01, "{:?}", backend);
+        assert_eq!(
+            source.read_u8(FileOffset::new(LEN - 1))?,
+            0x7f,
+            "{:?}",
+            backend
+        );
+        assert!(
+            is_corrupt(source.read_u8(FileOffset::new(LEN))),
+            "{:?}",
+            backend
+        );
+        assert!(
+            is_corrupt(source.read_u8(FileOffset::new(u64::MAX))),
+            "{:?}",
+            backend
+        );
+    }
+    let _ = std::fs::remove_file(path);
+    Ok(())
+}
+
+#[test]
+fn test_read_u32() -> Result<(), Error> {
+    let (path, sources) = sources("u32")?;
+    for (backend, source) in &sources {
+        assert_eq!(
+            source.read_u32(FileOffset::new(0))?,
+            0x0302_0111,
+            "{:?}",
+            backend
+        );
+        assert_eq!(
+            source.read_u32(FileOffset::new(1))?,
+            0x0403_0201,
+            "{:?}",
+            backend
+        );
+        assert_eq!(source.read_u32(FileOffset::new(35))?, 29, "{:?}", backend);
+        // The last four bytes, reaching the end exactly.
+        assert_eq!(
+            source.read_u32(FileOffset::new(LEN - 4))?,
+            u32::from_le_bytes([0, 0, 0, 0x7f]),
+            "{:?}",
+            backend
+        );
+        assert!(
+            is_corrupt(source.read_u32(FileOffset::new(LEN - 3))),
+            "{:?}",
+            backend
+        );
+        assert!(
+            is_corrupt(source.read_u32(FileOffset::new(LEN))),
+            "{:?}",
+            backend
+        );
+    }
+    let _ = std::fs::remove_file(path);
+    Ok(())
+}
+
+#[test]
+fn test_read_f32() -> Result<(), Error> {
+    let (path, sources) = sources("f32")?;
+    for (backend, source) in &sources {
+        assert_eq!(source.read_f32(FileOffset::new(5))?, 1.5, "{:?}", backend);
+        assert_eq!(
+            source.read_f32(FileOffset::new(LEN - 4))?.to_bits(),
+            u32::from_le_bytes([0, 0, 0, 0x7f]),
+            "{:?}",
+            backend
+        );
+        assert!(
+            is_corrupt(source.read_f32(FileOffset::new(LEN - 3))),
+            "{:?}",
+            backend
+        );
+    }
+    let _ = std::fs::remove_file(path);
+    Ok(())
+}
+
+#[test]
+fn test_read_ipv6() -> Result<(), Error> {
+    let (path, sources) = sources("ipv6")?;
+    let bytes = primitives();
+    let last = u128::from_le_bytes(bytes[bytes.len() - 16..].try_into().unwrap());
+    for (backend, source) in &sources {
+        assert_eq!(
+            source.read_ipv6(FileOffset::new(9))?,
+            Ipv6Addr::from(IPV6),
+            "{:?}",
+            backend
+        );
+        assert_eq!(
+            source.read_ipv6(FileOffset::new(LEN - 16))?,
+            Ipv6Addr::from(last),
+            "{:?}",
+            backend
+        );
+        assert!(
+            is_corrupt(source.read_ipv6(FileOffset::new(LEN - 15))),
+            "{:?}",
+            backend
+        );
+    }
+    let _ = std::fs::remove_file(path);
+    Ok(())
+}
+
+#[test]
+fn test_read_str() -> Result<(), Error> {
+    let (path, sources) = sources("str")?;
+    for (backend, source) in &sources {
+        assert_eq!(
+            source.read_str(FileOffset::new(25))?,
+            "abc",
+            "{:?}",
+            backend
+        );
+        assert_eq!(
+            source.read_str(FileOffset::new(29))?,
+            "hello",
+            "{:?}",
+            backend
+        );
+        // The last byte taken for the length of a string past the end.
+        assert!(
+            is_corrupt(source.read_str(FileOffset::new(LEN - 1))),
+            "{:?}",
+            backend
+        );
+        assert!(
+            is_corrupt(source.read_str(FileOffset::new(LEN))),
+            "{:?}",
+            backend
+        );
+    }
+    let _ = std::fs::remove_file(path);
+    Ok(())
+}
+
+#[test]
+fn test_read_bytes() -> Result<(), Error> {
+    let (path, sources) = sources("bytes")?;
+    let bytes = primitives();
+    for (backend, source) in &sources {
+        let all = source.read_bytes(FileOffset::new(0), LEN as usize)?;
+        assert_eq!(*all, bytes[..], "{:?}", backend);
+        let abc = source.read_bytes(FileOffset::new(25), 4)?;
+        assert_eq!(*abc, *b"\x03abc", "{:?}", backend);
+        assert!(
+            source.read_bytes(FileOffset::new(LEN), 0)?.is_empty(),
+            "{:?}",
+            backend
+        );
+        assert!(
+            is_corrupt(source.read_bytes(FileOffset::new(LEN - 2), 3)),
+            "{:?}",
+            backend
+        );
+
+        let (mut head, mut tail, mut past) = ([0; 4], [0; 4], [0; 4]);
+        let read = source.read_batch(&mut [
+            (FileOffset::new(0), &mut head),
+            (FileOffset::new(LEN - 2), &mut tail),
+            (FileOffset::new(LEN + 4), &mut past),
+        ])?;
+        assert_eq!(read, [4, 2, 0], "{:?}", backend);
+        assert_eq!(head, bytes[..4], "{:?}", backend);
+        assert_eq!(tail[..2], bytes[bytes.len() - 2..], "{:?}", backend);
+    }
+    let _ = std::fs::remove_file(path);
+    Ok(())
+}
+
+#[test]
+fn test_row_reader() -> Result<(), Error> {
+    let (path, sources) = sources("row")?;
+    for (backend, source) in &sources {
+        // A row of two words whose second is the pointer to "hello".
+        let row = RowReader::new(source, FileOffset::new(31), 2, &[1], None)?;
+        assert_eq!(row.read_u32(FileOffset::new(35))?, 29, "{:?}", backend);
+        assert_eq!(row.read_str(FileOffset::new(29))?, "hello", "{:?}", backend);
+        // Outside of the row and its strings, the source is read.
+        assert_eq!(row.read_f32(FileOffset::new(5))?, 1.5, "{:?}", backend);
+        assert_eq!(row.read_str(FileOffset::new(25))?, "abc", "{:?}", backend);
+        assert!(
+            is_corrupt(row.read_u32(FileOffset::new(LEN - 3))),
+            "{:?}",
+            backend
+        );
+        assert!(
+            is_corrupt(row.read_str(FileOffset::new(LEN))),
+            "{:?}",
+            backend
+        );
+    }
+    let _ = std::fs::remove_file(path);
+    Ok(())
+}
+
+/// Reads the primitives through the methods of `Source`.
+fn check_source<S: Source + ?Sized>(name: &str, source: &S) -> Result<(), Error> {
+    assert_eq!(source.len(), LEN, "{}", name);
+    let mut word = [0; 4];
+    source.read_exact_at(FileOffset::new(1), &mut word)?;
+    assert_eq!(u32::from_le_bytes(word), 0x0403_0201, "{}", name);
+    assert!(
+        source
+            .read_exact_at(FileOffset::new(LEN - 3), &mut word)
+            .is_err(),
+        "{}",
+        name
+    );
+    let (mut ip, mut text, mut last) = ([0; 16], [0; 6], [0; 4]);
+    let read = source.read_batch(&mut [
+        (FileOffset::new(9), &mut ip),
+        (FileOffset::new(29), &mut text),
+        (FileOffset::new(LEN - 1), &mut last),
+    ])?;
+    assert_eq!(read, [16, 6, 1], "{}", name);
+    assert_eq!(
+        Ipv6Addr::from(u128::from_le_bytes(ip)),
+        Ipv6Addr::from(IPV6)
+    );
+    assert_eq!(&text, b"\x05hello", "{}", name);
+    assert_eq!(last[0], 0x7f, "{}", name);
+    Ok(())
+}
+
+#[test]
+fn test_source_trait() -> Result<(), Error> {
+    let bytes = primitives();
+    let (path, _) = sources("trait")?;
+    check_source("slice", bytes.as_slice())?;
+    check_source("vec", &bytes)?;
+    check_source("file", &std::fs::File::open(&path)?)?;
+    let _ = std::fs::remove_file(path);
+    Ok(())
+}
+
+/// A source that panics when asked for bytes it does not have.
+struct Strict(Vec<u8>);
+
+impl Source for Strict {
+    fn len(&self) -> u64 {
+        self.0.len() as u64
+    }
+
+    fn read_exact_at(&self, offset: FileOffset, buf: &mut [u8]) -> Result<(), Error> {
+        let start = offset.get() as usize;
+        buf.copy_from_slice(&self.0[start..start + buf.len()]);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_from_source() -> Result<(), Error> {
+    let ips: Vec<IpAddr> = (0..500u32)
+        .map(|i| IpAddr::from((i * 8_589_934).to_be_bytes()))
+        .chain([
+            "2a01:b600:8001::".parse().unwrap(),
+            "2001:db8::1".parse().unwrap(),
+        ])
+        .collect();
+    for path in [
+        "data/IP2LOCATION-LITE-DB1.BIN",
+        "data/IP2LOCATION-LITE-DB1.IPV6.BIN",
+        "data/IP2PROXY-IP-COUNTRY.BIN",
+    ] {
+        let bytes = std::fs::read(path)?;
+        let db = DB::from_source(Strict(bytes.clone()))?;
+        let expected = DB::from_file(path)?;
+        assert_eq!(db.kind(), expected.kind());
+        for &ip in &ips {
+            assert_eq!(
+                db.ip_lookup_owned(ip),
+                expected.ip_lookup_owned(ip),
+                "{}",
+                ip
+            );
+        }
+
+        // Truncated, the database fails to open rather than the source
+        // being read past its end.
+        let truncated = Strict(bytes[..bytes.len() / 2].to_vec());
+        assert!(matches!(DB::from_source(truncated), Err(Error::Corrupt(_))));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_generic_db() -> Result<(), Error> {
+    let ips: Vec<IpAddr> = (0..500u32)
+        .map(|i| IpAddr::from((i * 8_589_934).to_be_bytes()))
+        .chain(["2a01:b600:8001::".parse().unwrap()])
+        .collect();
+
+    let path = "data/IP2LOCATION-LITE-DB1.IPV6.BIN";
+    let db: LocationDB<Strict> = LocationDB::from_source(Strict(std::fs::read(path)?))?;
+    let expected = LocationDB::from_file(path)?;
+    for &ip in &ips {
+        assert_eq!(
+            db.ip_lookup_owned(ip),
+            expected.ip_lookup_owned(ip),
+            "{}",
+            ip
+        );
+        assert_eq!(
+            db.resolve(ip).map(|record| record.into_owned()),
+            expected.resolve(ip).map(|record| record.into_owned())
+        );
+    }
+    assert_eq!(db.verify().issues, expected.verify().issues);
+
+    let path = "data/IP2PROXY-IP-COUNTRY.BIN";
+    let options = DbOptions::default().preload_index(true);
+    let db = ProxyDB::from_source_with_options(std::fs::read(path)?, &options)?;
+    let expected = ProxyDB::from_file(path)?;
+    for &ip in &ips {
+        assert_eq!(
+            db.ip_lookup_owned(ip),
+            expected.ip_lookup_owned(ip),
+            "{}",
+            ip
+        );
+    }
+    assert_eq!(
+        db.memory_stats().resident_bytes,
+        Some(db.file_size() as usize)
+    );
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use crate::{error::Error, testing::BinBuilder, LocationDB, LocationRecord};
+use std::borrow::Cow;
+
+const IPV4BIN: &str = "data/IP2LOCATION-LITE-DB1.BIN";
+
+fn cities() -> LocationDB {
+    let city = |name, latitude, longitude| LocationRecord {
+        city: Some(Cow::Borrowed(name)),
+        latitude: Some(latitude),
+        longitude: Some(longitude),
+        ..LocationRecord::default()
+    };
+    let bytes = BinBuilder::new(5)
+        .range("1.0.0.0", "1.0.0.255", city("London", 51.5, -0.12))
+        .range("1.0.1.0", "1.0.1.255", city("Paris", 48.86, 2.35))
+        .range("1.0.2.0", "1.0.2.255", city("London", 51.5, -0.12))
+        .range("1.0.3.0", "1.0.3.255", city("Tokyo", 35.68, 139.69))
+        .range("1.0.4.0", "1.0.4.255", city("Suva", -18.14, 178.44))
+        .build();
+    LocationDB::from_bytes(bytes).unwrap()
+}
+
+#[test]
+fn test_spatial_index_nearest() -> Result<(), Error> {
+    let db = cities();
+    let index = db.build_spatial_index()?;
+    assert_eq!(index.len(), 5);
+    for (lat, lon) in [(50.85, 4.35), (-33.87, 151.21), (0.0, 0.0), (64.0, -150.0)] {
+        assert_eq!(index.nearest(&db, lat, lon, 3)?, db.nearest(lat, lon, 3)?);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_spatial_index_ranges_in_bbox() -> Result<(), Error> {
+    let db = cities();
+    let index = db.build_spatial_index()?;
+    for (min_lat, min_lon, max_lat, max_lon) in [
+        (51.28, -0.51, 51.69, 0.33),
+        (40.0, -10.0, 60.0, 10.0),
+        (-60.0, 170.0, 60.0, -170.0),
+        (-90.0, -180.0, 90.0, 180.0),
+    ] {
+        let scanned = db
+            .ranges_in_bbox(min_lat, min_lon, max_lat, max_lon)
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            index.ranges_in_bbox(&db, min_lat, min_lon, max_lat, max_lon)?,
+            scanned
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_spatial_index_checks_database() -> Result<(), Error> {
+    let index = cities().build_spatial_index()?;
+    let db = LocationDB::from_file(IPV4BIN)?;
+    assert!(db.build_spatial_index()?.is_empty());
+    assert!(index.nearest(&db, 0.0, 0.0, 1).is_err());
+    assert!(index
+        .ranges_in_bbox(&db, -90.0, -180.0, 90.0, 180.0)
+        .is_err());
+    Ok(())
+}
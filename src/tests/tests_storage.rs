@@ -0,0 +1,49 @@
+use crate::{
+    error,
+    opendal::{services::Memory, Operator},
+    DbOptions, Record, DB,
+};
+
+const IPV4BIN: &str = "data/IP2LOCATION-LITE-DB1.BIN";
+
+#[tokio::test]
+async fn test_from_operator() -> Result<(), error::Error> {
+    let operator = Operator::new(Memory::default())?;
+    operator
+        .write("geo/IP2LOCATION-LITE-DB1.BIN", std::fs::read(IPV4BIN)?)
+        .await?;
+
+    let options = DbOptions::new().collect_stats(true);
+    let db = DB::from_operator(&operator, "geo/IP2LOCATION-LITE-DB1.BIN", &options).await?;
+    match db.ip_lookup("43.224.159.155".parse().unwrap())? {
+        Record::LocationDb(rec) => assert_eq!(rec.country.unwrap().short_name, "IN"),
+        Record::ProxyDb(_) => unreachable!(),
+    }
+    assert!(db.stats().is_some());
+
+    assert!(matches!(
+        DB::from_operator(&operator, "geo/missing.BIN", &options).await,
+        Err(error::Error::IoError(_))
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_from_url() {
+    // A fresh memory operator holds no object.
+    assert!(matches!(
+        DB::from_url("memory://localhost/geo/DB1.BIN", []).await,
+        Err(error::Error::IoError(_))
+    ));
+    for url in [
+        "s3:/bucket/DB1.BIN",
+        "s3://bucket",
+        "s3://bucket/",
+        "s3:///DB1.BIN",
+    ] {
+        assert!(matches!(
+            DB::from_url(url, []).await,
+            Err(error::Error::GenericError(_))
+        ));
+    }
+}
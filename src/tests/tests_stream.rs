@@ -0,0 +1,57 @@
+use crate::{error, GeoLookupExt, LookupService, DB};
+use futures_util::{stream, StreamExt};
+use std::{net::IpAddr, sync::Arc};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_geo_lookup() -> Result<(), error::Error> {
+    let db = Arc::new(DB::from_file("data/IP2LOCATION-LITE-DB1.BIN")?);
+    let ips: Vec<IpAddr> = (0..200u32)
+        .map(|i| IpAddr::from((i * 21_474_836).to_be_bytes()))
+        .collect();
+    let records: Vec<_> = stream::iter(ips.clone()).geo_lookup(&db, 4).collect().await;
+    assert_eq!(records.len(), ips.len());
+    for ((ip, record), expected) in records.into_iter().zip(&ips) {
+        assert_eq!(ip, *expected);
+        assert_eq!(record?, db.ip_lookup_owned(ip)?);
+    }
+
+    let (_, record) = stream::iter(["2a01:b600:8001::".parse().unwrap()])
+        .geo_lookup(&db, 0)
+        .next()
+        .await
+        .unwrap();
+    assert_eq!(record.unwrap_err(), error::Error::RecordNotFound);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ip_lookup_async() -> Result<(), error::Error> {
+    let db = Arc::new(DB::from_file("data/IP2LOCATION-LITE-DB1.BIN")?);
+    let ip: IpAddr = "43.224.159.155".parse().unwrap();
+    assert_eq!(db.ip_lookup_async(ip).await?, db.ip_lookup_owned(ip)?);
+    let err = db
+        .ip_lookup_async("2a01:b600:8001::".parse().unwrap())
+        .await
+        .unwrap_err();
+    assert_eq!(err, error::Error::RecordNotFound);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_lookup_service() -> Result<(), error::Error> {
+    let db = DB::from_file("data/IP2LOCATION-LITE-DB1.BIN")?;
+    let ip: IpAddr = "43.224.159.155".parse().unwrap();
+    let expected = db.ip_lookup_owned(ip)?;
+    let service = LookupService::spawn_with_threads(db, 2);
+    let receivers: Vec<_> = (0..50).map(|_| service.clone().lookup(ip)).collect();
+    for receiver in receivers {
+        assert_eq!(receiver.await.unwrap()?, expected);
+    }
+    let err = service
+        .lookup("2a01:b600:8001::".parse().unwrap())
+        .await
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(err, error::Error::RecordNotFound);
+    Ok(())
+}
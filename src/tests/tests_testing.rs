@@ -0,0 +1,112 @@
+use crate::{
+    error::Error,
+    testing::{BinBuilder, MockResolver},
+    Country, IpRange, IpResolver, LocationRecord, ProxyRecord, Record,
+};
+
+#[test]
+fn test_mock_resolver() {
+    let range = IpRange {
+        start: "10.0.0.0".parse().unwrap(),
+        end: "10.0.0.255".parse().unwrap(),
+    };
+    let proxy = Record::ProxyDb(Box::new(ProxyRecord {
+        is_proxy: Some(crate::Proxy::IsAProxy),
+        ..ProxyRecord::default()
+    }));
+    let resolver = MockResolver::new()
+        .with_range(range, proxy.clone())
+        .with_country("10.0.0.1".parse().unwrap(), "DE", "Germany");
+    assert_eq!(resolver.len(), 2);
+
+    let ip = "10.0.0.1".parse().unwrap();
+    match resolver.resolve(ip).unwrap() {
+        Record::LocationDb(rec) => {
+            assert_eq!(rec.ip, ip);
+            assert_eq!(rec.country.unwrap().short_name, "DE");
+        }
+        Record::ProxyDb(_) => panic!("the later registration should win"),
+    }
+    let ip = "10.0.0.2".parse().unwrap();
+    match resolver.resolve(ip).unwrap() {
+        Record::ProxyDb(rec) => assert_eq!(rec.ip, ip),
+        Record::LocationDb(_) => panic!("expected the range record"),
+    }
+    assert_eq!(
+        resolver.resolve("10.0.1.0".parse().unwrap()),
+        Err(Error::RecordNotFound)
+    );
+
+    let resolver = MockResolver::new().or_record(proxy);
+    assert!(matches!(
+        resolver.resolve("::1".parse().unwrap()),
+        Ok(Record::ProxyDb(_))
+    ));
+    let resolver = MockResolver::default().or_error(|ip| Error::GenericError(ip.to_string()));
+    assert!(resolver.is_empty());
+    assert_eq!(
+        resolver.resolve("::1".parse().unwrap()),
+        Err(Error::GenericError("::1".to_string()))
+    );
+}
+
+fn country(db: &crate::DB, ip: &str) -> String {
+    match db.ip_lookup(ip.parse().unwrap()).unwrap() {
+        Record::LocationDb(rec) => rec.country.unwrap().short_name.into_owned(),
+        Record::ProxyDb(rec) => rec.country.unwrap().short_name.into_owned(),
+    }
+}
+
+#[test]
+fn test_bin_builder_edge_cases() {
+    // No ranges at all: every address falls in the gap row.
+    let db = BinBuilder::new(1).open();
+    assert_eq!(country(&db, "0.0.0.0"), "-");
+    assert_eq!(country(&db, "255.255.255.255"), "-");
+
+    // Ranges at both ends of the IPv4 space; the last address is answered
+    // by the range ending on it.
+    let db = BinBuilder::new(1)
+        .country_range("0.0.0.0", "0.0.0.0", "AA", "First")
+        .country_range("255.255.255.128", "255.255.255.255", "ZZ", "Last")
+        .open();
+    assert_eq!(country(&db, "0.0.0.0"), "AA");
+    assert_eq!(country(&db, "0.0.0.1"), "-");
+    assert_eq!(country(&db, "255.255.255.127"), "-");
+    assert_eq!(country(&db, "255.255.255.255"), "ZZ");
+
+    // An IPv6 table without IPv6 ranges.
+    let db = BinBuilder::new(1).ipv6_table().open();
+    assert_eq!(country(&db, "2001:db8::1"), "-");
+
+    // Strings 64 MiB into the file.
+    let builder = BinBuilder::new(3)
+        .date(24, 2, 29)
+        .range(
+            "10.0.0.0",
+            "10.255.255.255",
+            LocationRecord {
+                country: Some(Country {
+                    short_name: "US".into(),
+                    long_name: "United States of America".into(),
+                }),
+                region: Some("California".into()),
+                city: Some("Mountain View".into()),
+                ..LocationRecord::default()
+            },
+        )
+        .padding(64 << 20);
+    assert!(builder.build().len() > 64 << 20);
+    match builder
+        .open()
+        .ip_lookup("10.1.2.3".parse().unwrap())
+        .unwrap()
+    {
+        Record::LocationDb(rec) => {
+            assert_eq!(rec.country.unwrap().long_name, "United States of America");
+            assert_eq!(rec.region.as_deref(), Some("California"));
+            assert_eq!(rec.city.as_deref(), Some("Mountain View"));
+        }
+        Record::ProxyDb(_) => unreachable!(),
+    }
+}
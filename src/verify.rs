@@ -0,0 +1,558 @@
+use crate::{
+    common::{Storage, INDEX_ENTRIES},
+    error::Error,
+    offset::FileOffset,
+    options::Validation,
+    source::Source,
+};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{
+    fmt,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+use serde::Serialize;
+
+/// Number of issues kept in a `VerifyReport`; further ones are only
+/// counted.
+pub const MAX_ISSUES: usize = 1000;
+
+/// A problem found by `DB::verify`.
+#[derive(PartialEq, Eq, Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Issue {
+    /// The header contradicts itself or the file.
+    Header { message: String },
+    /// A row does not start after the previous one.
+    UnsortedRange {
+        row: u32,
+        start: IpAddr,
+        previous_start: IpAddr,
+    },
+    /// A column of a row points outside the file.
+    OutOfBoundsPointer {
+        ipv6: bool,
+        row: u32,
+        column: u32,
+        pointer: u32,
+    },
+    /// An entry of an index table names rows outside the table or in the
+    /// wrong order.
+    BadIndexEntry {
+        ipv6: bool,
+        block: u32,
+        low: u32,
+        high: u32,
+    },
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let family = |ipv6: &bool| if *ipv6 { "IPv6" } else { "IPv4" };
+        match self {
+            Issue::Header { message } => write!(f, "header: {}", message),
+            Issue::UnsortedRange {
+                row,
+                start,
+                previous_start,
+            } => write!(
+                f,
+                "row {} starts at {}, not after the previous row starting at {}",
+                row, start, previous_start
+            ),
+            Issue::OutOfBoundsPointer {
+                ipv6,
+                row,
+                column,
+                pointer,
+            } => write!(
+                f,
+                "{} row {} column {} points outside the file ({})",
+                family(ipv6),
+                row,
+                column,
+                pointer
+            ),
+            Issue::BadIndexEntry {
+                ipv6,
+                block,
+                low,
+                high,
+            } => write!(
+                f,
+                "{} index block {} has invalid bounds {}..{}",
+                family(ipv6),
+                block,
+                low,
+                high
+            ),
+        }
+    }
+}
+
+/// Result of a full scan of a database.
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize)]
+pub struct VerifyReport {
+    pub ipv4_rows: u32,
+    pub ipv6_rows: u32,
+    /// The first `MAX_ISSUES` issues found.
+    pub issues: Vec<Issue>,
+    /// Total number of issues, including the ones not kept.
+    pub issue_count: usize,
+}
+
+impl VerifyReport {
+    /// Returns true if no issue was found.
+    pub fn is_ok(&self) -> bool {
+        self.issue_count == 0
+    }
+
+    fn header(&mut self, message: String) {
+        self.push(Issue::Header { message });
+    }
+
+    fn push(&mut self, issue: Issue) {
+        if self.issues.len() < MAX_ISSUES {
+            self.issues.push(issue);
+        }
+        self.issue_count += 1;
+    }
+}
+
+impl fmt::Display for VerifyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} IPv4 rows, {} IPv6 rows, {} issue(s)",
+            self.ipv4_rows, self.ipv6_rows, self.issue_count
+        )?;
+        for issue in &self.issues {
+            writeln!(f, " - {}", issue)?;
+        }
+        if self.issue_count > self.issues.len() {
+            writeln!(f, " ... and {} more", self.issue_count - self.issues.len())?;
+        }
+        Ok(())
+    }
+}
+
+/// Where the tables of a database are, as read from its header.
+pub(crate) struct Layout<'a, S> {
+    pub source: &'a Storage<S>,
+    pub db_column: u8,
+    pub ipv4_count: u32,
+    pub ipv4_addr: u32,
+    pub ipv6_count: u32,
+    pub ipv6_addr: u32,
+    pub ipv4_index_base: u32,
+    pub ipv6_index_base: u32,
+    pub database_size: u32,
+    /// 1-based positions of the columns holding floats rather than string
+    /// pointers.
+    pub float_columns: Vec<u32>,
+    /// 1-based position of the country column, whose pointer is followed
+    /// by a second string with the long name.
+    pub country_column: u32,
+    /// Set when the database type is not one the reader knows.
+    pub unknown_type: Option<u8>,
+}
+
+impl<S: Source> Layout<'_, S> {
+    fn row_size(&self, ipv6: bool) -> u64 {
+        self.db_column as u64 * 4 + if ipv6 { 12 } else { 0 }
+    }
+
+    /// Returns true if the byte range `[offset, offset + len)` is inside
+    /// the file.
+    fn in_file(&self, offset: FileOffset, len: u64) -> bool {
+        offset.get() + len <= self.source.len() as u64
+    }
+
+    /// Returns true if the string pointed to (length byte and contents)
+    /// is inside the file.
+    fn string_in_file(&self, pointer: FileOffset) -> bool {
+        self.in_file(pointer, 1)
+            && self.in_file(
+                pointer + 1,
+                self.source.read_u8(pointer).unwrap_or(0) as u64,
+            )
+    }
+
+    fn check_header(&self, report: &mut VerifyReport) -> (bool, bool) {
+        let file_len = self.source.len() as u64;
+        if let Some(db_type) = self.unknown_type {
+            report.header(format!("unknown database type {}", db_type));
+        }
+        if self.db_column == 0 {
+            report.header("zero columns per row".to_string());
+        }
+        if self.database_size as u64 != file_len {
+            report.header(format!(
+                "database size {} does not match the file length {}",
+                self.database_size, file_len
+            ));
+        }
+        let mut tables_ok = [true, true];
+        for (ipv6, count, addr) in [
+            (false, self.ipv4_count, self.ipv4_addr),
+            (true, self.ipv6_count, self.ipv6_addr),
+        ] {
+            if count == 0 {
+                continue;
+            }
+            let len = count as u64 * self.row_size(ipv6);
+            if self.db_column == 0
+                || addr == 0
+                || !self.in_file(FileOffset::from_position(addr), len)
+            {
+                report.header(format!(
+                    "{} table of {} rows at {} does not fit in the file",
+                    if ipv6 { "IPv6" } else { "IPv4" },
+                    count,
+                    addr
+                ));
+                tables_ok[ipv6 as usize] = false;
+            }
+        }
+        (tables_ok[0], tables_ok[1])
+    }
+
+    /// Fails with `Error::Corrupt` unless the tables and index areas the
+    /// header describes end within the file and within the database size
+    /// it records, so lookups never read past them.
+    fn check_bounds(&self) -> Result<(), Error> {
+        // The readers index their column positions by the type.
+        if let Some(db_type) = self.unknown_type {
+            return Err(Error::Corrupt(format!("unknown database type {}", db_type)));
+        }
+        let file_len = self.source.len() as u64;
+        let end = match self.database_size {
+            0 => file_len,
+            size => file_len.min(size as u64),
+        };
+        let family = |ipv6| if ipv6 { "IPv6" } else { "IPv4" };
+        for ipv6 in [false, true] {
+            let count = self.count(ipv6);
+            if count == 0 {
+                continue;
+            }
+            if self.db_column == 0 {
+                return Err(Error::Corrupt(format!(
+                    "{} table of {} rows with zero columns per row",
+                    family(ipv6),
+                    count
+                )));
+            }
+            let addr = self.table_addr(ipv6);
+            let table_end = self.row_offset(ipv6, count).get();
+            if addr == 0 || table_end > end {
+                return Err(Error::Corrupt(format!(
+                    "{} table of {} rows of {} bytes at {} ends at {}, past the end of the database at {} (file of {} bytes, database size {})",
+                    family(ipv6),
+                    count,
+                    self.row_size(ipv6),
+                    addr,
+                    table_end,
+                    end,
+                    file_len,
+                    self.database_size
+                )));
+            }
+            let base = self.index_base(ipv6);
+            let index_end = (FileOffset::from_position(base) + INDEX_ENTRIES as u64 * 8).get();
+            if base != 0 && index_end > end {
+                return Err(Error::Corrupt(format!(
+                    "{} index at {} ends at {}, past the end of the database at {} (file of {} bytes, database size {})",
+                    family(ipv6),
+                    base,
+                    index_end,
+                    end,
+                    file_len,
+                    self.database_size
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn index_base(&self, ipv6: bool) -> u32 {
+        if ipv6 {
+            self.ipv6_index_base
+        } else {
+            self.ipv4_index_base
+        }
+    }
+
+    /// Offset of a row of the IPv4 or IPv6 table.
+    fn row_offset(&self, ipv6: bool, row: u32) -> FileOffset {
+        FileOffset::from_position(self.table_addr(ipv6)) + row as u64 * self.row_size(ipv6)
+    }
+
+    fn ip_from(&self, ipv6: bool, row: u32) -> IpAddr {
+        let offset = self.row_offset(ipv6, row);
+        if ipv6 {
+            IpAddr::V6(
+                self.source
+                    .read_ipv6(offset)
+                    .unwrap_or(Ipv6Addr::UNSPECIFIED),
+            )
+        } else {
+            IpAddr::V4(Ipv4Addr::from(self.source.read_u32(offset).unwrap_or(0)))
+        }
+    }
+
+    fn table_addr(&self, ipv6: bool) -> u32 {
+        if ipv6 {
+            self.ipv6_addr
+        } else {
+            self.ipv4_addr
+        }
+    }
+
+    fn scan_table(&self, ipv6: bool, report: &mut VerifyReport) {
+        let count = if ipv6 {
+            self.ipv6_count
+        } else {
+            self.ipv4_count
+        };
+        let mut previous = None;
+        for row in 0..count {
+            // The end of a range is the start of the next row, so the last
+            // row only marks the end of the address space.
+            let start = self.ip_from(ipv6, row);
+            if let Some(previous) = previous.filter(|previous| start <= *previous) {
+                report.push(Issue::UnsortedRange {
+                    row,
+                    start,
+                    previous_start: previous,
+                });
+            }
+            previous = Some(start);
+
+            self.check_pointers(ipv6, row, report);
+        }
+    }
+
+    /// Checks that the string pointers of a row lead inside the file.
+    fn check_pointers(&self, ipv6: bool, row: u32, report: &mut VerifyReport) {
+        let columns_start = if ipv6 { 12 } else { 0 };
+        let row_addr = self.row_offset(ipv6, row) + columns_start;
+        for column in 2..=self.db_column as u32 {
+            if self.float_columns.contains(&column) {
+                continue;
+            }
+            let pointer = self.source.read_u32(row_addr.column(column)).unwrap_or(0);
+            let mut ok = self.string_in_file(pointer.into());
+            if ok && column == self.country_column {
+                ok = self.string_in_file(FileOffset::from(pointer) + 3);
+            }
+            if !ok {
+                report.push(Issue::OutOfBoundsPointer {
+                    ipv6,
+                    row,
+                    column,
+                    pointer,
+                });
+            }
+        }
+    }
+
+    fn count(&self, ipv6: bool) -> u32 {
+        if ipv6 {
+            self.ipv6_count
+        } else {
+            self.ipv4_count
+        }
+    }
+
+    fn check_order(&self, ipv6: bool) -> Result<(), Issue> {
+        let mut previous = None;
+        for row in 0..self.count(ipv6) {
+            let start = self.ip_from(ipv6, row);
+            if let Some(previous) = previous.filter(|previous| start <= *previous) {
+                return Err(Issue::UnsortedRange {
+                    row,
+                    start,
+                    previous_start: previous,
+                });
+            }
+            previous = Some(start);
+        }
+        Ok(())
+    }
+
+    /// Besides the bounds checked by `scan_index`, the entries must not go
+    /// backwards and the first row of a block must start at or before the
+    /// block, or lookups at its start would miss.
+    fn check_index(&self, ipv6: bool) -> Result<(), Issue> {
+        let base = if ipv6 {
+            self.ipv6_index_base
+        } else {
+            self.ipv4_index_base
+        };
+        let count = self.count(ipv6);
+        if base == 0 || count == 0 {
+            return Ok(());
+        }
+        if !self.in_file(FileOffset::from_position(base), INDEX_ENTRIES as u64 * 8) {
+            return Err(Issue::Header {
+                message: format!(
+                    "{} index at {} does not fit in the file",
+                    if ipv6 { "IPv6" } else { "IPv4" },
+                    base
+                ),
+            });
+        }
+        let (mut previous_low, mut previous_high) = (0, 0);
+        for block in 0..INDEX_ENTRIES as u32 {
+            let offset = FileOffset::from_position(base) + block as u64 * 8;
+            let low = self.source.read_u32(offset).unwrap_or(0);
+            let high = self.source.read_u32(offset + 4).unwrap_or(0);
+            let block_start = if ipv6 {
+                IpAddr::V6(Ipv6Addr::from((block as u128) << 112))
+            } else {
+                IpAddr::V4(Ipv4Addr::from(block << 16))
+            };
+            if low > high
+                || high > count
+                || low < previous_low
+                || high < previous_high
+                || (low < count && self.ip_from(ipv6, low) > block_start)
+            {
+                return Err(Issue::BadIndexEntry {
+                    ipv6,
+                    block,
+                    low,
+                    high,
+                });
+            }
+            (previous_low, previous_high) = (low, high);
+        }
+        Ok(())
+    }
+
+    fn scan_index(&self, ipv6: bool, report: &mut VerifyReport) {
+        let (base, count) = if ipv6 {
+            (self.ipv6_index_base, self.ipv6_count)
+        } else {
+            (self.ipv4_index_base, self.ipv4_count)
+        };
+        if base == 0 || count == 0 {
+            return;
+        }
+        if !self.in_file(FileOffset::from_position(base), INDEX_ENTRIES as u64 * 8) {
+            report.header(format!(
+                "{} index at {} does not fit in the file",
+                if ipv6 { "IPv6" } else { "IPv4" },
+                base
+            ));
+            return;
+        }
+        for block in 0..INDEX_ENTRIES as u32 {
+            let offset = FileOffset::from_position(base) + block as u64 * 8;
+            let low = self.source.read_u32(offset).unwrap_or(0);
+            let high = self.source.read_u32(offset + 4).unwrap_or(0);
+            if low > high || high > count {
+                report.push(Issue::BadIndexEntry {
+                    ipv6,
+                    block,
+                    low,
+                    high,
+                });
+            }
+        }
+    }
+}
+
+/// Checks the header, that the rows of each table start at strictly
+/// increasing addresses and that every index entry brackets the rows of its
+/// block, returning the first problem. Only the first column of each row
+/// and the index tables are read.
+pub(crate) fn check<S: Source>(layout: &Layout<S>) -> Result<(), Issue> {
+    let mut report = VerifyReport::default();
+    layout.check_header(&mut report);
+    if let Some(issue) = report.issues.into_iter().next() {
+        return Err(issue);
+    }
+    for ipv6 in [false, true] {
+        layout.check_order(ipv6)?;
+        layout.check_index(ipv6)?;
+    }
+    Ok(())
+}
+
+/// Checks the header and the index tables, and spot-checks the first and
+/// last rows of each table, returning the first problem. Unlike `check`,
+/// the rows in between are not read.
+pub(crate) fn spot_check<S: Source>(layout: &Layout<S>) -> Result<(), Issue> {
+    let mut report = VerifyReport::default();
+    let tables_ok = layout.check_header(&mut report);
+    for (ipv6, table_ok) in [(false, tables_ok.0), (true, tables_ok.1)] {
+        let count = layout.count(ipv6);
+        if !table_ok || count == 0 {
+            continue;
+        }
+        if let Err(issue) = layout.check_index(ipv6) {
+            report.push(issue);
+        }
+        let last = count - 1;
+        let (first_start, last_start) = (layout.ip_from(ipv6, 0), layout.ip_from(ipv6, last));
+        if last > 0 && last_start <= first_start {
+            report.push(Issue::UnsortedRange {
+                row: last,
+                start: last_start,
+                previous_start: first_start,
+            });
+        }
+        layout.check_pointers(ipv6, 0, &mut report);
+        layout.check_pointers(ipv6, last, &mut report);
+    }
+    match report.issues.into_iter().next() {
+        Some(issue) => Err(issue),
+        None => Ok(()),
+    }
+}
+
+/// Applies the validation level chosen when opening a database.
+pub(crate) fn validate<S: Source>(layout: &Layout<S>, validation: Validation) -> Result<(), Error> {
+    layout.check_bounds()?;
+    let invalid = |issue: Issue| Error::GenericError(format!("invalid database: {}", issue));
+    match validation {
+        Validation::HeaderOnly => Ok(()),
+        Validation::Quick => spot_check(layout).map_err(invalid),
+        Validation::Full => {
+            let report = scan(layout);
+            match report.issues.into_iter().next() {
+                Some(issue) if report.issue_count > 1 => Err(Error::GenericError(format!(
+                    "invalid database: {} (and {} more issues)",
+                    issue,
+                    report.issue_count - 1
+                ))),
+                Some(issue) => Err(invalid(issue)),
+                None => Ok(()),
+            }
+        }
+    }
+}
+
+/// Scans every row and index entry of the database.
+pub(crate) fn scan<S: Source>(layout: &Layout<S>) -> VerifyReport {
+    let mut report = VerifyReport {
+        ipv4_rows: layout.ipv4_count,
+        ipv6_rows: layout.ipv6_count,
+        ..Default::default()
+    };
+    let (ipv4_ok, ipv6_ok) = layout.check_header(&mut report);
+    if ipv4_ok && layout.ipv4_count > 0 {
+        layout.scan_table(false, &mut report);
+        layout.scan_index(false, &mut report);
+    }
+    if ipv6_ok && layout.ipv6_count > 0 {
+        layout.scan_table(true, &mut report);
+        layout.scan_index(true, &mut report);
+    }
+    report
+}
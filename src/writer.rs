@@ -0,0 +1,521 @@
+//! Building IP2Location BIN databases.
+//!
+//! `BinWriter` collects ranges with their `LocationRecord` and lays them out
+//! as a BIN file of a given database type, with the /16 index tables, so
+//! the result can be opened with `DB::from_file`. `BinWriter::read_csv`
+//! loads the CSV distribution of the same database type.
+use crate::{error::Error, ip2location::consts::*, Country, IpRange, LocationRecord};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fs,
+    io::Read,
+    net::{IpAddr, Ipv6Addr},
+    path::Path,
+};
+
+/// Size of the header; tables start right after it.
+const HEADER_LEN: usize = 64;
+/// Number of /16 blocks in an index table.
+const INDEX_BLOCKS: usize = 65536;
+/// Value written for fields a record does not have.
+const MISSING: &str = "-";
+
+/// Text columns of a database row, in the order of the vendor CSV files,
+/// with their position tables.
+const TEXT_FIELDS: [(&str, &[u32; 27]); 21] = [
+    ("region", &REGION_POSITION),
+    ("city", &CITY_POSITION),
+    ("zip_code", &ZIPCODE_POSITION),
+    ("time_zone", &TIMEZONE_POSITION),
+    ("isp", &ISP_POSITION),
+    ("domain", &DOMAIN_POSITION),
+    ("net_speed", &NETSPEED_POSITION),
+    ("idd_code", &IDDCODE_POSITION),
+    ("area_code", &AREACODE_POSITION),
+    ("weather_station_code", &WEATHERSTATIONCODE_POSITION),
+    ("weather_station_name", &WEATHERSTATIONNAME_POSITION),
+    ("mcc", &MCC_POSITION),
+    ("mnc", &MNC_POSITION),
+    ("mobile_brand", &MOBILEBRAND_POSITION),
+    ("elevation", &ELEVATION_POSITION),
+    ("usage_type", &USAGETYPE_POSITION),
+    ("address_type", &ADDRESSTYPE_POSITION),
+    ("category", &CATEGORY_POSITION),
+    ("district", &DISTRICT_POSITION),
+    ("asn", &ASN_POSITION),
+    ("as_name", &AS_POSITION),
+];
+
+fn text_field<'r, 'a>(record: &'r LocationRecord<'a>, name: &str) -> &'r Option<Cow<'a, str>> {
+    match name {
+        "region" => &record.region,
+        "city" => &record.city,
+        "zip_code" => &record.zip_code,
+        "time_zone" => &record.time_zone,
+        "isp" => &record.isp,
+        "domain" => &record.domain,
+        "net_speed" => &record.net_speed,
+        "idd_code" => &record.idd_code,
+        "area_code" => &record.area_code,
+        "weather_station_code" => &record.weather_station_code,
+        "weather_station_name" => &record.weather_station_name,
+        "mcc" => &record.mcc,
+        "mnc" => &record.mnc,
+        "mobile_brand" => &record.mobile_brand,
+        "elevation" => &record.elevation,
+        "usage_type" => &record.usage_type,
+        "address_type" => &record.address_type,
+        "category" => &record.category,
+        "district" => &record.district,
+        "asn" => &record.asn,
+        _ => &record.as_name,
+    }
+}
+
+fn text_field_mut<'r>(
+    record: &'r mut LocationRecord<'static>,
+    name: &str,
+) -> &'r mut Option<Cow<'static, str>> {
+    match name {
+        "region" => &mut record.region,
+        "city" => &mut record.city,
+        "zip_code" => &mut record.zip_code,
+        "time_zone" => &mut record.time_zone,
+        "isp" => &mut record.isp,
+        "domain" => &mut record.domain,
+        "net_speed" => &mut record.net_speed,
+        "idd_code" => &mut record.idd_code,
+        "area_code" => &mut record.area_code,
+        "weather_station_code" => &mut record.weather_station_code,
+        "weather_station_name" => &mut record.weather_station_name,
+        "mcc" => &mut record.mcc,
+        "mnc" => &mut record.mnc,
+        "mobile_brand" => &mut record.mobile_brand,
+        "elevation" => &mut record.elevation,
+        "usage_type" => &mut record.usage_type,
+        "address_type" => &mut record.address_type,
+        "category" => &mut record.category,
+        "district" => &mut record.district,
+        "asn" => &mut record.asn,
+        _ => &mut record.as_name,
+    }
+}
+
+/// Builds an IP2Location BIN database in memory.
+#[derive(Debug, Clone)]
+pub struct BinWriter {
+    db_type: u8,
+    date: (u8, u8, u8),
+    ipv4: Vec<(u32, u32, LocationRecord<'static>)>,
+    ipv6: Vec<(u128, u128, LocationRecord<'static>)>,
+    /// Writes an IPv6 table even without IPv6 ranges.
+    pub(crate) ipv6_table: bool,
+    /// Zero bytes between the index tables and the strings.
+    pub(crate) padding: usize,
+}
+
+impl BinWriter {
+    pub fn new(db_type: u8) -> Result<Self, Error> {
+        //! Starts an empty database of the given type (1 to 26), e.g. 1 for
+        //! the country-only DB1.
+        //!
+        //! ## Example usage
+        //!
+        //!```rust
+        //! use ip2location::{writer::BinWriter, Country, IpRange, LocationRecord, Record, DB};
+        //!
+        //! let mut writer = BinWriter::new(1).unwrap().date(24, 1, 31);
+        //! let record = LocationRecord {
+        //!     country: Some(Country { short_name: "FR".into(), long_name: "France".into() }),
+        //!     ..Default::default()
+        //! };
+        //! let range = IpRange { start: "2.0.0.0".parse().unwrap(), end: "2.16.0.255".parse().unwrap() };
+        //! writer.push(range, &record).unwrap();
+        //!
+        //! let path = std::env::temp_dir().join("ip2location-writer-doc.BIN");
+        //! writer.write_to(&path).unwrap();
+        //! let db = DB::from_file(&path).unwrap();
+        //! assert!(matches!(db.ip_lookup("2.1.2.3".parse().unwrap()).unwrap(), Record::LocationDb(_)));
+        //!```
+        if !(1..COUNTRY_POSITION.len() as u8).contains(&db_type) {
+            return Err(Error::GenericError(format!(
+                "unsupported database type {}",
+                db_type
+            )));
+        }
+        Ok(Self {
+            db_type,
+            date: (0, 1, 1),
+            ipv4: Vec::new(),
+            ipv6: Vec::new(),
+            ipv6_table: false,
+            padding: 0,
+        })
+    }
+
+    /// Sets the date written to the header, as a two-digit year, month and
+    /// day.
+    pub fn date(mut self, year: u8, month: u8, day: u8) -> Self {
+        self.date = (year, month, day);
+        self
+    }
+
+    /// Number of ranges added so far.
+    pub fn len(&self) -> usize {
+        self.ipv4.len() + self.ipv6.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Adds a range with its record. IPv4-mapped IPv6 ranges are stored
+    /// in the IPv4 table. Only the fields of the database type are kept.
+    pub fn push(&mut self, range: IpRange, record: &LocationRecord<'_>) -> Result<(), Error> {
+        let record = record.clone().into_owned();
+        match (range.start, range.end) {
+            (IpAddr::V4(start), IpAddr::V4(end)) if start <= end => {
+                self.ipv4.push((start.into(), end.into(), record));
+            }
+            (IpAddr::V6(start), IpAddr::V6(end)) if start <= end => {
+                match (start.to_ipv4_mapped(), end.to_ipv4_mapped()) {
+                    (Some(start), Some(end)) => self.ipv4.push((start.into(), end.into(), record)),
+                    _ => self.ipv6.push((start.into(), end.into(), record)),
+                }
+            }
+            _ => {
+                return Err(Error::GenericError(format!(
+                    "invalid range {}-{}",
+                    range.start, range.end
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the rows of a vendor CSV file of the writer's database type:
+    /// `ip_from`, `ip_to` (as integers or addresses), then the fields of
+    /// the type in the vendor's column order. Rows without a header.
+    pub fn read_csv<R: Read>(&mut self, reader: R) -> Result<(), Error> {
+        let db_type = self.db_type as usize;
+        let has_coordinates = LATITUDE_POSITION[db_type] > 0;
+        let fields: Vec<&str> = TEXT_FIELDS
+            .iter()
+            .filter(|(_, positions)| positions[db_type] > 0)
+            .map(|(name, _)| *name)
+            .collect();
+        // Region and city come before the coordinates, the other text
+        // fields after them.
+        let (before, after) = fields.split_at(
+            fields
+                .iter()
+                .take_while(|f| ["region", "city"].contains(f))
+                .count(),
+        );
+        let expected = 4 + before.len() + if has_coordinates { 2 } else { 0 } + after.len();
+
+        let mut rdr = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(reader);
+        for (line, row) in rdr.records().enumerate() {
+            let row = row?;
+            let err = |msg: &str| Error::GenericError(format!("line {}: {}", line + 1, msg));
+            if row.len() != expected {
+                return Err(err(&format!(
+                    "expected {} columns for DB{}, found {}",
+                    expected,
+                    self.db_type,
+                    row.len()
+                )));
+            }
+            let start = parse_ip(&row[0]).ok_or_else(|| err("invalid ip_from"))?;
+            let end = parse_ip(&row[1]).ok_or_else(|| err("invalid ip_to"))?;
+            let mut record = LocationRecord {
+                ip: start,
+                country: Some(Country {
+                    short_name: Cow::Owned(row[2].to_string()),
+                    long_name: Cow::Owned(row[3].to_string()),
+                }),
+                ..Default::default()
+            };
+            let mut column = 4;
+            for name in before {
+                *text_field_mut(&mut record, name) = Some(Cow::Owned(row[column].to_string()));
+                column += 1;
+            }
+            if has_coordinates {
+                record.latitude = Some(row[column].parse().map_err(|_| err("invalid latitude"))?);
+                record.longitude = Some(
+                    row[column + 1]
+                        .parse()
+                        .map_err(|_| err("invalid longitude"))?,
+                );
+                column += 2;
+            }
+            for name in after {
+                *text_field_mut(&mut record, name) = Some(Cow::Owned(row[column].to_string()));
+                column += 1;
+            }
+            self.push(IpRange { start, end }, &record)
+                .map_err(|e| err(&e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Lays out the database. Ranges are sorted; overlapping ranges are an
+    /// error and gaps are filled with rows whose fields are all "-".
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let db_type = self.db_type as usize;
+        let db_column = TEXT_FIELDS
+            .iter()
+            .map(|(_, positions)| positions[db_type])
+            .chain([
+                COUNTRY_POSITION[db_type],
+                LATITUDE_POSITION[db_type],
+                LONGITUDE_POSITION[db_type],
+            ])
+            .max()
+            .unwrap_or(1)
+            .max(1);
+        let ipv4 = fill_gaps(&self.ipv4, u32::MAX as u128)?;
+        let ipv6 = if self.ipv6.is_empty() && !self.ipv6_table {
+            Vec::new()
+        } else {
+            fill_gaps(&self.ipv6, u128::MAX)?
+        };
+
+        // Tables, then index tables, then strings.
+        let ipv4_row = db_column as usize * 4;
+        let ipv6_row = ipv4_row + 12;
+        // Each table ends with a row starting at the last address.
+        let ipv4_count = ipv4.len() + 1;
+        let ipv6_count = if ipv6.is_empty() { 0 } else { ipv6.len() + 1 };
+        let ipv4_addr = HEADER_LEN;
+        let ipv6_addr = ipv4_addr + ipv4_count * ipv4_row;
+        let ipv4_index = ipv6_addr + ipv6_count * ipv6_row;
+        let ipv6_index = ipv4_index + INDEX_BLOCKS * 8;
+        let strings = ipv6_index + if ipv6_count > 0 { INDEX_BLOCKS * 8 } else { 0 } + self.padding;
+
+        let mut out = vec![0u8; strings];
+        let mut pool = StringPool::new(strings);
+        for (row, (start, _, record)) in ipv4.iter().enumerate() {
+            let at = ipv4_addr + row * ipv4_row;
+            out[at..at + 4].copy_from_slice(&(*start as u32).to_le_bytes());
+            self.write_columns(&mut out[at + 4..at + ipv4_row], record, &mut pool);
+        }
+        let last = ipv4_addr + ipv4.len() * ipv4_row;
+        out[last..last + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        let (_, _, record) = &ipv4[ipv4.len() - 1];
+        self.write_columns(&mut out[last + 4..last + ipv4_row], record, &mut pool);
+        if !ipv6.is_empty() {
+            for (row, (start, _, record)) in ipv6.iter().enumerate() {
+                let at = ipv6_addr + row * ipv6_row;
+                out[at..at + 16].copy_from_slice(&start.to_le_bytes());
+                self.write_columns(&mut out[at + 16..at + ipv6_row], record, &mut pool);
+            }
+            let last = ipv6_addr + ipv6.len() * ipv6_row;
+            out[last..last + 16].copy_from_slice(&u128::MAX.to_le_bytes());
+            let (_, _, record) = &ipv6[ipv6.len() - 1];
+            self.write_columns(&mut out[last + 16..last + ipv6_row], record, &mut pool);
+            write_index(&mut out[ipv6_index..], &ipv6, 112);
+        }
+        write_index(&mut out[ipv4_index..], &ipv4, 16);
+        out.extend_from_slice(&pool.bytes);
+
+        let (year, month, day) = self.date;
+        let header = &mut out[..HEADER_LEN];
+        header[0] = self.db_type;
+        header[1] = db_column as u8;
+        header[2] = year;
+        header[3] = month;
+        header[4] = day;
+        // Addresses in the header are 1-based.
+        let words = [
+            (5, ipv4_count),
+            (9, ipv4_addr + 1),
+            (13, ipv6_count),
+            (17, if ipv6_count > 0 { ipv6_addr + 1 } else { 1 }),
+            (21, ipv4_index + 1),
+            (25, if ipv6_count > 0 { ipv6_index + 1 } else { 1 }),
+        ];
+        for (at, value) in words {
+            header[at..at + 4].copy_from_slice(&(value as u32).to_le_bytes());
+        }
+        header[29] = 1; // product code: IP2Location
+        header[30] = 2; // license code, as in the LITE databases
+        let size = out.len() as u32;
+        out[31..35].copy_from_slice(&size.to_le_bytes());
+        Ok(out)
+    }
+
+    /// Writes the database to a file.
+    pub fn write_to<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        fs::write(path, self.to_bytes()?)?;
+        Ok(())
+    }
+
+    fn write_columns(&self, row: &mut [u8], record: &LocationRecord<'_>, pool: &mut StringPool) {
+        let db_type = self.db_type as usize;
+        // `row` starts at column 2, the first one after `ip_from`.
+        let mut put = |position: u32, value: u32| {
+            if position > 0 {
+                let at = (position as usize - 2) * 4;
+                row[at..at + 4].copy_from_slice(&value.to_le_bytes());
+            }
+        };
+        let (short, long) = record.country.as_ref().map_or((MISSING, MISSING), |c| {
+            (c.short_name.as_ref(), c.long_name.as_ref())
+        });
+        put(COUNTRY_POSITION[db_type], pool.country(short, long));
+        put(
+            LATITUDE_POSITION[db_type],
+            record.latitude.unwrap_or(0.0).to_bits(),
+        );
+        put(
+            LONGITUDE_POSITION[db_type],
+            record.longitude.unwrap_or(0.0).to_bits(),
+        );
+        for (name, positions) in TEXT_FIELDS {
+            let value = text_field(record, name).as_deref().unwrap_or(MISSING);
+            put(positions[db_type], pool.string(value));
+        }
+    }
+}
+
+pub(crate) fn parse_ip(value: &str) -> Option<IpAddr> {
+    let value = value.trim();
+    if let Ok(ip) = value.parse() {
+        return Some(ip);
+    }
+    // Vendor files store addresses as integers: IPv4 files as u32, IPv6
+    // files as u128 with IPv4 ranges mapped into ::ffff:0:0/96.
+    let number: u128 = value.parse().ok()?;
+    Some(match u32::try_from(number) {
+        Ok(v4) => IpAddr::V4(v4.into()),
+        Err(_) => IpAddr::V6(Ipv6Addr::from(number)),
+    })
+}
+
+/// Sorts the ranges and fills the gaps between them, so that every row
+/// ends where the next one starts. The last address, `max`, belongs to the
+/// sentinel row that ends the table.
+fn fill_gaps<T: Copy + Into<u128>>(
+    ranges: &[(T, T, LocationRecord<'static>)],
+    max: u128,
+) -> Result<Vec<(u128, u128, LocationRecord<'static>)>, Error> {
+    let mut sorted: Vec<(u128, u128, &LocationRecord<'static>)> = ranges
+        .iter()
+        .map(|(start, end, record)| ((*start).into(), (*end).into(), record))
+        .filter(|(start, _, _)| *start < max)
+        .collect();
+    sorted.sort_by_key(|(start, _, _)| *start);
+    let mut rows = Vec::with_capacity(sorted.len() + 1);
+    let mut next = 0u128;
+    for (start, end, record) in sorted {
+        if start < next {
+            return Err(Error::GenericError(format!(
+                "overlapping ranges at {}",
+                if max == u32::MAX as u128 {
+                    IpAddr::V4((start as u32).into())
+                } else {
+                    IpAddr::V6(start.into())
+                }
+            )));
+        }
+        if start > next {
+            rows.push((next, start - 1, LocationRecord::default()));
+        }
+        let end = end.min(max - 1);
+        rows.push((start, end, record.clone()));
+        next = end + 1;
+    }
+    if next < max {
+        rows.push((next, max - 1, LocationRecord::default()));
+    }
+    Ok(rows)
+}
+
+/// Writes the `(low, high)` row bounds of every block of the top 16 bits
+/// of the address. `shift` is the width of the address minus 16; the
+/// last block ends on the sentinel row.
+fn write_index(out: &mut [u8], rows: &[(u128, u128, LocationRecord<'static>)], shift: u32) {
+    let mut row = 0;
+    for block in 0..INDEX_BLOCKS {
+        let first = (block as u128) << shift;
+        let last = first | ((1u128 << shift) - 1);
+        while rows[row].1 < first {
+            row += 1;
+        }
+        let low = row;
+        let mut high = row;
+        while high < rows.len() && rows[high].1 < last {
+            high += 1;
+        }
+        let at = block * 8;
+        out[at..at + 4].copy_from_slice(&(low as u32).to_le_bytes());
+        out[at + 4..at + 8].copy_from_slice(&(high as u32).to_le_bytes());
+    }
+}
+
+/// Deduplicated string area, addressed by the position of each length
+/// byte in the file.
+struct StringPool {
+    base: usize,
+    bytes: Vec<u8>,
+    offsets: HashMap<String, u32>,
+}
+
+impl StringPool {
+    fn new(base: usize) -> Self {
+        Self {
+            base,
+            bytes: Vec::new(),
+            offsets: HashMap::new(),
+        }
+    }
+
+    fn push(&mut self, value: &str) -> u32 {
+        let value = truncate(value);
+        let offset = (self.base + self.bytes.len()) as u32;
+        self.bytes.push(value.len() as u8);
+        self.bytes.extend_from_slice(value.as_bytes());
+        offset
+    }
+
+    fn string(&mut self, value: &str) -> u32 {
+        if let Some(offset) = self.offsets.get(value) {
+            return *offset;
+        }
+        let offset = self.push(value);
+        self.offsets.insert(value.to_string(), offset);
+        offset
+    }
+
+    /// Countries are read as the short name followed, 3 bytes later, by
+    /// the long name.
+    fn country(&mut self, short: &str, long: &str) -> u32 {
+        let key = format!("{}\0{}", short, long);
+        if let Some(offset) = self.offsets.get(&key) {
+            return *offset;
+        }
+        let short = truncate(short);
+        let offset = (self.base + self.bytes.len()) as u32;
+        let mut slot = [0u8; 3];
+        slot[0] = short.len().min(2) as u8;
+        slot[1..1 + short.len().min(2)].copy_from_slice(&short.as_bytes()[..short.len().min(2)]);
+        self.bytes.extend_from_slice(&slot);
+        self.push(long);
+        self.offsets.insert(key, offset);
+        offset
+    }
+}
+
+/// Strings are stored with a one byte length.
+fn truncate(value: &str) -> &str {
+    let mut end = value.len().min(255);
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    &value[..end]
+}